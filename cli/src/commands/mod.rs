@@ -1,6 +1,7 @@
 pub mod build_info;
 pub mod misc;
 pub mod node;
+pub mod remote_signer;
 pub mod replay;
 pub mod snark;
 
@@ -20,6 +21,8 @@ pub enum Command {
     /// Miscilaneous utilities.
     Misc(misc::Misc),
     Replay(replay::Replay),
+    /// Reference remote signer for the block producer.
+    RemoteSigner(remote_signer::RemoteSigner),
     BuildInfo(build_info::Command),
 }
 
@@ -30,6 +33,7 @@ impl Command {
             Self::Node(v) => v.run(),
             Self::Misc(v) => v.run(),
             Self::Replay(v) => v.run(),
+            Self::RemoteSigner(v) => v.run(),
             Self::BuildInfo(v) => v.run(),
         }
     }
@@ -1,15 +1,15 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
 use libp2p_identity::Keypair;
 use mina_p2p_messages::v2::{
-    CurrencyFeeStableV1, NonZeroCurvePoint, NonZeroCurvePointUncompressedStableV1,
-    UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+    CurrencyFeeStableV1, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
 };
+use node::daemon_json::DaemonJson;
 use node::transition_frontier::genesis::GenesisConfig;
 use rand::prelude::*;
 
@@ -31,11 +31,18 @@ use node::service::{Recorder, Service};
 use node::snark::{get_srs, get_verifier_index, VerifierKind};
 use node::stats::Stats;
 use node::{
-    BlockProducerConfig, BuildEnv, Config, GlobalConfig, LedgerConfig, SnarkConfig, SnarkerConfig,
-    SnarkerStrategy, State, TransitionFrontierConfig,
+    BlockProducerConfig, BuildEnv, Config, GlobalConfig, HealthCheckConfig, LedgerConfig,
+    SnarkConfig, SnarkCoordinatorConfig, SnarkerConfig, SnarkerStrategy, State,
+    TransitionFrontierConfig, TransitionFrontierHardForkConfig,
 };
 
+use node::block_producer::CoinbaseReceiverPolicy;
+use openmina_node_native::block_producer::remote_signer::{RemoteSignerAddr, RemoteSignerClient};
+use openmina_node_native::block_producer::{read_production_log, PayoutReportFormat};
+use openmina_node_native::ext_snark_worker::SnarkWorkerKind;
 use openmina_node_native::rpc::RpcService;
+use openmina_node_native::snark_pool::{read_snark_pool, SnarkPoolWriter};
+use openmina_node_native::tracing::{LogFormat, TracingConfig};
 use openmina_node_native::{http_server, tracing, NodeService, P2pTaskSpawner, RpcSender};
 
 /// Openmina node
@@ -60,6 +67,17 @@ pub struct Node {
     #[arg(long, short, env, default_value = "info")]
     pub verbosity: Level,
 
+    /// Log output format: `pretty` or `json`.
+    #[arg(long, env, default_value = "pretty")]
+    pub log_format: LogFormat,
+
+    /// OpenTelemetry collector endpoint to export spans to over OTLP,
+    /// e.g. `http://localhost:4317`.
+    ///
+    /// Requires the node to be built with `otlp` feature.
+    #[arg(long, env)]
+    pub otlp_endpoint: Option<String>,
+
     #[arg(long, short = 'P', alias = "peer")]
     pub peers: Vec<P2pConnectionOutgoingInitOpts>,
 
@@ -87,16 +105,106 @@ pub struct Node {
 
     /// Enable block producer with this key file
     ///
-    /// MINA_PRIVKEY_PASS must be set to decrypt the keyfile
+    /// Can be repeated (or comma separated in env) to produce blocks
+    /// with multiple keys. MINA_PRIVKEY_PASS must be set to decrypt
+    /// the keyfiles.
+    #[arg(long, env, value_delimiter = ',')]
+    pub producer_key: Vec<PathBuf>,
+
+    /// Enable block producer with keys held by the remote signer.
+    ///
+    /// Either `host:port` or a path to the Unix socket, which must be
+    /// absolute, start with `./`, or be prefixed with `unix:`. Vrf
+    /// evaluation and block proving is delegated to the signer.
+    #[arg(long, env, conflicts_with = "producer_key")]
+    pub remote_signer: Option<RemoteSignerAddr>,
+
+    /// Token to authenticate with the remote signer.
+    #[arg(long, env = "OPENMINA_REMOTE_SIGNER_TOKEN", requires = "remote_signer")]
+    pub remote_signer_token: Option<String>,
+
+    /// Coinbase receiver for the produced blocks. Defaults to the
+    /// producer key which won the slot.
+    #[arg(long, env)]
+    pub coinbase_receiver: Option<AccountPublicKey>,
+
+    /// Rule for choosing the coinbase receiver.
+    ///
+    /// One of `producer` (uses `--coinbase-receiver` if set), `slot-winner`
+    /// (delegator whose stake won the slot), or a comma separated list of
+    /// `<producer>=<receiver>` pairs.
+    #[arg(long, env, default_value = "producer")]
+    pub coinbase_receiver_policy: CoinbaseReceiverPolicy,
+
+    /// Directory where payout report is written for every produced block,
+    /// once the block becomes our best tip.
+    ///
+    /// Report lists the stake shares of the producer's delegators in the
+    /// staking ledger used for the won slot.
     #[arg(long, env)]
-    pub producer_key: Option<PathBuf>,
+    pub payout_report_dir: Option<PathBuf>,
+
+    /// Format of the payout report: `json` or `csv`.
+    #[arg(long, env, default_value = "json")]
+    pub payout_report_format: PayoutReportFormat,
+
+    /// Produce and prove blocks, but don't inject or broadcast them.
+    ///
+    /// Used to validate the block producer setup and benchmark
+    /// block production without risking forks or duplicate blocks.
+    #[arg(long, env)]
+    pub producer_dry_run: bool,
+
     /// Snark fee, in Mina
     #[arg(long, env, default_value_t = 1_000_000)]
     pub snarker_fee: u64,
 
+    /// Lowest snark fee the `profit` strategy can undercut competing
+    /// snarks with. If not set, the fee isn't lowered.
+    #[arg(long, env)]
+    pub snarker_min_fee: Option<u64>,
+
+    /// Job selection strategy: `seq`, `rand` or `profit`.
     #[arg(long, env, default_value = "seq")]
     pub snarker_strategy: SnarkerStrategy,
 
+    /// Number of external snark worker processes to run.
+    #[arg(long, env, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub snarker_workers: u16,
+
+    /// Snark worker implementation: `external` runs the `mina` snark
+    /// worker process, `rust` proves jobs in-process.
+    #[arg(long, env, default_value = "external")]
+    pub snark_worker: SnarkWorkerKind,
+
+    /// Thread pool size of each in-process (`rust`) snark worker.
+    /// Defaults to the number of CPUs divided by `--snarker-workers`.
+    #[arg(long, env)]
+    pub snark_worker_threads: Option<usize>,
+
+    /// Run as a snark coordinator: remote workers (`openmina snark worker`)
+    /// register over http, lease jobs and submit proofs.
+    #[arg(long, env)]
+    pub snark_coordinator: bool,
+
+    /// Seconds without a heartbeat after which a remote worker's lease
+    /// expires and its job is re-queued.
+    #[arg(long, env, default_value_t = 30)]
+    pub snark_coordinator_lease_timeout: u64,
+
+    /// Seconds without contact after which an idle remote worker is
+    /// unregistered.
+    #[arg(long, env, default_value_t = 300)]
+    pub snark_coordinator_worker_timeout: u64,
+
+    /// Maximum number of registered remote workers.
+    #[arg(long, env, default_value_t = 64)]
+    pub snark_coordinator_max_workers: usize,
+
+    /// Token which remote workers must present with every request.
+    #[arg(long, env, requires = "snark_coordinator")]
+    pub snark_coordinator_auth_token: Option<String>,
+
     #[arg(long, default_value = "none", env)]
     pub record: String,
 
@@ -107,10 +215,70 @@ pub struct Node {
     #[arg(long)]
     pub no_peers_discovery: bool,
 
-    /// Config JSON file to load at startup.
+    /// Config file (daemon.json) to load at startup.
+    ///
+    /// Describes the network profile: genesis ledger, protocol constants
+    /// (`genesis` section) and slot duration (`proof.block_window_duration_ms`).
+    /// Files with `.toml` extension are parsed as TOML with the same structure.
     // TODO: make this argument required.
     #[arg(short = 'c', long, env)]
     pub config: Option<PathBuf>,
+
+    /// Config JSON file of the chain to switch to after the hard fork.
+    ///
+    /// The current chain must have `daemon.slot_chain_end` set in the
+    /// file passed with `--config`.
+    #[arg(long, env, requires = "config")]
+    pub hard_fork_config: Option<PathBuf>,
+
+    /// Slots the best tip can lag behind the current slot before the
+    /// health check warns.
+    #[arg(long, env)]
+    pub health_best_tip_age_warn_slots: Option<u32>,
+
+    /// Slots the best tip can lag behind the current slot before the
+    /// health check fails.
+    #[arg(long, env)]
+    pub health_best_tip_age_fail_slots: Option<u32>,
+
+    /// Health check warns with less ready peers than this.
+    #[arg(long, env)]
+    pub health_min_peers: Option<usize>,
+
+    /// Seconds the sync can stay in one phase before the health check fails.
+    #[arg(long, env)]
+    pub health_sync_stuck_timeout: Option<u64>,
+
+    /// Seconds to look back for missed won slots.
+    #[arg(long, env)]
+    pub health_missed_slot_window: Option<u64>,
+
+    /// Seconds in which the snark worker errors are counted.
+    #[arg(long, env)]
+    pub health_snark_worker_crash_window: Option<u64>,
+
+    /// Number of snark worker errors within the window after which the
+    /// health check fails.
+    #[arg(long, env)]
+    pub health_snark_worker_crash_threshold: Option<usize>,
+
+    /// Health check warns with more pending ledger read requests than this.
+    #[arg(long, env)]
+    pub health_ledger_backlog: Option<usize>,
+
+    /// Seconds a ledger request can be pending before the health check fails.
+    #[arg(long, env)]
+    pub health_ledger_request_timeout: Option<u64>,
+}
+
+fn read_daemon_json(path: &Path) -> anyhow::Result<DaemonJson> {
+    if path.extension().map_or(false, |ext| ext == "toml") {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("config file {path:?}"))?;
+        return toml::from_str(&content).with_context(|| format!("config file {path:?}"));
+    }
+    let reader = File::open(path).with_context(|| format!("config file {path:?}"))?;
+    serde_json::from_reader(reader).with_context(|| format!("config file {path:?}"))
 }
 
 fn default_peers() -> Vec<P2pConnectionOutgoingInitOpts> {
@@ -137,7 +305,12 @@ fn default_peers() -> Vec<P2pConnectionOutgoingInitOpts> {
 
 impl Node {
     pub fn run(mut self) -> anyhow::Result<()> {
-        tracing::initialize(self.verbosity);
+        tracing::initialize_with_config(TracingConfig {
+            max_log_level: self.verbosity,
+            format: self.log_format,
+            otlp_endpoint: self.otlp_endpoint.clone(),
+        })
+        .context("failed to initialize tracing")?;
 
         rayon::ThreadPoolBuilder::new()
             .num_threads(num_cpus::get().max(2) - 1)
@@ -178,35 +351,91 @@ impl Node {
             result
         };
 
-        let block_producer = self.producer_key.clone().map(|producer_key_path| {
-            let keypair = AccountSecretKey::from_encrypted_file(producer_key_path)
-                .expect("Failed to decrypt secret key file");
-            let compressed_pub_key = keypair.public_key_compressed();
-            (
-                BlockProducerConfig {
-                    pub_key: NonZeroCurvePoint::from(NonZeroCurvePointUncompressedStableV1 {
-                        x: compressed_pub_key.x.into(),
-                        is_odd: compressed_pub_key.is_odd,
-                    }),
-                    custom_coinbase_receiver: None,
+        let producer_keypairs = self
+            .producer_key
+            .iter()
+            .map(|producer_key_path| {
+                AccountSecretKey::from_encrypted_file(producer_key_path.clone())
+                    .expect("Failed to decrypt secret key file")
+            })
+            .collect::<Vec<_>>();
+        let remote_signer = self
+            .remote_signer
+            .clone()
+            .map(|addr| RemoteSignerClient::new(addr, self.remote_signer_token.clone()));
+        let producer_pub_keys = match &remote_signer {
+            Some(signer) => signer.pub_keys().with_context(|| {
+                format!(
+                    "failed to get producer keys from remote signer {}",
+                    signer.addr()
+                )
+            })?,
+            None => producer_keypairs.iter().map(|v| v.public_key()).collect(),
+        };
+        let block_producer =
+            producer_pub_keys
+                .split_first()
+                .map(|(pub_key, rest)| BlockProducerConfig {
+                    pub_key: pub_key.clone().into(),
+                    additional_pub_keys: rest.iter().cloned().map(Into::into).collect(),
+                    custom_coinbase_receiver: self.coinbase_receiver.clone().map(Into::into),
+                    coinbase_receiver_policy: self.coinbase_receiver_policy.clone(),
                     proposed_protocol_version: None,
-                },
-                keypair,
-            )
-        });
+                    dry_run: self.producer_dry_run,
+                });
 
         let work_dir = shellexpand::full(&self.work_dir).unwrap().into_owned();
         let srs: Arc<_> = get_srs();
 
+        let mut slot_chain_end = None;
         let genesis_config = match self.config {
             Some(config) => {
-                let reader = File::open(config).context("config file {config:?}")?;
-                let c = serde_json::from_reader(reader).context("config file {config:?}")?;
-                Arc::new(GenesisConfig::DaemonJson(c))
+                let c = read_daemon_json(&config)?;
+                slot_chain_end = c.slot_chain_end();
+                Arc::new(GenesisConfig::DaemonJson(Box::new(c)))
             }
             None => node::config::DEVNET_CONFIG.clone(),
         };
-        let transition_frontier = TransitionFrontierConfig::new(genesis_config);
+        let mut transition_frontier = TransitionFrontierConfig::new(genesis_config);
+        if let Some(hard_fork_config) = self.hard_fork_config {
+            let slot_chain_end = slot_chain_end
+                .context("hard fork config requires `daemon.slot_chain_end` in the config file")?;
+            let c = read_daemon_json(&hard_fork_config)?;
+            transition_frontier =
+                transition_frontier.with_hard_fork(TransitionFrontierHardForkConfig {
+                    slot_chain_end,
+                    genesis: Arc::new(GenesisConfig::DaemonJson(Box::new(c))),
+                });
+        }
+        let mut health = HealthCheckConfig::default();
+        if let Some(v) = self.health_best_tip_age_warn_slots {
+            health.best_tip_age_warn_slots = v;
+        }
+        if let Some(v) = self.health_best_tip_age_fail_slots {
+            health.best_tip_age_fail_slots = v;
+        }
+        if let Some(v) = self.health_min_peers {
+            health.min_peers = v;
+        }
+        if let Some(v) = self.health_sync_stuck_timeout {
+            health.sync_stuck_timeout = Duration::from_secs(v);
+        }
+        if let Some(v) = self.health_missed_slot_window {
+            health.missed_slot_window = Duration::from_secs(v);
+        }
+        if let Some(v) = self.health_snark_worker_crash_window {
+            health.snark_worker_crash_window = Duration::from_secs(v);
+        }
+        if let Some(v) = self.health_snark_worker_crash_threshold {
+            health.snark_worker_crash_threshold = v;
+        }
+        if let Some(v) = self.health_ledger_backlog {
+            health.ledger_backlog = v;
+        }
+        if let Some(v) = self.health_ledger_request_timeout {
+            health.ledger_request_timeout = Duration::from_secs(v);
+        }
+
         let config = Config {
             ledger: LedgerConfig {},
             snark: SnarkConfig {
@@ -225,6 +454,19 @@ impl Node {
                     )),
                     strategy: self.snarker_strategy,
                     auto_commit: true,
+                    workers: self.snarker_workers.into(),
+                    min_fee: self.snarker_min_fee.map(|fee| {
+                        CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+                            fee.into(),
+                        ))
+                    }),
+                }),
+                health,
+                snark_coordinator: self.snark_coordinator.then(|| SnarkCoordinatorConfig {
+                    lease_timeout: Duration::from_secs(self.snark_coordinator_lease_timeout),
+                    worker_timeout: Duration::from_secs(self.snark_coordinator_worker_timeout),
+                    max_workers: self.snark_coordinator_max_workers,
+                    auth_token: self.snark_coordinator_auth_token.clone(),
                 }),
             },
             p2p: P2pConfig {
@@ -242,7 +484,7 @@ impl Node {
                 limits: P2pLimits::default().with_max_peers(Some(100)),
             },
             transition_frontier,
-            block_producer: block_producer.clone().map(|(config, _)| config),
+            block_producer: block_producer.clone(),
         };
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
 
@@ -280,6 +522,7 @@ impl Node {
         } else {
             LedgerCtx::default()
         };
+        ledger.set_epoch_ledgers_dir(Path::new(&work_dir).join("epoch_ledgers"));
 
         // TODO(tizoc): Only used for the current workaround to make staged ledger
         // reconstruction async, can be removed when the ledger services are made async
@@ -287,6 +530,29 @@ impl Node {
 
         let ledger_manager = LedgerManager::spawn(ledger);
 
+        let production_log_path = Path::new(&work_dir).join("block_producer_log.jsonl");
+        let production_log = read_production_log(&production_log_path).with_context(|| {
+            format!("failed to read block production log {production_log_path:?}")
+        })?;
+        let snark_pool_path = Path::new(&work_dir).join("snark_pool.json");
+        // Snark pool is only a cache, so the node starts without it if
+        // it can't be read.
+        let snark_pool = read_snark_pool(&snark_pool_path).unwrap_or_else(|error| {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "SnarkPoolRestore",
+                summary = "failed to read snark pool, starting with an empty one",
+                path = format!("{snark_pool_path:?}"),
+                error = error.to_string());
+            Vec::new()
+        });
+        let payout_report_dir = self.payout_report_dir.clone();
+        let payout_report_format = self.payout_report_format;
+        let snark_worker = self.snark_worker;
+        let snark_worker_threads = self.snark_worker_threads.unwrap_or_else(|| {
+            let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+            (cpus / usize::from(self.snarker_workers)).max(1)
+        });
+
         let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .thread_stack_size(64 * 1024 * 1024)
@@ -307,7 +573,10 @@ impl Node {
                 block_producer: None,
                 keypair: libp2p_keypair,
                 rpc: rpc_service,
-                snark_worker_sender: None,
+                snark_worker_kind: snark_worker,
+                snark_worker_threads: Some(snark_worker_threads),
+                snark_workers: Default::default(),
+                snark_pool_writer: Some(SnarkPoolWriter::spawn(snark_pool_path)),
                 stats: Stats::new(),
                 recorder: match record.trim() {
                     "none" => Recorder::None,
@@ -318,11 +587,22 @@ impl Node {
                 invariants_state: Default::default(),
             };
 
-            if let Some((_, keypair)) = block_producer {
-                service.block_producer_start(keypair);
+            if block_producer.is_some() {
+                match remote_signer {
+                    Some(signer) => service.block_producer_start_with_remote_signer(signer),
+                    None => service.block_producer_start(producer_keypairs),
+                }
+                if let Some(bp) = service.block_producer.as_mut() {
+                    bp.set_production_log_path(production_log_path);
+                    if let Some(dir) = payout_report_dir {
+                        bp.set_payout_report(dir, payout_report_format);
+                    }
+                }
             }
 
-            let state = State::new(config, redux::Timestamp::global_now());
+            let mut state = State::new(config, redux::Timestamp::global_now());
+            state.block_producer.production_log_restore(production_log);
+            state.snark_pool.restore_snarks(snark_pool);
             let mut node = ::node::Node::new(state, service, None);
 
             // record initial state.
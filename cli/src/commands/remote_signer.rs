@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use node::account::AccountSecretKey;
+use node::core::log::inner::Level;
+use openmina_node_native::block_producer::remote_signer::{
+    RemoteSigner as Signer, RemoteSignerAddr,
+};
+use openmina_node_native::tracing;
+
+/// Reference remote signer for the block producer.
+///
+/// Holds producer keys and does vrf evaluation and block proving on
+/// behalf of the node started with `--remote-signer`.
+#[derive(Debug, clap::Args)]
+pub struct RemoteSigner {
+    /// Address to listen on. Either `host:port` or a path to the Unix socket.
+    #[arg(long, env = "OPENMINA_REMOTE_SIGNER_ADDR")]
+    pub listen: RemoteSignerAddr,
+
+    /// Producer key file. Can be repeated (or comma separated in env).
+    ///
+    /// MINA_PRIVKEY_PASS must be set to decrypt the keyfiles.
+    #[arg(long, env, value_delimiter = ',', required = true)]
+    pub producer_key: Vec<PathBuf>,
+
+    /// Token that the node must authenticate with. Required when
+    /// listening on a non-loopback tcp address.
+    #[arg(long, env = "OPENMINA_REMOTE_SIGNER_TOKEN")]
+    pub auth_token: Option<String>,
+
+    /// File where proven blocks are recorded, so that the signer refuses
+    /// to produce two different blocks for the same slot, even after restart.
+    #[arg(
+        long,
+        env,
+        default_value = "~/.openmina/remote_signer_proven_blocks.jsonl"
+    )]
+    pub proven_blocks_file: String,
+
+    /// Verbosity level
+    #[arg(long, short, env, default_value = "info")]
+    pub verbosity: Level,
+}
+
+impl RemoteSigner {
+    pub fn run(self) -> anyhow::Result<()> {
+        tracing::initialize(self.verbosity);
+
+        let keypairs = self
+            .producer_key
+            .into_iter()
+            .map(|path| {
+                AccountSecretKey::from_encrypted_file(path.clone())
+                    .with_context(|| format!("failed to decrypt secret key file {path:?}"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for keypair in &keypairs {
+            openmina_core::info!(
+                openmina_core::log::system_time();
+                summary = "remote signer producer key",
+                pub_key = keypair.public_key().to_string(),
+            );
+        }
+
+        let proven_blocks_file = shellexpand::full(&self.proven_blocks_file)?.into_owned();
+        Signer::new(keypairs, self.auth_token, proven_blocks_file.clone().into())
+            .with_context(|| format!("failed to load proven blocks from {proven_blocks_file}"))?
+            .serve(&self.listen)
+            .with_context(|| format!("remote signer failed to listen on {}", self.listen))
+    }
+}
@@ -1,6 +1,9 @@
 pub mod precalculate_block_verifier_index_and_srs;
 pub use precalculate_block_verifier_index_and_srs::PrecalculateBlockVerifierIndexAndSrs;
 
+pub mod worker;
+pub use worker::Worker;
+
 #[derive(Debug, clap::Args)]
 pub struct Snark {
     #[command(subcommand)]
@@ -10,12 +13,14 @@ pub struct Snark {
 #[derive(Debug, clap::Subcommand)]
 pub enum SnarkCommand {
     PrecalculateBlockVerifierIndexAndSrs(PrecalculateBlockVerifierIndexAndSrs),
+    Worker(Worker),
 }
 
 impl Snark {
     pub fn run(self) -> anyhow::Result<()> {
         match self.command {
             SnarkCommand::PrecalculateBlockVerifierIndexAndSrs(v) => v.run(),
+            SnarkCommand::Worker(v) => v.run(),
         }
     }
 }
@@ -0,0 +1,324 @@
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Context;
+use ledger::scan_state::currency::Fee;
+use ledger::scan_state::scan_state::transaction_snark::SokMessage;
+use mina_p2p_messages::v2::{
+    CurrencyFeeStableV1, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+};
+use node::account::AccountPublicKey;
+use node::core::log::inner::Level;
+use node::rpc::{
+    RpcSnarkCoordinatorHeartbeatResponse, RpcSnarkCoordinatorLeaseResponse,
+    RpcSnarkCoordinatorRegisterRequest, RpcSnarkCoordinatorRegisterResponse,
+    RpcSnarkCoordinatorSubmitRequest, RpcSnarkCoordinatorSubmitResponse,
+    RpcSnarkCoordinatorWorkerRequest,
+};
+use node::snark_pool::coordinator::RemoteSnarkWorkerId;
+use openmina_node_native::rust_snark_worker::prove;
+use openmina_node_native::tracing;
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// How often the lease is renewed while proving. Must be well below
+/// coordinator's `--snark-coordinator-lease-timeout`.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// How long to wait before asking for a job again, if there were none.
+const NO_JOBS_DELAY: Duration = Duration::from_secs(5);
+/// Delay before the first retry of a failed request. Doubled on every
+/// further failure, up to `RETRY_DELAY_MAX`.
+const RETRY_DELAY_MIN: Duration = Duration::from_secs(1);
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(60);
+
+/// Remote snark worker for the node started with `--snark-coordinator`.
+///
+/// Registers with the coordinator, then leases jobs, proves them
+/// in-process and submits the proofs. Any number of workers, on one or
+/// more hosts, can work for the same coordinator.
+#[derive(Debug, clap::Args)]
+pub struct Worker {
+    /// Url of the coordinator's http server.
+    #[arg(
+        long,
+        env = "OPENMINA_SNARK_COORDINATOR",
+        default_value = "http://127.0.0.1:3000"
+    )]
+    pub coordinator: Url,
+
+    /// Public key which receives fees for the work of this worker.
+    #[arg(long, env)]
+    pub public_key: AccountPublicKey,
+
+    /// Snark fee, in nanomina.
+    #[arg(long, env, default_value_t = 1_000_000)]
+    pub fee: u64,
+
+    /// Token expected by the coordinator (`--snark-coordinator-auth-token`).
+    #[arg(long, env = "OPENMINA_SNARK_COORDINATOR_AUTH_TOKEN")]
+    pub auth_token: Option<String>,
+
+    /// Verbosity level
+    #[arg(long, short, env, default_value = "info")]
+    pub verbosity: Level,
+}
+
+impl Worker {
+    pub fn run(self) -> anyhow::Result<()> {
+        tracing::initialize(self.verbosity);
+
+        let client = CoordinatorClient {
+            url: self.coordinator,
+            client: reqwest::blocking::Client::new(),
+        };
+        let register = RpcSnarkCoordinatorRegisterRequest {
+            public_key: self.public_key.into(),
+            fee: CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+                self.fee.into(),
+            )),
+            auth_token: self.auth_token.clone(),
+        };
+        // Coordinator checks the token on every request, not only on
+        // registration.
+        let worker_req = |worker_id| RpcSnarkCoordinatorWorkerRequest {
+            worker_id,
+            auth_token: self.auth_token.clone(),
+        };
+
+        let mut worker_id = client.register(&register)?;
+        loop {
+            let (job_id, spec) = match client.post("lease", &worker_req(worker_id))? {
+                RpcSnarkCoordinatorLeaseResponse::Ok { job_id, spec } => (job_id, spec),
+                RpcSnarkCoordinatorLeaseResponse::NoJobs => {
+                    thread::sleep(NO_JOBS_DELAY);
+                    continue;
+                }
+                RpcSnarkCoordinatorLeaseResponse::UnknownWorker => {
+                    // Coordinator was restarted.
+                    worker_id = client.register(&register)?;
+                    continue;
+                }
+                RpcSnarkCoordinatorLeaseResponse::Unauthorized => {
+                    anyhow::bail!("coordinator rejected the auth token")
+                }
+                RpcSnarkCoordinatorLeaseResponse::Err(err) => {
+                    anyhow::bail!("coordinator failed to create job spec: {err:?}")
+                }
+            };
+            let Some((spec, public_key)) = spec.0 else {
+                continue;
+            };
+            openmina_core::info!(
+                openmina_core::log::system_time();
+                summary = "job leased",
+                worker_id = worker_id,
+                job_id = job_id.to_string(),
+            );
+
+            let message =
+                SokMessage::create(Fee::from_u64(spec.fee.as_u64()), (&public_key).into());
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let _ = tx.send(prove(spec.instances, &message));
+            });
+
+            let mut lease_lost = false;
+            let result = loop {
+                match rx.recv_timeout(HEARTBEAT_INTERVAL) {
+                    Ok(result) => break result,
+                    Err(mpsc::RecvTimeoutError::Timeout) if lease_lost => {}
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        // Not retried, the next heartbeat is due soon anyway.
+                        match client.try_post("heartbeat", &worker_req(worker_id)) {
+                            Ok(resp) => {
+                                lease_lost =
+                                    !matches!(resp, RpcSnarkCoordinatorHeartbeatResponse::Ok);
+                            }
+                            Err(err) => openmina_core::warn!(
+                                openmina_core::log::system_time();
+                                summary = "heartbeat failed",
+                                worker_id = worker_id,
+                                error = format!("{err:#}"),
+                            ),
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        anyhow::bail!("prover thread panicked")
+                    }
+                }
+            };
+            if lease_lost {
+                // Proving can't be interrupted, so the work is only dropped
+                // once it's done.
+                openmina_core::warn!(
+                    openmina_core::log::system_time();
+                    summary = "lease lost, dropping the work",
+                    worker_id = worker_id,
+                    job_id = job_id.to_string(),
+                );
+                continue;
+            }
+            let proofs = match result {
+                Ok(proofs) => proofs,
+                Err(err) => {
+                    // Lease will expire and the job re-queued.
+                    openmina_core::error!(
+                        openmina_core::log::system_time();
+                        summary = "proving failed",
+                        worker_id = worker_id,
+                        job_id = job_id.to_string(),
+                        error = format!("{err:?}"),
+                    );
+                    continue;
+                }
+            };
+
+            let submit = RpcSnarkCoordinatorSubmitRequest {
+                worker_id,
+                proofs: Arc::new(proofs),
+                auth_token: self.auth_token.clone(),
+            };
+            let resp: RpcSnarkCoordinatorSubmitResponse = client.post("submit", &submit)?;
+            if let RpcSnarkCoordinatorSubmitResponse::Unauthorized = resp {
+                anyhow::bail!("coordinator rejected the auth token");
+            }
+            openmina_core::info!(
+                openmina_core::log::system_time();
+                summary = "work submitted",
+                worker_id = worker_id,
+                job_id = job_id.to_string(),
+                response = format!("{resp:?}"),
+            );
+        }
+    }
+}
+
+struct CoordinatorClient {
+    url: Url,
+    client: reqwest::blocking::Client,
+}
+
+impl CoordinatorClient {
+    fn register(
+        &self,
+        req: &RpcSnarkCoordinatorRegisterRequest,
+    ) -> anyhow::Result<RemoteSnarkWorkerId> {
+        let mut delay = RETRY_DELAY_MIN;
+        loop {
+            match self.post("register", req)? {
+                RpcSnarkCoordinatorRegisterResponse::Ok { worker_id } => {
+                    openmina_core::info!(
+                        openmina_core::log::system_time();
+                        summary = "registered with the coordinator",
+                        worker_id = worker_id,
+                    );
+                    return Ok(worker_id);
+                }
+                RpcSnarkCoordinatorRegisterResponse::Disabled => {
+                    anyhow::bail!("node at {} is not running as a snark coordinator", self.url)
+                }
+                RpcSnarkCoordinatorRegisterResponse::Unauthorized => {
+                    anyhow::bail!("coordinator at {} rejected the auth token", self.url)
+                }
+                RpcSnarkCoordinatorRegisterResponse::TooManyWorkers => {
+                    openmina_core::warn!(
+                        openmina_core::log::system_time();
+                        summary = "coordinator has too many workers, retrying",
+                        delay = format!("{delay:?}"),
+                    );
+                    thread::sleep(delay);
+                    delay = next_retry_delay(delay);
+                }
+            }
+        }
+    }
+
+    /// Sends the request, retrying with a backoff while the coordinator
+    /// is unreachable or fails with a server error.
+    fn post<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        req: &Req,
+    ) -> anyhow::Result<Res> {
+        let mut delay = RETRY_DELAY_MIN;
+        loop {
+            match self.send(endpoint, req)? {
+                Ok(res) => return Ok(res),
+                Err(err) if is_transient(&err) => {
+                    openmina_core::warn!(
+                        openmina_core::log::system_time();
+                        summary = "request to the coordinator failed, retrying",
+                        endpoint = endpoint,
+                        delay = format!("{delay:?}"),
+                        error = err.to_string(),
+                    );
+                    thread::sleep(delay);
+                    delay = next_retry_delay(delay);
+                }
+                Err(err) => {
+                    return Err(err).with_context(|| format!("request to {endpoint} failed"))
+                }
+            }
+        }
+    }
+
+    /// Sends the request once.
+    fn try_post<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        req: &Req,
+    ) -> anyhow::Result<Res> {
+        self.send(endpoint, req)?
+            .with_context(|| format!("request to {endpoint} failed"))
+    }
+
+    fn send<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        req: &Req,
+    ) -> anyhow::Result<reqwest::Result<Res>> {
+        let url = self.url.join(&format!("snarker/coordinator/{endpoint}"))?;
+        Ok(self
+            .client
+            .post(url)
+            .json(req)
+            .send()
+            .and_then(|res| res.error_for_status())
+            .and_then(|res| res.json()))
+    }
+}
+
+/// Whether the request may succeed if retried later, e.g. the coordinator
+/// is restarting or overloaded.
+fn is_transient(err: &reqwest::Error) -> bool {
+    match err.status() {
+        Some(status) => {
+            status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        None => err.is_connect() || err.is_timeout() || err.is_request() || err.is_body(),
+    }
+}
+
+fn next_retry_delay(delay: Duration) -> Duration {
+    (delay * 2).min(RETRY_DELAY_MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{next_retry_delay, RETRY_DELAY_MAX, RETRY_DELAY_MIN};
+
+    #[test]
+    fn retry_delay_backoff() {
+        let delays = std::iter::successors(Some(RETRY_DELAY_MIN), |d| Some(next_retry_delay(*d)))
+            .take(9)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            [1, 2, 4, 8, 16, 32, 60, 60, 60].map(Duration::from_secs)
+        );
+        assert!(delays.iter().all(|d| *d <= RETRY_DELAY_MAX));
+    }
+}
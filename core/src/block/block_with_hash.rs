@@ -68,8 +68,9 @@ impl<T: AsRef<Block>> BlockWithHash<T> {
         global_slot_diff(self.header())
     }
 
-    pub fn timestamp(&self) -> Timestamp {
-        timestamp(self.header())
+    /// Start time of the block's slot, given the slot duration of the chain.
+    pub fn timestamp(&self, block_window_duration_ms: u64) -> Timestamp {
+        timestamp(self.header(), block_window_duration_ms)
     }
 
     pub fn genesis_timestamp(&self) -> Timestamp {
@@ -183,8 +184,9 @@ impl<T: AsRef<BlockHeader>> BlockHeaderWithHash<T> {
         global_slot_diff(self.header())
     }
 
-    pub fn timestamp(&self) -> Timestamp {
-        timestamp(self.header())
+    /// Start time of the block's slot, given the slot duration of the chain.
+    pub fn timestamp(&self, block_window_duration_ms: u64) -> Timestamp {
+        timestamp(self.header(), block_window_duration_ms)
     }
 
     pub fn genesis_timestamp(&self) -> Timestamp {
@@ -247,12 +249,10 @@ fn global_slot_diff(header: &BlockHeader) -> u32 {
         .saturating_sub(s.global_slot())
 }
 
-fn timestamp(header: &BlockHeader) -> Timestamp {
+fn timestamp(header: &BlockHeader, block_window_duration_ms: u64) -> Timestamp {
     let genesis_timestamp = constants(header).genesis_state_timestamp.0.as_u64();
     let slot = global_slot_since_genesis(header) as u64;
-    // FIXME: this calculation must use values from the protocol constants,
-    // now it assumes 3 minutes blocks.
-    let time_ms = genesis_timestamp + slot * 3 * 60 * 1000;
+    let time_ms = genesis_timestamp + slot * block_window_duration_ms;
     Timestamp::new(time_ms * 1_000_000)
 }
 
@@ -6,11 +6,6 @@ use serde::{Deserialize, Serialize};
 use crate::constants::CONSTRAINT_CONSTANTS;
 pub use crate::constants::{checkpoint_window_size_in_slots, grace_period_end, slots_per_window};
 
-// TODO get constants from elsewhere
-const GRACE_PERIOD_END: u32 = 1440;
-const SUB_WINDOWS_PER_WINDOW: u32 = 11;
-const SLOTS_PER_SUB_WINDOW: u32 = 7;
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ConsensusShortRangeForkDecisionReason {
     ChainLength,
@@ -59,19 +54,33 @@ pub fn is_short_range_fork(a: &MinaConsensusState, b: &MinaConsensusState) -> bo
 
 /// Relative minimum window density.
 ///
+/// Minimum window density of `b1`'s chain, projected to the global slot of
+/// the more recent one of the two blocks. Sub-windows between the slot of
+/// `b1` and the projected slot count as empty, since `b1`'s chain has no
+/// blocks there.
+///
 /// See [specification](https://github.com/MinaProtocol/mina/tree/develop/docs/specs/consensus#5412-relative-minimum-window-density)
-pub fn relative_min_window_density(b1: &MinaConsensusState, b2: &MinaConsensusState) -> u32 {
+pub fn relative_min_window_density(
+    b1: &MinaConsensusState,
+    b2: &MinaConsensusState,
+    constants: &v2::MinaBaseProtocolConstantsCheckedValueStableV1,
+) -> u32 {
     use std::cmp::{max, min};
 
-    let max_slot = max(global_slot(b1), global_slot(b2));
-
-    if max_slot < GRACE_PERIOD_END {
+    // Grace-period rule
+    let max_slot_since_genesis = max(global_slot_since_genesis(b1), global_slot_since_genesis(b2));
+    if max_slot_since_genesis < grace_period_end(constants) {
         return b1.min_window_density.as_u32();
     }
 
+    let sub_windows_per_window = CONSTRAINT_CONSTANTS.sub_windows_per_window as u32;
+    let slots_per_sub_window = constants.slots_per_sub_window.as_u32();
+    let b1_sub_window = global_slot(b1) / slots_per_sub_window;
+    let max_sub_window = max(global_slot(b1), global_slot(b2)) / slots_per_sub_window;
+
     let projected_window = {
         // Compute shift count
-        let shift_count = (max_slot - global_slot(b1) - 1).clamp(0, SUB_WINDOWS_PER_WINDOW);
+        let shift_count = min(max_sub_window - b1_sub_window, sub_windows_per_window);
 
         // Initialize projected window
         let mut projected_window = b1
@@ -81,32 +90,32 @@ pub fn relative_min_window_density(b1: &MinaConsensusState, b2: &MinaConsensusSt
             .collect::<Vec<_>>();
 
         // Ring-shift
-        let mut i = relative_sub_window_from_global_slot(global_slot(b1));
-        for _ in 0..=shift_count {
-            i = (i + 1) % SUB_WINDOWS_PER_WINDOW;
+        let mut i = b1_sub_window % sub_windows_per_window;
+        for _ in 0..shift_count {
+            i = (i + 1) % sub_windows_per_window;
             projected_window[i as usize] = 0;
         }
 
         projected_window
     };
 
-    let projected_window_density = density(projected_window);
+    let projected_window_density = density(&projected_window);
 
     min(b1.min_window_density.as_u32(), projected_window_density)
 }
 
-fn density(projected_window: Vec<u32>) -> u32 {
+fn density(projected_window: &[u32]) -> u32 {
     projected_window.iter().sum()
 }
 
-fn relative_sub_window_from_global_slot(global_slot: u32) -> u32 {
-    (global_slot / SLOTS_PER_SUB_WINDOW) % SUB_WINDOWS_PER_WINDOW
-}
-
 fn global_slot(b: &MinaConsensusState) -> u32 {
     b.curr_global_slot_since_hard_fork.slot_number.as_u32()
 }
 
+fn global_slot_since_genesis(b: &MinaConsensusState) -> u32 {
+    b.global_slot_since_genesis.as_u32()
+}
+
 pub fn short_range_fork_take(
     tip_cs: &MinaConsensusState,
     candidate_cs: &MinaConsensusState,
@@ -135,17 +144,26 @@ pub fn short_range_fork_take(
     (candidate_hash > tip_hash, StateHash)
 }
 
+/// Chain selection for long range forks.
+///
+/// Chains are compared by their relative minimum window densities first,
+/// so that a chain that was built in private (e.g. by an attacker holding
+/// keys of a past stake distribution) loses to the honest chain, which has
+/// a higher participation.
+///
+/// `constants` must be protocol constants of the chain that we follow.
 pub fn long_range_fork_take(
     tip_cs: &MinaConsensusState,
     candidate_cs: &MinaConsensusState,
     tip_hash: &StateHash,
     candidate_hash: &StateHash,
+    constants: &v2::MinaBaseProtocolConstantsCheckedValueStableV1,
 ) -> (bool, ConsensusLongRangeForkDecisionReason) {
     use std::cmp::Ordering::*;
     use ConsensusLongRangeForkDecisionReason::*;
 
-    let tip_density = relative_min_window_density(tip_cs, candidate_cs);
-    let candidate_density = relative_min_window_density(candidate_cs, tip_cs);
+    let tip_density = relative_min_window_density(tip_cs, candidate_cs, constants);
+    let candidate_density = relative_min_window_density(candidate_cs, tip_cs, constants);
     match candidate_density.cmp(&tip_density) {
         Greater => return (true, SubWindowDensity),
         Less => return (false, SubWindowDensity),
@@ -176,11 +194,12 @@ pub fn consensus_take(
     candidate_cs: &MinaConsensusState,
     tip_hash: &StateHash,
     candidate_hash: &StateHash,
+    constants: &v2::MinaBaseProtocolConstantsCheckedValueStableV1,
 ) -> bool {
     if is_short_range_fork(tip_cs, candidate_cs) {
         short_range_fork_take(tip_cs, candidate_cs, tip_hash, candidate_hash).0
     } else {
-        long_range_fork_take(tip_cs, candidate_cs, tip_hash, candidate_hash).0
+        long_range_fork_take(tip_cs, candidate_cs, tip_hash, candidate_hash, constants).0
     }
 }
 
@@ -217,8 +236,15 @@ pub fn relative_sub_window(global_sub_window: u32) -> u32 {
 
 #[cfg(test)]
 mod tests {
-    use super::{long_range_fork_take, short_range_fork_take};
-    use mina_p2p_messages::v2::{MinaStateProtocolStateValueStableV2, StateHash};
+    use super::{
+        grace_period_end, long_range_fork_take, relative_min_window_density, short_range_fork_take,
+    };
+    use mina_p2p_messages::v2::{
+        ConsensusProofOfStakeDataConsensusStateValueStableV2 as ConsensusState,
+        MinaBaseProtocolConstantsCheckedValueStableV1, MinaNumbersGlobalSlotSinceGenesisMStableV1,
+        MinaNumbersGlobalSlotSinceHardForkMStableV1, MinaStateProtocolStateValueStableV2,
+        StateHash,
+    };
 
     macro_rules! fork_file {
         ($prefix:expr, $tip:expr, $cnd:expr, $suffix:expr) => {
@@ -249,6 +275,7 @@ mod tests {
                 &cnd.body.consensus_state,
                 &tip_hash,
                 &cnd_hash,
+                &tip.body.constants,
             );
             assert_eq!(take, $decision);
         };
@@ -278,7 +305,7 @@ mod tests {
                 concat!("short-take-", $prefix),
                 $tip,
                 $cnd,
-                short_range_fork_take,
+                short_range_fork_take_ignore_constants,
                 true
             );
         };
@@ -288,12 +315,22 @@ mod tests {
                 concat!("short-keep-", $prefix),
                 $tip,
                 $cnd,
-                short_range_fork_take,
+                short_range_fork_take_ignore_constants,
                 false
             );
         };
     }
 
+    fn short_range_fork_take_ignore_constants(
+        tip_cs: &ConsensusState,
+        candidate_cs: &ConsensusState,
+        tip_hash: &StateHash,
+        candidate_hash: &StateHash,
+        _constants: &MinaBaseProtocolConstantsCheckedValueStableV1,
+    ) -> (bool, super::ConsensusShortRangeForkDecisionReason) {
+        short_range_fork_take(tip_cs, candidate_cs, tip_hash, candidate_hash)
+    }
+
     #[test]
     fn long_range_fork() {
         fork_test!(
@@ -331,4 +368,41 @@ mod tests {
                 "3NKLEnUBTAhC95XEdJpLvJPqAUuvkC176tFKyLDcXUcofXXgQUvY"
         );
     }
+
+    #[test]
+    fn relative_min_window_density_projection() {
+        let tip_str = include_str!(fork_file!(
+            "long-take-density-92-97",
+            "3NLESd9gzU52bDWSXL5uUAYbCojHXSVdeBX4sCMF3V8Ns9D1Sriy",
+            "3NLQfKJ4kBagLgmiwyiVw9zbi53tiNy8TNu2ua1jmCyEecgbBJoN",
+            "tip"
+        ));
+        let body = serde_json::from_str::<MinaStateProtocolStateValueStableV2>(tip_str)
+            .unwrap()
+            .body;
+        let constants = body.constants;
+        let b1 = body.consensus_state;
+        // densities: [1, 3, 1, 1, 5, 4, 7, 6, 7, 5, 4], min density: 40,
+        // slot 92 is in the sub-window 2 (relative).
+        assert_eq!(b1.min_window_density.as_u32(), 40);
+
+        let b2_at = |sub_windows_ahead: u32| {
+            let mut b2 = b1.clone();
+            let slot = b1.curr_global_slot_since_hard_fork.slot_number.as_u32()
+                + sub_windows_ahead * constants.slots_per_sub_window.as_u32();
+            b2.curr_global_slot_since_hard_fork.slot_number =
+                MinaNumbersGlobalSlotSinceHardForkMStableV1::SinceHardFork(slot.into());
+            b2.global_slot_since_genesis = MinaNumbersGlobalSlotSinceGenesisMStableV1::SinceGenesis(
+                grace_period_end(&constants).into(),
+            );
+            b2
+        };
+
+        // same sub-window, nothing gets projected out.
+        assert_eq!(relative_min_window_density(&b1, &b2_at(0), &constants), 40);
+        // sub-windows 3, 4 and 5 are empty in the projected window.
+        assert_eq!(relative_min_window_density(&b1, &b2_at(3), &constants), 34);
+        // whole window is empty.
+        assert_eq!(relative_min_window_density(&b1, &b2_at(20), &constants), 0);
+    }
 }
@@ -137,6 +137,78 @@ pub trait ActionEvent {
         T: EventContext;
 }
 
+pub const BLOCK_LIFECYCLE_TARGET: &str = "openmina_core::log::block_lifecycle";
+
+#[derive(Debug, Clone, Copy)]
+pub enum BlockLifecycleStage {
+    Produced,
+    Received,
+    Verified,
+    Applied,
+    BestTip,
+    Broadcast,
+}
+
+impl BlockLifecycleStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Produced => "produced",
+            Self::Received => "received",
+            Self::Verified => "verified",
+            Self::Applied => "applied",
+            Self::BestTip => "best_tip",
+            Self::Broadcast => "broadcast",
+        }
+    }
+}
+
+/// Records the block reaching a stage of its lifecycle as a span with the
+/// node id and the block hash attached, so that the block can be followed
+/// across the cluster.
+///
+/// Spans start at the block's slot start and end when the block reaches
+/// the stage on this node, so the span's duration, also recorded as
+/// `since_slot_start_ms`, is the latency of the stage. The time spent in
+/// a stage is the difference of latencies of consecutive stages.
+pub fn block_lifecycle<T>(
+    context: &T,
+    block_hash: &BlockHash,
+    stage: BlockLifecycleStage,
+    slot_start: redux::Timestamp,
+) where
+    T: EventContext,
+{
+    let name = format!("block_{}", stage.as_str());
+    let since_slot_start_ms = context
+        .timestamp()
+        .checked_sub(slot_start)
+        .map_or(0, |dur| dur.as_millis() as u64);
+    let span = tracing::info_span!(
+        target: BLOCK_LIFECYCLE_TARGET,
+        "block",
+        otel.name = name.as_str(),
+        time = context.time(),
+        node_id = context.node_id(),
+        block_hash = %block_hash,
+        stage = stage.as_str(),
+        slot_start = u64::from(slot_start),
+        since_slot_start_ms,
+    );
+    span.in_scope(|| {
+        tracing::info!(
+            target: BLOCK_LIFECYCLE_TARGET,
+            time = context.time(),
+            node_id = context.node_id(),
+            kind = "BlockLifecycle",
+            summary = format!("block {}: {block_hash}", stage.as_str()),
+            block_hash = %block_hash,
+            since_slot_start_ms,
+        )
+    });
+}
+
 use tracing::Value;
 
+use crate::block::BlockHash;
+
 pub use crate::{debug, error, info, trace, warn};
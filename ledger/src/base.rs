@@ -92,6 +92,9 @@ pub trait BaseLedger {
     /// Get all of the tokens for which a public key has accounts.
     fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId>;
 
+    /// Get all of the accounts delegating to `delegate`.
+    fn delegators(&self, delegate: CompressedPubKey) -> HashSet<AccountId>;
+
     fn location_of_account(&self, account_id: &AccountId) -> Option<Address>;
 
     fn location_of_account_batch(
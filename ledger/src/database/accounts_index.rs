@@ -0,0 +1,82 @@
+use std::collections::{HashMap, HashSet};
+
+use mina_hasher::Fp;
+use mina_signer::CompressedPubKey;
+
+use crate::{Account, AccountId, TokenId};
+
+/// `CompressedPubKey` doesn't implement `Hash`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PubKeyKey {
+    x: Fp,
+    is_odd: bool,
+}
+
+impl From<&CompressedPubKey> for PubKeyKey {
+    fn from(pk: &CompressedPubKey) -> Self {
+        Self {
+            x: pk.x,
+            is_odd: pk.is_odd,
+        }
+    }
+}
+
+/// Secondary indexes over the accounts of a ledger.
+///
+/// They allow to find the token accounts of a public key, or the delegators
+/// of a key, without scanning the whole ledger.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AccountsIndex {
+    /// Public key -> tokens for which the key has an account
+    tokens: HashMap<PubKeyKey, HashSet<TokenId>>,
+    /// Delegate -> accounts delegating to it
+    delegators: HashMap<PubKeyKey, HashSet<AccountId>>,
+}
+
+impl AccountsIndex {
+    pub fn insert(&mut self, account: &Account) {
+        self.tokens
+            .entry((&account.public_key).into())
+            .or_default()
+            .insert(account.token_id.clone());
+
+        if let Some(delegate) = account.delegate.as_ref() {
+            self.delegators
+                .entry(delegate.into())
+                .or_default()
+                .insert(account.id());
+        }
+    }
+
+    pub fn remove(&mut self, account: &Account) {
+        let public_key = PubKeyKey::from(&account.public_key);
+        if let Some(tokens) = self.tokens.get_mut(&public_key) {
+            tokens.remove(&account.token_id);
+            if tokens.is_empty() {
+                self.tokens.remove(&public_key);
+            }
+        }
+
+        if let Some(delegate) = account.delegate.as_ref().map(PubKeyKey::from) {
+            if let Some(delegators) = self.delegators.get_mut(&delegate) {
+                delegators.remove(&account.id());
+                if delegators.is_empty() {
+                    self.delegators.remove(&delegate);
+                }
+            }
+        }
+    }
+
+    pub fn tokens(&self, public_key: &CompressedPubKey) -> Option<&HashSet<TokenId>> {
+        self.tokens.get(&PubKeyKey::from(public_key))
+    }
+
+    pub fn delegators(&self, delegate: &CompressedPubKey) -> Option<&HashSet<AccountId>> {
+        self.delegators.get(&PubKeyKey::from(delegate))
+    }
+
+    pub fn clear(&mut self) {
+        self.tokens.clear();
+        self.delegators.clear();
+    }
+}
@@ -164,6 +164,10 @@ impl BaseLedger for Database<V2> {
         self.with(|this| this.tokens(public_key))
     }
 
+    fn delegators(&self, delegate: CompressedPubKey) -> HashSet<AccountId> {
+        self.with(|this| this.delegators(delegate))
+    }
+
     fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
         self.with(|this| this.location_of_account(account_id))
     }
@@ -662,6 +666,32 @@ export function performance_now() {
         assert_eq!(root_hash_1, root_hash_3);
     }
 
+    /// Hashes computed in parallel are the same as the ones computed recursively
+    #[test]
+    fn test_root_hash_parallel_same_as_recursive() {
+        const DEPTH: usize = 20;
+
+        fn recursive_root_hash(db: &Database<V2>) -> Fp {
+            let mut db = db.with(|this| this.clone());
+            db.hashes_matrix.clear();
+            let last_account = db.last_filled().unwrap();
+            db.emulate_tree_recursive(Address::root(), &last_account)
+        }
+
+        let mut db = Database::<V2>::create(DEPTH as u8);
+        for account in (0..1000).map(|_| Account::rand()) {
+            db.get_or_create_account(account.id(), account).unwrap();
+        }
+        assert_eq!(db.merkle_root(), recursive_root_hash(&db));
+
+        // Only the dirty paths are recomputed
+        for index in [0, 1, 500, 999] {
+            let addr = Address::from_index(AccountIndex(index), DEPTH);
+            db.set(addr, Box::new(Account::rand()));
+        }
+        assert_eq!(db.merkle_root(), recursive_root_hash(&db));
+    }
+
     // /// An empty tree produces the same hash than a tree full of empty accounts
     // #[test]
     // fn test_root_hash_legacy() {
@@ -8,12 +8,12 @@ use mina_hasher::Fp;
 use mina_signer::CompressedPubKey;
 
 use crate::{
-    next_uuid, Account, AccountId, AccountIndex, AccountLegacy, Address, AddressIterator,
-    BaseLedger, Direction, GetOrCreated, HashesMatrix, MerklePath, TokenId, TreeVersion, Uuid, V1,
-    V2,
+    next_uuid, tree::DirtyNodes, Account, AccountId, AccountIndex, AccountLegacy, Address,
+    AddressIterator, BaseLedger, Direction, GetOrCreated, HashesMatrix, MerklePath, TokenId,
+    TreeVersion, Uuid, V1, V2,
 };
 
-use super::DatabaseError;
+use super::{AccountsIndex, DatabaseError};
 
 #[derive(Clone)]
 pub struct DatabaseImpl<T: TreeVersion> {
@@ -21,6 +21,7 @@ pub struct DatabaseImpl<T: TreeVersion> {
     pub hashes_matrix: HashesMatrix,
     id_to_addr: HashMap<AccountId, Address>,
     token_to_account: HashMap<T::TokenId, AccountId>,
+    accounts_index: AccountsIndex,
     depth: u8,
     last_location: Option<Address>,
     naccounts: usize,
@@ -56,6 +57,7 @@ impl DatabaseImpl<V2> {
             accounts: self.accounts.clone(),
             id_to_addr: self.id_to_addr.clone(),
             token_to_account: self.token_to_account.clone(),
+            accounts_index: self.accounts_index.clone(),
             depth: self.depth,
             last_location: self.last_location.clone(),
             naccounts: self.naccounts,
@@ -97,6 +99,7 @@ impl DatabaseImpl<V2> {
         };
 
         assert_eq!(location.to_index(), self.accounts.len());
+        self.accounts_index.insert(&account);
         self.accounts.push(Some(account));
 
         // let root = self.root.as_mut().unwrap();
@@ -148,7 +151,50 @@ impl DatabaseImpl<V2> {
             .last_filled()
             .unwrap_or_else(|| Address::first(self.depth as usize));
 
-        self.emulate_tree_recursive(addr, &last_account)
+        self.emulate_tree_parallel(addr, &last_account)
+    }
+
+    /// Same as [`Self::emulate_tree_recursive`], but the missing hashes are
+    /// computed level by level, in parallel.
+    pub fn emulate_tree_parallel(&mut self, addr: Address, last_account: &Address) -> Fp {
+        if let Some(hash) = self.hashes_matrix.get(&addr) {
+            return *hash;
+        }
+
+        let mut dirty = DirtyNodes::new(self.depth as usize);
+        self.collect_dirty_nodes(addr.clone(), last_account, &mut dirty);
+
+        let accounts = &self.accounts;
+        self.hashes_matrix.compute_dirty_hashes(dirty, |addr| {
+            let index = addr.to_index().0 as usize;
+            accounts[index].as_ref().unwrap().hash()
+        });
+
+        match self.hashes_matrix.get(&addr) {
+            Some(hash) => *hash,
+            // Leaf without an account
+            None => self.hashes_matrix.empty_hash_at_height(0),
+        }
+    }
+
+    /// Walks the subtree at `addr` (which must not have a hash), down to
+    /// the nodes which have one, or are after `last_account`.
+    fn collect_dirty_nodes(&self, addr: Address, last_account: &Address, dirty: &mut DirtyNodes) {
+        let height = self.depth as usize - addr.length();
+
+        if height == 0 {
+            if self.get_account_ref(addr.clone()).is_some() {
+                dirty.push(addr, height);
+            }
+            return;
+        }
+
+        for child in [addr.child_left(), addr.child_right()] {
+            if self.hashes_matrix.get(&child).is_none() && child.is_before(last_account) {
+                self.collect_dirty_nodes(child, last_account, dirty);
+            }
+        }
+        dirty.push(addr, height);
     }
 
     // fn emulate_recursive(&mut self, addr: Address, nremaining: &mut usize) -> Fp {
@@ -341,6 +387,7 @@ impl DatabaseImpl<V2> {
             naccounts: 0,
             id_to_addr: HashMap::with_capacity(NACCOUNTS),
             token_to_account: HashMap::with_capacity(NACCOUNTS),
+            accounts_index: AccountsIndex::default(),
             uuid,
             directory: path,
             hashes_matrix: HashesMatrix::new(depth as usize),
@@ -530,14 +577,6 @@ impl BaseLedger for DatabaseImpl<V2> {
     }
 
     fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId> {
-        let mut set = HashSet::with_capacity(100);
-
-        for account in self.accounts.iter().filter_map(Option::as_ref) {
-            if account.public_key == public_key {
-                set.insert(account.token_id.clone());
-            }
-        }
-
         // let root = match self.root.as_ref() {
         //     Some(root) => root,
         //     None => return HashSet::default(),
@@ -553,7 +592,17 @@ impl BaseLedger for DatabaseImpl<V2> {
         //     ControlFlow::Continue(())
         // });
 
-        set
+        self.accounts_index
+            .tokens(&public_key)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn delegators(&self, delegate: CompressedPubKey) -> HashSet<AccountId> {
+        self.accounts_index
+            .delegators(&delegate)
+            .cloned()
+            .unwrap_or_default()
     }
 
     fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
@@ -687,6 +736,7 @@ impl BaseLedger for DatabaseImpl<V2> {
             let id = account.id();
             self.id_to_addr.remove(&id);
             self.token_to_account.remove(&id.token_id);
+            self.accounts_index.remove(&account);
         } else {
             self.naccounts += 1;
         }
@@ -694,6 +744,7 @@ impl BaseLedger for DatabaseImpl<V2> {
         self.token_to_account
             .insert(account.token_id.clone(), id.clone());
         self.id_to_addr.insert(id, addr.clone());
+        self.accounts_index.insert(&account);
         self.accounts[index] = Some(*account);
         // root.add_account_on_path(account, addr.iter());
 
@@ -823,6 +874,7 @@ impl BaseLedger for DatabaseImpl<V2> {
             let id = account.id();
             self.id_to_addr.remove(&id);
             self.token_to_account.remove(&id.token_id);
+            self.accounts_index.remove(&account);
 
             self.naccounts = self
                 .naccounts
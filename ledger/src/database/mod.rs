@@ -1,6 +1,8 @@
 #![allow(clippy::module_inception)]
 
+mod accounts_index;
 mod database;
 mod database_impl;
 
+pub(crate) use accounts_index::AccountsIndex;
 pub use database::*;
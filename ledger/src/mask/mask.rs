@@ -102,6 +102,7 @@ impl Mask {
                 owning_account: Default::default(),
                 token_to_account: Default::default(),
                 id_to_addr: Default::default(),
+                accounts_index: Default::default(),
                 last_location: None,
                 depth: depth as u8,
                 childs: HashMap::with_capacity(2),
@@ -341,6 +342,10 @@ impl BaseLedger for Mask {
         self.with(|this| this.tokens(public_key))
     }
 
+    fn delegators(&self, delegate: CompressedPubKey) -> HashSet<AccountId> {
+        self.with(|this| this.delegators(delegate))
+    }
+
     fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
         self.with(|this| this.location_of_account(account_id))
     }
@@ -659,6 +664,112 @@ mod tests {
         }
     }
 
+    // Hashes of the accounts spread across the masks are the same as in a
+    // database with all of them
+    #[test]
+    fn test_masks_root_hash_same_as_database() {
+        const DEPTH: usize = 10;
+
+        let (mut root, mut layer1, mut layer2) = new_chain(DEPTH);
+        let mut db = Database::<V2>::create(DEPTH as u8);
+
+        for index in 0..300 {
+            let account = Account::rand();
+            let mask = match index / 100 {
+                0 => &mut root,
+                1 => &mut layer1,
+                _ => &mut layer2,
+            };
+            mask.get_or_create_account(account.id(), account.clone())
+                .unwrap();
+            db.get_or_create_account(account.id(), account).unwrap();
+        }
+
+        let expected = db.with(|db| {
+            let last_account = db.last_filled().unwrap();
+            db.emulate_tree_recursive(Address::root(), &last_account)
+        });
+        assert_eq!(layer2.merkle_root(), expected);
+    }
+
+    // Delegators and tokens found with the accounts indexes are the same as
+    // the ones found by scanning the accounts
+    #[test]
+    fn test_masks_accounts_index() {
+        const DEPTH: usize = 10;
+
+        let (mut root, mut layer1, mut layer2) = new_chain(DEPTH);
+        let delegate = Account::rand().public_key;
+
+        for index in 0..300 {
+            let mut account = Account::rand();
+            if index % 3 == 0 {
+                account.delegate = Some(delegate.clone());
+            }
+            let mask = match index / 100 {
+                0 => &mut root,
+                1 => &mut layer1,
+                _ => &mut layer2,
+            };
+            mask.get_or_create_account(account.id(), account).unwrap();
+        }
+
+        // A delegator of the root changes its delegate in the top mask
+        let addr = Address::from_index(AccountIndex(0), DEPTH);
+        let mut account = layer2.get(addr.clone()).unwrap();
+        account.delegate = None;
+        layer2.set(addr, account);
+
+        // A public key of the middle mask gets another token account
+        let addr = Address::from_index(AccountIndex(150), DEPTH);
+        let mut account = *layer1.get(addr).unwrap();
+        let public_key = account.public_key.clone();
+        account.token_id = TokenId::from(42);
+        layer2.get_or_create_account(account.id(), account).unwrap();
+
+        // Delegators already owned by a mask are set again, in the mask and in
+        // its child, keeping their delegate
+        let addr = Address::from_index(AccountIndex(102), DEPTH);
+        let account = layer1.get(addr.clone()).unwrap();
+        let delegator = account.id();
+        assert_eq!(account.delegate.as_ref(), Some(&delegate));
+        for _ in 0..2 {
+            layer1.set(addr.clone(), account.clone());
+            layer2.set(addr.clone(), account.clone());
+        }
+
+        let scan_delegators = |mask: &Mask| {
+            let mut set = HashSet::new();
+            mask.iter(|account| {
+                if account.delegate.as_ref() == Some(&delegate) {
+                    set.insert(account.id());
+                }
+            });
+            set
+        };
+
+        assert_eq!(layer2.delegators(delegate.clone()).len(), 99);
+        for mask in [&root, &layer1, &layer2] {
+            assert_eq!(mask.delegators(delegate.clone()), scan_delegators(mask));
+        }
+        assert_eq!(layer2.tokens(public_key.clone()).len(), 2);
+        assert_eq!(layer1.tokens(public_key.clone()).len(), 1);
+        assert!(layer1.delegators(delegate.clone()).contains(&delegator));
+        assert!(layer2.delegators(delegate.clone()).contains(&delegator));
+
+        // Indexes are propagated to the parents on commit
+        layer2.commit();
+        assert_eq!(
+            layer1.delegators(delegate.clone()),
+            scan_delegators(&layer1)
+        );
+        assert_eq!(layer1.tokens(public_key.clone()).len(), 2);
+        assert!(layer1.delegators(delegate.clone()).contains(&delegator));
+        layer1.commit();
+        assert_eq!(root.delegators(delegate.clone()), scan_delegators(&root));
+        assert_eq!(root.tokens(public_key).len(), 2);
+    }
+
     #[test]
     fn test_cached_merkle_path() {
         let (mut root, mask) = new_instances(DEPTH);
@@ -10,9 +10,10 @@ use crate::{
     account::{Account, AccountId, TokenId},
     address::{Address, AddressIterator, Direction},
     base::{AccountIndex, BaseLedger, GetOrCreated, MerklePath, Uuid},
-    database::{Database, DatabaseError},
+    database::{AccountsIndex, Database, DatabaseError},
     mask::UnregisterBehavior,
     next_uuid,
+    tree::DirtyNodes,
     tree_version::{TreeVersion, V2},
     HashesMatrix,
 };
@@ -29,6 +30,7 @@ pub enum MaskImpl {
         owning_account: HashMap<AccountIndex, Account>,
         token_to_account: HashMap<TokenId, AccountId>,
         id_to_addr: HashMap<AccountId, Address>,
+        accounts_index: AccountsIndex,
         last_location: Option<Address>,
         depth: u8,
         childs: HashMap<Uuid, Mask>,
@@ -41,6 +43,7 @@ pub enum MaskImpl {
         owning_account: HashMap<AccountIndex, Account>,
         token_to_account: HashMap<TokenId, AccountId>,
         id_to_addr: HashMap<AccountId, Address>,
+        accounts_index: AccountsIndex,
         last_location: Option<Address>,
         hashes: HashesMatrix,
         uuid: Uuid,
@@ -70,6 +73,7 @@ impl Clone for MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 depth,
                 childs,
@@ -80,6 +84,7 @@ impl Clone for MaskImpl {
                 owning_account: owning_account.clone(),
                 token_to_account: token_to_account.clone(),
                 id_to_addr: id_to_addr.clone(),
+                accounts_index: accounts_index.clone(),
                 last_location: last_location.clone(),
                 depth: *depth,
                 childs: childs.clone(),
@@ -92,6 +97,7 @@ impl Clone for MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 hashes,
                 uuid: _,
@@ -101,6 +107,7 @@ impl Clone for MaskImpl {
                 owning_account: owning_account.clone(),
                 token_to_account: token_to_account.clone(),
                 id_to_addr: id_to_addr.clone(),
+                accounts_index: accounts_index.clone(),
                 last_location: last_location.clone(),
                 hashes: hashes.clone(),
                 uuid: next_uuid(),
@@ -123,6 +130,7 @@ impl std::fmt::Debug for MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index: _,
                 last_location,
                 depth,
                 childs,
@@ -147,6 +155,7 @@ impl std::fmt::Debug for MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index: _,
                 last_location,
                 hashes,
                 uuid,
@@ -300,6 +309,7 @@ impl MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 hashes,
             } => {
@@ -310,6 +320,7 @@ impl MaskImpl {
                     owning_account: take(owning_account),
                     token_to_account: take(token_to_account),
                     id_to_addr: take(id_to_addr),
+                    accounts_index: take(accounts_index),
                     last_location: take(last_location),
                     depth: *depth,
                     childs: take(childs),
@@ -380,6 +391,7 @@ impl MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 hashes,
                 ..
             } => {
@@ -388,6 +400,7 @@ impl MaskImpl {
                 let (accounts, hashes) = {
                     token_to_account.clear();
                     id_to_addr.clear();
+                    accounts_index.clear();
                     (std::mem::take(owning_account), hashes.take())
                 };
 
@@ -487,6 +500,7 @@ impl MaskImpl {
             owning_account,
             token_to_account,
             id_to_addr,
+            accounts_index,
             last_location,
             depth,
             childs,
@@ -504,6 +518,7 @@ impl MaskImpl {
         let childs = std::mem::take(childs);
         let token_to_account = std::mem::take(token_to_account);
         let id_to_addr = std::mem::take(id_to_addr);
+        let accounts_index = std::mem::take(accounts_index);
         let last_location = std::mem::take(last_location);
         let hashes = std::mem::replace(hashes, HashesMatrix::new(depth as usize));
         let uuid = std::mem::replace(uuid, "temporary".to_string());
@@ -512,6 +527,7 @@ impl MaskImpl {
             owning_account,
             token_to_account,
             id_to_addr,
+            accounts_index,
             last_location,
             depth,
             childs,
@@ -581,7 +597,7 @@ impl MaskImpl {
     pub fn compute_hash_or_parent(&mut self, addr: Address, last_account: &Address) -> Fp {
         let (matrix, own, parent) = match self {
             Root { database, .. } => {
-                return database.with(|db| db.emulate_tree_recursive(addr, last_account));
+                return database.with(|db| db.emulate_tree_parallel(addr, last_account));
             }
             Attached {
                 hashes,
@@ -602,7 +618,7 @@ impl MaskImpl {
         // When we don't have accounts here, delegate to parent
         // TODO: Make that faster
         let hash = if own.values().any(|a| addr.is_parent_of(a)) {
-            self.emulate_tree_recursive(addr, last_account)
+            self.emulate_tree_parallel(addr, last_account)
         } else {
             // Recurse to parents until we found a mask having accounts on this address
             let parent = parent.as_ref().unwrap();
@@ -685,38 +701,95 @@ impl MaskImpl {
         // self.emulate_tree_recursive(addr, &last_account)
     }
 
-    // fn emulate_recursive(&mut self, addr: Address, nremaining: &mut usize) -> Fp {
-    fn emulate_tree_recursive(&mut self, addr: Address, last_account: &Address) -> Fp {
+    /// Computes the hash at `addr`, which must have accounts of this mask
+    /// below it. Missing hashes are computed level by level, in parallel.
+    fn emulate_tree_parallel(&mut self, addr: Address, last_account: &Address) -> Fp {
         let tree_depth = self.depth() as usize;
-        let current_depth = tree_depth - addr.length();
 
-        if current_depth == 0 {
+        if addr.length() == tree_depth {
             return self
                 .get_account_hash(addr.to_index())
                 .unwrap_or_else(|| self.empty_hash_at_height(0));
         }
 
-        let mut get_child_hash = |addr: Address| {
-            if let Some(hash) = self.get_cached_hash(&addr) {
-                hash
-            } else if addr.is_before(last_account) {
-                self.compute_hash_or_parent(addr, last_account)
-            } else {
-                self.empty_hash_at_height(current_depth - 1)
+        let mut dirty = DirtyNodes::new(tree_depth);
+        self.collect_dirty_nodes(addr.clone(), last_account, &mut dirty);
+
+        let (owning_account, hashes) = match self {
+            Root { database, .. } => {
+                return database.with(|db| db.emulate_tree_parallel(addr, last_account));
+            }
+            Attached {
+                owning_account,
+                hashes,
+                ..
             }
+            | Unattached {
+                owning_account,
+                hashes,
+                ..
+            } => (owning_account, hashes),
         };
 
-        let left_hash = get_child_hash(addr.child_left());
-        let right_hash = get_child_hash(addr.child_right());
+        hashes.compute_dirty_hashes(dirty, |addr| owning_account[&addr.to_index()].hash());
+        hashes.get(&addr).copied().unwrap()
+    }
 
-        match self.get_cached_hash(&addr) {
-            Some(hash) => hash,
-            None => {
-                let hash = V2::hash_node(current_depth - 1, left_hash, right_hash);
-                self.set_cached_hash(&addr, hash);
-                hash
+    /// Walks the subtree at `addr` (which must not have a hash in this mask),
+    /// down to the nodes which have one, or are after `last_account`.
+    /// Hashes of the nodes without accounts of this mask below them are
+    /// taken from the parent.
+    fn collect_dirty_nodes(
+        &mut self,
+        addr: Address,
+        last_account: &Address,
+        dirty: &mut DirtyNodes,
+    ) {
+        let height = self.depth() as usize - addr.length();
+
+        let (own, owning_account, parent) = match self {
+            Root { .. } => return,
+            Attached {
+                id_to_addr,
+                owning_account,
+                parent,
+                ..
+            } => (id_to_addr, owning_account, Some(parent)),
+            Unattached {
+                id_to_addr,
+                owning_account,
+                ..
+            } => (id_to_addr, owning_account, None),
+        };
+
+        if height == 0 {
+            if owning_account.contains_key(&addr.to_index()) {
+                dirty.push(addr, height);
+            } else {
+                let hash = self
+                    .get_account_hash(addr.to_index())
+                    .unwrap_or_else(|| self.empty_hash_at_height(0));
+                dirty.resolve(&addr, hash);
             }
+            return;
         }
+
+        // TODO: Make that faster
+        if !own.values().any(|a| addr.is_parent_of(a)) {
+            // Recurse to parents until we found a mask having accounts on this address
+            let parent = parent.unwrap();
+            let hash =
+                parent.with(|parent| parent.compute_hash_or_parent(addr.clone(), last_account));
+            dirty.resolve(&addr, hash);
+            return;
+        }
+
+        for child in [addr.child_left(), addr.child_right()] {
+            if self.get_cached_hash(&child).is_none() && child.is_before(last_account) {
+                self.collect_dirty_nodes(child, last_account, dirty);
+            }
+        }
+        dirty.push(addr, height);
     }
 
     fn emulate_merkle_path_recursive(
@@ -792,6 +865,7 @@ impl MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 hashes,
                 ..
@@ -800,6 +874,7 @@ impl MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 hashes,
                 ..
@@ -816,6 +891,7 @@ impl MaskImpl {
 
                     let account = owning_account.remove(&account_index).unwrap();
                     token_to_account.remove(&account.token_id).unwrap();
+                    accounts_index.remove(&account);
 
                     if last_location
                         .as_ref()
@@ -854,6 +930,7 @@ impl MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 ..
             }
@@ -861,12 +938,17 @@ impl MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 ..
             } => {
                 let account_id = account.id();
                 let token_id = account.token_id.clone();
 
+                if let Some(old) = owning_account.get(&account_index) {
+                    accounts_index.remove(old);
+                }
+                accounts_index.insert(&account);
                 owning_account.insert(account_index, *account);
                 id_to_addr.insert(account_id.clone(), addr.clone());
                 token_to_account.insert(token_id, account_id);
@@ -1140,13 +1222,63 @@ impl BaseLedger for MaskImpl {
     }
 
     fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId> {
-        let mut set = HashSet::with_capacity(1024);
+        let (parent, accounts_index) = match self {
+            Root { database, .. } => return database.tokens(public_key),
+            Attached {
+                parent,
+                accounts_index,
+                ..
+            } => (Some(parent), accounts_index),
+            Unattached { accounts_index, .. } => (None, accounts_index),
+        };
 
-        for account in self.to_list() {
-            if account.public_key == public_key {
-                set.insert(account.token_id);
-            }
-        }
+        // Accounts are never moved to another public key or token, so the
+        // tokens of the parent are still valid here
+        let mut set = match parent {
+            Some(parent) => parent.tokens(public_key.clone()),
+            None => HashSet::default(),
+        };
+        set.extend(
+            accounts_index
+                .tokens(&public_key)
+                .into_iter()
+                .flatten()
+                .cloned(),
+        );
+
+        set
+    }
+
+    fn delegators(&self, delegate: CompressedPubKey) -> HashSet<AccountId> {
+        let (parent, id_to_addr, accounts_index) = match self {
+            Root { database, .. } => return database.delegators(delegate),
+            Attached {
+                parent,
+                id_to_addr,
+                accounts_index,
+                ..
+            } => (Some(parent), id_to_addr, accounts_index),
+            Unattached {
+                id_to_addr,
+                accounts_index,
+                ..
+            } => (None, id_to_addr, accounts_index),
+        };
+
+        // Accounts owned by this mask might have changed their delegate, so
+        // only our own index is authoritative for them
+        let mut set = match parent {
+            Some(parent) => parent.delegators(delegate.clone()),
+            None => HashSet::default(),
+        };
+        set.retain(|account_id| !id_to_addr.contains_key(account_id));
+        set.extend(
+            accounts_index
+                .delegators(&delegate)
+                .into_iter()
+                .flatten()
+                .cloned(),
+        );
 
         set
     }
@@ -1197,6 +1329,7 @@ impl BaseLedger for MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 depth,
                 ..
@@ -1205,6 +1338,7 @@ impl BaseLedger for MaskImpl {
                 owning_account,
                 token_to_account,
                 id_to_addr,
+                accounts_index,
                 last_location,
                 depth,
                 ..
@@ -1220,6 +1354,7 @@ impl BaseLedger for MaskImpl {
                 id_to_addr.insert(account_id.clone(), location.clone());
                 *last_location = Some(location.clone());
                 token_to_account.insert(token_id, account_id);
+                accounts_index.insert(&account);
                 owning_account.insert(account_index, account);
 
                 self.invalidate_hashes(account_index);
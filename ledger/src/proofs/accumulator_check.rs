@@ -10,38 +10,57 @@ pub fn accumulator_check(
     urs: &SRS<Vesta>,
     proof: &PicklesProofProofsVerified2ReprStableV2,
 ) -> bool {
+    batch_accumulator_check(urs, [proof])
+}
+
+/// Checks the IPA accumulators of all `proofs` with a single batched
+/// multi-scalar multiplication.
+///
+/// Succeeds only if every accumulator is valid, so a failed check doesn't
+/// tell which proof is invalid. Callers need to split the batch in order to
+/// find out.
+pub fn batch_accumulator_check<'a, I>(urs: &SRS<Vesta>, proofs: I) -> bool
+where
+    I: IntoIterator<Item = &'a PicklesProofProofsVerified2ReprStableV2>,
+{
     // accumulator check
     // Note:
     // comms: statement.proof_state.messages_for_next_wrap_proof.challenge_polynomial_commitment
     // chals: statement.proof_state.deferred_values.bulletproof_challenges
 
-    let deferred_values = &proof.statement.proof_state.deferred_values;
-    let bulletproof_challenges = &deferred_values.bulletproof_challenges;
-    let bulletproof_challenges: Vec<Fp> = bulletproof_challenges
-        .iter()
-        .map(|chal| {
+    let of_coord = |(x, y): &(BigInt, BigInt)| Vesta::of_coordinates(x.to_field(), y.to_field());
+
+    let mut acc_comms: Vec<Vesta> = Vec::new();
+    let mut bulletproof_challenges: Vec<Fp> = Vec::new();
+
+    for proof in proofs {
+        let deferred_values = &proof.statement.proof_state.deferred_values;
+        bulletproof_challenges.extend(deferred_values.bulletproof_challenges.iter().map(|chal| {
             let prechallenge = &chal.prechallenge.inner;
             let prechallenge: [u64; 2] = prechallenge.each_ref().map(|c| c.as_u64());
 
             ScalarChallenge::limbs_to_field(&prechallenge)
-        })
-        .collect();
-
-    let of_coord = |(x, y): &(BigInt, BigInt)| Vesta::of_coordinates(x.to_field(), y.to_field());
+        }));
 
-    // statement.proof_state.messages_for_next_wrap_proof.challenge_polynomial_commitment
-    let acc_comm = &proof
-        .statement
-        .proof_state
-        .messages_for_next_wrap_proof
-        .challenge_polynomial_commitment;
-    let acc_comm: Vesta = of_coord(acc_comm);
+        // statement.proof_state.messages_for_next_wrap_proof.challenge_polynomial_commitment
+        let acc_comm = &proof
+            .statement
+            .proof_state
+            .messages_for_next_wrap_proof
+            .challenge_polynomial_commitment;
+        acc_comms.push(of_coord(acc_comm));
+    }
 
     let acc_check =
-        urs_utils::batch_dlog_accumulator_check(urs, &[acc_comm], &bulletproof_challenges);
+        urs_utils::batch_dlog_accumulator_check(urs, &acc_comms, &bulletproof_challenges);
 
     if !acc_check {
-        println!("accumulator_check failed");
+        openmina_core::warn!(
+            openmina_core::log::system_time();
+            kind = "accumulator_check",
+            summary = "accumulator check failed",
+            batch_size = acc_comms.len(),
+        );
     }
 
     acc_check
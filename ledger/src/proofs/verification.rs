@@ -626,11 +626,15 @@ pub fn verify_transaction<'a>(
         data: (),
     };
 
-    proofs.into_iter().all(|(statement, transaction_proof)| {
-        let accum_check = accumulator_check::accumulator_check(srs, transaction_proof);
-        let verified = verify_impl(statement, transaction_proof, &vk);
-        accum_check && verified
-    })
+    let proofs = proofs.into_iter().collect::<Vec<_>>();
+
+    let accum_check =
+        accumulator_check::batch_accumulator_check(srs, proofs.iter().map(|(_, proof)| &proof.0));
+
+    accum_check
+        && proofs
+            .iter()
+            .all(|&(statement, transaction_proof)| verify_impl(statement, transaction_proof, &vk))
 }
 
 /// https://github.com/MinaProtocol/mina/blob/bfd1009abdbee78979ff0343cc73a3480e862f58/src/lib/crypto/kimchi_bindings/stubs/src/pasta_fq_plonk_proof.rs#L116
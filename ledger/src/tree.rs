@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, fmt::Debug, sync::Mutex};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    sync::Mutex,
+};
 
 use crate::{
     address::Address,
@@ -7,6 +11,7 @@ use crate::{
 };
 use mina_hasher::Fp;
 use once_cell::sync::Lazy;
+use rayon::prelude::*;
 
 #[derive(Clone, Debug)]
 struct Leaf<T: TreeVersion> {
@@ -203,6 +208,45 @@ impl HashesMatrix {
         }
     }
 
+    /// Computes the hashes of the `dirty` nodes, level by level from the
+    /// leaves up. Nodes of the same level are independent, so they are hashed
+    /// in parallel on [HASHING_POOL], unless there are only a few of them.
+    /// `leaf_hash` returns the hash of the account at a dirty leaf.
+    ///
+    /// Results are the same as when computing them one by one recursively.
+    pub(crate) fn compute_dirty_hashes<F>(&mut self, dirty: DirtyNodes, leaf_hash: F)
+    where
+        F: Fn(&Address) -> Fp + Sync,
+    {
+        let DirtyNodes { levels, resolved } = dirty;
+
+        for (height, addrs) in levels.into_iter().enumerate() {
+            if addrs.is_empty() {
+                continue;
+            }
+            let hashes = if height == 0 {
+                hash_nodes(&addrs, &leaf_hash)
+            } else {
+                let empty = self.empty_hash_at_height(height - 1);
+                let this = &*self;
+                let child_hash = |addr: Address| {
+                    this.get(&addr)
+                        .or_else(|| resolved.get(&addr.to_linear_index()))
+                        .copied()
+                        .unwrap_or(empty)
+                };
+                hash_nodes(&addrs, |addr| {
+                    let left = child_hash(addr.child_left());
+                    let right = child_hash(addr.child_right());
+                    V2::hash_node(height - 1, left, right)
+                })
+            };
+            for (addr, hash) in addrs.iter().zip(hashes) {
+                self.set(addr, hash);
+            }
+        }
+    }
+
     pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
         self.matrix.clone().into_iter().collect()
     }
@@ -214,6 +258,59 @@ impl HashesMatrix {
     }
 }
 
+/// Levels with fewer nodes are hashed serially, spreading them over the
+/// threads would cost more than it saves.
+const PARALLEL_HASHING_MIN_NODES: usize = 64;
+
+/// Pool for hashing ledgers, so that hashing doesn't queue behind the
+/// long running jobs (e.g. proving) on the global rayon pool.
+static HASHING_POOL: Lazy<rayon::ThreadPool> = Lazy::new(|| {
+    rayon::ThreadPoolBuilder::new()
+        .thread_name(|i| format!("openmina_ledger_hashing_{i}"))
+        .build()
+        .expect("failed to build ledger hashing thread pool")
+});
+
+fn hash_nodes<F>(addrs: &[Address], hash: F) -> Vec<Fp>
+where
+    F: Fn(&Address) -> Fp + Send + Sync,
+{
+    if addrs.len() < PARALLEL_HASHING_MIN_NODES {
+        addrs.iter().map(hash).collect()
+    } else {
+        HASHING_POOL.install(|| addrs.par_iter().map(hash).collect())
+    }
+}
+
+/// Nodes of a subtree whose hashes are missing, grouped by height.
+///
+/// Dirty paths of the accounts share their ancestors, each node is listed
+/// only once however many dirty leaves are below it.
+pub(crate) struct DirtyNodes {
+    /// Indexed by the height of the nodes, leaves being at height 0.
+    levels: Vec<Vec<Address>>,
+    /// Hashes of the clean children of the dirty nodes, which aren't
+    /// in the hashes matrix (e.g. the ones found in a parent mask).
+    resolved: HashMap<usize, Fp>,
+}
+
+impl DirtyNodes {
+    pub(crate) fn new(ledger_depth: usize) -> Self {
+        Self {
+            levels: vec![Vec::new(); ledger_depth + 1],
+            resolved: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, addr: Address, height: usize) {
+        self.levels[height].push(addr);
+    }
+
+    pub(crate) fn resolve(&mut self, addr: &Address, hash: Fp) {
+        self.resolved.insert(addr.to_linear_index(), hash);
+    }
+}
+
 static HASH_EMPTIES: Lazy<Mutex<Vec<Fp>>> = Lazy::new(|| {
     /// This value needs to be changed when the tree's height change
     const RANGE_HEIGHT: std::ops::Range<usize> = 0..36;
@@ -42,6 +42,7 @@ impl Invariant for TransitionFrontierOnlySyncsToBetterBlocks {
                     best_tip.consensus_state(),
                     prev_tip.hash(),
                     best_tip.hash(),
+                    prev_tip.constants(),
                 ) {
                     return InvariantResult::Violation(format!(
                         "best tip got downgraded!\nprev({}): {}\nnew({}): {}",
@@ -65,6 +66,7 @@ impl Invariant for TransitionFrontierOnlySyncsToBetterBlocks {
                     target_best_tip.consensus_state(),
                     best_tip.hash(),
                     target_best_tip.hash(),
+                    best_tip.constants(),
                 )
             {
                 return InvariantResult::Violation(format!(
@@ -89,6 +91,7 @@ impl Invariant for TransitionFrontierOnlySyncsToBetterBlocks {
                     new_target.consensus_state(),
                     prev_target.hash(),
                     new_target.hash(),
+                    prev_target.constants(),
                 ) {
                     return InvariantResult::Violation(format!(
                         "best tip target got downgraded!\nprev({}): {}\nnew({}): {}",
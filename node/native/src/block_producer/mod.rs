@@ -1,5 +1,11 @@
+pub mod remote_signer;
 mod vrf_evaluator;
 
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
 use ledger::proofs::{
     block::BlockParams, gates::get_provers, generate_block_proof, transaction::ProofError,
 };
@@ -7,75 +13,273 @@ use mina_p2p_messages::v2::{
     MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2, StateHash,
 };
 use node::{
-    account::AccountSecretKey,
-    block_producer::{vrf_evaluator::VrfEvaluatorInput, BlockProducerEvent},
+    account::{AccountPublicKey, AccountSecretKey},
+    block_producer::{
+        vrf_evaluator::VrfEvaluatorInput, BlockProducerEvent, BlockProducerLog,
+        BlockProducerLogEntry, BlockProducerPayoutReport,
+    },
     core::{channels::mpsc, constants::CONSTRAINT_CONSTANTS},
 };
 
 use crate::NodeService;
 
+use self::remote_signer::RemoteSignerClient;
+
 pub struct BlockProducerService {
-    keypair: AccountSecretKey,
+    signer: BlockProducerSigner,
     vrf_evaluation_sender: mpsc::UnboundedSender<VrfEvaluatorInput>,
+    /// File where production log entries are appended.
+    production_log_path: Option<PathBuf>,
+    /// Directory and format of the payout reports.
+    payout_report: Option<(PathBuf, PayoutReportFormat)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PayoutReportFormat {
+    Json,
+    Csv,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("invalid payout report format: {0}! expected one of: json/csv")]
+pub struct PayoutReportFormatParseError(String);
+
+impl std::str::FromStr for PayoutReportFormat {
+    type Err = PayoutReportFormatParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "json" => Self::Json,
+            "csv" => Self::Csv,
+            other => return Err(PayoutReportFormatParseError(other.to_owned())),
+        })
+    }
+}
+
+pub enum BlockProducerSigner {
+    /// Producer keys are held by the node itself.
+    Local(BTreeMap<AccountPublicKey, AccountSecretKey>),
+    /// Vrf evaluation and block proving is delegated to the remote signer.
+    Remote(RemoteSignerClient),
 }
 
 impl BlockProducerService {
     pub fn new(
-        keypair: AccountSecretKey,
+        signer: BlockProducerSigner,
         vrf_evaluation_sender: mpsc::UnboundedSender<VrfEvaluatorInput>,
     ) -> Self {
         Self {
-            keypair,
+            signer,
             vrf_evaluation_sender,
+            production_log_path: None,
+            payout_report: None,
         }
     }
 
-    pub fn keypair(&self) -> AccountSecretKey {
-        self.keypair.clone()
+    pub fn set_production_log_path(&mut self, path: PathBuf) {
+        self.production_log_path = Some(path);
+    }
+
+    pub fn set_payout_report(&mut self, dir: PathBuf, format: PayoutReportFormat) {
+        self.payout_report = Some((dir, format));
+    }
+
+    pub fn signer(&self) -> &BlockProducerSigner {
+        &self.signer
+    }
+
+    /// Returns the producer's private key, if it is held by the node.
+    pub fn keypair(&self, pub_key: &AccountPublicKey) -> Option<AccountSecretKey> {
+        match &self.signer {
+            BlockProducerSigner::Local(keypairs) => keypairs.get(pub_key).cloned(),
+            BlockProducerSigner::Remote(_) => None,
+        }
+    }
+
+    /// Keypair of the creator of the block that is being proven.
+    ///
+    /// Genesis block isn't created by any of our keys, it is proven with
+    /// the genesis producer key.
+    pub fn producer_keypair(
+        &self,
+        input: &ProverExtendBlockchainInputStableV2,
+    ) -> Result<AccountSecretKey, String> {
+        let block_creator = &input.next_state.body.consensus_state.block_creator;
+        self.block_creator_keypair(&block_creator.clone().into(), is_genesis_block(input))
+    }
+
+    fn block_creator_keypair(
+        &self,
+        block_creator: &AccountPublicKey,
+        is_genesis_block: bool,
+    ) -> Result<AccountSecretKey, String> {
+        if is_genesis_block {
+            return Ok(AccountSecretKey::genesis_producer());
+        }
+        self.keypair(block_creator)
+            .ok_or_else(|| format!("no key for block creator {block_creator}"))
     }
 }
 
 impl NodeService {
-    pub fn block_producer_start(&mut self, producer_keypair: AccountSecretKey) {
+    pub fn block_producer_start(&mut self, producer_keypairs: Vec<AccountSecretKey>) {
+        let event_sender = self.event_sender.clone();
+        let (vrf_evaluation_sender, vrf_evaluation_receiver) =
+            mpsc::unbounded_channel::<VrfEvaluatorInput>();
+
+        let keypairs = producer_keypairs
+            .into_iter()
+            .map(|keypair| (keypair.public_key(), keypair))
+            .collect::<BTreeMap<_, _>>();
+        let vrf_keypairs = keypairs
+            .iter()
+            .map(|(pub_key, keypair)| (pub_key.clone(), keypair.clone().into()))
+            .collect();
+
+        self.block_producer = Some(BlockProducerService::new(
+            BlockProducerSigner::Local(keypairs),
+            vrf_evaluation_sender,
+        ));
+
+        std::thread::Builder::new()
+            .name("openmina_vrf_evaluator".to_owned())
+            .spawn(move || {
+                vrf_evaluator::vrf_evaluator(event_sender, vrf_evaluation_receiver, vrf_keypairs);
+            })
+            .unwrap();
+    }
+
+    /// Start block producer, with private keys held by the remote signer.
+    pub fn block_producer_start_with_remote_signer(&mut self, signer: RemoteSignerClient) {
         let event_sender = self.event_sender.clone();
         let (vrf_evaluation_sender, vrf_evaluation_receiver) =
             mpsc::unbounded_channel::<VrfEvaluatorInput>();
 
         self.block_producer = Some(BlockProducerService::new(
-            producer_keypair.clone(),
+            BlockProducerSigner::Remote(signer.clone()),
             vrf_evaluation_sender,
         ));
 
         std::thread::Builder::new()
             .name("openmina_vrf_evaluator".to_owned())
             .spawn(move || {
-                vrf_evaluator::vrf_evaluator(
-                    event_sender,
-                    vrf_evaluation_receiver,
-                    producer_keypair.into(),
-                );
+                vrf_evaluator::remote_vrf_evaluator(event_sender, vrf_evaluation_receiver, signer);
             })
             .unwrap();
     }
 }
 
-pub fn prove(
-    mut input: Box<ProverExtendBlockchainInputStableV2>,
-    keypair: AccountSecretKey,
-    only_verify_constraints: bool,
-) -> Result<Box<MinaBaseProofStableV2>, ProofError> {
+/// Reads the production log persisted by [BlockProducerService].
+///
+/// Returns empty log if the file doesn't exist yet. The file only grows
+/// while the node runs, so it is rewritten with the retained entries
+/// if it contains superseded or pruned ones.
+pub fn read_production_log(path: &Path) -> io::Result<BlockProducerLog> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Default::default()),
+        Err(err) => return Err(err),
+    };
+    let entries = BufReader::new(file)
+        .lines()
+        .map(|line| Ok(serde_json::from_str::<BlockProducerLogEntry>(&line?)?))
+        .collect::<io::Result<Vec<_>>>()?;
+    let lines = entries.len();
+    let log = BlockProducerLog::from_entries(entries);
+    if log.len() < lines {
+        compact_production_log(path, &log)?;
+    }
+    Ok(log)
+}
+
+fn compact_production_log(path: &Path, log: &BlockProducerLog) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut file = BufWriter::new(File::create(&tmp_path)?);
+    for entry in log.iter() {
+        serde_json::to_writer(&mut file, entry)?;
+        file.write_all(b"\n")?;
+    }
+    file.into_inner()?.sync_data()?;
+    std::fs::rename(tmp_path, path)
+}
+
+fn append_production_log(path: &Path, entry: &BlockProducerLogEntry) -> io::Result<()> {
+    let mut encoded = serde_json::to_vec(entry)?;
+    encoded.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&encoded)?;
+    file.sync_data()
+}
+
+fn write_payout_report(
+    dir: &Path,
+    format: PayoutReportFormat,
+    report: &BlockProducerPayoutReport,
+) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let name = format!("{}-{}", report.global_slot, report.block_hash);
+    match format {
+        PayoutReportFormat::Json => {
+            let file = File::create(dir.join(format!("{name}.json")))?;
+            serde_json::to_writer_pretty(file, report)?;
+        }
+        PayoutReportFormat::Csv => {
+            let mut file = File::create(dir.join(format!("{name}.csv")))?;
+            writeln!(
+                file,
+                "block_hash,height,global_slot,epoch,producer,slot_winner,coinbase_receiver,\
+                 staking_ledger_hash,coinbase,transaction_fees,snark_fees,total_stake,\
+                 delegator,delegator_index,stake,share"
+            )?;
+            for delegator in &report.delegators {
+                writeln!(
+                    file,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                    report.block_hash,
+                    report.height,
+                    report.global_slot,
+                    report.epoch,
+                    report.producer,
+                    report.slot_winner,
+                    report.coinbase_receiver,
+                    report.staking_ledger_hash,
+                    report.coinbase,
+                    report.transaction_fees,
+                    report.snark_fees,
+                    report.total_stake,
+                    delegator.delegator,
+                    delegator.index.as_u64(),
+                    delegator.stake,
+                    delegator.share,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Genesis block is proven with the genesis producer key, which isn't
+/// held by the block producer.
+fn is_genesis_block(input: &ProverExtendBlockchainInputStableV2) -> bool {
     let height = input
         .next_state
         .body
         .consensus_state
         .blockchain_length
         .as_u32();
-    let is_genesis = height == 1
+    height == 1
         || CONSTRAINT_CONSTANTS
             .fork
             .as_ref()
-            .map_or(false, |fork| fork.blockchain_length + 1 == height);
-    if !is_genesis {
+            .map_or(false, |fork| fork.blockchain_length + 1 == height)
+}
+
+pub fn prove(
+    mut input: Box<ProverExtendBlockchainInputStableV2>,
+    keypair: AccountSecretKey,
+    only_verify_constraints: bool,
+) -> Result<Box<MinaBaseProofStableV2>, ProofError> {
+    if !is_genesis_block(&input) {
         input.prover_state.producer_private_key = keypair.into();
     }
 
@@ -99,12 +303,111 @@ impl node::service::BlockProducerService for crate::NodeService {
         if self.replayer.is_some() {
             return;
         }
-        let keypair = self.block_producer.as_ref().unwrap().keypair();
-
+        let block_producer = self.block_producer.as_ref().unwrap();
         let tx = self.event_sender.clone();
+
+        // Genesis block doesn't need producer's key, so it is proven locally.
+        if let (BlockProducerSigner::Remote(signer), false) =
+            (block_producer.signer(), is_genesis_block(&input))
+        {
+            let signer = signer.clone();
+            std::thread::spawn(move || {
+                let res = signer.prove(block_hash.clone(), input);
+                let _ = tx.send(BlockProducerEvent::BlockProve(block_hash, res).into());
+            });
+            return;
+        }
+
+        let keypair = match block_producer.producer_keypair(&input) {
+            Ok(keypair) => keypair,
+            Err(err) => {
+                let _ = tx.send(BlockProducerEvent::BlockProve(block_hash, Err(err)).into());
+                return;
+            }
+        };
         std::thread::spawn(move || {
             let res = prove(input, keypair, false).map_err(|err| format!("{err:?}"));
             let _ = tx.send(BlockProducerEvent::BlockProve(block_hash, res).into());
         });
     }
+
+    fn production_log_record(&mut self, entry: BlockProducerLogEntry) {
+        if self.replayer.is_some() {
+            return;
+        }
+        let Some(path) = self
+            .block_producer
+            .as_ref()
+            .and_then(|bp| bp.production_log_path.as_ref())
+        else {
+            return;
+        };
+        if let Err(error) = append_production_log(path, &entry) {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "BlockProducerLogPersist",
+                summary = "failed to persist block production log",
+                global_slot = entry.global_slot,
+                error = error.to_string());
+        }
+    }
+
+    fn payout_report_write(&mut self, report: BlockProducerPayoutReport) {
+        if self.replayer.is_some() {
+            return;
+        }
+        let Some((dir, format)) = self
+            .block_producer
+            .as_ref()
+            .and_then(|bp| bp.payout_report.as_ref())
+        else {
+            return;
+        };
+        if let Err(error) = write_payout_report(dir, *format, &report) {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "BlockProducerPayoutReport",
+                summary = format!("failed to write payout report for block {}", report.block_hash),
+                error = error.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_creator_keypair_lookup() {
+        let keys = (0..3)
+            .map(AccountSecretKey::deterministic)
+            .collect::<Vec<_>>();
+        let (vrf_evaluation_sender, _) = mpsc::unbounded_channel();
+        let service = BlockProducerService::new(
+            BlockProducerSigner::Local(
+                keys.iter()
+                    .map(|key| (key.public_key(), key.clone()))
+                    .collect(),
+            ),
+            vrf_evaluation_sender,
+        );
+
+        for key in &keys {
+            let pub_key = key.public_key();
+            assert_eq!(service.keypair(&pub_key).unwrap().public_key(), pub_key);
+            let found = service.block_creator_keypair(&pub_key, false).unwrap();
+            assert_eq!(found.public_key(), pub_key);
+        }
+
+        let unknown = AccountSecretKey::deterministic(3).public_key();
+        assert!(service.keypair(&unknown).is_none());
+        assert!(service.block_creator_keypair(&unknown, false).is_err());
+
+        // Genesis block is proven with the genesis producer key, whoever
+        // the creator is.
+        let genesis_producer = AccountSecretKey::genesis_producer().public_key();
+        let found = service.block_creator_keypair(&unknown, true).unwrap();
+        assert_eq!(found.public_key(), genesis_producer);
+        assert!(service
+            .block_creator_keypair(&genesis_producer, false)
+            .is_err());
+    }
 }
@@ -0,0 +1,555 @@
+//! Remote signer for the block producer.
+//!
+//! Signer holds producer private keys and does vrf evaluation and block
+//! proving (which requires producer's private key) on behalf of the node,
+//! so that the node host never holds the hot key.
+//!
+//! Node and signer communicate over a Unix socket or TCP connection,
+//! using newline delimited json messages. Each request gets exactly one
+//! response.
+//!
+//! If the signer is started with an auth token, the first request on each
+//! connection must be [RemoteSignerRequest::Auth] with that token. Signer
+//! refuses to listen on a non-loopback TCP address without a token.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use mina_p2p_messages::v2::{
+    MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2, StateHash,
+};
+use node::{
+    account::{AccountPublicKey, AccountSecretKey},
+    block_producer::vrf_evaluator::{VrfEvaluationOutputWithHash, VrfEvaluatorInput},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RemoteSignerRequest {
+    /// Authenticates the connection with the signer's auth token.
+    Auth {
+        token: String,
+    },
+    /// Public keys of the producers for which signer holds private keys.
+    PubKeys,
+    VrfEvaluate(VrfEvaluatorInput),
+    Prove {
+        /// Hash of `input.next_state`. Signer computes it itself and
+        /// refuses the request if it differs.
+        block_hash: StateHash,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RemoteSignerResponse {
+    Authenticated,
+    PubKeys(Vec<AccountPublicKey>),
+    VrfEvaluated(VrfEvaluationOutputWithHash),
+    Proved(Box<MinaBaseProofStableV2>),
+    Error(String),
+}
+
+/// Address of the remote signer. Either a `host:port` tcp address, or
+/// a path to the Unix socket.
+#[derive(Debug, Clone)]
+pub enum RemoteSignerAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for RemoteSignerAddr {
+    type Err = String;
+
+    /// Unix socket path must be absolute, start with `./` or `../`, or be
+    /// prefixed with `unix:`. Anything else is a tcp address, with the host
+    /// name resolved right away.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(Self::Unix(path.into()));
+        }
+        if std::path::Path::new(s).is_absolute() || s.starts_with("./") || s.starts_with("../") {
+            return Ok(Self::Unix(s.into()));
+        }
+        s.to_socket_addrs()
+            .map_err(|err| format!("invalid remote signer address `{s}`: {err}"))?
+            .next()
+            .map(Self::Tcp)
+            .ok_or_else(|| format!("remote signer address `{s}` didn't resolve"))
+    }
+}
+
+impl std::fmt::Display for RemoteSignerAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{addr}"),
+            Self::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+trait Stream: io::Read + io::Write + Send {}
+impl<T: io::Read + io::Write + Send> Stream for T {}
+
+#[derive(Debug, Clone)]
+pub struct RemoteSignerClient {
+    addr: RemoteSignerAddr,
+    token: Option<String>,
+}
+
+impl RemoteSignerClient {
+    pub fn new(addr: RemoteSignerAddr, token: Option<String>) -> Self {
+        Self { addr, token }
+    }
+
+    pub fn addr(&self) -> &RemoteSignerAddr {
+        &self.addr
+    }
+
+    /// Sends the request to the signer and blocks until response is received.
+    pub fn call(&self, req: RemoteSignerRequest) -> io::Result<RemoteSignerResponse> {
+        let stream: Box<dyn Stream> = match &self.addr {
+            RemoteSignerAddr::Tcp(addr) => Box::new(TcpStream::connect(addr)?),
+            #[cfg(unix)]
+            RemoteSignerAddr::Unix(path) => Box::new(UnixStream::connect(path)?),
+            #[cfg(not(unix))]
+            RemoteSignerAddr::Unix(_) => return Err(unix_socket_unsupported()),
+        };
+        let mut stream = BufReader::new(stream);
+        let mut request = |req: &RemoteSignerRequest| {
+            write_message(stream.get_mut(), req)?;
+            read_message(&mut stream)?.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+        };
+        if let Some(token) = self.token.clone() {
+            match request(&RemoteSignerRequest::Auth { token })? {
+                RemoteSignerResponse::Authenticated => {}
+                RemoteSignerResponse::Error(error) => {
+                    return Err(io::Error::new(io::ErrorKind::PermissionDenied, error))
+                }
+                res => return Err(unexpected_response(res)),
+            }
+        }
+        request(&req)
+    }
+
+    pub fn pub_keys(&self) -> io::Result<Vec<AccountPublicKey>> {
+        match self.call(RemoteSignerRequest::PubKeys)? {
+            RemoteSignerResponse::PubKeys(keys) => Ok(keys),
+            res => Err(unexpected_response(res)),
+        }
+    }
+
+    pub fn prove(
+        &self,
+        block_hash: StateHash,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    ) -> Result<Box<MinaBaseProofStableV2>, String> {
+        match self.call(RemoteSignerRequest::Prove { block_hash, input }) {
+            Ok(RemoteSignerResponse::Proved(proof)) => Ok(proof),
+            Ok(RemoteSignerResponse::Error(error)) => Err(error),
+            Ok(res) => Err(unexpected_response(res).to_string()),
+            Err(error) => Err(format!("remote signer request failed: {error}")),
+        }
+    }
+}
+
+fn unexpected_response(res: RemoteSignerResponse) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected remote signer response: {res:?}"),
+    )
+}
+
+#[cfg(not(unix))]
+fn unix_socket_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unix sockets are not supported on this platform",
+    )
+}
+
+fn write_message<W: Write, T: Serialize>(w: &mut W, msg: &T) -> io::Result<()> {
+    let mut encoded = serde_json::to_vec(msg)?;
+    encoded.push(b'\n');
+    w.write_all(&encoded)?;
+    w.flush()
+}
+
+fn read_message<R: BufRead, T: for<'a> Deserialize<'a>>(r: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if r.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+/// Reference remote signer.
+pub struct RemoteSigner {
+    keypairs: BTreeMap<AccountPublicKey, AccountSecretKey>,
+    token: Option<String>,
+    /// Used to refuse producing two different blocks for the same slot.
+    proven: Mutex<ProvenBlocks>,
+}
+
+impl RemoteSigner {
+    /// Blocks proven by previous runs of the signer are loaded from
+    /// `proven_blocks_path`.
+    pub fn new(
+        keypairs: Vec<AccountSecretKey>,
+        token: Option<String>,
+        proven_blocks_path: PathBuf,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            keypairs: keypairs
+                .into_iter()
+                .map(|keypair| (keypair.public_key(), keypair))
+                .collect(),
+            token,
+            proven: Mutex::new(ProvenBlocks::load(proven_blocks_path)?),
+        })
+    }
+
+    /// Listens for connections on `addr` and serves each of them in a
+    /// separate thread. Blocks forever, unless listening fails.
+    pub fn serve(self, addr: &RemoteSignerAddr) -> io::Result<()> {
+        let signer = Arc::new(self);
+        match addr {
+            RemoteSignerAddr::Tcp(addr) => {
+                if signer.token.is_none() && !addr.ip().is_loopback() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "auth token is required to listen on a non-loopback address",
+                    ));
+                }
+                let listener = TcpListener::bind(addr)?;
+                for stream in listener.incoming() {
+                    signer.clone().spawn_connection_handler(stream?);
+                }
+            }
+            #[cfg(unix)]
+            RemoteSignerAddr::Unix(path) => {
+                remove_stale_socket(path)?;
+                let listener = UnixListener::bind(path)?;
+                for stream in listener.incoming() {
+                    signer.clone().spawn_connection_handler(stream?);
+                }
+            }
+            #[cfg(not(unix))]
+            RemoteSignerAddr::Unix(_) => return Err(unix_socket_unsupported()),
+        }
+        Ok(())
+    }
+
+    fn spawn_connection_handler<S: Stream + 'static>(self: Arc<Self>, stream: S) {
+        std::thread::spawn(move || {
+            let mut stream = BufReader::new(stream);
+            let mut authenticated = self.token.is_none();
+            loop {
+                let req = match read_message::<_, RemoteSignerRequest>(&mut stream) {
+                    Ok(Some(req)) => req,
+                    Ok(None) => return,
+                    Err(error) => {
+                        let res = RemoteSignerResponse::Error(format!("invalid request: {error}"));
+                        let _ = write_message(stream.get_mut(), &res);
+                        return;
+                    }
+                };
+                let res = match req {
+                    RemoteSignerRequest::Auth { token } => {
+                        authenticated = self.is_token_valid(&token);
+                        if authenticated {
+                            RemoteSignerResponse::Authenticated
+                        } else {
+                            let res = RemoteSignerResponse::Error("invalid auth token".to_owned());
+                            let _ = write_message(stream.get_mut(), &res);
+                            return;
+                        }
+                    }
+                    _ if !authenticated => {
+                        let res = RemoteSignerResponse::Error("authentication required".to_owned());
+                        let _ = write_message(stream.get_mut(), &res);
+                        return;
+                    }
+                    req => self.handle(req),
+                };
+                if write_message(stream.get_mut(), &res).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn is_token_valid(&self, token: &str) -> bool {
+        let Some(expected) = self.token.as_ref() else {
+            return true;
+        };
+        // Constant time comparison, to not leak the token through timing.
+        expected.len() == token.len()
+            && expected
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+
+    /// Handles request on an authenticated connection.
+    pub fn handle(&self, req: RemoteSignerRequest) -> RemoteSignerResponse {
+        match req {
+            RemoteSignerRequest::Auth { .. } => RemoteSignerResponse::Authenticated,
+            RemoteSignerRequest::PubKeys => {
+                RemoteSignerResponse::PubKeys(self.keypairs.keys().cloned().collect())
+            }
+            RemoteSignerRequest::VrfEvaluate(input) => {
+                let keypairs = self
+                    .keypairs
+                    .iter()
+                    .map(|(pub_key, keypair)| (pub_key.clone(), keypair.clone().into()))
+                    .collect();
+                RemoteSignerResponse::VrfEvaluated(super::vrf_evaluator::evaluate(
+                    &input, &keypairs,
+                ))
+            }
+            RemoteSignerRequest::Prove { block_hash, input } => {
+                match self.prove(block_hash, input) {
+                    Ok(proof) => RemoteSignerResponse::Proved(proof),
+                    Err(error) => RemoteSignerResponse::Error(error),
+                }
+            }
+        }
+    }
+
+    fn prove(
+        &self,
+        block_hash: StateHash,
+        input: Box<ProverExtendBlockchainInputStableV2>,
+    ) -> Result<Box<MinaBaseProofStableV2>, String> {
+        // Hash sent by the client is only trusted if it's the hash of the
+        // state being proven, otherwise a different block could be proven
+        // for an already proven slot.
+        let computed_hash = input.next_state.hash();
+        if computed_hash != block_hash {
+            return Err(format!(
+                "block hash {block_hash} doesn't match the proven state hash {computed_hash}"
+            ));
+        }
+        let consensus_state = &input.next_state.body.consensus_state;
+        let producer = AccountPublicKey::from(consensus_state.block_creator.clone());
+        let global_slot = consensus_state.global_slot_since_genesis.as_u32();
+        let keypair = self
+            .keypairs
+            .get(&producer)
+            .cloned()
+            .ok_or_else(|| format!("block creator {producer} is not held by the signer"))?;
+
+        self.proven.lock().unwrap().record(ProvenBlock {
+            producer,
+            global_slot,
+            block_hash: computed_hash,
+        })?;
+
+        super::prove(input, keypair, false).map_err(|err| format!("{err:?}"))
+    }
+}
+
+/// Removes the socket file left by a signer that didn't shut down cleanly,
+/// so that binding doesn't fail.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("remote signer is already listening on {}", path.display()),
+                ));
+            }
+            std::fs::remove_file(path)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ProvenBlock {
+    producer: AccountPublicKey,
+    global_slot: u32,
+    block_hash: StateHash,
+}
+
+/// Blocks proven by the signer: (producer, global slot since genesis) ->
+/// block hash.
+///
+/// Persisted as newline delimited json, so that refusing to produce two
+/// different blocks for the same slot survives restarts.
+struct ProvenBlocks {
+    path: PathBuf,
+    blocks: BTreeMap<(AccountPublicKey, u32), StateHash>,
+}
+
+impl ProvenBlocks {
+    fn load(path: PathBuf) -> io::Result<Self> {
+        let blocks = match File::open(&path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .map(|line| {
+                    let block: ProvenBlock = serde_json::from_str(&line?)?;
+                    Ok(((block.producer, block.global_slot), block.block_hash))
+                })
+                .collect::<io::Result<_>>()?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self { path, blocks })
+    }
+
+    /// Records the block, unless a different block was already proven for
+    /// the same producer and slot. Block is persisted before it's recorded.
+    fn record(&mut self, block: ProvenBlock) -> Result<(), String> {
+        let ProvenBlock {
+            producer,
+            global_slot,
+            block_hash,
+        } = &block;
+        match self.blocks.get(&(producer.clone(), *global_slot)) {
+            Some(hash) if hash == block_hash => return Ok(()),
+            Some(hash) => {
+                return Err(format!(
+                    "refusing to produce block {block_hash} by {producer} at slot {global_slot}, \
+                     already produced {hash}"
+                ))
+            }
+            None => {}
+        }
+        self.append(&block)
+            .map_err(|err| format!("failed to persist proven block {block_hash}: {err}"))?;
+        self.blocks
+            .insert((block.producer, block.global_slot), block.block_hash);
+        Ok(())
+    }
+
+    fn append(&self, block: &ProvenBlock) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        write_message(&mut file, block)?;
+        file.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "openmina-test-remote-signer-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn proven_block(global_slot: u32, block_hash: StateHash) -> ProvenBlock {
+        ProvenBlock {
+            producer: AccountSecretKey::genesis_producer().public_key(),
+            global_slot,
+            block_hash,
+        }
+    }
+
+    #[test]
+    fn proven_blocks_survive_restart() {
+        let path = temp_path("proven-blocks");
+        let hash: StateHash = "3NKxUSAJE3wqJkrtBhMYhwzrMq3B5sKjPJQRyXz1YrPWA7761opD"
+            .parse()
+            .unwrap();
+
+        let mut proven = ProvenBlocks::load(path.clone()).unwrap();
+        proven.record(proven_block(10, hash.clone())).unwrap();
+        proven.record(proven_block(10, hash.clone())).unwrap();
+        assert!(proven.record(proven_block(10, StateHash::zero())).is_err());
+
+        let mut proven = ProvenBlocks::load(path.clone()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(proven.blocks.len(), 1);
+        assert!(proven.record(proven_block(10, StateHash::zero())).is_err());
+    }
+
+    #[test]
+    fn remote_signer_addr() {
+        let addr = |s: &str| s.parse::<RemoteSignerAddr>();
+        assert!(matches!(addr("127.0.0.1:7000"), Ok(RemoteSignerAddr::Tcp(a)) if a.port() == 7000));
+        assert!(matches!(addr("localhost:7000"), Ok(RemoteSignerAddr::Tcp(a)) if a.port() == 7000));
+        for path in ["/run/signer.sock", "./signer.sock", "unix:signer.sock"] {
+            assert!(
+                matches!(addr(path), Ok(RemoteSignerAddr::Unix(_))),
+                "{path}"
+            );
+        }
+        let Ok(RemoteSignerAddr::Unix(path)) = addr("unix:signer.sock") else {
+            panic!("not a unix socket");
+        };
+        assert_eq!(path, PathBuf::from("signer.sock"));
+        // Not silently taken for a socket path.
+        assert!(addr("signer.sock").is_err());
+        assert!(addr("signer.invalid:7000").is_err());
+    }
+
+    #[test]
+    fn tcp_requires_token_on_non_loopback_address() {
+        let signer = RemoteSigner::new(vec![], None, temp_path("no-token")).unwrap();
+        let error = signer.serve(&"0.0.0.0:0".parse().unwrap()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_auth() {
+        let socket = temp_path("socket");
+        // Stale socket from a previous run
+        drop(UnixListener::bind(&socket).unwrap());
+
+        let keypair = AccountSecretKey::rand();
+        let token = Some("secret".to_owned());
+        let signer =
+            RemoteSigner::new(vec![keypair.clone()], token.clone(), temp_path("auth")).unwrap();
+        let addr = RemoteSignerAddr::Unix(socket.clone());
+        std::thread::spawn({
+            let addr = addr.clone();
+            move || signer.serve(&addr)
+        });
+
+        let client = RemoteSignerClient::new(addr.clone(), token);
+        let pub_keys = (0..100)
+            .find_map(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                client.pub_keys().ok()
+            })
+            .expect("remote signer didn't start");
+        assert_eq!(pub_keys, vec![keypair.public_key()]);
+
+        let error = RemoteSignerClient::new(addr.clone(), Some("wrong".to_owned()))
+            .pub_keys()
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        let res = RemoteSignerClient::new(addr, None)
+            .call(RemoteSignerRequest::PubKeys)
+            .unwrap();
+        assert!(matches!(res, RemoteSignerResponse::Error(_)));
+
+        let _ = std::fs::remove_file(socket);
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
+use mina_p2p_messages::v2::ConsensusVrfOutputTruncatedStableV1;
 use mina_signer::Keypair;
 use node::{
+    account::AccountPublicKey,
     block_producer::BlockProducerVrfEvaluatorEvent,
     block_producer::{
         vrf_evaluator::{VrfEvaluationOutputWithHash, VrfEvaluatorInput},
@@ -12,45 +16,111 @@ use vrf::{VrfEvaluationInput, VrfEvaluationOutput};
 
 use crate::NodeService;
 
+use super::remote_signer::{RemoteSignerClient, RemoteSignerRequest, RemoteSignerResponse};
+
 pub fn vrf_evaluator(
     event_sender: UnboundedSender<Event>,
     mut vrf_evaluation_receiver: UnboundedReceiver<VrfEvaluatorInput>,
-    keypair: Keypair,
+    keypairs: BTreeMap<AccountPublicKey, Keypair>,
+) {
+    while let Some(vrf_evaluator_input) = vrf_evaluation_receiver.blocking_recv() {
+        let vrf_result_with_hash = evaluate(&vrf_evaluator_input, &keypairs);
+        send_evaluated(&event_sender, vrf_result_with_hash);
+    }
+}
+
+/// Same as [vrf_evaluator], but evaluation is delegated to the remote signer.
+pub fn remote_vrf_evaluator(
+    event_sender: UnboundedSender<Event>,
+    mut vrf_evaluation_receiver: UnboundedReceiver<VrfEvaluatorInput>,
+    signer: RemoteSignerClient,
 ) {
     while let Some(vrf_evaluator_input) = vrf_evaluation_receiver.blocking_recv() {
-        let mut vrf_result = VrfEvaluationOutput::SlotLost(vrf_evaluator_input.global_slot);
-
-        for (index, (pub_key, stake)) in vrf_evaluator_input.delegator_table.iter() {
-            let vrf_input = VrfEvaluationInput::new(
-                keypair.clone(),
-                vrf_evaluator_input.epoch_seed.clone(),
-                pub_key.to_string(),
-                vrf_evaluator_input.global_slot,
-                *index,
-                (*stake).into(),
-                vrf_evaluator_input.total_currency.into(),
-            );
-            vrf_result = vrf::evaluate_vrf(vrf_input).unwrap();
-
-            // the first delegate that won the slot
-            if let VrfEvaluationOutput::SlotWon(_) = vrf_result {
-                break;
+        // State machine waits for the result of each evaluation, so we
+        // keep retrying until signer is reachable again.
+        let vrf_result_with_hash = loop {
+            let req = RemoteSignerRequest::VrfEvaluate(vrf_evaluator_input.clone());
+            match signer.call(req) {
+                Ok(RemoteSignerResponse::VrfEvaluated(res)) => break res,
+                Ok(RemoteSignerResponse::Error(error)) => {
+                    openmina_core::error!(openmina_core::log::system_time();
+                        kind = "RemoteSignerVrfEvaluate",
+                        summary = "remote signer failed to evaluate vrf",
+                        error = error);
+                }
+                Ok(_) => {
+                    openmina_core::error!(openmina_core::log::system_time();
+                        kind = "RemoteSignerVrfEvaluate",
+                        summary = "unexpected response from remote signer");
+                }
+                Err(error) => {
+                    openmina_core::error!(openmina_core::log::system_time();
+                        kind = "RemoteSignerVrfEvaluate",
+                        summary = "remote signer request failed",
+                        error = error.to_string());
+                }
             }
-        }
-        let vrf_result_with_hash = VrfEvaluationOutputWithHash::new(
-            vrf_result,
-            vrf_evaluator_input.staking_ledger_hash.clone(),
-        );
-        // send the result back to the state machine
-        let _ = event_sender.send(
-            BlockProducerEvent::VrfEvaluator(BlockProducerVrfEvaluatorEvent::Evaluated(
-                vrf_result_with_hash,
-            ))
-            .into(),
-        );
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        };
+        send_evaluated(&event_sender, vrf_result_with_hash);
     }
 }
 
+fn send_evaluated(
+    event_sender: &UnboundedSender<Event>,
+    vrf_result_with_hash: VrfEvaluationOutputWithHash,
+) {
+    // send the result back to the state machine
+    let _ = event_sender.send(
+        BlockProducerEvent::VrfEvaluator(BlockProducerVrfEvaluatorEvent::Evaluated(
+            vrf_result_with_hash,
+        ))
+        .into(),
+    );
+}
+
+/// Evaluates vrf for the slot with each of the producer `keypairs`.
+pub fn evaluate(
+    vrf_evaluator_input: &VrfEvaluatorInput,
+    keypairs: &BTreeMap<AccountPublicKey, Keypair>,
+) -> VrfEvaluationOutputWithHash {
+    let vrf_result = vrf_evaluator_input
+        .delegator_table
+        .iter()
+        .filter_map(|(producer, delegators)| {
+            let keypair = keypairs.get(producer)?;
+            delegators
+                .iter()
+                .map(|(index, (pub_key, stake))| {
+                    let vrf_input = VrfEvaluationInput::new(
+                        keypair.clone(),
+                        vrf_evaluator_input.epoch_seed.clone(),
+                        pub_key.to_string(),
+                        vrf_evaluator_input.global_slot,
+                        *index,
+                        (*stake).into(),
+                        vrf_evaluator_input.total_currency.into(),
+                    );
+                    vrf::evaluate_vrf(vrf_input).unwrap()
+                })
+                // the first delegate that won the slot
+                .find(|vrf_result| matches!(vrf_result, VrfEvaluationOutput::SlotWon(_)))
+        })
+        // if multiple producer keys won the slot, pick the one which
+        // wins the short range fork tie-break.
+        .max_by_key(|vrf_result| match vrf_result {
+            VrfEvaluationOutput::SlotWon(won_slot) => {
+                ConsensusVrfOutputTruncatedStableV1::from(&won_slot.vrf_output).blake2b()
+            }
+            VrfEvaluationOutput::SlotLost(_) => Default::default(),
+        })
+        .unwrap_or(VrfEvaluationOutput::SlotLost(
+            vrf_evaluator_input.global_slot,
+        ));
+
+    VrfEvaluationOutputWithHash::new(vrf_result, vrf_evaluator_input.staking_ledger_hash.clone())
+}
+
 impl node::block_producer::vrf_evaluator::BlockProducerVrfEvaluatorService for NodeService {
     fn evaluate(&mut self, data: VrfEvaluatorInput) {
         if let Some(bp) = self.block_producer.as_mut() {
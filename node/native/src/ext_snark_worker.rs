@@ -20,13 +20,15 @@ use mina_p2p_messages::{
 use node::core::channels::{mpsc, oneshot};
 use node::event_source::Event;
 use node::external_snark_worker::{
-    ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerService,
-    ExternalSnarkWorkerWorkError, SnarkWorkSpec,
+    ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerId,
+    ExternalSnarkWorkerService, ExternalSnarkWorkerWorkError, SnarkWorkSpec,
 };
 
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 
+use crate::rust_snark_worker::RustSnarkWorkerFacade;
+
 use super::NodeService;
 
 /// Error generated by external snarker controller.
@@ -111,7 +113,7 @@ where
 
 /// Facade for external worker process.
 pub struct ExternalSnarkWorkerFacade {
-    data_chan: mpsc::Sender<SnarkWorkSpec>,
+    data_chan: mpsc::Sender<(SnarkWorkSpec, CurrencyFeeStableV1)>,
     cancel_chan: mpsc::Sender<()>,
     kill_chan: oneshot::Sender<()>,
 }
@@ -194,15 +196,17 @@ async fn stderr_reader<R: AsyncRead + Unpin>(r: R) -> Result<(), SnarkerError> {
 }
 
 macro_rules! send_event {
-    ($channel:expr, $event:expr) => {
-        _ = $channel.send(node::event_source::Event::ExternalSnarkWorker($event));
+    ($channel:expr, $worker_id:expr, $event:expr) => {
+        _ = $channel.send(node::event_source::Event::ExternalSnarkWorker(
+            $worker_id, $event,
+        ));
     };
 }
 
 impl ExternalSnarkWorkerFacade {
     fn start(
+        worker_id: ExternalSnarkWorkerId,
         public_key: NonZeroCurvePoint,
-        fee: CurrencyFeeStableV1,
         event_sender: mpsc::UnboundedSender<Event>,
     ) -> Result<Self, SnarkerError> {
         let path = std::env::var_os("MINA_EXE_PATH")
@@ -223,7 +227,7 @@ impl ExternalSnarkWorkerFacade {
 
         // TODO(akoptelov) make the block return terminal errors instead of sending them down the channel and exit.
         std::thread::Builder::new()
-            .name("external-snark-worker".into())
+            .name(format!("external-snark-worker-{worker_id}"))
             .spawn(move || {
                 let runtime = tokio::runtime::Builder::new_current_thread()
                     .enable_all()
@@ -240,7 +244,11 @@ impl ExternalSnarkWorkerFacade {
                     {
                         Ok(v) => v,
                         Err(err) => {
-                            send_event!(event_sender_clone, SnarkerError::from(err).into());
+                            send_event!(
+                                event_sender_clone,
+                                worker_id,
+                                SnarkerError::from(err).into()
+                            );
                             return;
                         }
                     };
@@ -254,7 +262,7 @@ impl ExternalSnarkWorkerFacade {
                             // readiness
                             let request = ExternalSnarkWorkerRequest::await_readiness();
                             if let Err(err) = write_binprot(request, &mut child_stdin).await {
-                                send_event!(event_sender_clone, err.into());
+                                send_event!(event_sender_clone, worker_id, err.into());
                                 return;
                             }
                             let response = read_binprot(&mut child_stdout).await;
@@ -262,12 +270,14 @@ impl ExternalSnarkWorkerFacade {
                                 Ok(v) if v => {
                                     send_event!(
                                         event_sender_clone,
+                                        worker_id,
                                         ExternalSnarkWorkerEvent::Started
                                     );
                                 }
                                 Ok(_) => {
                                     send_event!(
                                         event_sender_clone,
+                                        worker_id,
                                         SnarkerError::Broken(
                                             "snarker responded `false` on readiness request".into()
                                         )
@@ -276,39 +286,45 @@ impl ExternalSnarkWorkerFacade {
                                     return;
                                 }
                                 Err(err) => {
-                                    send_event!(event_sender_clone, err.into());
+                                    send_event!(event_sender_clone, worker_id, err.into());
                                     return;
                                 }
                             }
 
                             loop {
-                                let Some(spec) = data_rx.recv().await else {
+                                let Some((spec, fee)) = data_rx.recv().await else {
                                     return;
                                 };
                                 let request = ExternalSnarkWorkerRequest::perform_job(
                                     spec,
                                     public_key.clone(),
-                                    fee.clone(),
+                                    fee,
                                 );
                                 if let Err(err) = write_binprot(request, &mut child_stdin).await {
-                                    send_event!(event_sender_clone, err.into());
+                                    send_event!(event_sender_clone, worker_id, err.into());
                                     return;
                                 }
                                 let response = read_binprot(&mut child_stdout).await;
                                 match response {
                                     Ok(result) => match result {
                                         ExternalSnarkWorkerResult::Ok(Some(v)) => {
-                                            send_event!(event_sender_clone, Arc::new(v).into());
+                                            send_event!(
+                                                event_sender_clone,
+                                                worker_id,
+                                                Arc::new(v).into()
+                                            );
                                         }
                                         ExternalSnarkWorkerResult::Ok(None) => {
                                             send_event!(
                                                 event_sender_clone,
+                                                worker_id,
                                                 ExternalSnarkWorkerEvent::WorkCancelled
                                             );
                                         }
                                         ExternalSnarkWorkerResult::Err(err) => {
                                             send_event!(
                                                 event_sender_clone,
+                                                worker_id,
                                                 ExternalSnarkWorkerWorkError::Error(
                                                     err.to_string()
                                                 )
@@ -317,7 +333,7 @@ impl ExternalSnarkWorkerFacade {
                                         }
                                     },
                                     Err(err) => {
-                                        send_event!(event_sender_clone, err.into());
+                                        send_event!(event_sender_clone, worker_id, err.into());
                                     }
                                 }
                             }
@@ -333,7 +349,11 @@ impl ExternalSnarkWorkerFacade {
                                 if let Err(err) =
                                     nix::sys::signal::kill(pid, nix::sys::signal::Signal::SIGINT)
                                 {
-                                    send_event!(event_sender_clone, SnarkerError::from(err).into());
+                                    send_event!(
+                                        event_sender_clone,
+                                        worker_id,
+                                        SnarkerError::from(err).into()
+                                    );
                                 }
                             }
                         });
@@ -343,16 +363,24 @@ impl ExternalSnarkWorkerFacade {
                         let event_sender_clone = event_sender.clone();
                         tokio::spawn(async move {
                             if let Err(err) = stderr_reader(child_stderr).await {
-                                send_event!(event_sender_clone, err.into());
+                                send_event!(event_sender_clone, worker_id, err.into());
                             }
                         });
 
                         tokio::select! {
                             _ = kill_rx => {
                                 if let Err(err) = child.kill().await {
-                                    send_event!(event_sender, SnarkerError::from(err).into());
+                                    send_event!(
+                                        event_sender,
+                                        worker_id,
+                                        SnarkerError::from(err).into()
+                                    );
                                 } else {
-                                    send_event!(event_sender, ExternalSnarkWorkerEvent::Killed);
+                                    send_event!(
+                                        event_sender,
+                                        worker_id,
+                                        ExternalSnarkWorkerEvent::Killed
+                                    );
                                 }
                             }
                             _ = child.wait() => {
@@ -375,9 +403,13 @@ impl ExternalSnarkWorkerFacade {
             .map_err(|_| SnarkerError::Broken("already cancelled".into()))
     }
 
-    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), SnarkerError> {
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
+    ) -> Result<(), SnarkerError> {
         self.data_chan
-            .try_send(spec)
+            .try_send((spec, fee))
             .map_err(|_| SnarkerError::Busy)
     }
 
@@ -388,52 +420,151 @@ impl ExternalSnarkWorkerFacade {
     }
 }
 
+/// Implementation of the snark worker used by the [NodeService].
+#[derive(Debug, Default, Clone, Copy)]
+pub enum SnarkWorkerKind {
+    /// External `mina` snark worker process.
+    #[default]
+    External,
+    /// In-process worker, see [RustSnarkWorkerFacade].
+    Rust,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("invalid snark worker kind: {0}! expected one of: external/rust")]
+pub struct SnarkWorkerKindParseError(String);
+
+impl std::str::FromStr for SnarkWorkerKind {
+    type Err = SnarkWorkerKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "external" => Self::External,
+            "rust" => Self::Rust,
+            other => return Err(SnarkWorkerKindParseError(other.to_owned())),
+        })
+    }
+}
+
+pub enum SnarkWorkerFacade {
+    External(ExternalSnarkWorkerFacade),
+    Rust(RustSnarkWorkerFacade),
+}
+
+impl SnarkWorkerFacade {
+    fn start(
+        kind: SnarkWorkerKind,
+        worker_id: ExternalSnarkWorkerId,
+        public_key: NonZeroCurvePoint,
+        event_sender: mpsc::UnboundedSender<Event>,
+        rust_threads: Option<usize>,
+    ) -> Result<Self, SnarkerError> {
+        Ok(match kind {
+            SnarkWorkerKind::External => Self::External(ExternalSnarkWorkerFacade::start(
+                worker_id,
+                public_key,
+                event_sender,
+            )?),
+            SnarkWorkerKind::Rust => Self::Rust(RustSnarkWorkerFacade::start(
+                worker_id,
+                public_key,
+                event_sender,
+                rust_threads,
+            )?),
+        })
+    }
+
+    fn submit(
+        &mut self,
+        spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
+    ) -> Result<(), SnarkerError> {
+        match self {
+            Self::External(worker) => worker.submit(spec, fee),
+            Self::Rust(worker) => {
+                worker.submit(spec, fee);
+                Ok(())
+            }
+        }
+    }
+
+    fn cancel(&mut self) -> Result<(), SnarkerError> {
+        match self {
+            Self::External(worker) => worker.cancel(),
+            Self::Rust(worker) => {
+                worker.cancel();
+                Ok(())
+            }
+        }
+    }
+
+    fn kill(self) -> Result<(), SnarkerError> {
+        match self {
+            Self::External(worker) => worker.kill(),
+            Self::Rust(worker) => {
+                worker.kill();
+                Ok(())
+            }
+        }
+    }
+}
+
 impl ExternalSnarkWorkerService for NodeService {
     fn start(
         &mut self,
+        worker_id: ExternalSnarkWorkerId,
         public_key: NonZeroCurvePoint,
-        fee: CurrencyFeeStableV1,
     ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        let cmd_sender =
-            ExternalSnarkWorkerFacade::start(public_key, fee, self.event_sender.clone())?;
-        self.snark_worker_sender = Some(cmd_sender);
+        let cmd_sender = SnarkWorkerFacade::start(
+            self.snark_worker_kind,
+            worker_id,
+            public_key,
+            self.event_sender.clone(),
+            self.snark_worker_threads,
+        )?;
+        self.snark_workers.insert(worker_id, cmd_sender);
         Ok(())
     }
 
     fn submit(
         &mut self,
+        worker_id: ExternalSnarkWorkerId,
         spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
     ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        self.snark_worker_sender
-            .as_mut()
+        self.snark_workers
+            .get_mut(&worker_id)
             .ok_or(SnarkerError::NotRunning)
-            .and_then(|sender| sender.submit(spec))?;
+            .and_then(|sender| sender.submit(spec, fee))?;
         Ok(())
     }
 
-    fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError> {
+    fn cancel(&mut self, worker_id: ExternalSnarkWorkerId) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        self.snark_worker_sender
-            .as_mut()
+        self.snark_workers
+            .get_mut(&worker_id)
             .ok_or(SnarkerError::NotRunning)
             .and_then(|sender| sender.cancel())?;
         Ok(())
     }
 
-    fn kill(&mut self) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
+    fn kill(
+        &mut self,
+        worker_id: ExternalSnarkWorkerId,
+    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        self.snark_worker_sender
-            .take()
+        self.snark_workers
+            .remove(&worker_id)
             .ok_or(SnarkerError::NotRunning)
             .and_then(|sender| sender.kill())?;
         Ok(())
@@ -462,7 +593,7 @@ mod tests {
     macro_rules! expect_event {
         ($source:expr, $event:pat) => {
             let result = $source.recv().await.expect("failed to receive an event");
-            let Event::ExternalSnarkWorker(result) = result else {
+            let Event::ExternalSnarkWorker(0, result) = result else {
                 panic!("unexpected event kind");
             };
             let $event = result else {
@@ -474,16 +605,8 @@ mod tests {
     #[tokio::test]
     async fn test_kill() {
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let cmd_sender = ExternalSnarkWorkerFacade::start(
-            NonZeroCurvePoint::default(),
-            CurrencyFeeStableV1(
-                mina_p2p_messages::v2::UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
-                    10_u64.into(),
-                ),
-            ),
-            event_tx,
-        )
-        .unwrap();
+        let cmd_sender =
+            ExternalSnarkWorkerFacade::start(0, NonZeroCurvePoint::default(), event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
@@ -514,11 +637,11 @@ mod tests {
         let (public_key, fee, instances) = read_input(&mut r);
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let mut cmd_sender = ExternalSnarkWorkerFacade::start(public_key, fee, event_tx).unwrap();
+        let mut cmd_sender = ExternalSnarkWorkerFacade::start(0, public_key, event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
-        cmd_sender.submit(instances).unwrap();
+        cmd_sender.submit(instances, fee).unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkResult(_));
 
         cmd_sender.kill().expect("cannot kill worker");
@@ -532,11 +655,11 @@ mod tests {
         let (public_key, fee, instances) = read_input(&mut r);
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let mut cmd_sender = ExternalSnarkWorkerFacade::start(public_key, fee, event_tx).unwrap();
+        let mut cmd_sender = ExternalSnarkWorkerFacade::start(0, public_key, event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
-        cmd_sender.submit(instances.clone()).unwrap();
+        cmd_sender.submit(instances.clone(), fee.clone()).unwrap();
 
         // ensure that for 5 seconds no feedback is received
         let _ = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
@@ -548,7 +671,7 @@ mod tests {
         cmd_sender.cancel().unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkCancelled);
 
-        cmd_sender.submit(instances).unwrap();
+        cmd_sender.submit(instances, fee).unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkResult(_));
 
         cmd_sender.kill().expect("cannot kill worker");
@@ -562,11 +685,11 @@ mod tests {
         let (public_key, fee, instances) = read_input(&mut r);
 
         let (event_tx, mut event_rx) = mpsc::unbounded_channel();
-        let mut cmd_sender = ExternalSnarkWorkerFacade::start(public_key, fee, event_tx).unwrap();
+        let mut cmd_sender = ExternalSnarkWorkerFacade::start(0, public_key, event_tx).unwrap();
 
         expect_event!(event_rx, ExternalSnarkWorkerEvent::Started);
 
-        cmd_sender.submit(instances.clone()).unwrap();
+        cmd_sender.submit(instances.clone(), fee.clone()).unwrap();
 
         // ensure that for 5 seconds no feedback is received
         let _ = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
@@ -578,7 +701,7 @@ mod tests {
         cmd_sender.cancel().unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkCancelled);
 
-        cmd_sender.submit(instances.clone()).unwrap();
+        cmd_sender.submit(instances.clone(), fee.clone()).unwrap();
 
         // ensure that for 5 seconds no feedback is received
         let _ = tokio::time::timeout(Duration::from_secs(5), event_rx.recv())
@@ -590,7 +713,7 @@ mod tests {
         cmd_sender.cancel().unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkCancelled);
 
-        cmd_sender.submit(instances).unwrap();
+        cmd_sender.submit(instances, fee).unwrap();
         expect_event!(event_rx, ExternalSnarkWorkerEvent::WorkResult(_));
 
         cmd_sender.kill().expect("cannot kill worker");
@@ -1,6 +1,9 @@
-use juniper::{EmptyMutation, EmptySubscription, GraphQLEnum, RootNode};
+use juniper::{EmptyMutation, EmptySubscription, GraphQLEnum, GraphQLObject, RootNode};
 use node::{
-    rpc::{RpcRequest, RpcSyncStatsGetResponse, SyncStatsQuery},
+    rpc::{
+        RpcBlockProducerEpochSchedule, RpcBlockProducerSlotScheduleGetResponse, RpcRequest,
+        RpcSyncStatsGetResponse, SyncStatsQuery,
+    },
     stats::sync::SyncKind,
 };
 use warp::{Filter, Rejection, Reply};
@@ -78,6 +81,81 @@ impl BestChain {
     }
 }
 
+#[derive(Clone, Debug, GraphQLObject)]
+struct EpochSlotSchedule {
+    epoch: i32,
+    start_slot: i32,
+    end_slot: i32,
+    seed: String,
+    staking_ledger_hash: String,
+    /// Whether vrf evaluation is finished for the whole epoch.
+    is_evaluated: bool,
+    won_slots: Vec<ScheduledSlot>,
+    delegators: Vec<ScheduledSlotDelegator>,
+}
+
+#[derive(Clone, Debug, GraphQLObject)]
+struct ScheduledSlot {
+    global_slot: i32,
+    /// Unix timestamp in milliseconds.
+    slot_time: String,
+    producer: String,
+    delegator: String,
+    delegator_index: String,
+    delegator_stake: Option<String>,
+    vrf_output: String,
+    vrf_value: Option<f64>,
+    vrf_threshold: Option<f64>,
+}
+
+#[derive(Clone, Debug, GraphQLObject)]
+struct ScheduledSlotDelegator {
+    producer: String,
+    delegator: String,
+    index: String,
+    stake: String,
+    won_slots: i32,
+}
+
+impl From<RpcBlockProducerEpochSchedule> for EpochSlotSchedule {
+    fn from(epoch: RpcBlockProducerEpochSchedule) -> Self {
+        Self {
+            epoch: epoch.epoch as _,
+            start_slot: epoch.start_slot as _,
+            end_slot: epoch.end_slot as _,
+            seed: epoch.seed.to_string(),
+            staking_ledger_hash: epoch.staking_ledger_hash.to_string(),
+            is_evaluated: epoch.is_evaluated,
+            won_slots: epoch
+                .won_slots
+                .into_iter()
+                .map(|slot| ScheduledSlot {
+                    global_slot: slot.global_slot as _,
+                    slot_time: (u64::from(slot.slot_time) / 1_000_000).to_string(),
+                    producer: slot.producer.to_string(),
+                    delegator: slot.delegator.to_string(),
+                    delegator_index: slot.delegator_index.as_u64().to_string(),
+                    delegator_stake: slot.delegator_stake.map(|v| v.to_string()),
+                    vrf_output: slot.vrf_output,
+                    vrf_value: slot.value_with_threshold.map(|(v, _)| v),
+                    vrf_threshold: slot.value_with_threshold.map(|(_, t)| t),
+                })
+                .collect(),
+            delegators: epoch
+                .delegators
+                .into_iter()
+                .map(|delegator| ScheduledSlotDelegator {
+                    producer: delegator.producer.to_string(),
+                    delegator: delegator.delegator.to_string(),
+                    index: delegator.index.as_u64().to_string(),
+                    stake: delegator.stake.to_string(),
+                    won_slots: delegator.won_slots as _,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 struct Query;
 
@@ -132,6 +210,18 @@ impl Query {
             })
             .collect()
     }
+
+    /// Won slot schedule of our block producers for the current and next epoch.
+    async fn block_producer_slot_schedule(context: &Context) -> Vec<EpochSlotSchedule> {
+        let schedule: RpcBlockProducerSlotScheduleGetResponse = context
+            .0
+            .oneshot_request(RpcRequest::BlockProducerSlotScheduleGet)
+            .await
+            .flatten();
+        schedule
+            .map(|schedule| schedule.epochs.into_iter().map(Into::into).collect())
+            .unwrap_or_default()
+    }
 }
 
 pub fn routes(
@@ -11,8 +11,9 @@ use warp::{
 
 use node::core::snark::SnarkJobId;
 use node::rpc::{
-    ActionStatsQuery, RpcBlockProducerStatsGetResponse, RpcMessageProgressResponse, RpcPeerInfo,
-    RpcRequest, RpcScanStateSummaryGetQuery, RpcScanStateSummaryGetResponse,
+    ActionStatsQuery, BlockProducerStatsQuery, RpcBlockProducerStatsGetResponse,
+    RpcMessageProgressResponse, RpcMetricsGetResponse, RpcPeerInfo, RpcRequest,
+    RpcScanStateSummaryGetQuery, RpcScanStateSummaryGetResponse, RpcSnarkMarketStatsGetResponse,
     RpcSnarkPoolJobGetResponse, RpcSnarkerWorkersResponse, RpcStateGetError, RpcStatusGetResponse,
     SyncStatsQuery,
 };
@@ -231,11 +232,12 @@ pub async fn run(port: u16, rpc_sender: super::RpcSender) {
         let rpc_sender_clone = rpc_sender.clone();
         let block_producer_stats = warp::path!("stats" / "block_producer")
             .and(warp::get())
-            .then(move || {
+            .and(optq::<BlockProducerStatsQuery>())
+            .then(move |query: BlockProducerStatsQuery| {
                 let rpc_sender_clone = rpc_sender_clone.clone();
                 async move {
                     let result: RpcBlockProducerStatsGetResponse = rpc_sender_clone
-                        .oneshot_request(RpcRequest::BlockProducerStatsGet)
+                        .oneshot_request(RpcRequest::BlockProducerStatsGet(query))
                         .await
                         .flatten();
 
@@ -422,6 +424,24 @@ pub async fn run(port: u16, rpc_sender: super::RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let snark_market_stats = warp::path!("snark-pool" / "market-stats")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::SnarkMarketStatsGet)
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: RpcSnarkMarketStatsGetResponse| {
+                            with_json_reply(&reply, StatusCode::OK)
+                        },
+                    )
+            }
+        });
+
     let rpc_sender_clone = rpc_sender.clone();
     let snarker_config = warp::path!("snarker" / "config")
         .and(warp::get())
@@ -440,6 +460,50 @@ pub async fn run(port: u16, rpc_sender: super::RpcSender) {
             }
         });
 
+    let rpc_sender_clone = rpc_sender.clone();
+    let block_producer_slot_schedule = warp::path!("block-producer" / "slot-schedule")
+        .and(warp::get())
+        .then(move || {
+            let rpc_sender_clone = rpc_sender_clone.clone();
+            async move {
+                rpc_sender_clone
+                    .oneshot_request(RpcRequest::BlockProducerSlotScheduleGet)
+                    .await
+                    .map_or_else(
+                        dropped_channel_response,
+                        |reply: node::rpc::RpcBlockProducerSlotScheduleGetResponse| {
+                            with_json_reply(&reply, StatusCode::OK)
+                        },
+                    )
+            }
+        });
+
+    let rpc_sender_clone = rpc_sender.clone();
+    let metrics = warp::path!("metrics").and(warp::get()).then(move || {
+        let rpc_sender_clone = rpc_sender_clone.clone();
+        async move {
+            let result: RpcMetricsGetResponse = rpc_sender_clone
+                .oneshot_request(RpcRequest::MetricsGet)
+                .await
+                .flatten();
+            match result {
+                Some(metrics) => {
+                    let mut res = Response::new(super::metrics::encode(&metrics).into());
+                    res.headers_mut().insert(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static(super::metrics::CONTENT_TYPE),
+                    );
+                    res
+                }
+                None => {
+                    let mut res = Response::new("metrics not available".into());
+                    *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                    res
+                }
+            }
+        }
+    });
+
     let cors = warp::cors().allow_any_origin();
     #[cfg(not(feature = "p2p-webrtc"))]
     let routes = state_get.or(state_post);
@@ -453,10 +517,14 @@ pub async fn run(port: u16, rpc_sender: super::RpcSender) {
         .or(scan_state_summary_get)
         .or(snark_pool_jobs_get)
         .or(snark_pool_job_get)
+        .or(snark_market_stats)
         .or(snarker_config)
         .or(snarker_job_commit)
         .or(snarker_job_spec)
         .or(snark_workers)
+        .or(snark_coordinator::routes(rpc_sender.clone()))
+        .or(block_producer_slot_schedule)
+        .or(metrics)
         .or(healthcheck(rpc_sender.clone()))
         .or(readiness(rpc_sender.clone()))
         .or(discovery::routing_table(rpc_sender.clone()))
@@ -483,9 +551,13 @@ fn healthcheck(
                             StatusCode::INTERNAL_SERVER_ERROR,
                         )
                     },
-                    |reply: node::rpc::RpcHealthCheckResponse| match reply {
-                        Ok(()) => with_status(String::new(), StatusCode::OK),
-                        Err(err) => with_status(err, StatusCode::SERVICE_UNAVAILABLE),
+                    |reply: node::rpc::RpcHealthCheckResponse| {
+                        let status = match reply.is_failed() {
+                            false => StatusCode::OK,
+                            true => StatusCode::SERVICE_UNAVAILABLE,
+                        };
+                        let body = serde_json::to_string(&reply).unwrap_or_default();
+                        with_status(body, status)
                     },
                 )
         }
@@ -568,6 +640,99 @@ mod discovery {
     }
 }
 
+/// Endpoints used by remote snark workers (`openmina snark worker`)
+/// when the node runs in coordinator mode.
+mod snark_coordinator {
+    use node::rpc::{
+        RpcRequest, RpcSnarkCoordinatorHeartbeatResponse, RpcSnarkCoordinatorLeaseResponse,
+        RpcSnarkCoordinatorRegisterRequest, RpcSnarkCoordinatorRegisterResponse,
+        RpcSnarkCoordinatorSubmitRequest, RpcSnarkCoordinatorSubmitResponse,
+        RpcSnarkCoordinatorWorkerRequest,
+    };
+    use warp::Filter;
+
+    use super::super::RpcSender;
+    use super::{with_rpc_sender, DroppedChannel};
+
+    pub fn routes(
+        rpc_sender: RpcSender,
+    ) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+        let register = warp::path!("snarker" / "coordinator" / "register")
+            .and(warp::post())
+            .and(with_rpc_sender(rpc_sender.clone()))
+            .and(warp::body::json())
+            .and_then(register);
+        let lease = warp::path!("snarker" / "coordinator" / "lease")
+            .and(warp::post())
+            .and(with_rpc_sender(rpc_sender.clone()))
+            .and(warp::body::json())
+            .and_then(lease);
+        let heartbeat = warp::path!("snarker" / "coordinator" / "heartbeat")
+            .and(warp::post())
+            .and(with_rpc_sender(rpc_sender.clone()))
+            .and(warp::body::json())
+            .and_then(heartbeat);
+        let submit = warp::path!("snarker" / "coordinator" / "submit")
+            .and(warp::post())
+            .and(with_rpc_sender(rpc_sender))
+            .and(warp::body::json())
+            .and_then(submit);
+        register.or(lease).or(heartbeat).or(submit)
+    }
+
+    async fn register(
+        rpc_sender: RpcSender,
+        req: RpcSnarkCoordinatorRegisterRequest,
+    ) -> Result<impl warp::Reply, warp::Rejection> {
+        rpc_sender
+            .oneshot_request(RpcRequest::SnarkCoordinatorRegister(req))
+            .await
+            .map_or_else(
+                || Err(warp::reject::custom(DroppedChannel)),
+                |reply: RpcSnarkCoordinatorRegisterResponse| Ok(warp::reply::json(&reply)),
+            )
+    }
+
+    async fn lease(
+        rpc_sender: RpcSender,
+        req: RpcSnarkCoordinatorWorkerRequest,
+    ) -> Result<impl warp::Reply, warp::Rejection> {
+        rpc_sender
+            .oneshot_request(RpcRequest::SnarkCoordinatorLease(req))
+            .await
+            .map_or_else(
+                || Err(warp::reject::custom(DroppedChannel)),
+                |reply: RpcSnarkCoordinatorLeaseResponse| Ok(warp::reply::json(&reply)),
+            )
+    }
+
+    async fn heartbeat(
+        rpc_sender: RpcSender,
+        req: RpcSnarkCoordinatorWorkerRequest,
+    ) -> Result<impl warp::Reply, warp::Rejection> {
+        rpc_sender
+            .oneshot_request(RpcRequest::SnarkCoordinatorHeartbeat(req))
+            .await
+            .map_or_else(
+                || Err(warp::reject::custom(DroppedChannel)),
+                |reply: RpcSnarkCoordinatorHeartbeatResponse| Ok(warp::reply::json(&reply)),
+            )
+    }
+
+    async fn submit(
+        rpc_sender: RpcSender,
+        req: RpcSnarkCoordinatorSubmitRequest,
+    ) -> Result<impl warp::Reply, warp::Rejection> {
+        rpc_sender
+            .oneshot_request(RpcRequest::SnarkCoordinatorSubmit(req))
+            .await
+            .map_or_else(
+                || Err(warp::reject::custom(DroppedChannel)),
+                |reply: RpcSnarkCoordinatorSubmitResponse| Ok(warp::reply::json(&reply)),
+            )
+    }
+}
+
 fn with_rpc_sender(
     rpc_sender: super::RpcSender,
 ) -> impl warp::Filter<Extract = (super::RpcSender,), Error = Infallible> + Clone {
@@ -2,7 +2,10 @@ pub mod block_producer;
 pub mod ext_snark_worker;
 pub mod graphql;
 pub mod http_server;
+pub mod metrics;
 pub mod rpc;
+pub mod rust_snark_worker;
+pub mod snark_pool;
 pub mod tracing;
 
 mod service;
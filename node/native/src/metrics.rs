@@ -0,0 +1,479 @@
+//! Encoding of node metrics in the Prometheus text exposition format.
+
+use std::fmt::{Display, Write};
+
+use node::rpc::RpcMetrics;
+use node::stats::services::SERVICE_LATENCY_BUCKETS;
+use node::stats::sync::SyncLedger;
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+pub fn encode(metrics: &RpcMetrics) -> String {
+    let mut enc = Encoder::default();
+
+    enc.header(
+        "openmina_action_duration_seconds",
+        "histogram",
+        "Time spent processing actions of the kind.",
+    );
+    for (kind, stats) in metrics.actions.iter() {
+        let ranges = stats.ranges();
+        let count = ranges.iter().map(|(_, r)| r.total_calls).sum::<u64>();
+        if count == 0 {
+            continue;
+        }
+        let kind = kind.to_string();
+        let mut cumulative = 0;
+        for (bound, range) in ranges {
+            cumulative += range.total_calls;
+            if let Some(bound) = bound {
+                enc.bucket(
+                    "openmina_action_duration_seconds",
+                    &[("kind", kind.as_str())],
+                    seconds(bound),
+                    cumulative,
+                );
+            }
+        }
+        let sum = ranges.iter().map(|(_, r)| r.total_duration).sum::<u64>();
+        enc.histogram_end(
+            "openmina_action_duration_seconds",
+            &[("kind", kind.as_str())],
+            seconds(sum),
+            count,
+        );
+    }
+
+    enc.header(
+        "openmina_sync_phase",
+        "gauge",
+        "Current phase of the transition frontier sync.",
+    );
+    enc.sample(
+        "openmina_sync_phase",
+        &[("phase", metrics.sync.phase.as_str())],
+        1,
+    );
+    if let Some(height) = metrics.sync.best_tip_height {
+        enc.header(
+            "openmina_best_tip_height",
+            "gauge",
+            "Height of the best tip.",
+        );
+        enc.sample("openmina_best_tip_height", &[], height);
+    }
+    if let Some(latest) = &metrics.sync.latest {
+        let kind = format!("{:?}", latest.kind);
+        enc.header(
+            "openmina_sync_duration_seconds",
+            "gauge",
+            "Duration of the latest completed sync.",
+        );
+        if let Some(duration) = latest
+            .synced
+            .and_then(|synced| synced.checked_sub(latest.best_tip_received))
+        {
+            enc.sample(
+                "openmina_sync_duration_seconds",
+                &[("kind", kind.as_str())],
+                duration.as_secs_f64(),
+            );
+        }
+        enc.header(
+            "openmina_sync_ledger_phase_duration_seconds",
+            "gauge",
+            "Duration of the ledger sync phases in the latest sync.",
+        );
+        let ledgers = [
+            ("staking_epoch", &latest.ledgers.staking_epoch),
+            ("next_epoch", &latest.ledgers.next_epoch),
+            ("root", &latest.ledgers.root),
+        ];
+        for (ledger, sync) in ledgers {
+            let Some(sync) = sync else { continue };
+            for (phase, duration) in ledger_phase_durations(sync) {
+                enc.sample(
+                    "openmina_sync_ledger_phase_duration_seconds",
+                    &[("ledger", ledger), ("phase", phase)],
+                    duration,
+                );
+            }
+        }
+        enc.header(
+            "openmina_sync_blocks",
+            "gauge",
+            "Number of blocks fetched and applied in the latest sync.",
+        );
+        enc.sample("openmina_sync_blocks", &[], latest.blocks.len());
+        enc.header(
+            "openmina_sync_resyncs",
+            "gauge",
+            "Number of ledger resyncs during the latest sync.",
+        );
+        enc.sample("openmina_sync_resyncs", &[], latest.resyncs.len());
+    }
+
+    enc.header(
+        "openmina_peers",
+        "gauge",
+        "Number of peers by connection state.",
+    );
+    let peers = &metrics.peers;
+    enc.sample(
+        "openmina_peers",
+        &[("state", "connecting")],
+        peers.connecting,
+    );
+    enc.sample("openmina_peers", &[("state", "connected")], peers.connected);
+    enc.sample(
+        "openmina_peers",
+        &[("state", "disconnected")],
+        peers.disconnected,
+    );
+
+    // Labeled by protocol and RPC method rather than by peer, as the
+    // number of peers isn't bounded.
+    let traffic = &peers.traffic;
+    enc.header(
+        "openmina_p2p_traffic_bytes_total",
+        "counter",
+        "Bytes exchanged with peers by protocol.",
+    );
+    for (protocol, counters) in &traffic.protocols {
+        for (direction, value) in [("in", counters.bytes_in), ("out", counters.bytes_out)] {
+            enc.sample(
+                "openmina_p2p_traffic_bytes_total",
+                &[("protocol", protocol.as_str()), ("direction", direction)],
+                value,
+            );
+        }
+    }
+    // Only WebRTC channels count messages, yamux protocols report bytes only.
+    enc.header(
+        "openmina_p2p_traffic_messages_total",
+        "counter",
+        "Messages exchanged with peers by protocol (WebRTC channels only).",
+    );
+    for (protocol, counters) in &traffic.protocols {
+        let values = [("in", counters.messages_in), ("out", counters.messages_out)];
+        for (direction, value) in values {
+            enc.sample(
+                "openmina_p2p_traffic_messages_total",
+                &[("protocol", protocol.as_str()), ("direction", direction)],
+                value,
+            );
+        }
+    }
+    enc.header(
+        "openmina_p2p_rpc_bytes_total",
+        "counter",
+        "Bytes of RPC queries and responses exchanged with peers by method.",
+    );
+    for (method, counters) in &traffic.rpc_methods {
+        for (direction, value) in [("in", counters.bytes_in), ("out", counters.bytes_out)] {
+            enc.sample(
+                "openmina_p2p_rpc_bytes_total",
+                &[("method", method.as_str()), ("direction", direction)],
+                value,
+            );
+        }
+    }
+    enc.header(
+        "openmina_p2p_rpc_messages_total",
+        "counter",
+        "RPC queries and responses exchanged with peers by method.",
+    );
+    for (method, counters) in &traffic.rpc_methods {
+        let values = [("in", counters.messages_in), ("out", counters.messages_out)];
+        for (direction, value) in values {
+            enc.sample(
+                "openmina_p2p_rpc_messages_total",
+                &[("method", method.as_str()), ("direction", direction)],
+                value,
+            );
+        }
+    }
+
+    let snark_pool = &metrics.snark_pool;
+    enc.header(
+        "openmina_snark_pool_jobs",
+        "gauge",
+        "Number of available jobs in the snark pool.",
+    );
+    enc.sample("openmina_snark_pool_jobs", &[], snark_pool.jobs);
+    enc.header(
+        "openmina_snark_pool_commitments",
+        "gauge",
+        "Number of jobs in the snark pool with a commitment.",
+    );
+    enc.sample(
+        "openmina_snark_pool_commitments",
+        &[],
+        snark_pool.commitments,
+    );
+    enc.header(
+        "openmina_snark_pool_snarks",
+        "gauge",
+        "Number of jobs in the snark pool with a snark.",
+    );
+    enc.sample("openmina_snark_pool_snarks", &[], snark_pool.snarks);
+
+    enc.header(
+        "openmina_block_production_total",
+        "counter",
+        "Outcomes of the block production attempts by producer.",
+    );
+    for totals in &metrics.block_producer_totals {
+        let producer = totals.producer.to_string();
+        let outcomes = [
+            ("won_slot", totals.won_slots),
+            ("produced", totals.produced),
+            ("discarded", totals.discarded),
+        ];
+        for (outcome, value) in outcomes {
+            enc.sample(
+                "openmina_block_production_total",
+                &[("producer", producer.as_str()), ("outcome", outcome)],
+                value,
+            );
+        }
+    }
+    // Whether a block is canonical may still change, so it's a gauge.
+    enc.header(
+        "openmina_block_production_recent_blocks",
+        "gauge",
+        "Recently produced blocks by producer and whether they are canonical.",
+    );
+    for stats in &metrics.block_producers {
+        let producer = stats.producer.to_string();
+        for (status, value) in [("canonical", stats.canonical), ("orphaned", stats.orphaned)] {
+            enc.sample(
+                "openmina_block_production_recent_blocks",
+                &[("producer", producer.as_str()), ("status", status)],
+                value,
+            );
+        }
+    }
+
+    enc.header(
+        "openmina_service_latency_seconds",
+        "histogram",
+        "Latency of the ledger and verifier service requests.",
+    );
+    for (kind, stats) in &metrics.services {
+        let labels = [("service", kind.as_str())];
+        let mut cumulative = 0;
+        for (bound, count) in SERVICE_LATENCY_BUCKETS.iter().zip(stats.buckets) {
+            cumulative += count;
+            enc.bucket(
+                "openmina_service_latency_seconds",
+                &labels,
+                seconds(*bound),
+                cumulative,
+            );
+        }
+        enc.histogram_end(
+            "openmina_service_latency_seconds",
+            &labels,
+            seconds(stats.total_duration),
+            stats.count,
+        );
+    }
+
+    enc.out
+}
+
+fn seconds(nanos: u64) -> f64 {
+    nanos as f64 / 1_000_000_000.0
+}
+
+fn ledger_phase_durations(sync: &SyncLedger) -> Vec<(&'static str, f64)> {
+    let snarked = &sync.snarked;
+    let staged = &sync.staged;
+    [
+        (
+            "snarked_fetch_hashes",
+            snarked.fetch_hashes_start,
+            snarked.fetch_hashes_end,
+        ),
+        (
+            "snarked_fetch_accounts",
+            snarked.fetch_accounts_start,
+            snarked.fetch_accounts_end,
+        ),
+        (
+            "staged_fetch_parts",
+            staged.fetch_parts_start,
+            staged.fetch_parts_end,
+        ),
+        (
+            "staged_reconstruct",
+            staged.reconstruct_start,
+            staged.reconstruct_end,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(phase, start, end)| {
+        let duration = end?.checked_sub(start?)?;
+        Some((phase, duration.as_secs_f64()))
+    })
+    .collect()
+}
+
+#[derive(Default)]
+struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# HELP {name} {help}");
+        let _ = writeln!(self.out, "# TYPE {name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        self.labels(labels, None);
+        let _ = writeln!(self.out, " {value}");
+    }
+
+    fn bucket(&mut self, name: &str, labels: &[(&str, &str)], le: f64, value: u64) {
+        let _ = write!(self.out, "{name}_bucket");
+        self.labels(labels, Some(&le.to_string()));
+        let _ = writeln!(self.out, " {value}");
+    }
+
+    fn histogram_end(&mut self, name: &str, labels: &[(&str, &str)], sum: f64, count: u64) {
+        let _ = write!(self.out, "{name}_bucket");
+        self.labels(labels, Some("+Inf"));
+        let _ = writeln!(self.out, " {count}");
+        let _ = write!(self.out, "{name}_sum");
+        self.labels(labels, None);
+        let _ = writeln!(self.out, " {sum}");
+        let _ = write!(self.out, "{name}_count");
+        self.labels(labels, None);
+        let _ = writeln!(self.out, " {count}");
+    }
+
+    fn labels(&mut self, labels: &[(&str, &str)], le: Option<&str>) {
+        let le = le.map(|le| ("le", le));
+        let mut labels = labels.iter().copied().chain(le).peekable();
+        if labels.peek().is_none() {
+            return;
+        }
+        self.out.push('{');
+        for (i, (name, value)) in labels.enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            let _ = write!(self.out, "{name}=\"{value}\"");
+        }
+        self.out.push('}');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_p2p_messages::v2::NonZeroCurvePoint;
+    use node::account::AccountSecretKey;
+    use node::rpc::{RpcMetrics, RpcPeersMetrics, RpcSnarkPoolMetrics, RpcSyncMetrics};
+    use node::stats::block_producer::{BlockProducerKeyStats, BlockProducerKeyTotals};
+    use node::stats::services::{ServiceKind, ServiceLatencyStats};
+
+    use super::encode;
+
+    #[test]
+    fn encode_metrics() {
+        let producer: NonZeroCurvePoint = AccountSecretKey::genesis_producer().public_key().into();
+        let mut peers = RpcPeersMetrics {
+            connected: 2,
+            ..Default::default()
+        };
+        peers.traffic.bytes_in("/coda/rpcs/0.0.1", 100);
+        peers.traffic.rpc_incoming("get\"best_tip", 80);
+        let mut ledger_read = ServiceLatencyStats {
+            count: 2,
+            total_duration: 6_000_000,
+            max_duration: 5_000_000,
+            ..Default::default()
+        };
+        ledger_read.buckets[0] = 1;
+        ledger_read.buckets[1] = 1;
+
+        let metrics = RpcMetrics {
+            actions: Default::default(),
+            sync: RpcSyncMetrics {
+                phase: "Synced".to_owned(),
+                best_tip_height: Some(10),
+                latest: None,
+            },
+            peers,
+            snark_pool: RpcSnarkPoolMetrics::default(),
+            block_producers: vec![BlockProducerKeyStats {
+                producer: producer.clone(),
+                won_slots: 3,
+                produced: 2,
+                canonical: 1,
+                orphaned: 1,
+                discarded: 1,
+            }],
+            block_producer_totals: vec![BlockProducerKeyTotals {
+                producer: producer.clone(),
+                won_slots: 5,
+                produced: 4,
+                discarded: 1,
+            }],
+            services: [(ServiceKind::LedgerRead, ledger_read)].into(),
+        };
+        let out = encode(&metrics);
+        let lines = out.lines().collect::<Vec<_>>();
+
+        let producer_label = format!("producer=\"{producer}\"");
+        let won_slots =
+            format!("openmina_block_production_total{{{producer_label},outcome=\"won_slot\"}} 5");
+        let orphaned = format!(
+            "openmina_block_production_recent_blocks{{{producer_label},status=\"orphaned\"}} 1"
+        );
+        for line in [
+            "# TYPE openmina_sync_phase gauge",
+            "openmina_sync_phase{phase=\"Synced\"} 1",
+            "openmina_best_tip_height 10",
+            "openmina_peers{state=\"connected\"} 2",
+            r#"openmina_p2p_traffic_bytes_total{protocol="/coda/rpcs/0.0.1",direction="in"} 100"#,
+            r#"openmina_p2p_rpc_messages_total{method="get\"best_tip",direction="in"} 1"#,
+            "# TYPE openmina_block_production_total counter",
+            won_slots.as_str(),
+            orphaned.as_str(),
+            "openmina_service_latency_seconds_bucket{service=\"ledger_read\",le=\"0.001\"} 1",
+            "openmina_service_latency_seconds_bucket{service=\"ledger_read\",le=\"0.005\"} 2",
+            "openmina_service_latency_seconds_bucket{service=\"ledger_read\",le=\"+Inf\"} 2",
+            "openmina_service_latency_seconds_sum{service=\"ledger_read\"} 0.006",
+            "openmina_service_latency_seconds_count{service=\"ledger_read\"} 2",
+        ] {
+            assert!(lines.contains(&line), "missing `{line}` in:\n{out}");
+        }
+
+        // Every sample belongs to a declared metric, counters are `_total`.
+        for line in lines.iter().filter(|line| !line.starts_with('#')) {
+            let name = line.split(['{', ' ']).next().unwrap();
+            let declared = |name: &str| {
+                let prefix = format!("# TYPE {name} ");
+                lines
+                    .iter()
+                    .find_map(|line| line.strip_prefix(prefix.as_str()))
+            };
+            let kind = declared(name)
+                .or_else(|| declared(name.strip_suffix("_bucket")?))
+                .or_else(|| declared(name.strip_suffix("_sum")?))
+                .or_else(|| declared(name.strip_suffix("_count")?))
+                .unwrap_or_else(|| panic!("undeclared metric `{name}`"));
+            if kind == "counter" {
+                assert!(name.ends_with("_total"), "counter `{name}`");
+            }
+        }
+    }
+}
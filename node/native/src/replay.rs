@@ -53,7 +53,10 @@ pub fn replay_state_with_input_actions(
         block_producer: None,
         keypair: p2p_sec_key.into(),
         rpc: RpcService::new(),
-        snark_worker_sender: None,
+        snark_worker_kind: Default::default(),
+        snark_worker_threads: None,
+        snark_workers: Default::default(),
+        snark_pool_writer: None,
         stats: Default::default(),
         recorder: Recorder::None,
         replayer: Some(ReplayerState {
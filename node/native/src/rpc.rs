@@ -1,7 +1,8 @@
 use node::rpc::{
-    RpcBlockProducerStatsGetResponse, RpcDiscoveryBoostrapStatsResponse,
-    RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse, RpcMessageProgressResponse,
-    RpcPeersGetResponse, RpcReadinessCheckResponse, RpcStateGetError, RpcStatusGetResponse,
+    RpcBlockProducerSlotScheduleGetResponse, RpcBlockProducerStatsGetResponse,
+    RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse,
+    RpcMessageProgressResponse, RpcMetricsGetResponse, RpcPeersGetResponse,
+    RpcReadinessCheckResponse, RpcStateGetError, RpcStatusGetResponse,
 };
 use serde::{Deserialize, Serialize};
 
@@ -200,6 +201,11 @@ impl node::rpc::RpcService for NodeService {
         respond_block_producer_stats_get,
         RpcBlockProducerStatsGetResponse
     );
+    rpc_service_impl!(
+        respond_block_producer_slot_schedule_get,
+        RpcBlockProducerSlotScheduleGetResponse
+    );
+    rpc_service_impl!(respond_metrics_get, RpcMetricsGetResponse);
     rpc_service_impl!(
         respond_message_progress_stats_get,
         RpcMessageProgressResponse
@@ -247,6 +253,10 @@ impl node::rpc::RpcService for NodeService {
     );
     rpc_service_impl!(respond_snark_pool_get, RpcSnarkPoolGetResponse);
     rpc_service_impl!(respond_snark_pool_job_get, RpcSnarkPoolJobGetResponse);
+    rpc_service_impl!(
+        respond_snark_market_stats_get,
+        node::rpc::RpcSnarkMarketStatsGetResponse
+    );
     rpc_service_impl!(respond_snarker_job_commit, RpcSnarkerJobCommitResponse);
     rpc_service_impl!(
         respond_snarker_job_spec,
@@ -260,6 +270,22 @@ impl node::rpc::RpcService for NodeService {
         respond_snarker_config_get,
         node::rpc::RpcSnarkerConfigGetResponse
     );
+    rpc_service_impl!(
+        respond_snark_coordinator_register,
+        node::rpc::RpcSnarkCoordinatorRegisterResponse
+    );
+    rpc_service_impl!(
+        respond_snark_coordinator_lease,
+        node::rpc::RpcSnarkCoordinatorLeaseResponse
+    );
+    rpc_service_impl!(
+        respond_snark_coordinator_heartbeat,
+        node::rpc::RpcSnarkCoordinatorHeartbeatResponse
+    );
+    rpc_service_impl!(
+        respond_snark_coordinator_submit,
+        node::rpc::RpcSnarkCoordinatorSubmitResponse
+    );
     rpc_service_impl!(respond_health_check, RpcHealthCheckResponse);
     rpc_service_impl!(respond_readiness_check, RpcReadinessCheckResponse);
     rpc_service_impl!(
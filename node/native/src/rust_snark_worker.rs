@@ -0,0 +1,262 @@
+use std::any::Any;
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use ledger::proofs::{
+    gates::get_provers,
+    generate_merge_proof, generate_tx_proof, generate_zkapp_proof,
+    merge::MergeParams,
+    transaction::{ProofError, TransactionParams},
+    zkapp::{LedgerProof, ZkappParams},
+};
+use ledger::scan_state::currency::Fee;
+use ledger::scan_state::scan_state::transaction_snark::{SokMessage, Statement};
+use mina_p2p_messages::v2::{
+    CurrencyFeeStableV1, LedgerProofProdStableV2, MinaBaseUserCommandStableV2,
+    MinaTransactionTransactionStableV2, NonZeroCurvePoint,
+    SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single, TransactionSnarkWorkTStableV2Proofs,
+};
+use node::core::channels::mpsc;
+use node::event_source::Event;
+use node::external_snark_worker::{
+    ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerId,
+    ExternalSnarkWorkerWorkError, SnarkWorkSpec,
+};
+
+/// Snark worker that proves jobs in-process, on its own rayon thread
+/// pool, using the provers from [ledger::proofs].
+///
+/// Proving a statement can't be interrupted, so cancelled (or killed)
+/// job is only stopped before proving the next statement, and its result
+/// is dropped.
+pub struct RustSnarkWorkerFacade {
+    worker_id: ExternalSnarkWorkerId,
+    public_key: NonZeroCurvePoint,
+    event_sender: mpsc::UnboundedSender<Event>,
+    /// Dedicated pool, so that proving neither waits for nor starves the
+    /// other users of the global rayon pool.
+    pool: rayon::ThreadPool,
+    /// Incremented on every submit, cancel and kill. Result of the job
+    /// is only reported if generation didn't change while proving it.
+    generation: Arc<AtomicU64>,
+}
+
+impl RustSnarkWorkerFacade {
+    /// Starts the worker with a pool of `threads` threads, or of the
+    /// rayon's default size if not set.
+    pub fn start(
+        worker_id: ExternalSnarkWorkerId,
+        public_key: NonZeroCurvePoint,
+        event_sender: mpsc::UnboundedSender<Event>,
+        threads: Option<usize>,
+    ) -> io::Result<Self> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.unwrap_or(0))
+            .thread_name(move |i| format!("openmina_snark_worker_{worker_id}_{i}"))
+            .build()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        let sender = event_sender.clone();
+        pool.spawn_fifo(move || {
+            // Loads the provers, which is slow the first time.
+            let event = match std::panic::catch_unwind(|| {
+                let _ = get_provers();
+            }) {
+                Ok(()) => ExternalSnarkWorkerEvent::Started,
+                Err(panic) => ExternalSnarkWorkerEvent::Error(ExternalSnarkWorkerError::Error(
+                    format!("loading provers panicked: {}", panic_message(&*panic)),
+                )),
+            };
+            let _ = sender.send(Event::ExternalSnarkWorker(worker_id, event));
+        });
+
+        Ok(Self {
+            worker_id,
+            public_key,
+            event_sender,
+            pool,
+            generation: Default::default(),
+        })
+    }
+
+    pub fn submit(&mut self, spec: SnarkWorkSpec, fee: CurrencyFeeStableV1) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+        let worker_id = self.worker_id;
+        let message = SokMessage::create(Fee::from_u64(fee.as_u64()), (&self.public_key).into());
+        let sender = self.event_sender.clone();
+
+        self.pool.spawn_fifo(move || {
+            let is_cancelled = || current_generation.load(Ordering::SeqCst) != generation;
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                prove_unless_cancelled(spec, &message, is_cancelled)
+            }));
+            if is_cancelled() {
+                return;
+            }
+            let event = match result {
+                Ok(Ok(Some(proofs))) => ExternalSnarkWorkerEvent::WorkResult(Arc::new(proofs)),
+                Ok(Ok(None)) => return,
+                Ok(Err(err)) => ExternalSnarkWorkerWorkError::Error(format!("{err:?}")).into(),
+                Err(panic) => ExternalSnarkWorkerWorkError::Error(format!(
+                    "prover panicked: {}",
+                    panic_message(&*panic)
+                ))
+                .into(),
+            };
+            let _ = sender.send(Event::ExternalSnarkWorker(worker_id, event));
+        });
+    }
+
+    pub fn cancel(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.send_event(ExternalSnarkWorkerEvent::WorkCancelled);
+    }
+
+    pub fn kill(self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.send_event(ExternalSnarkWorkerEvent::Killed);
+    }
+
+    fn send_event(&self, event: ExternalSnarkWorkerEvent) {
+        let _ = self
+            .event_sender
+            .send(Event::ExternalSnarkWorker(self.worker_id, event));
+    }
+}
+
+/// Proves the job, with fee and prover public key from the `message`.
+///
+/// Also used by the remote snark worker client.
+pub fn prove(
+    spec: SnarkWorkSpec,
+    message: &SokMessage,
+) -> Result<TransactionSnarkWorkTStableV2Proofs, ProofError> {
+    prove_unless_cancelled(spec, message, || false)
+        .map(|proofs| proofs.expect("proving is never cancelled"))
+}
+
+/// Like [prove], but `is_cancelled` is checked before proving each of the
+/// statements. Returns `None` if the job was cancelled.
+fn prove_unless_cancelled(
+    spec: SnarkWorkSpec,
+    message: &SokMessage,
+    is_cancelled: impl Fn() -> bool,
+) -> Result<Option<TransactionSnarkWorkTStableV2Proofs>, ProofError> {
+    let prove_next = |single| {
+        if is_cancelled() {
+            Ok(None)
+        } else {
+            prove_single(single, message).map(Some)
+        }
+    };
+    Ok(match spec {
+        SnarkWorkSpec::One(single) => {
+            prove_next(single)?.map(TransactionSnarkWorkTStableV2Proofs::One)
+        }
+        SnarkWorkSpec::Two((first, second)) => match prove_next(first)? {
+            Some(first) => prove_next(second)?
+                .map(|second| TransactionSnarkWorkTStableV2Proofs::Two((first, second))),
+            None => None,
+        },
+    })
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+fn prove_single(
+    single: SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single,
+    message: &SokMessage,
+) -> Result<LedgerProofProdStableV2, ProofError> {
+    let provers = get_provers();
+    let sok_digest = message.digest();
+
+    let proof = match single {
+        SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single::Transition(
+            statement,
+            tx_witness,
+        ) => {
+            let is_zkapp = matches!(
+                &tx_witness.transaction,
+                MinaTransactionTransactionStableV2::Command(cmd)
+                    if matches!(&**cmd, MinaBaseUserCommandStableV2::ZkappCommand(_))
+            );
+            if is_zkapp {
+                generate_zkapp_proof(ZkappParams {
+                    statement: &statement,
+                    tx_witness: &tx_witness,
+                    message,
+                    step_opt_signed_opt_signed_prover: &provers
+                        .zkapp_step_opt_signed_opt_signed_prover,
+                    step_opt_signed_prover: &provers.zkapp_step_opt_signed_prover,
+                    step_proof_prover: &provers.zkapp_step_proof_prover,
+                    merge_step_prover: &provers.merge_step_prover,
+                    tx_wrap_prover: &provers.tx_wrap_prover,
+                    opt_signed_path: None,
+                    proved_path: None,
+                })?
+            } else {
+                let proof = generate_tx_proof(TransactionParams {
+                    statement: &statement,
+                    tx_witness: &tx_witness,
+                    message,
+                    tx_step_prover: &provers.tx_step_prover,
+                    tx_wrap_prover: &provers.tx_wrap_prover,
+                    only_verify_constraints: false,
+                    expected_step_proof: None,
+                    ocaml_wrap_witness: None,
+                })?;
+                LedgerProof {
+                    statement: Statement::<()>::from(&statement).with_digest(sok_digest),
+                    proof,
+                }
+            }
+        }
+        SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single::Merge(merge) => {
+            let (statement, first, second) = *merge;
+            let statement = Statement::<()>::from(&statement);
+            let proof = generate_merge_proof(MergeParams {
+                statement: statement.clone(),
+                proofs: &[first, second],
+                message,
+                step_prover: &provers.merge_step_prover,
+                wrap_prover: &provers.tx_wrap_prover,
+                only_verify_constraints: false,
+                expected_step_proof: None,
+                ocaml_wrap_witness: None,
+            })?;
+            LedgerProof {
+                statement: statement.with_digest(sok_digest),
+                proof,
+            }
+        }
+    };
+
+    Ok((&proof).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::panic_message;
+
+    #[test]
+    fn panic_message_of_payload() {
+        let panic = std::panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(panic_message(&*panic), "static");
+
+        let slot = 5;
+        let panic = std::panic::catch_unwind(|| panic!("formatted {slot}")).unwrap_err();
+        assert_eq!(panic_message(&*panic), "formatted 5");
+
+        let panic = std::panic::catch_unwind(|| std::panic::panic_any(5)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "unknown panic");
+    }
+}
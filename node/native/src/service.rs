@@ -16,6 +16,7 @@ use node::core::channels::{mpsc, oneshot};
 use node::core::invariants::InvariantsState;
 use node::core::snark::{Snark, SnarkJobId};
 use node::event_source::Event;
+use node::external_snark_worker::ExternalSnarkWorkerId;
 use node::ledger::ledger_manager::LedgerManager;
 use node::ledger::LedgerService;
 use node::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
@@ -30,7 +31,7 @@ use node::snark::block_verify::{
 };
 use node::snark::work_verify::{SnarkWorkVerifyError, SnarkWorkVerifyId, SnarkWorkVerifyService};
 use node::snark::{SnarkEvent, VerifierIndex, VerifierSRS};
-use node::snark_pool::SnarkPoolService;
+use node::snark_pool::{SnarkPoolService, SnarkWork};
 use node::stats::Stats;
 use node::transition_frontier::genesis::GenesisConfig;
 use node::ActionKind;
@@ -38,6 +39,7 @@ use node::ActionKind;
 use crate::block_producer::BlockProducerService;
 use crate::ext_snark_worker;
 use crate::rpc::RpcService;
+use crate::snark_pool::SnarkPoolWriter;
 
 pub struct NodeService {
     pub rng: StdRng,
@@ -53,7 +55,12 @@ pub struct NodeService {
     pub network: NativeP2pNetworkService,
     pub block_producer: Option<BlockProducerService>,
     pub keypair: Keypair,
-    pub snark_worker_sender: Option<ext_snark_worker::ExternalSnarkWorkerFacade>,
+    pub snark_worker_kind: ext_snark_worker::SnarkWorkerKind,
+    /// Size of the thread pool of each in-process snark worker.
+    pub snark_worker_threads: Option<usize>,
+    pub snark_workers: BTreeMap<ExternalSnarkWorkerId, ext_snark_worker::SnarkWorkerFacade>,
+    /// Persists completed work in the snark pool.
+    pub snark_pool_writer: Option<SnarkPoolWriter>,
     pub rpc: RpcService,
     pub stats: Stats,
     pub recorder: Recorder,
@@ -296,6 +303,15 @@ impl SnarkPoolService for NodeService {
             .cloned()
             .collect()
     }
+
+    fn persist_snarks(&mut self, snarks: Vec<SnarkWork>) {
+        if self.replayer.is_some() {
+            return;
+        }
+        if let Some(writer) = self.snark_pool_writer.as_ref() {
+            writer.write(snarks);
+        }
+    }
 }
 
 impl TransitionFrontierGenesisService for NodeService {
@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use node::snark_pool::SnarkWork;
+
+/// Reads the completed work persisted by the previous run of the node.
+///
+/// Returns no snarks if the file doesn't exist yet.
+pub fn read_snark_pool(path: &Path) -> io::Result<Vec<SnarkWork>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// Replaces persisted completed work. It's written to a temporary file
+/// first, so that the previous one stays intact if writing fails.
+fn write_snark_pool(path: &Path, snarks: &[SnarkWork]) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut file = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer(&mut file, snarks)?;
+    file.into_inner()?.sync_data()?;
+    std::fs::rename(tmp_path, path)
+}
+
+/// Persists completed work on a dedicated thread, so that the proofs
+/// don't block the state machine and writes never overlap.
+pub struct SnarkPoolWriter {
+    sender: mpsc::Sender<Vec<SnarkWork>>,
+}
+
+impl SnarkPoolWriter {
+    pub fn spawn(path: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel::<Vec<SnarkWork>>();
+        std::thread::Builder::new()
+            .name("openmina_snark_pool_writer".to_owned())
+            .spawn(move || {
+                while let Ok(mut snarks) = receiver.recv() {
+                    // If writes fall behind, only the latest pool is written.
+                    while let Ok(newer) = receiver.try_recv() {
+                        snarks = newer;
+                    }
+                    if let Err(error) = write_snark_pool(&path, &snarks) {
+                        openmina_core::error!(openmina_core::log::system_time();
+                            kind = "SnarkPoolPersist",
+                            summary = "failed to persist snark pool",
+                            error = error.to_string());
+                    }
+                }
+            })
+            .expect("failed to spawn snark pool writer thread");
+        Self { sender }
+    }
+
+    pub fn write(&self, snarks: Vec<SnarkWork>) {
+        let _ = self.sender.send(snarks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_p2p_messages::binprot::BinProtRead;
+    use mina_p2p_messages::gossip::GossipNetMessageV2;
+    use mina_p2p_messages::v2::NetworkPoolSnarkPoolDiffVersionedStableV2;
+    use node::core::snark::Snark;
+    use node::p2p::PeerId;
+    use node::snark_pool::SnarkWork;
+
+    use super::{read_snark_pool, write_snark_pool};
+
+    fn sample_snark() -> Snark {
+        const BYTES: &[u8] =
+            include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/snark_pool_diff.bin");
+        let message = GossipNetMessageV2::binprot_read(&mut &BYTES[..]).unwrap();
+        match NetworkPoolSnarkPoolDiffVersionedStableV2::try_from(message).unwrap() {
+            NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work) => work.1.into(),
+            NetworkPoolSnarkPoolDiffVersionedStableV2::Empty => panic!("no work"),
+        }
+    }
+
+    #[test]
+    fn snark_pool_round_trip() {
+        let dir =
+            std::env::temp_dir().join(format!("openmina-test-snark-pool-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snark_pool.json");
+
+        // Missing file is an empty pool.
+        assert!(read_snark_pool(&path).unwrap().is_empty());
+
+        let snark = sample_snark();
+        let snarks = vec![SnarkWork {
+            work: snark.clone(),
+            received_t: redux::Timestamp::new(1_000),
+            sender: PeerId::from_bytes([1; 32]),
+        }];
+        write_snark_pool(&path, &snarks).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+
+        let restored = read_snark_pool(&path).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].work, snark);
+        assert_eq!(restored[0].work.proofs, snark.proofs);
+        assert_eq!(restored[0].received_t, snarks[0].received_t);
+        assert_eq!(restored[0].sender, snarks[0].sender);
+
+        // Corrupt file is an error, not an empty pool.
+        std::fs::write(&path, b"[{\"work\":").unwrap();
+        assert!(read_snark_pool(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,15 +1,19 @@
 pub use tracing::Level;
 
 use std::fmt::Result;
+use std::str::FromStr;
 
 use tracing::field::Visit;
 use tracing_subscriber::{
     field::{RecordFields, VisitOutput},
+    filter::LevelFilter,
     fmt::{
         format::{PrettyVisitor, Writer},
         time::FormatTime,
         FormatFields,
     },
+    layer::SubscriberExt,
+    Layer, Registry,
 };
 
 #[allow(unused)]
@@ -65,19 +69,211 @@ impl<'writer> FormatFields<'writer> for TracingFieldFormatter {
     }
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable output.
+    #[default]
+    Pretty,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown log format `{0}`, expected `pretty` or `json`")]
+pub struct LogFormatParseError(String);
+
+impl FromStr for LogFormat {
+    type Err = LogFormatParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            _ => Err(LogFormatParseError(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TracingConfig {
+    pub max_log_level: Level,
+    pub format: LogFormat,
+    /// Endpoint of the OpenTelemetry collector, to which spans are
+    /// exported over OTLP.
+    pub otlp_endpoint: Option<String>,
+}
+
+impl TracingConfig {
+    pub fn new(max_log_level: Level) -> Self {
+        Self {
+            max_log_level,
+            format: LogFormat::default(),
+            otlp_endpoint: None,
+        }
+    }
+}
+
 pub fn initialize(max_log_level: Level) {
-    let builder = tracing_subscriber::FmtSubscriber::builder()
-        .with_max_level(max_log_level)
+    initialize_with_config(TracingConfig::new(max_log_level))
+        .expect("global subscriber should be configurable");
+}
+
+pub fn initialize_with_config(config: TracingConfig) -> anyhow::Result<()> {
+    let builder = tracing_subscriber::fmt::layer()
         .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stdout()))
         .with_test_writer()
         //.with_timer(ReduxTimer)
         ;
-    if max_log_level != Level::TRACE {
-        let subscriber = builder.fmt_fields(TracingFieldFormatter).finish();
-        tracing::subscriber::set_global_default(subscriber)
-    } else {
-        let subscriber = builder.finish();
-        tracing::subscriber::set_global_default(subscriber)
+    let fmt_layer = match config.format {
+        LogFormat::Json => builder
+            .with_ansi(false)
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+        LogFormat::Pretty if config.max_log_level != Level::TRACE => {
+            builder.fmt_fields(TracingFieldFormatter).boxed()
+        }
+        LogFormat::Pretty => builder.boxed(),
+    };
+    let otlp_layer = config
+        .otlp_endpoint
+        .as_deref()
+        .map(otlp::layer::<Registry>)
+        .transpose()?;
+
+    let subscriber = tracing_subscriber::registry()
+        .with(fmt_layer.and_then(otlp_layer))
+        .with(LevelFilter::from_level(config.max_log_level));
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(())
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use std::time::{Duration, SystemTime};
+
+    use opentelemetry::trace::TraceId;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{trace, Resource};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::Subscriber;
+    use tracing_opentelemetry::OtelData;
+    use tracing_subscriber::layer::Context;
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    use node::core::log::BLOCK_LIFECYCLE_TARGET;
+
+    pub fn layer<S>(endpoint: &str) -> anyhow::Result<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let endpoint = endpoint.to_owned();
+        let (tx, rx) = std::sync::mpsc::channel();
+        // Batch exporter needs tokio runtime for the whole node lifetime.
+        std::thread::Builder::new()
+            .name("openmina_otlp".to_owned())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("failed to build otlp exporter runtime");
+                let tracer = runtime.block_on(async {
+                    opentelemetry_otlp::new_pipeline()
+                        .tracing()
+                        .with_exporter(
+                            opentelemetry_otlp::new_exporter()
+                                .tonic()
+                                .with_endpoint(endpoint),
+                        )
+                        .with_trace_config(trace::config().with_resource(Resource::new([
+                            KeyValue::new("service.name", "openmina"),
+                        ])))
+                        .install_batch(opentelemetry_sdk::runtime::Tokio)
+                });
+                let _ = tx.send(tracer);
+                runtime.block_on(std::future::pending::<()>());
+            })?;
+        let tracer = rx.recv()??;
+
+        Ok(tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .and_then(BlockTraceLayer)
+            .boxed())
+    }
+
+    /// Puts spans of the same block into the same trace on every node,
+    /// by deriving the trace id from the block hash, and starts them at
+    /// the block's slot start, see [node::core::log::block_lifecycle].
+    struct BlockTraceLayer;
+
+    impl<S> Layer<S> for BlockTraceLayer
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            if attrs.metadata().target() != BLOCK_LIFECYCLE_TARGET {
+                return;
+            }
+            let mut visitor = BlockSpanVisitor::default();
+            attrs.record(&mut visitor);
+            let (Some(block_hash), Some(span)) = (visitor.block_hash, ctx.span(id)) else {
+                return;
+            };
+            let mut extensions = span.extensions_mut();
+            if let Some(data) = extensions.get_mut::<OtelData>() {
+                data.builder.trace_id = Some(block_trace_id(&block_hash));
+                if let Some(slot_start) = visitor.slot_start {
+                    data.builder.start_time =
+                        Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(slot_start));
+                }
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct BlockSpanVisitor {
+        block_hash: Option<String>,
+        slot_start: Option<u64>,
+    }
+
+    impl Visit for BlockSpanVisitor {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "slot_start" {
+                self.slot_start = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "block_hash" {
+                self.block_hash = Some(format!("{value:?}"));
+            }
+        }
+    }
+
+    /// 128-bit FNV-1a hash of the block hash.
+    fn block_trace_id(block_hash: &str) -> TraceId {
+        let hash = block_hash
+            .bytes()
+            .fold(0x6c62272e07bb014262b821756295c58d_u128, |hash, byte| {
+                (hash ^ byte as u128).wrapping_mul(0x0000000001000000000000000000013b)
+            });
+        TraceId::from_bytes(hash.to_be_bytes())
+    }
+}
+
+#[cfg(not(feature = "otlp"))]
+mod otlp {
+    use tracing::Subscriber;
+    use tracing_subscriber::Layer;
+
+    pub fn layer<S>(_endpoint: &str) -> anyhow::Result<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber,
+    {
+        anyhow::bail!("otlp export is not supported, node must be built with `otlp` feature")
     }
-    .expect("global subscriber should be configurable");
 }
@@ -28,7 +28,9 @@ use crate::p2p::channels::rpc::P2pChannelsRpcAction;
 use crate::p2p::channels::snark::P2pChannelsSnarkAction;
 use crate::p2p::channels::snark_job_commitment::P2pChannelsSnarkJobCommitmentAction;
 use crate::p2p::channels::transaction::P2pChannelsTransactionAction;
-use crate::p2p::channels::{P2pChannelsAction, P2pChannelsMessageReceivedAction};
+use crate::p2p::channels::{
+    P2pChannelsAction, P2pChannelsMessageReceivedAction, P2pChannelsMessageSentAction,
+};
 use crate::p2p::connection::incoming::P2pConnectionIncomingAction;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingAction;
 use crate::p2p::connection::P2pConnectionAction;
@@ -58,6 +60,7 @@ use crate::snark::work_verify::SnarkWorkVerifyAction;
 use crate::snark::work_verify_effectful::SnarkWorkVerifyEffectfulAction;
 use crate::snark::SnarkAction;
 use crate::snark_pool::candidate::SnarkPoolCandidateAction;
+use crate::snark_pool::coordinator::SnarkPoolCoordinatorAction;
 use crate::snark_pool::{SnarkPoolAction, SnarkPoolEffectfulAction};
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
 use crate::transition_frontier::genesis_effectful::TransitionFrontierGenesisEffectfulAction;
@@ -94,6 +97,7 @@ pub enum ActionKind {
     BlockProducerBlockProvePending,
     BlockProducerBlockProveSuccess,
     BlockProducerBlockUnprovenBuild,
+    BlockProducerReset,
     BlockProducerStagedLedgerDiffCreateInit,
     BlockProducerStagedLedgerDiffCreatePending,
     BlockProducerStagedLedgerDiffCreateSuccess,
@@ -129,6 +133,7 @@ pub enum ActionKind {
     ConsensusDetectForkRange,
     ConsensusLongRangeForkResolve,
     ConsensusPrune,
+    ConsensusReset,
     ConsensusShortRangeForkResolve,
     EventSourceNewEvent,
     EventSourceProcessEvents,
@@ -163,6 +168,7 @@ pub enum ActionKind {
     P2pChannelsBestTipRequestSend,
     P2pChannelsBestTipResponseSend,
     P2pChannelsMessageReceived,
+    P2pChannelsMessageSent,
     P2pChannelsRpcInit,
     P2pChannelsRpcPending,
     P2pChannelsRpcReady,
@@ -183,6 +189,8 @@ pub enum ActionKind {
     P2pChannelsSnarkRequestSend,
     P2pChannelsSnarkResponseSend,
     P2pChannelsSnarkJobCommitmentInit,
+    P2pChannelsSnarkJobCommitmentLibp2pBroadcast,
+    P2pChannelsSnarkJobCommitmentLibp2pReceived,
     P2pChannelsSnarkJobCommitmentPending,
     P2pChannelsSnarkJobCommitmentPromiseReceived,
     P2pChannelsSnarkJobCommitmentReady,
@@ -237,6 +245,7 @@ pub enum ActionKind {
     P2pDiscoverySuccess,
     P2pIdentifyNewRequest,
     P2pIdentifyUpdatePeerInformation,
+    P2pInitializeChainIdChange,
     P2pInitializeInitialize,
     P2pNetworkIdentifyStreamClose,
     P2pNetworkIdentifyStreamIncomingData,
@@ -285,6 +294,7 @@ pub enum ActionKind {
     P2pNetworkPnetTimeout,
     P2pNetworkPubsubBroadcast,
     P2pNetworkPubsubBroadcastSigned,
+    P2pNetworkPubsubBroadcastSnarkJobCommitment,
     P2pNetworkPubsubIncomingData,
     P2pNetworkPubsubNewStream,
     P2pNetworkPubsubOutgoingData,
@@ -337,6 +347,7 @@ pub enum ActionKind {
     P2pPeerDiscovered,
     P2pPeerReady,
     RpcActionStatsGet,
+    RpcBlockProducerSlotScheduleGet,
     RpcBlockProducerStatsGet,
     RpcDiscoveryBoostrapStats,
     RpcDiscoveryRoutingTable,
@@ -344,6 +355,7 @@ pub enum ActionKind {
     RpcGlobalStateGet,
     RpcHealthCheck,
     RpcMessageProgressGet,
+    RpcMetricsGet,
     RpcP2pConnectionIncomingError,
     RpcP2pConnectionIncomingInit,
     RpcP2pConnectionIncomingPending,
@@ -359,6 +371,11 @@ pub enum ActionKind {
     RpcScanStateSummaryGetPending,
     RpcScanStateSummaryGetSuccess,
     RpcScanStateSummaryLedgerGetInit,
+    RpcSnarkCoordinatorHeartbeat,
+    RpcSnarkCoordinatorLease,
+    RpcSnarkCoordinatorRegister,
+    RpcSnarkCoordinatorSubmit,
+    RpcSnarkMarketStatsGet,
     RpcSnarkPoolAvailableJobsGet,
     RpcSnarkPoolJobGet,
     RpcSnarkerConfigGet,
@@ -378,10 +395,14 @@ pub enum ActionKind {
     SnarkPoolCommitmentAdd,
     SnarkPoolCommitmentCreate,
     SnarkPoolCommitmentCreateMany,
+    SnarkPoolCommitmentRemove,
     SnarkPoolJobCommitmentTimeout,
     SnarkPoolJobsUpdate,
     SnarkPoolP2pSend,
     SnarkPoolP2pSendAll,
+    SnarkPoolPersist,
+    SnarkPoolRebroadcast,
+    SnarkPoolReset,
     SnarkPoolWorkAdd,
     SnarkPoolCandidateInfoReceived,
     SnarkPoolCandidatePeerPrune,
@@ -393,7 +414,17 @@ pub enum ActionKind {
     SnarkPoolCandidateWorkVerifyNext,
     SnarkPoolCandidateWorkVerifyPending,
     SnarkPoolCandidateWorkVerifySuccess,
+    SnarkPoolCoordinatorJobLease,
+    SnarkPoolCoordinatorLeaseRelease,
+    SnarkPoolCoordinatorWorkSubmit,
+    SnarkPoolCoordinatorWorkVerifyError,
+    SnarkPoolCoordinatorWorkVerifyPending,
+    SnarkPoolCoordinatorWorkVerifySuccess,
+    SnarkPoolCoordinatorWorkerHeartbeat,
+    SnarkPoolCoordinatorWorkerRegister,
+    SnarkPoolCoordinatorWorkerRemove,
     SnarkPoolEffectfulSnarkPoolJobsRandomChoose,
+    SnarkPoolEffectfulSnarkPoolPersist,
     SnarkWorkVerifyError,
     SnarkWorkVerifyFinish,
     SnarkWorkVerifyInit,
@@ -401,11 +432,13 @@ pub enum ActionKind {
     SnarkWorkVerifySuccess,
     SnarkWorkVerifyEffectfulInit,
     TransitionFrontierGenesisInject,
+    TransitionFrontierHardForkTransition,
     TransitionFrontierSynced,
     TransitionFrontierGenesisLedgerLoadInit,
     TransitionFrontierGenesisLedgerLoadPending,
     TransitionFrontierGenesisLedgerLoadSuccess,
     TransitionFrontierGenesisProduce,
+    TransitionFrontierGenesisProveError,
     TransitionFrontierGenesisProveInit,
     TransitionFrontierGenesisProvePending,
     TransitionFrontierGenesisProveSuccess,
@@ -488,7 +521,7 @@ pub enum ActionKind {
 }
 
 impl ActionKind {
-    pub const COUNT: u16 = 400;
+    pub const COUNT: u16 = 430;
 }
 
 impl std::fmt::Display for ActionKind {
@@ -582,6 +615,7 @@ impl ActionKindGet for ConsensusAction {
             Self::LongRangeForkResolve { .. } => ActionKind::ConsensusLongRangeForkResolve,
             Self::BestTipUpdate { .. } => ActionKind::ConsensusBestTipUpdate,
             Self::Prune => ActionKind::ConsensusPrune,
+            Self::Reset => ActionKind::ConsensusReset,
         }
     }
 }
@@ -593,6 +627,7 @@ impl ActionKindGet for TransitionFrontierAction {
             Self::GenesisEffect(a) => a.kind(),
             Self::Sync(a) => a.kind(),
             Self::GenesisInject => ActionKind::TransitionFrontierGenesisInject,
+            Self::HardForkTransition => ActionKind::TransitionFrontierHardForkTransition,
             Self::Synced { .. } => ActionKind::TransitionFrontierSynced,
         }
     }
@@ -602,6 +637,7 @@ impl ActionKindGet for SnarkPoolAction {
     fn kind(&self) -> ActionKind {
         match self {
             Self::Candidate(a) => a.kind(),
+            Self::Coordinator(a) => a.kind(),
             Self::JobsUpdate { .. } => ActionKind::SnarkPoolJobsUpdate,
             Self::AutoCreateCommitment => ActionKind::SnarkPoolAutoCreateCommitment,
             Self::CommitmentCreateMany { .. } => ActionKind::SnarkPoolCommitmentCreateMany,
@@ -612,6 +648,10 @@ impl ActionKindGet for SnarkPoolAction {
             Self::P2pSend { .. } => ActionKind::SnarkPoolP2pSend,
             Self::CheckTimeouts => ActionKind::SnarkPoolCheckTimeouts,
             Self::JobCommitmentTimeout { .. } => ActionKind::SnarkPoolJobCommitmentTimeout,
+            Self::CommitmentRemove { .. } => ActionKind::SnarkPoolCommitmentRemove,
+            Self::Persist => ActionKind::SnarkPoolPersist,
+            Self::Rebroadcast { .. } => ActionKind::SnarkPoolRebroadcast,
+            Self::Reset => ActionKind::SnarkPoolReset,
         }
     }
 }
@@ -622,6 +662,7 @@ impl ActionKindGet for SnarkPoolEffectfulAction {
             Self::SnarkPoolJobsRandomChoose { .. } => {
                 ActionKind::SnarkPoolEffectfulSnarkPoolJobsRandomChoose
             }
+            Self::SnarkPoolPersist => ActionKind::SnarkPoolEffectfulSnarkPoolPersist,
         }
     }
 }
@@ -629,18 +670,18 @@ impl ActionKindGet for SnarkPoolEffectfulAction {
 impl ActionKindGet for ExternalSnarkWorkerAction {
     fn kind(&self) -> ActionKind {
         match self {
-            Self::Start => ActionKind::ExternalSnarkWorkerStart,
-            Self::Started => ActionKind::ExternalSnarkWorkerStarted,
+            Self::Start { .. } => ActionKind::ExternalSnarkWorkerStart,
+            Self::Started { .. } => ActionKind::ExternalSnarkWorkerStarted,
             Self::StartTimeout { .. } => ActionKind::ExternalSnarkWorkerStartTimeout,
-            Self::Kill => ActionKind::ExternalSnarkWorkerKill,
-            Self::Killed => ActionKind::ExternalSnarkWorkerKilled,
+            Self::Kill { .. } => ActionKind::ExternalSnarkWorkerKill,
+            Self::Killed { .. } => ActionKind::ExternalSnarkWorkerKilled,
             Self::SubmitWork { .. } => ActionKind::ExternalSnarkWorkerSubmitWork,
             Self::WorkResult { .. } => ActionKind::ExternalSnarkWorkerWorkResult,
             Self::WorkError { .. } => ActionKind::ExternalSnarkWorkerWorkError,
             Self::WorkTimeout { .. } => ActionKind::ExternalSnarkWorkerWorkTimeout,
-            Self::CancelWork => ActionKind::ExternalSnarkWorkerCancelWork,
-            Self::WorkCancelled => ActionKind::ExternalSnarkWorkerWorkCancelled,
-            Self::PruneWork => ActionKind::ExternalSnarkWorkerPruneWork,
+            Self::CancelWork { .. } => ActionKind::ExternalSnarkWorkerCancelWork,
+            Self::WorkCancelled { .. } => ActionKind::ExternalSnarkWorkerWorkCancelled,
+            Self::PruneWork { .. } => ActionKind::ExternalSnarkWorkerPruneWork,
             Self::Error { .. } => ActionKind::ExternalSnarkWorkerError,
         }
     }
@@ -651,6 +692,7 @@ impl ActionKindGet for BlockProducerAction {
         match self {
             Self::VrfEvaluator(a) => a.kind(),
             Self::BestTipUpdate { .. } => ActionKind::BlockProducerBestTipUpdate,
+            Self::Reset => ActionKind::BlockProducerReset,
             Self::WonSlotSearch => ActionKind::BlockProducerWonSlotSearch,
             Self::WonSlot { .. } => ActionKind::BlockProducerWonSlot,
             Self::WonSlotDiscard { .. } => ActionKind::BlockProducerWonSlotDiscard,
@@ -682,6 +724,10 @@ impl ActionKindGet for RpcAction {
             Self::ActionStatsGet { .. } => ActionKind::RpcActionStatsGet,
             Self::SyncStatsGet { .. } => ActionKind::RpcSyncStatsGet,
             Self::BlockProducerStatsGet { .. } => ActionKind::RpcBlockProducerStatsGet,
+            Self::BlockProducerSlotScheduleGet { .. } => {
+                ActionKind::RpcBlockProducerSlotScheduleGet
+            }
+            Self::MetricsGet { .. } => ActionKind::RpcMetricsGet,
             Self::MessageProgressGet { .. } => ActionKind::RpcMessageProgressGet,
             Self::PeersGet { .. } => ActionKind::RpcPeersGet,
             Self::P2pConnectionOutgoingInit { .. } => ActionKind::RpcP2pConnectionOutgoingInit,
@@ -711,10 +757,15 @@ impl ActionKindGet for RpcAction {
             Self::ScanStateSummaryGetSuccess { .. } => ActionKind::RpcScanStateSummaryGetSuccess,
             Self::SnarkPoolAvailableJobsGet { .. } => ActionKind::RpcSnarkPoolAvailableJobsGet,
             Self::SnarkPoolJobGet { .. } => ActionKind::RpcSnarkPoolJobGet,
+            Self::SnarkMarketStatsGet { .. } => ActionKind::RpcSnarkMarketStatsGet,
             Self::SnarkerConfigGet { .. } => ActionKind::RpcSnarkerConfigGet,
             Self::SnarkerJobCommit { .. } => ActionKind::RpcSnarkerJobCommit,
             Self::SnarkerJobSpec { .. } => ActionKind::RpcSnarkerJobSpec,
             Self::SnarkerWorkersGet { .. } => ActionKind::RpcSnarkerWorkersGet,
+            Self::SnarkCoordinatorRegister { .. } => ActionKind::RpcSnarkCoordinatorRegister,
+            Self::SnarkCoordinatorLease { .. } => ActionKind::RpcSnarkCoordinatorLease,
+            Self::SnarkCoordinatorHeartbeat { .. } => ActionKind::RpcSnarkCoordinatorHeartbeat,
+            Self::SnarkCoordinatorSubmit { .. } => ActionKind::RpcSnarkCoordinatorSubmit,
             Self::HealthCheck { .. } => ActionKind::RpcHealthCheck,
             Self::ReadinessCheck { .. } => ActionKind::RpcReadinessCheck,
             Self::DiscoveryRoutingTable { .. } => ActionKind::RpcDiscoveryRoutingTable,
@@ -761,6 +812,7 @@ impl ActionKindGet for P2pInitializeAction {
     fn kind(&self) -> ActionKind {
         match self {
             Self::Initialize { .. } => ActionKind::P2pInitializeInitialize,
+            Self::ChainIdChange { .. } => ActionKind::P2pInitializeChainIdChange,
         }
     }
 }
@@ -805,6 +857,7 @@ impl ActionKindGet for P2pChannelsAction {
     fn kind(&self) -> ActionKind {
         match self {
             Self::MessageReceived(a) => a.kind(),
+            Self::MessageSent(a) => a.kind(),
             Self::BestTip(a) => a.kind(),
             Self::Transaction(a) => a.kind(),
             Self::Snark(a) => a.kind(),
@@ -914,6 +967,7 @@ impl ActionKindGet for TransitionFrontierGenesisAction {
             Self::ProveInit => ActionKind::TransitionFrontierGenesisProveInit,
             Self::ProvePending => ActionKind::TransitionFrontierGenesisProvePending,
             Self::ProveSuccess { .. } => ActionKind::TransitionFrontierGenesisProveSuccess,
+            Self::ProveError { .. } => ActionKind::TransitionFrontierGenesisProveError,
         }
     }
 }
@@ -995,6 +1049,22 @@ impl ActionKindGet for SnarkPoolCandidateAction {
     }
 }
 
+impl ActionKindGet for SnarkPoolCoordinatorAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::WorkerRegister { .. } => ActionKind::SnarkPoolCoordinatorWorkerRegister,
+            Self::WorkerHeartbeat { .. } => ActionKind::SnarkPoolCoordinatorWorkerHeartbeat,
+            Self::WorkerRemove { .. } => ActionKind::SnarkPoolCoordinatorWorkerRemove,
+            Self::JobLease { .. } => ActionKind::SnarkPoolCoordinatorJobLease,
+            Self::LeaseRelease { .. } => ActionKind::SnarkPoolCoordinatorLeaseRelease,
+            Self::WorkSubmit { .. } => ActionKind::SnarkPoolCoordinatorWorkSubmit,
+            Self::WorkVerifyPending { .. } => ActionKind::SnarkPoolCoordinatorWorkVerifyPending,
+            Self::WorkVerifyError { .. } => ActionKind::SnarkPoolCoordinatorWorkVerifyError,
+            Self::WorkVerifySuccess { .. } => ActionKind::SnarkPoolCoordinatorWorkVerifySuccess,
+        }
+    }
+}
+
 impl ActionKindGet for BlockProducerVrfEvaluatorAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1112,6 +1182,12 @@ impl ActionKindGet for P2pChannelsMessageReceivedAction {
     }
 }
 
+impl ActionKindGet for P2pChannelsMessageSentAction {
+    fn kind(&self) -> ActionKind {
+        ActionKind::P2pChannelsMessageSent
+    }
+}
+
 impl ActionKindGet for P2pChannelsBestTipAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1175,6 +1251,10 @@ impl ActionKindGet for P2pChannelsSnarkJobCommitmentAction {
                 ActionKind::P2pChannelsSnarkJobCommitmentRequestReceived
             }
             Self::ResponseSend { .. } => ActionKind::P2pChannelsSnarkJobCommitmentResponseSend,
+            Self::Libp2pReceived { .. } => ActionKind::P2pChannelsSnarkJobCommitmentLibp2pReceived,
+            Self::Libp2pBroadcast { .. } => {
+                ActionKind::P2pChannelsSnarkJobCommitmentLibp2pBroadcast
+            }
         }
     }
 }
@@ -1309,6 +1389,9 @@ impl ActionKindGet for P2pNetworkPubsubAction {
             Self::NewStream { .. } => ActionKind::P2pNetworkPubsubNewStream,
             Self::IncomingData { .. } => ActionKind::P2pNetworkPubsubIncomingData,
             Self::Broadcast { .. } => ActionKind::P2pNetworkPubsubBroadcast,
+            Self::BroadcastSnarkJobCommitment { .. } => {
+                ActionKind::P2pNetworkPubsubBroadcastSnarkJobCommitment
+            }
             Self::Sign { .. } => ActionKind::P2pNetworkPubsubSign,
             Self::BroadcastSigned { .. } => ActionKind::P2pNetworkPubsubBroadcastSigned,
             Self::OutgoingMessage { .. } => ActionKind::P2pNetworkPubsubOutgoingMessage,
@@ -19,6 +19,9 @@ pub enum BlockProducerAction {
     BestTipUpdate {
         best_tip: ArcBlockWithHash,
     },
+    /// Start over after the hard fork: won slots and the vrf evaluation
+    /// of the chain which ended are no longer valid.
+    Reset,
     WonSlotSearch,
     #[action_event(
         level = info,
@@ -59,6 +62,7 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
         match self {
             BlockProducerAction::VrfEvaluator(a) => a.is_enabled(state, time),
             BlockProducerAction::BestTipUpdate { .. } => true,
+            BlockProducerAction::Reset => state.block_producer.is_enabled(),
             BlockProducerAction::WonSlotSearch => state
                 .block_producer
                 .with(None, |this| {
@@ -70,7 +74,12 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                     }
                     let best_tip = state.transition_frontier.best_tip()?;
                     let cur_global_slot = state.cur_global_slot()?;
-                    let next = this.vrf_evaluator.next_won_slot(cur_global_slot, best_tip);
+                    let next = this.vrf_evaluator.next_won_slot(
+                        cur_global_slot,
+                        best_tip,
+                        &this.production_log,
+                        state.block_window_duration_ms(),
+                    );
                     Some(next.is_some())
                 })
                 .is_some_and(|v| v),
@@ -82,16 +91,31 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                     return false;
                 }
 
+                let won_slot_since_genesis = won_slot.global_slot() + best_tip.global_slot_diff();
+
                 this.current.won_slot_should_search()
                     && won_slot.global_slot() >= state.cur_global_slot().unwrap()
                     && won_slot > best_tip
+                    // never produce twice for the same slot.
+                    && !this.production_log.contains(won_slot_since_genesis)
+                    && !state
+                        .transition_frontier
+                        .is_past_chain_end(won_slot_since_genesis)
             }),
             BlockProducerAction::WonSlotWait => state
                 .block_producer
                 .with(false, |this| this.current.won_slot_should_wait(time)),
-            BlockProducerAction::WonSlotProduceInit => state
-                .block_producer
-                .with(false, |this| this.current.won_slot_should_produce(time)),
+            BlockProducerAction::WonSlotProduceInit => state.block_producer.with(false, |this| {
+                let Some(best_tip) = state.transition_frontier.best_tip() else {
+                    return false;
+                };
+                this.current.won_slot_should_produce(time)
+                    && this.current.won_slot().map_or(false, |won_slot| {
+                        !this
+                            .production_log
+                            .contains(won_slot.global_slot() + best_tip.global_slot_diff())
+                    })
+            }),
             BlockProducerAction::StagedLedgerDiffCreateInit => {
                 state.block_producer.with(false, |this| {
                     matches!(
@@ -149,19 +173,37 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                 )
             }),
             BlockProducerAction::BlockInject => state.block_producer.with(false, |this| {
-                matches!(this.current, BlockProducerCurrentState::Produced { .. })
+                !this.config.dry_run
+                    && matches!(this.current, BlockProducerCurrentState::Produced { .. })
                     && !state.transition_frontier.sync.is_commit_pending()
             }),
             BlockProducerAction::BlockInjected => state.block_producer.with(false, |this| {
                 matches!(this.current, BlockProducerCurrentState::Produced { .. })
             }),
-            BlockProducerAction::WonSlotDiscard { reason } => {
-                let current_reason = state.block_producer.with(None, |bp| {
-                    let best_tip = state.transition_frontier.best_tip()?;
-                    bp.current.won_slot_should_discard(best_tip)
-                });
-                Some(reason) == current_reason.as_ref()
-            }
+            BlockProducerAction::WonSlotDiscard { reason } => match reason {
+                BlockProducerWonSlotDiscardReason::BlockProofFailed { block_hash, .. } => state
+                    .block_producer
+                    .with(false, |this| match &this.current {
+                        BlockProducerCurrentState::BlockProvePending {
+                            block_hash: pending_hash,
+                            ..
+                        } => pending_hash == block_hash,
+                        _ => false,
+                    }),
+                BlockProducerWonSlotDiscardReason::DryRun => {
+                    state.block_producer.with(false, |this| {
+                        this.config.dry_run
+                            && matches!(this.current, BlockProducerCurrentState::Produced { .. })
+                    })
+                }
+                _ => {
+                    let current_reason = state.block_producer.with(None, |bp| {
+                        let best_tip = state.transition_frontier.best_tip()?;
+                        bp.current.won_slot_should_discard(best_tip)
+                    });
+                    Some(reason) == current_reason.as_ref()
+                }
+            },
         }
     }
 }
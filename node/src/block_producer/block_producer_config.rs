@@ -1,25 +1,171 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
 use mina_p2p_messages::v2::{NonZeroCurvePoint, ProtocolVersionStableV2};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockProducerConfig {
     pub pub_key: NonZeroCurvePoint,
+    /// Additional producer keys. VRF evaluation is done for all keys
+    /// against the same staking ledger.
+    #[serde(default)]
+    pub additional_pub_keys: Vec<NonZeroCurvePoint>,
     pub custom_coinbase_receiver: Option<NonZeroCurvePoint>,
+    /// Rule for choosing the coinbase receiver. Block's fee transfers
+    /// (transaction fees minus snark fees) go to the coinbase receiver too.
+    #[serde(default)]
+    pub coinbase_receiver_policy: CoinbaseReceiverPolicy,
     pub proposed_protocol_version: Option<ProtocolVersionStableV2>,
+    /// Go through the whole block production, including proving, but
+    /// don't inject or broadcast the produced block.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub enum CoinbaseReceiverPolicy {
+    /// `custom_coinbase_receiver` if set, otherwise the producer key
+    /// which won the slot.
+    #[default]
+    Producer,
+    /// Delegator whose stake won the slot.
+    SlotWinner,
+    /// Receiver for each of the producer keys. Falls back to
+    /// [CoinbaseReceiverPolicy::Producer] for keys not in the map.
+    PerProducer(BTreeMap<NonZeroCurvePoint, NonZeroCurvePoint>),
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("invalid coinbase receiver policy: {0}! expected one of: producer/slot-winner/<producer>=<receiver>,...")]
+pub struct CoinbaseReceiverPolicyParseError(String);
+
+impl FromStr for CoinbaseReceiverPolicy {
+    type Err = CoinbaseReceiverPolicyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "producer" => Self::Producer,
+            "slot-winner" => Self::SlotWinner,
+            other => Self::PerProducer(
+                other
+                    .split(',')
+                    .map(|pair| {
+                        let (producer, receiver) = pair.split_once('=')?;
+                        Some((producer.parse().ok()?, receiver.parse().ok()?))
+                    })
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| CoinbaseReceiverPolicyParseError(other.to_owned()))?,
+            ),
+        })
+    }
 }
 
 impl BlockProducerConfig {
     pub fn new(pub_key: NonZeroCurvePoint) -> Self {
         Self {
             pub_key,
+            additional_pub_keys: vec![],
             custom_coinbase_receiver: None,
+            coinbase_receiver_policy: Default::default(),
             proposed_protocol_version: None,
+            dry_run: false,
+        }
+    }
+
+    /// All producer keys, starting with the primary one.
+    pub fn pub_keys(&self) -> impl Iterator<Item = &NonZeroCurvePoint> {
+        std::iter::once(&self.pub_key).chain(&self.additional_pub_keys)
+    }
+
+    pub fn is_producer(&self, pub_key: &NonZeroCurvePoint) -> bool {
+        self.pub_keys().any(|v| v == pub_key)
+    }
+
+    /// Coinbase receiver for the block produced by the `producer`, with
+    /// the slot won by the `delegator`'s stake.
+    pub fn coinbase_receiver<'a>(
+        &'a self,
+        producer: &'a NonZeroCurvePoint,
+        delegator: &'a NonZeroCurvePoint,
+    ) -> &'a NonZeroCurvePoint {
+        let default = || self.custom_coinbase_receiver.as_ref().unwrap_or(producer);
+        match &self.coinbase_receiver_policy {
+            CoinbaseReceiverPolicy::Producer => default(),
+            CoinbaseReceiverPolicy::SlotWinner => delegator,
+            CoinbaseReceiverPolicy::PerProducer(receivers) => {
+                receivers.get(producer).unwrap_or_else(default)
+            }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::account::AccountSecretKey;
+
+    use super::*;
+
+    #[test]
+    fn coinbase_receiver_policy_from_str() {
+        assert!(matches!(
+            "producer".parse::<CoinbaseReceiverPolicy>(),
+            Ok(CoinbaseReceiverPolicy::Producer)
+        ));
+        assert!(matches!(
+            "slot-winner".parse::<CoinbaseReceiverPolicy>(),
+            Ok(CoinbaseReceiverPolicy::SlotWinner)
+        ));
+
+        let keys = (0..4)
+            .map(|_| AccountSecretKey::rand().public_key())
+            .collect::<Vec<_>>();
+        let s = format!("{}={},{}={}", keys[0], keys[1], keys[2], keys[3]);
+        let Ok(CoinbaseReceiverPolicy::PerProducer(receivers)) =
+            s.parse::<CoinbaseReceiverPolicy>()
+        else {
+            panic!("failed to parse {s}");
+        };
+        let key = |i: usize| NonZeroCurvePoint::from(keys[i].clone());
+        assert_eq!(receivers.len(), 2);
+        assert_eq!(receivers.get(&key(0)), Some(&key(1)));
+        assert_eq!(receivers.get(&key(2)), Some(&key(3)));
+
+        let invalid = [
+            String::new(),
+            "producers".to_owned(),
+            keys[0].to_string(),
+            format!("{}=", keys[0]),
+        ];
+        for s in invalid {
+            assert!(s.parse::<CoinbaseReceiverPolicy>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn coinbase_receiver() {
+        let key = || NonZeroCurvePoint::from(AccountSecretKey::rand().public_key());
+        let (producer, other_producer, delegator, custom, per_producer) =
+            (key(), key(), key(), key(), key());
+        let mut config = BlockProducerConfig::new(producer.clone());
+        config.additional_pub_keys = vec![other_producer.clone()];
+
+        assert_eq!(config.coinbase_receiver(&producer, &delegator), &producer);
+        config.custom_coinbase_receiver = Some(custom.clone());
+        assert_eq!(config.coinbase_receiver(&producer, &delegator), &custom);
+
+        config.coinbase_receiver_policy = CoinbaseReceiverPolicy::SlotWinner;
+        assert_eq!(config.coinbase_receiver(&producer, &delegator), &delegator);
 
-    pub fn coinbase_receiver(&self) -> &NonZeroCurvePoint {
-        self.custom_coinbase_receiver
-            .as_ref()
-            .unwrap_or(&self.pub_key)
+        config.coinbase_receiver_policy =
+            CoinbaseReceiverPolicy::PerProducer([(producer.clone(), per_producer.clone())].into());
+        assert_eq!(
+            config.coinbase_receiver(&producer, &delegator),
+            &per_producer
+        );
+        assert_eq!(
+            config.coinbase_receiver(&other_producer, &delegator),
+            &custom
+        );
     }
 }
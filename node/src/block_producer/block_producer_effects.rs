@@ -3,6 +3,8 @@ use mina_p2p_messages::v2::{
     MinaStateSnarkTransitionValueStableV2, ProverExtendBlockchainInputStableV2,
 };
 
+use openmina_core::block::ArcBlockWithHash;
+
 use crate::account::AccountSecretKey;
 use crate::ledger::write::{LedgerWriteAction, LedgerWriteRequest};
 use crate::transition_frontier::sync::TransitionFrontierSyncAction;
@@ -11,7 +13,7 @@ use crate::Store;
 use super::vrf_evaluator::{BlockProducerVrfEvaluatorAction, InterruptReason};
 use super::{
     next_epoch_first_slot, to_epoch_and_slot, BlockProducerAction, BlockProducerActionWithMeta,
-    BlockProducerCurrentState,
+    BlockProducerCurrentState, BlockProducerPayoutReport, BlockProducerWonSlotDiscardReason,
 };
 
 pub fn block_producer_effects<S: crate::Service>(
@@ -38,6 +40,8 @@ pub fn block_producer_effects<S: crate::Service>(
             }
         }
         BlockProducerAction::BestTipUpdate { best_tip } => {
+            payout_report_write(store, &best_tip);
+
             let global_slot = best_tip
                 .consensus_state()
                 .curr_global_slot_since_hard_fork
@@ -89,7 +93,12 @@ pub fn block_producer_effects<S: crate::Service>(
             if let Some(won_slot) = store.state().block_producer.with(None, |bp| {
                 let best_tip = store.state().transition_frontier.best_tip()?;
                 let cur_global_slot = store.state().cur_global_slot()?;
-                bp.vrf_evaluator.next_won_slot(cur_global_slot, best_tip)
+                bp.vrf_evaluator.next_won_slot(
+                    cur_global_slot,
+                    best_tip,
+                    &bp.production_log,
+                    store.state().block_window_duration_ms(),
+                )
             }) {
                 store.dispatch(BlockProducerAction::WonSlot { won_slot });
             }
@@ -104,6 +113,7 @@ pub fn block_producer_effects<S: crate::Service>(
         }
         BlockProducerAction::WonSlotWait => {}
         BlockProducerAction::WonSlotProduceInit => {
+            production_log_persist(store);
             store.dispatch(BlockProducerAction::StagedLedgerDiffCreateInit);
         }
         BlockProducerAction::StagedLedgerDiffCreateInit => {
@@ -120,8 +130,8 @@ pub fn block_producer_effects<S: crate::Service>(
                 Some((
                     won_slot,
                     pred_block,
-                    &config.pub_key,
-                    config.coinbase_receiver(),
+                    &won_slot.producer,
+                    config.coinbase_receiver(&won_slot.producer, &won_slot.delegator.0),
                 ))
             }) else {
                 return;
@@ -256,7 +266,26 @@ pub fn block_producer_effects<S: crate::Service>(
             store.dispatch(BlockProducerAction::BlockProduced);
         }
         BlockProducerAction::BlockProduced => {
-            store.dispatch(BlockProducerAction::BlockInject);
+            production_log_persist(store);
+            if store
+                .state()
+                .block_producer
+                .config()
+                .map_or(false, |config| config.dry_run)
+            {
+                if let Some(block) = store.state().block_producer.produced_block() {
+                    openmina_core::info!(meta.time();
+                        kind = "BlockProducerDryRun",
+                        summary = format!("produced block {} in dry-run mode", block.hash()),
+                        height = block.height(),
+                        global_slot = block.global_slot());
+                }
+                store.dispatch(BlockProducerAction::WonSlotDiscard {
+                    reason: BlockProducerWonSlotDiscardReason::DryRun,
+                });
+            } else {
+                store.dispatch(BlockProducerAction::BlockInject);
+            }
         }
         BlockProducerAction::BlockInject => {
             let Some((best_tip, root_block, blocks_inbetween)) = None.or_else(|| {
@@ -277,10 +306,13 @@ pub fn block_producer_effects<S: crate::Service>(
                 store.dispatch(BlockProducerAction::BlockInjected);
             }
         }
+        BlockProducerAction::Reset => {}
         BlockProducerAction::BlockInjected => {
+            production_log_persist(store);
             store.dispatch(BlockProducerAction::WonSlotSearch);
         }
         BlockProducerAction::WonSlotDiscard { reason } => {
+            production_log_persist(store);
             if let Some(stats) = store.service.stats() {
                 stats.block_producer().discarded(meta.time(), reason);
             }
@@ -288,3 +320,42 @@ pub fn block_producer_effects<S: crate::Service>(
         }
     }
 }
+
+/// Write payout report for the block we injected, once it's the best tip.
+fn payout_report_write<S: crate::Service>(store: &mut Store<S>, best_tip: &ArcBlockWithHash) {
+    let Some(report) = None.or_else(|| {
+        let bp = &store.state.get().block_producer;
+        let (won_slot, block) = (bp.current_won_slot()?, bp.injected_block()?);
+        if block.hash() != best_tip.hash() {
+            return None;
+        }
+        let delegator_table = bp
+            .vrf_evaluator()?
+            .delegator_tables
+            .get(&won_slot.staking_ledger_hash);
+        Some(BlockProducerPayoutReport::new(
+            won_slot,
+            block,
+            delegator_table.map(|table| &**table),
+        ))
+    }) else {
+        return;
+    };
+    store.service.payout_report_write(report);
+}
+
+/// Persist production log entry for the current won slot.
+fn production_log_persist<S: crate::Service>(store: &mut Store<S>) {
+    let Some(entry) = None.or_else(|| {
+        let state = store.state.get();
+        let best_tip = state.transition_frontier.best_tip()?;
+        let bp = &state.block_producer;
+        let won_slot = bp.current_won_slot()?;
+        bp.production_log()?
+            .get(won_slot.global_slot() + best_tip.global_slot_diff())
+            .cloned()
+    }) else {
+        return;
+    };
+    store.service.production_log_record(entry);
+}
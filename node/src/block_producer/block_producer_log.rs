@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use mina_p2p_messages::v2::{NonZeroCurvePoint, StateHash};
+use serde::{Deserialize, Serialize};
+
+use super::{BlockProducerWonSlot, BlockProducerWonSlotDiscardReason};
+
+/// Log of the slots for which we started block production.
+///
+/// It is persisted by the service and restored on startup, so that the
+/// node never produces two blocks for the same global slot, even across
+/// restarts.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlockProducerLog {
+    /// Global slot since genesis -> entry.
+    ///
+    /// Unlike the slot since hard fork, it keeps growing after the hard
+    /// fork, so entries of the previous chain don't block the new one.
+    entries: BTreeMap<u32, BlockProducerLogEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerLogEntry {
+    /// Time when the block production for the slot was started.
+    pub time: redux::Timestamp,
+    /// Global slot since hard fork.
+    pub global_slot: u32,
+    pub global_slot_since_genesis: u32,
+    pub producer: NonZeroCurvePoint,
+    /// Hash of the produced block, if it was produced.
+    pub block_hash: Option<StateHash>,
+    pub status: BlockProducerLogStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum BlockProducerLogStatus {
+    Producing,
+    Produced,
+    Injected,
+    Discarded(BlockProducerWonSlotDiscardReason),
+}
+
+impl BlockProducerLog {
+    /// Max number of entries kept. Only the latest slots matter for
+    /// preventing double production, older ones are just history.
+    pub const MAX_LEN: usize = 1024;
+
+    /// Builds the log from the persisted entries. If there are multiple
+    /// entries for the same slot, the latest one wins.
+    pub fn from_entries(entries: impl IntoIterator<Item = BlockProducerLogEntry>) -> Self {
+        let mut log = Self {
+            entries: entries
+                .into_iter()
+                .map(|entry| (entry.global_slot_since_genesis, entry))
+                .collect(),
+        };
+        log.prune();
+        log
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, global_slot_since_genesis: u32) -> Option<&BlockProducerLogEntry> {
+        self.entries.get(&global_slot_since_genesis)
+    }
+
+    pub fn contains(&self, global_slot_since_genesis: u32) -> bool {
+        self.entries.contains_key(&global_slot_since_genesis)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &BlockProducerLogEntry> {
+        self.entries.values()
+    }
+
+    /// Latest entries first, at most `limit` of them, for slots before
+    /// `before` (global slot since genesis) if set.
+    pub fn page(
+        &self,
+        before: Option<u32>,
+        limit: usize,
+    ) -> impl Iterator<Item = &BlockProducerLogEntry> {
+        self.entries
+            .range(..before.unwrap_or(u32::MAX))
+            .rev()
+            .take(limit)
+            .map(|(_, entry)| entry)
+    }
+
+    pub(super) fn producing(
+        &mut self,
+        time: redux::Timestamp,
+        won_slot: &BlockProducerWonSlot,
+        global_slot_since_genesis: u32,
+    ) {
+        self.entries.insert(
+            global_slot_since_genesis,
+            BlockProducerLogEntry {
+                time,
+                global_slot: won_slot.global_slot(),
+                global_slot_since_genesis,
+                producer: won_slot.producer.clone(),
+                block_hash: None,
+                status: BlockProducerLogStatus::Producing,
+            },
+        );
+        self.prune();
+    }
+
+    pub(super) fn update(
+        &mut self,
+        global_slot_since_genesis: u32,
+        block_hash: Option<&StateHash>,
+        status: BlockProducerLogStatus,
+    ) {
+        if let Some(entry) = self.entries.get_mut(&global_slot_since_genesis) {
+            if let Some(block_hash) = block_hash {
+                entry.block_hash = Some(block_hash.clone());
+            }
+            entry.status = status;
+        }
+    }
+
+    /// Removes the oldest entries above [`Self::MAX_LEN`].
+    fn prune(&mut self) {
+        while self.entries.len() > Self::MAX_LEN {
+            self.entries.pop_first();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::account::AccountSecretKey;
+
+    use super::*;
+
+    fn entry(global_slot: u32, global_slot_since_genesis: u32) -> BlockProducerLogEntry {
+        BlockProducerLogEntry {
+            time: redux::Timestamp::ZERO,
+            global_slot,
+            global_slot_since_genesis,
+            producer: AccountSecretKey::rand().public_key().into(),
+            block_hash: None,
+            status: BlockProducerLogStatus::Producing,
+        }
+    }
+
+    #[test]
+    fn keyed_by_slot_since_genesis() {
+        let mut injected = entry(10, 10);
+        injected.status = BlockProducerLogStatus::Injected;
+        // Same slot since hard fork on the chain after the fork.
+        let log = BlockProducerLog::from_entries([entry(10, 10), injected, entry(10, 1010)]);
+
+        assert_eq!(log.len(), 2);
+        assert!(log.contains(10));
+        assert!(log.contains(1010));
+        assert!(!log.contains(11));
+        assert!(matches!(
+            log.get(10).unwrap().status,
+            BlockProducerLogStatus::Injected
+        ));
+    }
+
+    #[test]
+    fn pruned_to_max_len() {
+        let extra = 10;
+        let total = (BlockProducerLog::MAX_LEN + extra) as u32;
+        let log = BlockProducerLog::from_entries((0..total).map(|slot| entry(slot, slot)));
+
+        assert_eq!(log.len(), BlockProducerLog::MAX_LEN);
+        assert!(!log.contains(extra as u32 - 1));
+        assert!(log.contains(extra as u32));
+        assert!(log.contains(total - 1));
+    }
+
+    #[test]
+    fn page_latest_first() {
+        let log = BlockProducerLog::from_entries((0..10).map(|slot| entry(slot, slot * 2)));
+        let page = |before, limit| {
+            log.page(before, limit)
+                .map(|entry| entry.global_slot_since_genesis)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(page(None, 3), vec![18, 16, 14]);
+        assert_eq!(page(Some(14), 3), vec![12, 10, 8]);
+        assert_eq!(page(Some(3), 3), vec![2, 0]);
+        assert_eq!(page(Some(0), 3), Vec::<u32>::new());
+    }
+}
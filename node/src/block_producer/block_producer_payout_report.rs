@@ -0,0 +1,152 @@
+use ledger::AccountIndex;
+use mina_p2p_messages::v2::{
+    LedgerHash, NonZeroCurvePoint,
+    StagedLedgerDiffDiffPreDiffWithAtMostOneCoinbaseStableV2Coinbase,
+    StagedLedgerDiffDiffPreDiffWithAtMostTwoCoinbaseStableV2Coinbase, StateHash,
+};
+use openmina_core::block::ArcBlockWithHash;
+use openmina_core::constants::CONSTRAINT_CONSTANTS;
+use serde::{Deserialize, Serialize};
+
+use crate::account::AccountPublicKey;
+
+use super::vrf_evaluator::DelegatorTable;
+use super::BlockProducerWonSlot;
+
+/// Rewards of the produced block and stake shares of the producer's
+/// delegators, used for computing payouts of the delegation pool.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerPayoutReport {
+    pub block_hash: StateHash,
+    pub height: u32,
+    pub global_slot: u32,
+    pub epoch: u32,
+    pub producer: NonZeroCurvePoint,
+    /// Delegator whose stake won the slot.
+    pub slot_winner: NonZeroCurvePoint,
+    pub coinbase_receiver: NonZeroCurvePoint,
+    pub staking_ledger_hash: LedgerHash,
+    pub coinbase: u64,
+    pub transaction_fees: u64,
+    /// Fees paid to snark workers for the included snark work.
+    pub snark_fees: u64,
+    /// Total stake delegated to the producer in the staking ledger.
+    pub total_stake: u64,
+    /// Empty if delegator table for the staking ledger isn't available.
+    pub delegators: Vec<BlockProducerPayoutDelegator>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerPayoutDelegator {
+    pub delegator: NonZeroCurvePoint,
+    pub index: AccountIndex,
+    pub stake: u64,
+    /// `stake / total_stake`.
+    pub share: f64,
+}
+
+impl BlockProducerPayoutReport {
+    pub fn new(
+        won_slot: &BlockProducerWonSlot,
+        block: &ArcBlockWithHash,
+        delegator_table: Option<&DelegatorTable>,
+    ) -> Self {
+        let consensus_state = block.consensus_state();
+        let body = block.body();
+        let diff = body.diff();
+        let has_coinbase = !matches!(
+            diff.0.coinbase,
+            StagedLedgerDiffDiffPreDiffWithAtMostTwoCoinbaseStableV2Coinbase::Zero
+        ) || matches!(
+            diff.1.as_ref().map(|v| &v.coinbase),
+            Some(StagedLedgerDiffDiffPreDiffWithAtMostOneCoinbaseStableV2Coinbase::One(_))
+        );
+        let coinbase = match (has_coinbase, consensus_state.supercharge_coinbase) {
+            (false, _) => 0,
+            (true, false) => CONSTRAINT_CONSTANTS.coinbase_amount,
+            (true, true) => {
+                CONSTRAINT_CONSTANTS.coinbase_amount
+                    * CONSTRAINT_CONSTANTS.supercharged_coinbase_factor
+            }
+        };
+
+        let delegators = delegator_table
+            .and_then(|table| table.get(&AccountPublicKey::from(won_slot.producer.clone())))
+            .into_iter()
+            .flatten()
+            .map(|(index, (delegator, stake))| (delegator.clone().into(), *index, *stake))
+            .collect();
+        let (total_stake, delegators) = delegator_shares(delegators);
+
+        Self {
+            block_hash: block.hash().clone(),
+            height: block.height(),
+            global_slot: won_slot.global_slot(),
+            epoch: won_slot.epoch(),
+            producer: won_slot.producer.clone(),
+            slot_winner: won_slot.delegator.0.clone(),
+            coinbase_receiver: consensus_state.coinbase_receiver.clone(),
+            staking_ledger_hash: won_slot.staking_ledger_hash.clone(),
+            coinbase,
+            transaction_fees: body.fees_sum(),
+            snark_fees: body.snark_fees_sum(),
+            total_stake,
+            delegators,
+        }
+    }
+}
+
+/// Total stake and the share of it for each of the delegators.
+fn delegator_shares(
+    delegators: Vec<(NonZeroCurvePoint, AccountIndex, u64)>,
+) -> (u64, Vec<BlockProducerPayoutDelegator>) {
+    let total_stake = delegators.iter().map(|(_, _, stake)| stake).sum::<u64>();
+    let delegators = delegators
+        .into_iter()
+        .map(|(delegator, index, stake)| BlockProducerPayoutDelegator {
+            delegator,
+            index,
+            stake,
+            share: match total_stake {
+                0 => 0.0,
+                total => stake as f64 / total as f64,
+            },
+        })
+        .collect();
+    (total_stake, delegators)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::account::AccountSecretKey;
+
+    use super::*;
+
+    #[test]
+    fn delegator_shares_sum_to_one() {
+        let key = || AccountSecretKey::rand().public_key().into();
+        let (total_stake, delegators) = delegator_shares(vec![
+            (key(), AccountIndex(0), 1_000),
+            (key(), AccountIndex(1), 3_000),
+            (key(), AccountIndex(2), 0),
+        ]);
+
+        assert_eq!(total_stake, 4_000);
+        let shares = delegators.iter().map(|d| d.share).collect::<Vec<_>>();
+        assert_eq!(shares, vec![0.25, 0.75, 0.0]);
+        assert_eq!(delegators[1].index, AccountIndex(1));
+        assert_eq!(delegators[1].stake, 3_000);
+    }
+
+    #[test]
+    fn delegator_shares_without_stake() {
+        let key = AccountSecretKey::rand().public_key().into();
+        let (total_stake, delegators) = delegator_shares(vec![(key, AccountIndex(0), 0)]);
+        assert_eq!(total_stake, 0);
+        assert_eq!(delegators[0].share, 0.0);
+
+        let (total_stake, delegators) = delegator_shares(vec![]);
+        assert_eq!(total_stake, 0);
+        assert!(delegators.is_empty());
+    }
+}
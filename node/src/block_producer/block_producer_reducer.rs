@@ -19,9 +19,11 @@ use openmina_core::consensus::{
 };
 use openmina_core::constants::CONSTRAINT_CONSTANTS;
 
+use super::vrf_evaluator::BlockProducerVrfEvaluatorState;
 use super::{
     calc_epoch_seed, to_epoch_and_slot, BlockProducerAction, BlockProducerActionWithMetaRef,
-    BlockProducerCurrentState, BlockProducerEnabled, BlockProducerState, BlockWithoutProof,
+    BlockProducerCurrentState, BlockProducerEnabled, BlockProducerLogStatus, BlockProducerState,
+    BlockWithoutProof,
 };
 
 impl BlockProducerState {
@@ -50,8 +52,14 @@ impl BlockProducerEnabled {
                 // TODO: move/remove once we can generate the genesis block
                 if self.vrf_evaluator.genesis_timestamp == redux::Timestamp::ZERO {
                     self.vrf_evaluator.genesis_timestamp = best_tip.genesis_timestamp();
+                    self.vrf_evaluator.slots_per_epoch =
+                        best_tip.constants().slots_per_epoch.as_u32();
                 }
             }
+            BlockProducerAction::Reset => {
+                self.vrf_evaluator = BlockProducerVrfEvaluatorState::new(meta.time());
+                self.current = BlockProducerCurrentState::Idle { time: meta.time() };
+            }
             BlockProducerAction::WonSlotSearch => {}
             BlockProducerAction::WonSlot { won_slot } => {
                 self.current = BlockProducerCurrentState::WonSlot {
@@ -61,6 +69,13 @@ impl BlockProducerEnabled {
             }
             BlockProducerAction::WonSlotDiscard { reason } => {
                 if let Some(won_slot) = self.current.won_slot() {
+                    if let Some(best_tip) = best_chain.last() {
+                        self.production_log.update(
+                            won_slot.global_slot() + best_tip.global_slot_diff(),
+                            None,
+                            BlockProducerLogStatus::Discarded(reason.clone()),
+                        );
+                    }
                     self.current = BlockProducerCurrentState::WonSlotDiscarded {
                         time: meta.time(),
                         won_slot: won_slot.clone(),
@@ -78,17 +93,21 @@ impl BlockProducerEnabled {
             }
             BlockProducerAction::WonSlotProduceInit => {
                 if let Some(won_slot) = self.current.won_slot() {
-                    let Some(chain) = best_chain.last().map(|best_tip| {
-                        if best_tip.global_slot() == won_slot.global_slot() {
-                            // We are producing block which replaces current best tip
-                            // instead of extending it.
-                            best_chain[..(best_chain.len() - 1)].to_vec()
-                        } else {
-                            best_chain.to_vec()
-                        }
-                    }) else {
+                    let Some(best_tip) = best_chain.last() else {
                         return;
                     };
+                    let chain = if best_tip.global_slot() == won_slot.global_slot() {
+                        // We are producing block which replaces current best tip
+                        // instead of extending it.
+                        best_chain[..(best_chain.len() - 1)].to_vec()
+                    } else {
+                        best_chain.to_vec()
+                    };
+                    self.production_log.producing(
+                        meta.time(),
+                        won_slot,
+                        won_slot.global_slot() + best_tip.global_slot_diff(),
+                    );
                     self.current = BlockProducerCurrentState::WonSlotProduceInit {
                         time: meta.time(),
                         won_slot: won_slot.clone(),
@@ -175,8 +194,11 @@ impl BlockProducerEnabled {
                 let vrf_truncated_output: ConsensusVrfOutputTruncatedStableV1 =
                     won_slot.vrf_output.clone().into();
                 let vrf_hash = won_slot.vrf_output.hash();
-                let block_creator = self.config.pub_key.clone();
-                let coinbase_receiver = self.config.coinbase_receiver().clone();
+                let block_creator = won_slot.producer.clone();
+                let coinbase_receiver = self
+                    .config
+                    .coinbase_receiver(&block_creator, &block_stake_winner)
+                    .clone();
                 let proposed_protocol_version_opt = self.config.proposed_protocol_version.clone();
 
                 let ledger_proof_statement = ledger_proof_statement_from_emitted_proof(
@@ -474,6 +496,16 @@ impl BlockProducerEnabled {
                     ..
                 } = std::mem::take(&mut self.current)
                 {
+                    self.production_log.update(
+                        block
+                            .protocol_state
+                            .body
+                            .consensus_state
+                            .global_slot_since_genesis
+                            .as_u32(),
+                        Some(&block_hash),
+                        BlockProducerLogStatus::Produced,
+                    );
                     self.current = BlockProducerCurrentState::Produced {
                         time: meta.time(),
                         won_slot,
@@ -491,6 +523,11 @@ impl BlockProducerEnabled {
                     ..
                 } = &mut self.current
                 {
+                    self.production_log.update(
+                        block.global_slot_since_genesis(),
+                        None,
+                        BlockProducerLogStatus::Injected,
+                    );
                     self.current = BlockProducerCurrentState::Injected {
                         time: meta.time(),
                         won_slot: won_slot.clone(),
@@ -6,6 +6,8 @@ use mina_p2p_messages::v2::{
 };
 use serde::{Deserialize, Serialize};
 
+use super::{BlockProducerLogEntry, BlockProducerPayoutReport};
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StagedLedgerDiffCreateOutput {
     pub diff: StagedLedgerDiffDiffStableV2,
@@ -20,4 +22,13 @@ pub struct StagedLedgerDiffCreateOutput {
 
 pub trait BlockProducerService {
     fn prove(&mut self, block_hash: StateHash, input: Box<ProverExtendBlockchainInputStableV2>);
+
+    /// Persist the updated production log entry.
+    ///
+    /// Must be durable before returning, as it is used to prevent
+    /// producing twice for the same slot after restart.
+    fn production_log_record(&mut self, entry: BlockProducerLogEntry);
+
+    /// Write payout report for the produced block.
+    fn payout_report_write(&mut self, report: BlockProducerPayoutReport);
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use mina_p2p_messages::v2;
 use openmina_core::{block::ArcBlockWithHash, consensus::consensus_take};
 use serde::{Deserialize, Serialize};
@@ -5,8 +7,8 @@ use serde::{Deserialize, Serialize};
 use crate::account::AccountPublicKey;
 
 use super::{
-    vrf_evaluator::BlockProducerVrfEvaluatorState, BlockProducerConfig, BlockProducerWonSlot,
-    BlockWithoutProof,
+    vrf_evaluator::BlockProducerVrfEvaluatorState, BlockProducerConfig, BlockProducerLog,
+    BlockProducerWonSlot, BlockWithoutProof,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,6 +19,7 @@ pub struct BlockProducerEnabled {
     pub config: BlockProducerConfig,
     pub vrf_evaluator: BlockProducerVrfEvaluatorState,
     pub current: BlockProducerCurrentState,
+    pub production_log: BlockProducerLog,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -119,6 +122,13 @@ pub enum BlockProducerWonSlotDiscardReason {
     BestTipStakingLedgerDifferent,
     BestTipGlobalSlotHigher,
     BestTipSuperior,
+    /// Block proof generation failed.
+    BlockProofFailed {
+        block_hash: v2::StateHash,
+        error: String,
+    },
+    /// Block was produced in dry-run mode, so it isn't injected.
+    DryRun,
 }
 
 impl BlockProducerState {
@@ -127,9 +137,15 @@ impl BlockProducerState {
             config: config.clone(),
             vrf_evaluator: BlockProducerVrfEvaluatorState::new(now),
             current: BlockProducerCurrentState::Idle { time: now },
+            production_log: Default::default(),
         }))
     }
 
+    /// Restore production log persisted by the previous run of the node.
+    pub fn production_log_restore(&mut self, log: BlockProducerLog) {
+        self.with_mut((), |this| this.production_log = log)
+    }
+
     #[inline(always)]
     pub(super) fn with<'a, F, R: 'a>(&'a self, default: R, fun: F) -> R
     where
@@ -155,7 +171,7 @@ impl BlockProducerState {
     }
 
     pub fn is_me(&self, producer: &v2::NonZeroCurvePoint) -> bool {
-        self.with(false, |this| producer == &this.config.pub_key)
+        self.with(false, |this| this.config.is_producer(producer))
     }
 
     pub fn is_producing(&self) -> bool {
@@ -183,6 +199,14 @@ impl BlockProducerState {
         self.with(None, |this| this.current.produced_block_with_chain())
     }
 
+    pub fn injected_block(&self) -> Option<&ArcBlockWithHash> {
+        self.with(None, |this| this.current.injected_block())
+    }
+
+    pub fn production_log(&self) -> Option<&BlockProducerLog> {
+        self.with(None, |this| Some(&this.production_log))
+    }
+
     pub fn vrf_evaluator(&self) -> Option<&BlockProducerVrfEvaluatorState> {
         self.with(None, |this| Some(&this.vrf_evaluator))
     }
@@ -194,7 +218,9 @@ impl BlockProducerState {
     }
 
     /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         self.vrf_evaluator()?.vrf_delegator_table_inputs()
     }
 }
@@ -285,6 +311,7 @@ impl BlockProducerCurrentState {
                     block.consensus_state(),
                     best_tip.hash(),
                     block.hash(),
+                    best_tip.constants(),
                 )
             })
         {
@@ -324,6 +351,13 @@ impl BlockProducerCurrentState {
             _ => None,
         }
     }
+
+    pub fn injected_block(&self) -> Option<&ArcBlockWithHash> {
+        match self {
+            Self::Injected { block, .. } => Some(block),
+            _ => None,
+        }
+    }
 }
 
 impl Default for BlockProducerCurrentState {
@@ -6,6 +6,12 @@ pub use block_producer_config::*;
 mod block_producer_state;
 pub use block_producer_state::*;
 
+mod block_producer_log;
+pub use block_producer_log::*;
+
+mod block_producer_payout_report;
+pub use block_producer_payout_report::*;
+
 mod block_producer_event;
 pub use block_producer_event::*;
 
@@ -34,6 +40,8 @@ use self::vrf_evaluator::VrfWonSlotWithHash;
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct BlockProducerWonSlot {
     pub slot_time: redux::Timestamp,
+    /// Our producer key which won the slot.
+    pub producer: v2::NonZeroCurvePoint,
     pub delegator: (v2::NonZeroCurvePoint, AccountIndex),
     pub global_slot: v2::ConsensusGlobalSlotStableV1,
     pub vrf_output: VrfOutput,
@@ -55,27 +63,36 @@ impl BlockProducerWonSlot {
     pub fn from_vrf_won_slot(
         won_slot_with_hash: &VrfWonSlotWithHash,
         genesis_timestamp: redux::Timestamp,
+        slots_per_epoch: u32,
+        block_window_duration_ms: u64,
     ) -> Self {
         let VrfWonSlotWithHash {
             won_slot,
             staking_ledger_hash,
         } = won_slot_with_hash;
 
-        let slot_time = Self::calculate_slot_time(genesis_timestamp, won_slot.global_slot);
+        let slot_time = Self::calculate_slot_time(
+            genesis_timestamp,
+            won_slot.global_slot,
+            block_window_duration_ms,
+        );
 
         let winner_pub_key = AccountPublicKey::from(
             CompressedPubKey::from_address(&won_slot.winner_account).unwrap(),
         );
         let delegator = (winner_pub_key.into(), won_slot.account_index);
+        let producer =
+            AccountPublicKey::from(CompressedPubKey::from_address(&won_slot.producer).unwrap());
         let global_slot = v2::ConsensusGlobalSlotStableV1 {
             slot_number: v2::MinaNumbersGlobalSlotSinceHardForkMStableV1::SinceHardFork(
                 won_slot.global_slot.into(),
             ),
-            slots_per_epoch: 7140.into(), // TODO
+            slots_per_epoch: slots_per_epoch.into(),
         };
 
         Self {
             slot_time,
+            producer: producer.into(),
             delegator,
             global_slot,
             vrf_output: won_slot.vrf_output.clone(),
@@ -84,10 +101,12 @@ impl BlockProducerWonSlot {
         }
     }
 
-    fn calculate_slot_time(genesis_timestamp: redux::Timestamp, slot: u32) -> redux::Timestamp {
-        // FIXME: this calculation must use values from the protocol constants,
-        // now it assumes 3 minutes blocks.
-        genesis_timestamp + (slot as u64) * 3 * 60 * 1_000_000_000_u64
+    fn calculate_slot_time(
+        genesis_timestamp: redux::Timestamp,
+        slot: u32,
+        block_window_duration_ms: u64,
+    ) -> redux::Timestamp {
+        genesis_timestamp + (slot as u64) * block_window_duration_ms * 1_000_000
     }
 
     pub fn global_slot(&self) -> u32 {
@@ -113,8 +132,8 @@ impl BlockProducerWonSlot {
         ))
     }
 
-    pub fn next_slot_time(&self) -> redux::Timestamp {
-        self.slot_time + 3 * 60 * 1_000_000_000_u64
+    pub fn next_slot_time(&self, block_window_duration_ms: u64) -> redux::Timestamp {
+        self.slot_time + block_window_duration_ms * 1_000_000
     }
 }
 
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use crate::account::AccountPublicKey;
@@ -62,7 +63,7 @@ pub enum BlockProducerVrfEvaluatorAction {
         current_best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
         transition_frontier_size: u32,
     },
     /// Constructing delegator table.
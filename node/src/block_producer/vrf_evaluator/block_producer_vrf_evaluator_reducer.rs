@@ -43,7 +43,8 @@ impl BlockProducerVrfEvaluatorState {
                 epoch_number,
                 latest_evaluated_global_slot,
             } => {
-                let epoch_current_bound = Self::evaluate_epoch_bounds(latest_evaluated_global_slot);
+                let epoch_current_bound =
+                    Self::evaluate_epoch_bounds(latest_evaluated_global_slot, self.slots_per_epoch);
                 self.status = BlockProducerVrfEvaluatorStatus::EpochBoundsCheck {
                     time: meta.time(),
                     epoch_number: *epoch_number,
@@ -94,7 +95,7 @@ impl BlockProducerVrfEvaluatorState {
                 current_best_tip_global_slot,
                 next_epoch_first_slot,
                 staking_epoch_data,
-                producer,
+                producers,
                 transition_frontier_size,
                 ..
             } => {
@@ -108,7 +109,7 @@ impl BlockProducerVrfEvaluatorState {
                     current_best_tip_global_slot: *current_best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                     transition_frontier_size: *transition_frontier_size,
                 }
             }
@@ -120,7 +121,7 @@ impl BlockProducerVrfEvaluatorState {
                     current_best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     transition_frontier_size,
                     ..
                 } = &self.status
@@ -136,7 +137,7 @@ impl BlockProducerVrfEvaluatorState {
                     current_best_tip_global_slot: *current_best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                     transition_frontier_size: *transition_frontier_size,
                 }
             }
@@ -150,7 +151,7 @@ impl BlockProducerVrfEvaluatorState {
                     current_best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     transition_frontier_size,
                     ..
                 } = &self.status
@@ -170,7 +171,7 @@ impl BlockProducerVrfEvaluatorState {
                     current_best_tip_global_slot: *current_best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                     transition_frontier_size: *transition_frontier_size,
                 }
             }
@@ -1,22 +1,30 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
 use openmina_core::block::ArcBlockWithHash;
+use openmina_core::constants::PROTOCOL_CONSTANTS;
 use serde::{Deserialize, Serialize};
 
-use crate::{account::AccountPublicKey, block_producer::BlockProducerWonSlot};
+use crate::{
+    account::AccountPublicKey,
+    block_producer::{BlockProducerLog, BlockProducerWonSlot},
+};
 
 use super::{DelegatorTable, VrfEvaluatorInput, VrfWonSlotWithHash};
 
-pub const SLOTS_PER_EPOCH: u32 = 7140;
 /// Vrf evaluator sub-state
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockProducerVrfEvaluatorState {
     pub status: BlockProducerVrfEvaluatorStatus,
     pub won_slots: BTreeMap<u32, VrfWonSlotWithHash>,
+    /// Delegator tables used for evaluation of the won slots.
+    ///
+    /// staking ledger hash -> delegator table
+    pub delegator_tables: BTreeMap<v2::LedgerHash, Arc<DelegatorTable>>,
     pub latest_evaluated_slot: u32,
     pub genesis_timestamp: redux::Timestamp,
+    pub slots_per_epoch: u32,
     last_evaluated_epoch: Option<u32>,
     last_block_heights_in_epoch: BTreeMap<u32, u32>,
     pending_evaluation: Option<PendingEvaluation>,
@@ -28,8 +36,10 @@ impl BlockProducerVrfEvaluatorState {
         Self {
             status: BlockProducerVrfEvaluatorStatus::Idle { time: now },
             won_slots: Default::default(),
+            delegator_tables: Default::default(),
             latest_evaluated_slot: Default::default(),
             genesis_timestamp: redux::Timestamp::ZERO,
+            slots_per_epoch: PROTOCOL_CONSTANTS.slots_per_epoch.as_u32(),
             last_evaluated_epoch: Default::default(),
             last_block_heights_in_epoch: Default::default(),
             pending_evaluation: Default::default(),
@@ -38,17 +48,18 @@ impl BlockProducerVrfEvaluatorState {
     }
 
     /// Determines the position of a slot within an epoch (at the beginning, end, or within the epoch).
-    /// This function calculates the position based on the `global_slot` and a predefined number of slots per epoch.
+    /// This function calculates the position based on the `global_slot` and the number of slots per epoch.
     ///
     /// Arguments:
     /// - `global_slot`: A reference to a 32-bit unsigned integer representing the global slot number.
+    /// - `slots_per_epoch`: Number of slots per epoch, from the protocol constants.
     ///
     /// Returns:
     /// - `SlotPositionInEpoch`: An enum indicating the slot's position (Beginning, End, or Within).
-    pub fn evaluate_epoch_bounds(global_slot: &u32) -> SlotPositionInEpoch {
-        if global_slot % SLOTS_PER_EPOCH == 0 {
+    pub fn evaluate_epoch_bounds(global_slot: &u32, slots_per_epoch: u32) -> SlotPositionInEpoch {
+        if global_slot % slots_per_epoch == 0 {
             SlotPositionInEpoch::Beginning
-        } else if (global_slot + 1) % SLOTS_PER_EPOCH == 0 {
+        } else if (global_slot + 1) % slots_per_epoch == 0 {
             SlotPositionInEpoch::End
         } else {
             SlotPositionInEpoch::Within
@@ -61,6 +72,7 @@ impl BlockProducerVrfEvaluatorState {
     /// Arguments:
     /// - `cur_global_slot`: The current global slot as a 32-bit unsigned integer.
     /// - `best_tip`: A reference to the `ArcBlockWithHash` representing the current best tip.
+    /// - `production_log`: Slots recorded in the production log are skipped.
     ///
     /// Returns:
     /// - `Option<BlockProducerWonSlot>`: The next won slot, if any, as a `BlockProducerWonSlot` or `None` if there are no more slots won in the future.
@@ -68,13 +80,24 @@ impl BlockProducerVrfEvaluatorState {
         &self,
         cur_global_slot: u32,
         best_tip: &ArcBlockWithHash,
+        production_log: &BlockProducerLog,
+        block_window_duration_ms: u64,
     ) -> Option<BlockProducerWonSlot> {
         self.won_slots
             .range(cur_global_slot..)
             .map(|(_, won_slot)| {
-                BlockProducerWonSlot::from_vrf_won_slot(won_slot, best_tip.genesis_timestamp())
+                BlockProducerWonSlot::from_vrf_won_slot(
+                    won_slot,
+                    best_tip.genesis_timestamp(),
+                    best_tip.constants().slots_per_epoch.as_u32(),
+                    block_window_duration_ms,
+                )
+            })
+            .find(|won_slot| {
+                won_slot > best_tip
+                    && !production_log
+                        .contains(won_slot.global_slot() + best_tip.global_slot_diff())
             })
-            .find(|won_slot| won_slot > best_tip)
     }
 
     /// Retrieves the current epoch context.
@@ -334,6 +357,11 @@ impl BlockProducerVrfEvaluatorState {
     }
 
     pub fn set_pending_evaluation(&mut self, pending_evaluation: PendingEvaluation) {
+        let epoch_data = &pending_evaluation.epoch_data;
+        self.delegator_tables.insert(
+            epoch_data.ledger.clone(),
+            epoch_data.delegator_table.clone(),
+        );
         self.pending_evaluation = Some(pending_evaluation)
     }
 
@@ -362,23 +390,37 @@ impl BlockProducerVrfEvaluatorState {
     pub fn retention_slot(&self, current_epoch_number: &u32) -> u32 {
         const PAST_EPOCHS_TO_KEEP: u32 = 2;
         let cutoff_epoch = current_epoch_number.saturating_sub(PAST_EPOCHS_TO_KEEP);
-        (cutoff_epoch * SLOTS_PER_EPOCH).saturating_sub(1)
+        (cutoff_epoch * self.slots_per_epoch).saturating_sub(1)
     }
 
     pub fn cleanup_old_won_slots(&mut self, current_epoch_number: &u32) {
         let cutoff_slot = self.retention_slot(current_epoch_number);
         self.won_slots
             .retain(|global_slot, _| cutoff_slot < *global_slot);
+
+        let pending_ledger = self
+            .pending_evaluation
+            .as_ref()
+            .map(|pending| &pending.epoch_data.ledger);
+        let won_slots = &self.won_slots;
+        self.delegator_tables.retain(|ledger_hash, _| {
+            Some(ledger_hash) == pending_ledger
+                || won_slots
+                    .values()
+                    .any(|won_slot| &won_slot.staking_ledger_hash == ledger_hash)
+        });
     }
 
     /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         match &self.status {
             BlockProducerVrfEvaluatorStatus::EpochDelegatorTablePending {
                 staking_epoch_ledger_hash,
-                producer,
+                producers,
                 ..
-            } => Some((staking_epoch_ledger_hash, producer)),
+            } => Some((staking_epoch_ledger_hash, producers)),
             _ => None,
         }
     }
@@ -454,7 +496,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         current_best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
         transition_frontier_size: u32,
     },
     /// Waiting for delegator table building
@@ -468,7 +510,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         current_best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
         transition_frontier_size: u32,
     },
     /// Delegator table built successfully
@@ -482,7 +524,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         current_best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
         transition_frontier_size: u32,
     },
     InitialSlotSelection {
@@ -695,8 +737,10 @@ mod test {
                     next_epoch_data: DUMMY_NEXT_EPOCH_DATA.to_owned(),
                 },
                 won_slots: BTreeMap::new(),
+                delegator_tables: BTreeMap::new(),
                 latest_evaluated_slot: 0,
                 genesis_timestamp: redux::Timestamp::global_now(),
+                slots_per_epoch: 7140,
                 last_evaluated_epoch: None,
                 last_block_heights_in_epoch: BTreeMap::new(),
                 pending_evaluation: None,
@@ -719,8 +763,10 @@ mod test {
                     next_epoch_data: DUMMY_NEXT_EPOCH_DATA.to_owned(),
                 },
                 won_slots: BTreeMap::new(),
+                delegator_tables: BTreeMap::new(),
                 latest_evaluated_slot: 7139,
                 genesis_timestamp: redux::Timestamp::global_now(),
+                slots_per_epoch: 7140,
                 last_evaluated_epoch: Some(0),
                 last_block_heights_in_epoch: BTreeMap::new(),
                 pending_evaluation: None,
@@ -743,8 +789,10 @@ mod test {
                     next_epoch_data: DUMMY_NEXT_EPOCH_DATA.to_owned(),
                 },
                 won_slots: BTreeMap::new(),
+                delegator_tables: BTreeMap::new(),
                 latest_evaluated_slot: 14279,
                 genesis_timestamp: redux::Timestamp::global_now(),
+                slots_per_epoch: 7140,
                 last_evaluated_epoch: Some(1),
                 last_block_heights_in_epoch: BTreeMap::new(),
                 pending_evaluation: None,
@@ -767,8 +815,10 @@ mod test {
                     next_epoch_data: DUMMY_NEXT_EPOCH_DATA.to_owned(),
                 },
                 won_slots: BTreeMap::new(),
+                delegator_tables: BTreeMap::new(),
                 latest_evaluated_slot: 0,
                 genesis_timestamp: redux::Timestamp::global_now(),
+                slots_per_epoch: 7140,
                 last_evaluated_epoch: None,
                 last_block_heights_in_epoch: BTreeMap::new(),
                 pending_evaluation: None,
@@ -791,8 +841,10 @@ mod test {
                     next_epoch_data: DUMMY_NEXT_EPOCH_DATA.to_owned(),
                 },
                 won_slots: BTreeMap::new(),
+                delegator_tables: BTreeMap::new(),
                 latest_evaluated_slot: 21419,
                 genesis_timestamp: redux::Timestamp::global_now(),
+                slots_per_epoch: 7140,
                 last_evaluated_epoch: Some(2),
                 last_block_heights_in_epoch: BTreeMap::new(),
                 pending_evaluation: None,
@@ -917,6 +969,8 @@ mod test {
 
     #[test]
     fn test_evaluate_epoch_bounds() {
+        const SLOTS_PER_EPOCH: u32 = 7140;
+
         const GENESIS_EPOCH_BEGINNING: u32 = 0;
         const GENESIS_EPOCH_WITHIN: u32 = 2000;
         const GENESIS_EPOCH_END: u32 = 7139;
@@ -925,20 +979,30 @@ mod test {
         const WITHIN: u32 = 7500;
         const END: u32 = 14279;
 
-        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&GENESIS_EPOCH_BEGINNING);
+        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(
+            &GENESIS_EPOCH_BEGINNING,
+            SLOTS_PER_EPOCH,
+        );
         assert!(matches!(res, SlotPositionInEpoch::Beginning));
-        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&GENESIS_EPOCH_WITHIN);
+        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(
+            &GENESIS_EPOCH_WITHIN,
+            SLOTS_PER_EPOCH,
+        );
         assert!(matches!(res, SlotPositionInEpoch::Within));
-        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&GENESIS_EPOCH_END);
+        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(
+            &GENESIS_EPOCH_END,
+            SLOTS_PER_EPOCH,
+        );
         assert!(matches!(res, SlotPositionInEpoch::End));
 
-        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&BEGINNING);
+        let res =
+            BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&BEGINNING, SLOTS_PER_EPOCH);
         assert!(matches!(res, SlotPositionInEpoch::Beginning));
 
-        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&WITHIN);
+        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&WITHIN, SLOTS_PER_EPOCH);
         assert!(matches!(res, SlotPositionInEpoch::Within));
 
-        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&END);
+        let res = BlockProducerVrfEvaluatorState::evaluate_epoch_bounds(&END, SLOTS_PER_EPOCH);
         assert!(matches!(res, SlotPositionInEpoch::End));
     }
 
@@ -24,7 +24,11 @@ use vrf::{VrfEvaluationOutput, VrfWonSlot};
 
 use crate::account::AccountPublicKey;
 
-pub type DelegatorTable = BTreeMap<AccountIndex, (AccountPublicKey, u64)>;
+/// Delegators (including producer itself) of each of our producer keys.
+///
+/// producer -> account index -> (delegator, stake)
+pub type DelegatorTable =
+    BTreeMap<AccountPublicKey, BTreeMap<AccountIndex, (AccountPublicKey, u64)>>;
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct VrfEvaluatorInput {
@@ -1,5 +1,6 @@
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use mina_p2p_messages::v2::CurrencyFeeStableV1;
 use serde::{Deserialize, Serialize};
@@ -11,7 +12,7 @@ pub use crate::p2p::P2pConfig;
 pub use crate::snark::SnarkConfig;
 pub use crate::snark_pool::SnarkPoolConfig;
 use crate::transition_frontier::genesis::GenesisConfig;
-pub use crate::transition_frontier::TransitionFrontierConfig;
+pub use crate::transition_frontier::{TransitionFrontierConfig, TransitionFrontierHardForkConfig};
 pub use mina_p2p_messages::v2::MinaBaseProtocolConstantsCheckedValueStableV1 as ProtocolConstants;
 
 // TODO(binier): maybe make sure config is immutable.
@@ -30,6 +31,53 @@ pub struct Config {
 pub struct GlobalConfig {
     pub build: Box<BuildEnv>,
     pub snarker: Option<SnarkerConfig>,
+    #[serde(default)]
+    pub health: HealthCheckConfig,
+    /// Enables coordinator mode, in which remote snark workers can lease
+    /// jobs from the node over http.
+    #[serde(default)]
+    pub snark_coordinator: Option<SnarkCoordinatorConfig>,
+}
+
+/// Thresholds for the node health checks.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthCheckConfig {
+    /// Number of slots the best tip can lag behind the current slot
+    /// before the check warns.
+    pub best_tip_age_warn_slots: u32,
+    /// Number of slots the best tip can lag behind the current slot
+    /// before the check fails.
+    pub best_tip_age_fail_slots: u32,
+    /// Warn if we have less ready peers than this. No ready peers fails.
+    pub min_peers: usize,
+    /// Fail if the sync stays in the same phase for longer than this.
+    pub sync_stuck_timeout: Duration,
+    /// How far back to look for missed won slots.
+    pub missed_slot_window: Duration,
+    /// Window in which the snark worker errors are counted.
+    pub snark_worker_crash_window: Duration,
+    /// Fail if the snark worker errored this many times within the window.
+    pub snark_worker_crash_threshold: usize,
+    /// Warn if more ledger read requests than this are pending.
+    pub ledger_backlog: usize,
+    /// Fail if a ledger request is pending for longer than this.
+    pub ledger_request_timeout: Duration,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            best_tip_age_warn_slots: 3,
+            best_tip_age_fail_slots: 10,
+            min_peers: 3,
+            sync_stuck_timeout: Duration::from_secs(10 * 60),
+            missed_slot_window: Duration::from_secs(60 * 60),
+            snark_worker_crash_window: Duration::from_secs(10 * 60),
+            snark_worker_crash_threshold: 3,
+            ledger_backlog: 128,
+            ledger_request_timeout: Duration::from_secs(60),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -38,12 +86,81 @@ pub struct SnarkerConfig {
     pub fee: CurrencyFeeStableV1,
     pub strategy: SnarkerStrategy,
     pub auto_commit: bool,
+    /// Number of external snark worker processes to run.
+    #[serde(default = "default_snarker_workers")]
+    pub workers: usize,
+    /// Lowest fee [`SnarkerStrategy::Profit`] can lower the `fee` to, when
+    /// undercutting competing snarks. If not set, fee isn't lowered.
+    #[serde(default)]
+    pub min_fee: Option<CurrencyFeeStableV1>,
+}
+
+fn default_snarker_workers() -> usize {
+    1
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnarkCoordinatorConfig {
+    /// Lease is released and the job re-queued if the remote worker
+    /// didn't send a heartbeat for this long.
+    pub lease_timeout: Duration,
+    /// Idle worker, which didn't lease a job or send a heartbeat for this
+    /// long, is unregistered.
+    #[serde(default = "default_snark_coordinator_worker_timeout")]
+    pub worker_timeout: Duration,
+    /// Maximum number of registered workers.
+    #[serde(default = "default_snark_coordinator_max_workers")]
+    pub max_workers: usize,
+    /// Token which workers must present with every request. If not set,
+    /// anyone who can reach the http server can act as a worker.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+fn default_snark_coordinator_worker_timeout() -> Duration {
+    Duration::from_secs(5 * 60)
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+fn default_snark_coordinator_max_workers() -> usize {
+    64
+}
+
+impl SnarkCoordinatorConfig {
+    pub fn is_auth_token_valid(&self, token: Option<&str>) -> bool {
+        let Some(expected) = self.auth_token.as_ref() else {
+            return true;
+        };
+        let Some(token) = token else {
+            return false;
+        };
+        // Constant time comparison, to not leak the token through timing.
+        expected.len() == token.len()
+            && expected
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+}
+
+impl Default for SnarkCoordinatorConfig {
+    fn default() -> Self {
+        Self {
+            lease_timeout: Duration::from_secs(30),
+            worker_timeout: default_snark_coordinator_worker_timeout(),
+            max_workers: default_snark_coordinator_max_workers(),
+            auth_token: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SnarkerStrategy {
     Sequential,
     Random,
+    /// Picks jobs with the highest expected profit and undercuts the
+    /// cheapest competing commitment or snark for the job.
+    Profit,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -108,7 +225,7 @@ impl BuildEnv {
 }
 
 #[derive(thiserror::Error, Debug)]
-#[error("invalid strategy: {0}! expected one of: seq/sequential/rand/random")]
+#[error("invalid strategy: {0}! expected one of: seq/sequential/rand/random/profit")]
 pub struct SnarkerStrategyParseError(String);
 
 impl FromStr for SnarkerStrategy {
@@ -118,6 +235,7 @@ impl FromStr for SnarkerStrategy {
         Ok(match s {
             "seq" | "sequential" => SnarkerStrategy::Sequential,
             "rand" | "random" => SnarkerStrategy::Random,
+            "profit" => SnarkerStrategy::Profit,
             other => return Err(SnarkerStrategyParseError(other.to_owned())),
         })
     }
@@ -137,7 +255,7 @@ lazy_static::lazy_static! {
 mod tests {
     use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
-    use super::DEVNET_CONFIG;
+    use super::{SnarkCoordinatorConfig, DEVNET_CONFIG};
 
     #[test]
     fn devnet_config() {
@@ -156,4 +274,21 @@ mod tests {
                 .into()
         );
     }
+
+    #[test]
+    fn snark_coordinator_auth_token() {
+        let open = SnarkCoordinatorConfig::default();
+        assert!(open.is_auth_token_valid(None));
+        assert!(open.is_auth_token_valid(Some("anything")));
+
+        let config = SnarkCoordinatorConfig {
+            auth_token: Some("secret".to_owned()),
+            ..Default::default()
+        };
+        assert!(config.is_auth_token_valid(Some("secret")));
+        assert!(!config.is_auth_token_valid(None));
+        assert!(!config.is_auth_token_valid(Some("")));
+        assert!(!config.is_auth_token_valid(Some("secreT")));
+        assert!(!config.is_auth_token_valid(Some("secret2")));
+    }
 }
@@ -46,6 +46,10 @@ pub enum ConsensusAction {
         hash: StateHash,
     },
     Prune,
+    /// Forget all candidate blocks and the best tip.
+    ///
+    /// Used when switching to the new chain after the hard fork.
+    Reset,
 }
 
 impl redux::EnablingCondition<crate::State> for ConsensusAction {
@@ -56,7 +60,9 @@ impl redux::EnablingCondition<crate::State> for ConsensusAction {
                     hash: hash.clone(),
                     block: block.clone()
                 };
-                !block.is_genesis() && !state.consensus.blocks.contains_key(hash)
+                !block.is_genesis()
+                    && !state.consensus.blocks.contains_key(hash)
+                    && state.transition_frontier.is_block_on_followed_chain(&block)
             },
             ConsensusAction::BlockChainProofUpdate { hash, .. } => {
                 (state.consensus.best_tip.as_ref() == Some(hash)
@@ -141,6 +147,9 @@ impl redux::EnablingCondition<crate::State> for ConsensusAction {
             ConsensusAction::Prune => {
                 state.consensus.best_tip().is_some()
             },
+            ConsensusAction::Reset => {
+                state.consensus.best_tip.is_some() || !state.consensus.blocks.is_empty()
+            },
         }
     }
 }
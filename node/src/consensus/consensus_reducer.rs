@@ -186,8 +186,13 @@ impl ConsensusState {
                 let candidate = &candidate_state.block.header;
                 let candidate_cs = &candidate.protocol_state.body.consensus_state;
 
-                let (take, why) =
-                    long_range_fork_take(tip_cs, candidate_cs, &tip_hash, candidate_hash);
+                let (take, why) = long_range_fork_take(
+                    tip_cs,
+                    candidate_cs,
+                    &tip_hash,
+                    candidate_hash,
+                    &tip.protocol_state.body.constants,
+                );
 
                 let Some(candidate_state) = state.blocks.get_mut(candidate_hash) else {
                     return;
@@ -254,6 +259,9 @@ impl ConsensusState {
                     .collect();
                 *blocks = blocks_to_keep;
             }
+            ConsensusAction::Reset => {
+                *state = ConsensusState::new();
+            }
         }
     }
 }
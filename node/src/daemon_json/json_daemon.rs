@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Daemon section of the runtime config.
+///
+/// Slot limits are used to end the chain before the hard fork.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Daemon {
+    /// Last global slot since genesis in which transactions can be
+    /// included into the block.
+    pub slot_tx_end: Option<u32>,
+    /// Last global slot since genesis in which blocks can be produced.
+    /// No blocks after that slot are considered to be part of this chain.
+    pub slot_chain_end: Option<u32>,
+}
@@ -0,0 +1,38 @@
+use mina_p2p_messages::v2::StateHash;
+use openmina_core::constants::ForkConstants;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Proof {
+    /// Slot duration.
+    pub block_window_duration_ms: Option<u64>,
+    pub fork: Option<Fork>,
+}
+
+/// Point of the previous chain the new chain was forked from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fork {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+    pub global_slot_since_genesis: u32,
+}
+
+impl Fork {
+    /// Fork constants are compiled into the circuits, so the config
+    /// can only describe the fork that the node was built for.
+    pub fn matches(&self, constants: &ForkConstants) -> bool {
+        self.state_hash == StateHash::from_fp(constants.state_hash)
+            && self.blockchain_length == constants.blockchain_length
+            && self.global_slot_since_genesis == constants.global_slot_since_genesis
+    }
+}
+
+impl From<&ForkConstants> for Fork {
+    fn from(constants: &ForkConstants) -> Self {
+        Self {
+            state_hash: StateHash::from_fp(constants.state_hash),
+            blockchain_length: constants.blockchain_length,
+            global_slot_since_genesis: constants.global_slot_since_genesis,
+        }
+    }
+}
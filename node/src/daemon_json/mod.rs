@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
 
+mod json_daemon;
 mod json_genesis;
 mod json_ledger;
+mod json_proof;
+pub use json_daemon::Daemon;
 pub use json_genesis::Genesis;
 pub use json_ledger::{
     build_ledger_name, Account, AccountConfigError, AccountPermissions, AccountTiming, Ledger,
     Zkapp,
 };
+pub use json_proof::{Fork, Proof};
 
 /// This type represents a JSON object loaded from daemon.json
 /// file. It does not describe its full structure, as it's not
@@ -20,11 +24,29 @@ pub use json_ledger::{
 /// that happens, the format can be extended to accommodate.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DaemonJson {
+    pub daemon: Option<Daemon>,
     pub ledger: Option<Ledger>,
     pub genesis: Option<Genesis>,
+    pub proof: Option<Proof>,
     pub epoch_data: Option<Epochs>,
 }
 
+impl DaemonJson {
+    /// Last global slot of the chain described by this config, if the
+    /// chain is scheduled to end (e.g. because of the upcoming hard fork).
+    pub fn slot_chain_end(&self) -> Option<u32> {
+        self.daemon.as_ref()?.slot_chain_end
+    }
+
+    pub fn block_window_duration_ms(&self) -> Option<u64> {
+        self.proof.as_ref()?.block_window_duration_ms
+    }
+
+    pub fn fork(&self) -> Option<&Fork> {
+        self.proof.as_ref()?.fork.as_ref()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Epochs {
     pub staking: EpochData,
@@ -102,4 +124,25 @@ mod test {
             panic!("Expected Timed account");
         }
     }
+
+    #[test]
+    fn test_daemon_json_hard_fork_sections() {
+        let daemon_json: DaemonJson = serde_json::from_str(
+            r#"{
+                "daemon": { "slot_tx_end": 100, "slot_chain_end": 110 },
+                "proof": {
+                    "fork": {
+                        "state_hash": "3NKxUSAJE3wqJkrtBhMYhwzrMq3B5sKjPJQRyXz1YrPWA7761opD",
+                        "blockchain_length": 296371,
+                        "global_slot_since_genesis": 445860
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(daemon_json.slot_chain_end(), Some(110));
+        let fork = daemon_json.fork().unwrap();
+        assert_eq!(fork.blockchain_length, 296371);
+        assert_eq!(fork.global_slot_since_genesis, 445860);
+    }
 }
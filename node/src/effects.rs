@@ -13,7 +13,7 @@ use crate::snark::snark_effects;
 use crate::snark_pool::candidate::SnarkPoolCandidateAction;
 use crate::snark_pool::{snark_pool_effects, SnarkPoolAction};
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
-use crate::transition_frontier::transition_frontier_effects;
+use crate::transition_frontier::{transition_frontier_effects, TransitionFrontierAction};
 use crate::{p2p_ready, Action, ActionWithMeta, ExternalSnarkWorkerAction, Service, Store};
 
 use crate::p2p::channels::rpc::{P2pChannelsRpcAction, P2pRpcRequest};
@@ -32,9 +32,12 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
         // Following action gets dispatched very often, so ideally this
         // effect execution should be as light as possible.
         Action::CheckTimeouts(_) => {
+            store.dispatch(TransitionFrontierAction::HardForkTransition);
             // TODO(binier): create init action and dispatch these there.
             store.dispatch(TransitionFrontierGenesisAction::LedgerLoadInit);
-            store.dispatch(ExternalSnarkWorkerAction::Start);
+            for worker_id in store.state().external_snark_worker.ids() {
+                store.dispatch(ExternalSnarkWorkerAction::Start { worker_id });
+            }
 
             if store.state().p2p.ready().is_some() {
                 p2p_timeout_effects(store, &meta);
@@ -50,8 +53,11 @@ pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
             store.dispatch(SnarkPoolCandidateAction::WorkFetchAll);
             store.dispatch(SnarkPoolCandidateAction::WorkVerifyNext);
 
-            store.dispatch(ExternalSnarkWorkerAction::StartTimeout { now: meta.time() });
-            store.dispatch(ExternalSnarkWorkerAction::WorkTimeout { now: meta.time() });
+            for worker_id in store.state().external_snark_worker.ids() {
+                let now = meta.time();
+                store.dispatch(ExternalSnarkWorkerAction::StartTimeout { worker_id, now });
+                store.dispatch(ExternalSnarkWorkerAction::WorkTimeout { worker_id, now });
+            }
 
             store.dispatch(BlockProducerAction::WonSlotProduceInit);
             store.dispatch(BlockProducerAction::BlockInject);
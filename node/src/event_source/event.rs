@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 pub use crate::block_producer::BlockProducerEvent;
-pub use crate::external_snark_worker::ExternalSnarkWorkerEvent;
+pub use crate::external_snark_worker::{ExternalSnarkWorkerEvent, ExternalSnarkWorkerId};
 pub use crate::ledger::LedgerEvent;
 pub use crate::p2p::{P2pConnectionEvent, P2pEvent};
 pub use crate::rpc::{RpcId, RpcRequest};
@@ -15,7 +15,7 @@ pub enum Event {
     Ledger(LedgerEvent),
     Snark(SnarkEvent),
     Rpc(RpcId, Box<RpcRequest>),
-    ExternalSnarkWorker(ExternalSnarkWorkerEvent),
+    ExternalSnarkWorker(ExternalSnarkWorkerId, ExternalSnarkWorkerEvent),
     BlockProducerEvent(BlockProducerEvent),
 
     GenesisLoad(Result<GenesisConfigLoaded, String>),
@@ -34,7 +34,9 @@ impl std::fmt::Display for Event {
                     RpcRequest::StatusGet => write!(f, "StatusGet"),
                     RpcRequest::ActionStatsGet(query) => write!(f, "ActionStatsGet, {query:?}"),
                     RpcRequest::SyncStatsGet(query) => write!(f, "SyncStatsGet, {query:?}"),
-                    RpcRequest::BlockProducerStatsGet => write!(f, "BlockProducerStatsGet"),
+                    RpcRequest::BlockProducerStatsGet(query) => {
+                        write!(f, "BlockProducerStatsGet, {query:?}")
+                    }
                     RpcRequest::PeersGet => write!(f, "PeersGet"),
                     RpcRequest::MessageProgressGet => write!(f, "MessageProgressGet"),
                     RpcRequest::P2pConnectionOutgoing(opts) => {
@@ -50,20 +52,33 @@ impl std::fmt::Display for Event {
                     RpcRequest::SnarkPoolJobGet { job_id } => {
                         write!(f, "SnarkPoolJobGet, {job_id}")
                     }
+                    RpcRequest::SnarkMarketStatsGet => write!(f, "SnarkMarketStatsGet"),
                     RpcRequest::SnarkerConfig => write!(f, "SnarkerConfig"),
                     RpcRequest::SnarkerJobCommit { job_id } => {
                         write!(f, "SnarkerJobCommit, {job_id}")
                     }
                     RpcRequest::SnarkerJobSpec { job_id } => write!(f, "SnarkerJobSpec, {job_id}"),
                     RpcRequest::SnarkerWorkers => write!(f, "SnarkerWorkers"),
+                    RpcRequest::SnarkCoordinatorRegister(req) => {
+                        write!(f, "SnarkCoordinatorRegister, {}", req.public_key)
+                    }
+                    RpcRequest::SnarkCoordinatorLease(req) => {
+                        write!(f, "SnarkCoordinatorLease, {}", req.worker_id)
+                    }
+                    RpcRequest::SnarkCoordinatorHeartbeat(req) => {
+                        write!(f, "SnarkCoordinatorHeartbeat, {}", req.worker_id)
+                    }
+                    RpcRequest::SnarkCoordinatorSubmit(req) => {
+                        write!(f, "SnarkCoordinatorSubmit, {}", req.worker_id)
+                    }
                     RpcRequest::HealthCheck => write!(f, "HealthCheck"),
                     RpcRequest::ReadinessCheck => write!(f, "ReadinessCheck"),
                     RpcRequest::DiscoveryRoutingTable => write!(f, "DiscoveryRoutingTable"),
                     RpcRequest::DiscoveryBoostrapStats => write!(f, "DiscoveryBoostrapStats"),
                 }
             }
-            Self::ExternalSnarkWorker(event) => {
-                write!(f, "ExternalSnarkWorker, ")?;
+            Self::ExternalSnarkWorker(worker_id, event) => {
+                write!(f, "ExternalSnarkWorker, {worker_id}, ")?;
 
                 match event {
                     ExternalSnarkWorkerEvent::Started => write!(f, "Started"),
@@ -2,7 +2,9 @@ use p2p::channels::transaction::P2pChannelsTransactionAction;
 
 use crate::action::CheckTimeoutsAction;
 use crate::block_producer::vrf_evaluator::BlockProducerVrfEvaluatorAction;
-use crate::block_producer::{BlockProducerEvent, BlockProducerVrfEvaluatorEvent};
+use crate::block_producer::{
+    BlockProducerEvent, BlockProducerVrfEvaluatorEvent, BlockProducerWonSlotDiscardReason,
+};
 use crate::external_snark_worker::ExternalSnarkWorkerEvent;
 use crate::ledger::read::LedgerReadAction;
 use crate::ledger::write::LedgerWriteAction;
@@ -10,7 +12,9 @@ use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
 use crate::p2p::channels::rpc::P2pChannelsRpcAction;
 use crate::p2p::channels::snark::P2pChannelsSnarkAction;
 use crate::p2p::channels::snark_job_commitment::P2pChannelsSnarkJobCommitmentAction;
-use crate::p2p::channels::{ChannelId, P2pChannelsMessageReceivedAction};
+use crate::p2p::channels::{
+    ChannelId, P2pChannelsMessageReceivedAction, P2pChannelsMessageSentAction,
+};
 use crate::p2p::connection::incoming::P2pConnectionIncomingAction;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingAction;
 use crate::p2p::connection::{P2pConnectionErrorResponse, P2pConnectionResponse};
@@ -18,10 +22,14 @@ use crate::p2p::disconnection::{P2pDisconnectionAction, P2pDisconnectionReason};
 use crate::p2p::P2pChannelEvent;
 #[cfg(feature = "p2p-libp2p")]
 use crate::p2p::{MioEvent, P2pNetworkSchedulerAction};
-use crate::rpc::{RpcAction, RpcRequest};
+use crate::rpc::{
+    RpcAction, RpcRequest, RpcSnarkCoordinatorHeartbeatResponse, RpcSnarkCoordinatorLeaseResponse,
+    RpcSnarkCoordinatorRegisterResponse, RpcSnarkCoordinatorSubmitResponse,
+};
 use crate::snark::block_verify::SnarkBlockVerifyAction;
 use crate::snark::work_verify::SnarkWorkVerifyAction;
 use crate::snark::SnarkEvent;
+use crate::stats::services::ServiceKind;
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
 use crate::{BlockProducerAction, ExternalSnarkWorkerAction, Service, Store};
 
@@ -214,19 +222,30 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                             }
                         },
                     },
-                    P2pChannelEvent::Sent(peer_id, _, _, res) => {
-                        if let Err(err) = res {
+                    P2pChannelEvent::Sent(peer_id, channel_id, _, res) => match res {
+                        Err(err) => {
                             let reason = P2pDisconnectionReason::P2pChannelSendFailed(err);
                             store.dispatch(P2pDisconnectionAction::Init { peer_id, reason });
                         }
-                    }
+                        Ok(bytes) => {
+                            store.dispatch(P2pChannelsMessageSentAction {
+                                peer_id,
+                                channel_id,
+                                bytes,
+                            });
+                        }
+                    },
                     P2pChannelEvent::Received(peer_id, res) => match res {
                         Err(err) => {
                             let reason = P2pDisconnectionReason::P2pChannelReceiveFailed(err);
                             store.dispatch(P2pDisconnectionAction::Init { peer_id, reason });
                         }
-                        Ok(message) => {
-                            store.dispatch(P2pChannelsMessageReceivedAction { peer_id, message });
+                        Ok((message, bytes)) => {
+                            store.dispatch(P2pChannelsMessageReceivedAction {
+                                peer_id,
+                                message,
+                                bytes,
+                            });
                         }
                     },
                     P2pChannelEvent::Closed(peer_id, chan_id) => {
@@ -237,110 +256,219 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
             },
             Event::Ledger(event) => match event {
                 LedgerEvent::Write(response) => {
+                    let pending_since = store.state().ledger.write.pending_since();
+                    service_call_finished(store, ServiceKind::LedgerWrite, pending_since, &meta);
                     store.dispatch(LedgerWriteAction::Success { response });
                 }
                 LedgerEvent::Read(id, response) => {
+                    let pending_since = store
+                        .state()
+                        .ledger
+                        .read
+                        .get(id)
+                        .and_then(|req| req.pending_since());
+                    service_call_finished(store, ServiceKind::LedgerRead, pending_since, &meta);
                     store.dispatch(LedgerReadAction::Success { id, response });
                 }
             },
             Event::Snark(event) => match event {
-                SnarkEvent::BlockVerify(req_id, result) => match result {
-                    Err(error) => {
-                        store.dispatch(SnarkBlockVerifyAction::Error { req_id, error });
+                SnarkEvent::BlockVerify(req_id, result) => {
+                    let pending_since = store
+                        .state()
+                        .snark
+                        .block_verify
+                        .jobs
+                        .get(req_id)
+                        .and_then(|job| job.pending_since());
+                    service_call_finished(
+                        store,
+                        ServiceKind::SnarkBlockVerify,
+                        pending_since,
+                        &meta,
+                    );
+                    match result {
+                        Err(error) => {
+                            store.dispatch(SnarkBlockVerifyAction::Error { req_id, error });
+                        }
+                        Ok(()) => {
+                            store.dispatch(SnarkBlockVerifyAction::Success { req_id });
+                        }
                     }
-                    Ok(()) => {
-                        store.dispatch(SnarkBlockVerifyAction::Success { req_id });
+                }
+                SnarkEvent::WorkVerify(req_id, result) => {
+                    let pending_since = store
+                        .state()
+                        .snark
+                        .work_verify
+                        .jobs
+                        .get(req_id)
+                        .and_then(|job| job.pending_since());
+                    service_call_finished(
+                        store,
+                        ServiceKind::SnarkWorkVerify,
+                        pending_since,
+                        &meta,
+                    );
+                    match result {
+                        Err(error) => {
+                            store.dispatch(SnarkWorkVerifyAction::Error { req_id, error });
+                        }
+                        Ok(()) => {
+                            store.dispatch(SnarkWorkVerifyAction::Success { req_id });
+                        }
                     }
-                },
-                SnarkEvent::WorkVerify(req_id, result) => match result {
-                    Err(error) => {
-                        store.dispatch(SnarkWorkVerifyAction::Error { req_id, error });
+                }
+            },
+            Event::Rpc(rpc_id, e) => {
+                // Tokens aren't passed along, to keep them out of the actions.
+                let is_authorized = e.is_snark_coordinator_authorized(
+                    store.state().config.snark_coordinator.as_ref(),
+                );
+                match *e {
+                    RpcRequest::StateGet(filter) => {
+                        store.dispatch(RpcAction::GlobalStateGet { rpc_id, filter });
                     }
-                    Ok(()) => {
-                        store.dispatch(SnarkWorkVerifyAction::Success { req_id });
+                    RpcRequest::StatusGet => {
+                        store.dispatch(RpcAction::StatusGet { rpc_id });
+                    }
+                    RpcRequest::ActionStatsGet(query) => {
+                        store.dispatch(RpcAction::ActionStatsGet { rpc_id, query });
+                    }
+                    RpcRequest::SyncStatsGet(query) => {
+                        store.dispatch(RpcAction::SyncStatsGet { rpc_id, query });
+                    }
+                    RpcRequest::BlockProducerStatsGet(query) => {
+                        store.dispatch(RpcAction::BlockProducerStatsGet { rpc_id, query });
+                    }
+                    RpcRequest::BlockProducerSlotScheduleGet => {
+                        store.dispatch(RpcAction::BlockProducerSlotScheduleGet { rpc_id });
+                    }
+                    RpcRequest::MetricsGet => {
+                        store.dispatch(RpcAction::MetricsGet { rpc_id });
+                    }
+                    RpcRequest::PeersGet => {
+                        store.dispatch(RpcAction::PeersGet { rpc_id });
+                    }
+                    RpcRequest::MessageProgressGet => {
+                        store.dispatch(RpcAction::MessageProgressGet { rpc_id });
+                    }
+                    RpcRequest::P2pConnectionOutgoing(opts) => {
+                        store.dispatch(RpcAction::P2pConnectionOutgoingInit { rpc_id, opts });
+                    }
+                    RpcRequest::P2pConnectionIncoming(opts) => {
+                        store.dispatch(RpcAction::P2pConnectionIncomingInit { rpc_id, opts });
+                    }
+                    RpcRequest::ScanStateSummaryGet(query) => {
+                        store.dispatch(RpcAction::ScanStateSummaryGetInit { rpc_id, query });
+                    }
+                    RpcRequest::SnarkPoolGet => {
+                        store.dispatch(RpcAction::SnarkPoolAvailableJobsGet { rpc_id });
+                    }
+                    RpcRequest::SnarkPoolJobGet { job_id } => {
+                        store.dispatch(RpcAction::SnarkPoolJobGet { rpc_id, job_id });
+                    }
+                    RpcRequest::SnarkMarketStatsGet => {
+                        store.dispatch(RpcAction::SnarkMarketStatsGet { rpc_id });
+                    }
+                    RpcRequest::SnarkerConfig => {
+                        store.dispatch(RpcAction::SnarkerConfigGet { rpc_id });
+                    }
+                    RpcRequest::SnarkerJobCommit { job_id } => {
+                        store.dispatch(RpcAction::SnarkerJobCommit { rpc_id, job_id });
+                    }
+                    RpcRequest::SnarkerJobSpec { job_id } => {
+                        store.dispatch(RpcAction::SnarkerJobSpec { rpc_id, job_id });
+                    }
+                    RpcRequest::SnarkerWorkers => {
+                        store.dispatch(RpcAction::SnarkerWorkersGet { rpc_id });
+                    }
+                    RpcRequest::SnarkCoordinatorRegister(req) => {
+                        if is_authorized {
+                            store.dispatch(RpcAction::SnarkCoordinatorRegister {
+                                rpc_id,
+                                public_key: req.public_key,
+                                fee: req.fee,
+                            });
+                        } else {
+                            let _ = store.service().respond_snark_coordinator_register(
+                                rpc_id,
+                                RpcSnarkCoordinatorRegisterResponse::Unauthorized,
+                            );
+                        }
+                    }
+                    RpcRequest::SnarkCoordinatorLease(req) => {
+                        if is_authorized {
+                            store.dispatch(RpcAction::SnarkCoordinatorLease {
+                                rpc_id,
+                                worker_id: req.worker_id,
+                            });
+                        } else {
+                            let _ = store.service().respond_snark_coordinator_lease(
+                                rpc_id,
+                                RpcSnarkCoordinatorLeaseResponse::Unauthorized,
+                            );
+                        }
+                    }
+                    RpcRequest::SnarkCoordinatorHeartbeat(req) => {
+                        if is_authorized {
+                            store.dispatch(RpcAction::SnarkCoordinatorHeartbeat {
+                                rpc_id,
+                                worker_id: req.worker_id,
+                            });
+                        } else {
+                            let _ = store.service().respond_snark_coordinator_heartbeat(
+                                rpc_id,
+                                RpcSnarkCoordinatorHeartbeatResponse::Unauthorized,
+                            );
+                        }
+                    }
+                    RpcRequest::SnarkCoordinatorSubmit(req) => {
+                        if is_authorized {
+                            store.dispatch(RpcAction::SnarkCoordinatorSubmit {
+                                rpc_id,
+                                worker_id: req.worker_id,
+                                proofs: req.proofs,
+                            });
+                        } else {
+                            let _ = store.service().respond_snark_coordinator_submit(
+                                rpc_id,
+                                RpcSnarkCoordinatorSubmitResponse::Unauthorized,
+                            );
+                        }
+                    }
+                    RpcRequest::HealthCheck => {
+                        store.dispatch(RpcAction::HealthCheck { rpc_id });
+                    }
+                    RpcRequest::ReadinessCheck => {
+                        store.dispatch(RpcAction::ReadinessCheck { rpc_id });
+                    }
+                    RpcRequest::DiscoveryRoutingTable => {
+                        store.dispatch(RpcAction::DiscoveryRoutingTable { rpc_id });
+                    }
+                    RpcRequest::DiscoveryBoostrapStats => {
+                        store.dispatch(RpcAction::DiscoveryBoostrapStats { rpc_id });
                     }
-                },
-            },
-            Event::Rpc(rpc_id, e) => match *e {
-                RpcRequest::StateGet(filter) => {
-                    store.dispatch(RpcAction::GlobalStateGet { rpc_id, filter });
-                }
-                RpcRequest::StatusGet => {
-                    store.dispatch(RpcAction::StatusGet { rpc_id });
-                }
-                RpcRequest::ActionStatsGet(query) => {
-                    store.dispatch(RpcAction::ActionStatsGet { rpc_id, query });
-                }
-                RpcRequest::SyncStatsGet(query) => {
-                    store.dispatch(RpcAction::SyncStatsGet { rpc_id, query });
-                }
-                RpcRequest::BlockProducerStatsGet => {
-                    store.dispatch(RpcAction::BlockProducerStatsGet { rpc_id });
-                }
-                RpcRequest::PeersGet => {
-                    store.dispatch(RpcAction::PeersGet { rpc_id });
-                }
-                RpcRequest::MessageProgressGet => {
-                    store.dispatch(RpcAction::MessageProgressGet { rpc_id });
-                }
-                RpcRequest::P2pConnectionOutgoing(opts) => {
-                    store.dispatch(RpcAction::P2pConnectionOutgoingInit { rpc_id, opts });
-                }
-                RpcRequest::P2pConnectionIncoming(opts) => {
-                    store.dispatch(RpcAction::P2pConnectionIncomingInit { rpc_id, opts });
-                }
-                RpcRequest::ScanStateSummaryGet(query) => {
-                    store.dispatch(RpcAction::ScanStateSummaryGetInit { rpc_id, query });
-                }
-                RpcRequest::SnarkPoolGet => {
-                    store.dispatch(RpcAction::SnarkPoolAvailableJobsGet { rpc_id });
-                }
-                RpcRequest::SnarkPoolJobGet { job_id } => {
-                    store.dispatch(RpcAction::SnarkPoolJobGet { rpc_id, job_id });
-                }
-                RpcRequest::SnarkerConfig => {
-                    store.dispatch(RpcAction::SnarkerConfigGet { rpc_id });
-                }
-                RpcRequest::SnarkerJobCommit { job_id } => {
-                    store.dispatch(RpcAction::SnarkerJobCommit { rpc_id, job_id });
-                }
-                RpcRequest::SnarkerJobSpec { job_id } => {
-                    store.dispatch(RpcAction::SnarkerJobSpec { rpc_id, job_id });
-                }
-                RpcRequest::SnarkerWorkers => {
-                    store.dispatch(RpcAction::SnarkerWorkersGet { rpc_id });
-                }
-                RpcRequest::HealthCheck => {
-                    store.dispatch(RpcAction::HealthCheck { rpc_id });
-                }
-                RpcRequest::ReadinessCheck => {
-                    store.dispatch(RpcAction::ReadinessCheck { rpc_id });
-                }
-                RpcRequest::DiscoveryRoutingTable => {
-                    store.dispatch(RpcAction::DiscoveryRoutingTable { rpc_id });
-                }
-                RpcRequest::DiscoveryBoostrapStats => {
-                    store.dispatch(RpcAction::DiscoveryBoostrapStats { rpc_id });
                 }
-            },
-            Event::ExternalSnarkWorker(e) => match e {
+            }
+            Event::ExternalSnarkWorker(worker_id, e) => match e {
                 ExternalSnarkWorkerEvent::Started => {
-                    store.dispatch(ExternalSnarkWorkerAction::Started);
+                    store.dispatch(ExternalSnarkWorkerAction::Started { worker_id });
                 }
                 ExternalSnarkWorkerEvent::Killed => {
-                    store.dispatch(ExternalSnarkWorkerAction::Killed);
+                    store.dispatch(ExternalSnarkWorkerAction::Killed { worker_id });
                 }
                 ExternalSnarkWorkerEvent::WorkResult(result) => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkResult { result });
+                    store.dispatch(ExternalSnarkWorkerAction::WorkResult { worker_id, result });
                 }
                 ExternalSnarkWorkerEvent::WorkError(error) => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkError { error });
+                    store.dispatch(ExternalSnarkWorkerAction::WorkError { worker_id, error });
                 }
                 ExternalSnarkWorkerEvent::WorkCancelled => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkCancelled);
+                    store.dispatch(ExternalSnarkWorkerAction::WorkCancelled { worker_id });
                 }
                 ExternalSnarkWorkerEvent::Error(error) => {
                     store.dispatch(ExternalSnarkWorkerAction::Error {
+                        worker_id,
                         error,
                         permanent: false,
                     });
@@ -358,9 +486,32 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                     }
                 },
                 BlockProducerEvent::BlockProve(block_hash, res) => match res {
-                    Err(err) => todo!(
-                        "error while trying to produce block proof for block {block_hash} - {err}"
-                    ),
+                    Err(err)
+                        if store
+                            .state()
+                            .transition_frontier
+                            .genesis
+                            .prove_pending_block_hash()
+                            .map_or(false, |hash| hash == block_hash) =>
+                    {
+                        openmina_core::error!(meta.time();
+                            kind = "TransitionFrontierGenesisProve",
+                            summary = format!("failed to produce genesis block proof for block {block_hash}"),
+                            error = err.clone());
+                        store.dispatch(TransitionFrontierGenesisAction::ProveError { error: err });
+                    }
+                    Err(err) => {
+                        openmina_core::error!(meta.time();
+                            kind = "BlockProducerBlockProve",
+                            summary = format!("failed to produce block proof for block {block_hash}"),
+                            error = err.clone());
+                        store.dispatch(BlockProducerAction::WonSlotDiscard {
+                            reason: BlockProducerWonSlotDiscardReason::BlockProofFailed {
+                                block_hash,
+                                error: err,
+                            },
+                        });
+                    }
                     Ok(proof) => {
                         if store
                             .state()
@@ -390,3 +541,17 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
         EventSourceAction::WaitForEvents => {}
     }
 }
+
+fn service_call_finished<S: Service>(
+    store: &mut Store<S>,
+    kind: ServiceKind,
+    pending_since: Option<redux::Timestamp>,
+    meta: &redux::ActionMeta,
+) {
+    let Some(latency) = pending_since.and_then(|time| meta.time().checked_sub(time)) else {
+        return;
+    };
+    if let Some(stats) = store.service.stats() {
+        stats.service_call_finished(kind, latency);
+    }
+}
@@ -12,42 +12,45 @@ pub fn external_snark_worker_effects<S: crate::Service>(
     action: ExternalSnarkWorkerActionWithMeta,
 ) {
     let (action, meta) = action.split();
+    let worker_id = action.worker_id();
     match action {
-        ExternalSnarkWorkerAction::Start => {
+        ExternalSnarkWorkerAction::Start { .. } => {
             let Some(config) = &store.state.get().config.snarker else {
                 return;
             };
             let public_key = config.public_key.clone().into();
-            let fee = config.fee.clone();
-            if let Err(err) = store.service.start(public_key, fee) {
+            if let Err(err) = store.service.start(worker_id, public_key) {
                 store.dispatch(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error: err,
                     permanent: true,
                 });
             }
         }
-        ExternalSnarkWorkerAction::Started => {
+        ExternalSnarkWorkerAction::Started { .. } => {
             store.dispatch(SnarkPoolAction::AutoCreateCommitment);
         }
         ExternalSnarkWorkerAction::StartTimeout { .. } => {
             store.dispatch(ExternalSnarkWorkerAction::Error {
+                worker_id,
                 error: super::ExternalSnarkWorkerError::StartTimeout,
                 permanent: true,
             });
         }
-        ExternalSnarkWorkerAction::Kill => {
-            if let Err(err) = store.service().kill() {
+        ExternalSnarkWorkerAction::Kill { .. } => {
+            if let Err(err) = store.service().kill(worker_id) {
                 store.dispatch(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error: err,
                     permanent: true,
                 });
             }
         }
-        ExternalSnarkWorkerAction::Killed => {}
+        ExternalSnarkWorkerAction::Killed { .. } => {}
         ExternalSnarkWorkerAction::Error { .. } => {
-            store.dispatch(ExternalSnarkWorkerAction::Kill);
+            store.dispatch(ExternalSnarkWorkerAction::Kill { worker_id });
         }
-        ExternalSnarkWorkerAction::SubmitWork { job_id, .. } => {
+        ExternalSnarkWorkerAction::SubmitWork { job_id, fee, .. } => {
             let Some(job) = store.state().snark_pool.get(&job_id) else {
                 return;
             };
@@ -57,21 +60,32 @@ pub fn external_snark_worker_effects<S: crate::Service>(
             ) {
                 Ok(v) => v,
                 Err(err) => {
-                    store.dispatch(ExternalSnarkWorkerAction::WorkError { error: err.into() });
+                    store.dispatch(ExternalSnarkWorkerAction::WorkError {
+                        worker_id,
+                        error: err.into(),
+                    });
                     return;
                 }
             };
-            if let Err(err) = store.service().submit(input) {
-                store.dispatch(ExternalSnarkWorkerAction::WorkError { error: err.into() });
+            if let Err(err) = store.service().submit(worker_id, input, fee) {
+                store.dispatch(ExternalSnarkWorkerAction::WorkError {
+                    worker_id,
+                    error: err.into(),
+                });
             }
         }
-        ExternalSnarkWorkerAction::WorkResult { result } => {
+        ExternalSnarkWorkerAction::WorkResult { result, .. } => {
             let Some(config) = &store.state().config.snarker else {
                 return;
             };
             let p2p = p2p_ready!(store.state().p2p, meta.time());
             let snarker = config.public_key.clone().into();
-            let fee = config.fee.clone();
+            let fee = store
+                .state()
+                .external_snark_worker
+                .get(worker_id)
+                .and_then(|worker| worker.job_fee.clone())
+                .unwrap_or_else(|| config.fee.clone());
             let snark = Snark {
                 snarker,
                 fee,
@@ -80,26 +94,27 @@ pub fn external_snark_worker_effects<S: crate::Service>(
             let sender = p2p.my_id();
             // Directly add snark to the snark pool as it's produced by us.
             store.dispatch(SnarkPoolAction::WorkAdd { snark, sender });
-            store.dispatch(ExternalSnarkWorkerAction::PruneWork);
+            store.dispatch(ExternalSnarkWorkerAction::PruneWork { worker_id });
         }
         ExternalSnarkWorkerAction::WorkError { .. } => {
-            store.dispatch(ExternalSnarkWorkerAction::PruneWork);
+            store.dispatch(ExternalSnarkWorkerAction::PruneWork { worker_id });
         }
         ExternalSnarkWorkerAction::WorkTimeout { .. } => {
-            store.dispatch(ExternalSnarkWorkerAction::CancelWork);
+            store.dispatch(ExternalSnarkWorkerAction::CancelWork { worker_id });
         }
-        ExternalSnarkWorkerAction::CancelWork => {
-            if let Err(error) = store.service().cancel() {
+        ExternalSnarkWorkerAction::CancelWork { .. } => {
+            if let Err(error) = store.service().cancel(worker_id) {
                 store.dispatch(ExternalSnarkWorkerAction::Error {
+                    worker_id,
                     error,
                     permanent: true,
                 });
             }
         }
-        ExternalSnarkWorkerAction::WorkCancelled => {
-            store.dispatch(ExternalSnarkWorkerAction::PruneWork);
+        ExternalSnarkWorkerAction::WorkCancelled { .. } => {
+            store.dispatch(ExternalSnarkWorkerAction::PruneWork { worker_id });
         }
-        ExternalSnarkWorkerAction::PruneWork => {
+        ExternalSnarkWorkerAction::PruneWork { .. } => {
             store.dispatch(SnarkPoolAction::AutoCreateCommitment);
         }
     }
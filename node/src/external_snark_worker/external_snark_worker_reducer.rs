@@ -1,11 +1,15 @@
 use super::{
-    external_snark_worker_state::{ExternalSnarkWorker, ExternalSnarkWorkerState},
+    external_snark_worker_state::{
+        ExternalSnarkWorker, ExternalSnarkWorkerState, ERROR_TIMES_LIMIT,
+    },
     ExternalSnarkWorkerAction, ExternalSnarkWorkerActionWithMetaRef, ExternalSnarkWorkers,
 };
 
 impl ExternalSnarkWorkers {
     pub fn reducer(&mut self, action: ExternalSnarkWorkerActionWithMetaRef<'_>) {
-        self.0.reducer(action)
+        if let Some(worker) = self.get_mut(action.action().worker_id()) {
+            worker.reducer(action)
+        }
     }
 }
 
@@ -13,34 +17,46 @@ impl ExternalSnarkWorker {
     pub fn reducer(&mut self, action: ExternalSnarkWorkerActionWithMetaRef<'_>) {
         let (action, meta) = action.split();
         match action {
-            ExternalSnarkWorkerAction::Start => {
+            ExternalSnarkWorkerAction::Start { .. } => {
                 self.state = ExternalSnarkWorkerState::Starting;
             }
-            ExternalSnarkWorkerAction::Started => {
+            ExternalSnarkWorkerAction::Started { .. } => {
                 self.state = ExternalSnarkWorkerState::Idle;
             }
             ExternalSnarkWorkerAction::StartTimeout { .. } => {
                 return;
             }
-            ExternalSnarkWorkerAction::Kill => {
+            ExternalSnarkWorkerAction::Kill { .. } => {
                 self.state = ExternalSnarkWorkerState::Killing;
             }
-            ExternalSnarkWorkerAction::Killed => {
+            ExternalSnarkWorkerAction::Killed { .. } => {
                 self.state = ExternalSnarkWorkerState::None;
             }
-            ExternalSnarkWorkerAction::Error { error, permanent } => {
+            ExternalSnarkWorkerAction::Error {
+                error, permanent, ..
+            } => {
                 self.state = ExternalSnarkWorkerState::Error(error.clone(), *permanent);
+                if self.error_times.len() >= ERROR_TIMES_LIMIT {
+                    self.error_times.pop_front();
+                }
+                self.error_times.push_back(meta.time());
             }
-            ExternalSnarkWorkerAction::SubmitWork { job_id, summary } => {
+            ExternalSnarkWorkerAction::SubmitWork {
+                job_id,
+                summary,
+                fee,
+                ..
+            } => {
                 self.state = ExternalSnarkWorkerState::Working(job_id.clone(), summary.clone());
+                self.job_fee = Some(fee.clone());
             }
-            ExternalSnarkWorkerAction::WorkResult { result } => {
+            ExternalSnarkWorkerAction::WorkResult { result, .. } => {
                 let ExternalSnarkWorkerState::Working(job_id, _) = &self.state else {
                     return;
                 };
                 self.state = ExternalSnarkWorkerState::WorkReady(job_id.clone(), result.clone());
             }
-            ExternalSnarkWorkerAction::WorkError { error } => {
+            ExternalSnarkWorkerAction::WorkError { error, .. } => {
                 let ExternalSnarkWorkerState::Working(job_id, _) = &self.state else {
                     return;
                 };
@@ -49,19 +65,19 @@ impl ExternalSnarkWorker {
             ExternalSnarkWorkerAction::WorkTimeout { .. } => {
                 return;
             }
-            ExternalSnarkWorkerAction::CancelWork => {
+            ExternalSnarkWorkerAction::CancelWork { .. } => {
                 let ExternalSnarkWorkerState::Working(job_id, _) = &self.state else {
                     return;
                 };
                 self.state = ExternalSnarkWorkerState::Cancelling(job_id.clone());
             }
-            ExternalSnarkWorkerAction::WorkCancelled => {
+            ExternalSnarkWorkerAction::WorkCancelled { .. } => {
                 let ExternalSnarkWorkerState::Cancelling(job_id) = &self.state else {
                     return;
                 };
                 self.state = ExternalSnarkWorkerState::Cancelled(job_id.clone());
             }
-            ExternalSnarkWorkerAction::PruneWork => {
+            ExternalSnarkWorkerAction::PruneWork { .. } => {
                 self.state = ExternalSnarkWorkerState::Idle;
             }
         }
@@ -2,7 +2,8 @@ use mina_p2p_messages::v2::{CurrencyFeeStableV1, NonZeroCurvePoint};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkResult, SnarkWorkSpec,
+    ExternalSnarkWorkerError, ExternalSnarkWorkerId, ExternalSnarkWorkerWorkError, SnarkWorkResult,
+    SnarkWorkSpec,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, derive_more::From)]
@@ -16,19 +17,24 @@ pub enum ExternalSnarkWorkerEvent {
 }
 
 pub trait ExternalSnarkWorkerService {
-    /// Starts external process.
+    /// Starts external process for the worker `worker_id`.
     fn start(
         &mut self,
+        worker_id: ExternalSnarkWorkerId,
         public_key: NonZeroCurvePoint,
-        fee: CurrencyFeeStableV1,
     ) -> Result<(), ExternalSnarkWorkerError>;
 
-    /// Submits snark work
-    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError>;
+    /// Submits snark work, to be done for the `fee`.
+    fn submit(
+        &mut self,
+        worker_id: ExternalSnarkWorkerId,
+        spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
+    ) -> Result<(), ExternalSnarkWorkerError>;
 
     /// Cancel current work
-    fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError>;
+    fn cancel(&mut self, worker_id: ExternalSnarkWorkerId) -> Result<(), ExternalSnarkWorkerError>;
 
     /// Kills external process.
-    fn kill(&mut self) -> Result<(), ExternalSnarkWorkerError>;
+    fn kill(&mut self, worker_id: ExternalSnarkWorkerId) -> Result<(), ExternalSnarkWorkerError>;
 }
@@ -1,19 +1,35 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use mina_p2p_messages::v2::CurrencyFeeStableV1;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
 use crate::snark_pool::JobSummary;
 
-use super::{ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkId, SnarkWorkResult};
+use super::{
+    ExternalSnarkWorkerError, ExternalSnarkWorkerId, ExternalSnarkWorkerWorkError, SnarkWorkId,
+    SnarkWorkResult,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExternalSnarkWorkers(pub(crate) ExternalSnarkWorker);
+pub struct ExternalSnarkWorkers(pub(crate) Vec<ExternalSnarkWorker>);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExternalSnarkWorker {
     pub(crate) state: ExternalSnarkWorkerState,
     pub(crate) timestamp: Timestamp,
+    /// Times of the most recent worker errors, oldest first.
+    #[serde(default)]
+    pub(crate) error_times: VecDeque<Timestamp>,
+    /// Fee of the current or the last submitted job.
+    #[serde(default)]
+    pub(crate) job_fee: Option<CurrencyFeeStableV1>,
 }
 
+/// Max number of error times kept in [`ExternalSnarkWorker::error_times`].
+pub(crate) const ERROR_TIMES_LIMIT: usize = 32;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExternalSnarkWorkerState {
     None,
@@ -32,33 +48,231 @@ pub enum ExternalSnarkWorkerState {
 }
 
 impl ExternalSnarkWorkers {
-    pub fn new(now: Timestamp) -> Self {
-        ExternalSnarkWorkers(ExternalSnarkWorker {
+    pub fn new(now: Timestamp, count: usize) -> Self {
+        let worker = ExternalSnarkWorker {
             state: ExternalSnarkWorkerState::None,
             timestamp: now,
-        })
+            error_times: Default::default(),
+            job_fee: None,
+        };
+        ExternalSnarkWorkers(vec![worker; count])
     }
 
-    pub fn is_idle(&self) -> bool {
-        matches!(self.0.state, ExternalSnarkWorkerState::Idle)
+    pub fn get(&self, worker_id: ExternalSnarkWorkerId) -> Option<&ExternalSnarkWorker> {
+        self.0.get(worker_id)
+    }
+
+    pub fn get_mut(
+        &mut self,
+        worker_id: ExternalSnarkWorkerId,
+    ) -> Option<&mut ExternalSnarkWorker> {
+        self.0.get_mut(worker_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ExternalSnarkWorkerId, &ExternalSnarkWorker)> {
+        self.0.iter().enumerate()
+    }
+
+    pub fn ids(&self) -> std::ops::Range<ExternalSnarkWorkerId> {
+        0..self.0.len()
+    }
+
+    pub fn is_idle(&self, worker_id: ExternalSnarkWorkerId) -> bool {
+        self.get(worker_id).map_or(false, |w| w.is_idle())
+    }
+
+    /// First idle worker, if any.
+    pub fn idle_worker(&self) -> Option<ExternalSnarkWorkerId> {
+        self.iter()
+            .find(|(_, worker)| worker.is_idle())
+            .map(|(id, _)| id)
     }
 
     pub fn has_idle(&self) -> bool {
-        self.available() > 0
+        self.idle_worker().is_some()
     }
 
     pub fn available(&self) -> usize {
-        if matches!(self.0.state, ExternalSnarkWorkerState::Idle) {
-            1
-        } else {
-            0
-        }
+        self.0.iter().filter(|worker| worker.is_idle()).count()
+    }
+
+    /// Workers that are currently working on a job, with the job id.
+    pub fn working_job_ids(&self) -> impl Iterator<Item = (ExternalSnarkWorkerId, &SnarkWorkId)> {
+        self.iter()
+            .filter_map(|(id, worker)| Some((id, worker.working_job_id()?)))
+    }
+
+    /// Workers working on a job for which `is_needed` returns `false`.
+    pub fn working_on_unneeded_job<'a>(
+        &'a self,
+        is_needed: impl 'a + Fn(&SnarkWorkId) -> bool,
+    ) -> impl 'a + Iterator<Item = ExternalSnarkWorkerId> {
+        self.working_job_ids()
+            .filter(move |(_, job_id)| !is_needed(job_id))
+            .map(|(id, _)| id)
+    }
+
+    /// Number of worker errors, across all workers, within the `window`
+    /// before `now`.
+    pub fn errors_within(&self, now: Timestamp, window: Duration) -> usize {
+        self.0
+            .iter()
+            .flat_map(|worker| worker.error_times.iter())
+            .filter(|t| now.checked_sub(**t).map_or(true, |d| d <= window))
+            .count()
+    }
+
+    /// Returns errors of the workers that are stopped because of them.
+    pub fn errors(
+        &self,
+    ) -> impl Iterator<Item = (ExternalSnarkWorkerId, &ExternalSnarkWorkerError)> {
+        self.iter()
+            .filter_map(|(id, worker)| Some((id, worker.error()?)))
+    }
+}
+
+impl ExternalSnarkWorker {
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, ExternalSnarkWorkerState::Idle)
     }
 
     pub fn working_job_id(&self) -> Option<&SnarkWorkId> {
-        match &self.0.state {
+        match &self.state {
             ExternalSnarkWorkerState::Working(job_id, _) => Some(job_id),
             _ => None,
         }
     }
+
+    /// Returns the error if the worker is stopped because of it.
+    pub fn error(&self) -> Option<&ExternalSnarkWorkerError> {
+        match &self.state {
+            ExternalSnarkWorkerState::Error(error, _) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mina_p2p_messages::v2::{
+        CurrencyFeeStableV1, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+    };
+    use redux::{ActionMeta, Timestamp};
+
+    use super::{ExternalSnarkWorkers, SnarkWorkId, ERROR_TIMES_LIMIT};
+    use crate::external_snark_worker::{ExternalSnarkWorkerAction, ExternalSnarkWorkerError};
+    use crate::snark_pool::JobSummary;
+
+    const SECOND: u64 = 1_000_000_000;
+
+    fn job_id(i: usize) -> SnarkWorkId {
+        const LEDGERS: [&str; 2] = [
+            "jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N",
+            "jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr",
+        ];
+        let pass = "jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc";
+        let (source, target) = (LEDGERS[i % 2], LEDGERS[(i + 1) % 2]);
+        format!("{source}:{pass}->{target}:{pass}").parse().unwrap()
+    }
+
+    fn dispatch(workers: &mut ExternalSnarkWorkers, time: u64, action: ExternalSnarkWorkerAction) {
+        let meta = ActionMeta::zero_custom(Timestamp::new(time));
+        workers.reducer(meta.with_action(&action));
+    }
+
+    fn started(count: usize) -> ExternalSnarkWorkers {
+        let mut workers = ExternalSnarkWorkers::new(Timestamp::ZERO, count);
+        for worker_id in workers.ids() {
+            dispatch(
+                &mut workers,
+                0,
+                ExternalSnarkWorkerAction::Started { worker_id },
+            );
+        }
+        workers
+    }
+
+    fn submit(workers: &mut ExternalSnarkWorkers, worker_id: usize, job_id: SnarkWorkId) {
+        let fee = CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(1.into()));
+        let action = ExternalSnarkWorkerAction::SubmitWork {
+            worker_id,
+            job_id,
+            summary: JobSummary::Tx(1),
+            fee,
+        };
+        dispatch(workers, 0, action);
+    }
+
+    fn error(workers: &mut ExternalSnarkWorkers, worker_id: usize, time: u64) {
+        let action = ExternalSnarkWorkerAction::Error {
+            worker_id,
+            error: ExternalSnarkWorkerError::Error("crashed".to_owned()),
+            permanent: false,
+        };
+        dispatch(workers, time, action);
+    }
+
+    #[test]
+    fn idle_worker_skips_busy_ones() {
+        let mut workers = started(3);
+        assert_eq!(workers.idle_worker(), Some(0));
+
+        submit(&mut workers, 0, job_id(0));
+        assert_eq!(workers.idle_worker(), Some(1));
+        assert_eq!(workers.available(), 2);
+
+        submit(&mut workers, 1, job_id(1));
+        error(&mut workers, 2, 0);
+        assert_eq!(workers.idle_worker(), None);
+        assert!(!workers.has_idle());
+    }
+
+    #[test]
+    fn only_worker_with_unneeded_job_is_cancelled() {
+        let mut workers = started(3);
+        submit(&mut workers, 0, job_id(0));
+        submit(&mut workers, 2, job_id(1));
+
+        let working = workers
+            .working_job_ids()
+            .map(|(id, job_id)| (id, job_id.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(working, vec![(0, job_id(0)), (2, job_id(1))]);
+
+        // Job 1 left the pool.
+        let in_pool = [job_id(0)];
+        let cancel = workers
+            .working_on_unneeded_job(|job_id| in_pool.contains(job_id))
+            .collect::<Vec<_>>();
+        assert_eq!(cancel, vec![2]);
+    }
+
+    #[test]
+    fn errors_are_counted_per_worker_within_window() {
+        let mut workers = started(2);
+        error(&mut workers, 0, SECOND);
+        error(&mut workers, 0, 10 * SECOND);
+        error(&mut workers, 1, 20 * SECOND);
+
+        let now = Timestamp::new(25 * SECOND);
+        assert_eq!(workers.get(0).unwrap().error_times.len(), 2);
+        assert_eq!(workers.get(1).unwrap().error_times.len(), 1);
+        assert_eq!(workers.errors_within(now, Duration::from_secs(30)), 3);
+        assert_eq!(workers.errors_within(now, Duration::from_secs(20)), 2);
+        assert_eq!(workers.errors_within(now, Duration::from_secs(1)), 0);
+        assert_eq!(
+            workers.errors().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        // Only the most recent errors of the worker are kept.
+        for i in 0..ERROR_TIMES_LIMIT as u64 {
+            error(&mut workers, 0, 21 * SECOND + i);
+        }
+        assert_eq!(workers.get(0).unwrap().error_times.len(), ERROR_TIMES_LIMIT);
+        let total = ERROR_TIMES_LIMIT + 1;
+        assert_eq!(workers.errors_within(now, Duration::from_secs(30)), total);
+    }
 }
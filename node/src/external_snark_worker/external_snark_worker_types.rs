@@ -8,6 +8,9 @@ use openmina_core::snark::SnarkJobId;
 
 pub type SnarkWorkId = SnarkJobId;
 
+/// Index of the worker process in [`super::ExternalSnarkWorkers`].
+pub type ExternalSnarkWorkerId = usize;
+
 /// TODO use more slim type `OneOrTwo`...
 pub type SnarkWorkSpec = SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Instances;
 
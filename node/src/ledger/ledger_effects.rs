@@ -313,12 +313,12 @@ fn propagate_read_response<S: redux::Service>(
     };
     match (request, response) {
         (
-            LedgerReadRequest::DelegatorTable(ledger_hash, pub_key),
+            LedgerReadRequest::DelegatorTable(ledger_hash, producers),
             LedgerReadResponse::DelegatorTable(table),
         ) => {
             let expected = store.state().block_producer.vrf_delegator_table_inputs();
-            if !expected.map_or(false, |(expected_hash, producer)| {
-                ledger_hash == expected_hash && pub_key == producer
+            if !expected.map_or(false, |(expected_hash, expected_producers)| {
+                ledger_hash == expected_hash && producers == expected_producers
             }) {
                 eprintln!("delegator table unexpected");
                 return;
@@ -158,16 +158,20 @@ impl LedgerRequest {
             Self::Read(id, request) => LedgerResponse::Read(
                 id,
                 match request {
-                    LedgerReadRequest::DelegatorTable(ledger_hash, producer) => {
+                    LedgerReadRequest::DelegatorTable(ledger_hash, producers) => {
                         let res = ledger_ctx
-                            .producers_with_delegates(&ledger_hash, |pub_key| {
-                                AccountPublicKey::from(pub_key.clone()) == producer
-                            })
-                            .and_then(|list| list.into_iter().next())
-                            .map(|(_, table)| {
-                                table
-                                    .into_iter()
-                                    .map(|(index, pub_key, balance)| (index, (pub_key, balance)))
+                            .producers_with_delegates_of(&ledger_hash, &producers)
+                            .map(|list| {
+                                list.into_iter()
+                                    .map(|(producer, table)| {
+                                        let table = table
+                                            .into_iter()
+                                            .map(|(index, pub_key, balance)| {
+                                                (index, (pub_key, balance))
+                                            })
+                                            .collect();
+                                        (producer, table)
+                                    })
                                     .collect()
                             });
 
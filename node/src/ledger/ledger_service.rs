@@ -1,6 +1,6 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
-    path::Path,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
@@ -23,11 +23,11 @@ use ledger::{
         validate_block::block_body_hash,
     },
     verifier::Verifier,
-    Account, BaseLedger, Database, Mask, UnregisterBehavior,
+    Account, AccountId, BaseLedger, Database, Mask, UnregisterBehavior,
 };
 use mina_hasher::Fp;
 use mina_p2p_messages::{
-    binprot::BinProtRead,
+    binprot::{BinProtRead, BinProtWrite},
     v2::{
         self, DataHashLibStateHashStableV1, LedgerHash, MinaBaseLedgerHash0StableV1,
         MinaBasePendingCoinbaseStableV2, MinaBasePendingCoinbaseWitnessStableV2,
@@ -75,6 +75,15 @@ pub struct LedgerCtx {
     snarked_ledgers: BTreeMap<LedgerHash, Mask>,
     /// Additional snarked ledgers specified at startup (loaded from disk)
     additional_snarked_ledgers: BTreeMap<LedgerHash, Mask>,
+    /// Epoch ledgers that are currently persisted, so that they survive
+    /// restarts.
+    persisted_epoch_ledgers: BTreeSet<LedgerHash>,
+    /// Epoch ledgers queued for persisting, but not yet written.
+    persisting_epoch_ledgers: BTreeSet<LedgerHash>,
+    /// Writes staking and next epoch ledgers to the epoch ledgers directory
+    /// outside of the ledger thread, so that a commit doesn't wait for the
+    /// disk.
+    epoch_ledgers_writer: Option<EpochLedgersWriter>,
     staged_ledgers: BTreeMap<LedgerHash, StagedLedger>,
     sync: LedgerSyncState,
     event_sender:
@@ -92,36 +101,23 @@ impl LedgerCtx {
     where
         P: AsRef<Path>,
     {
-        use std::fs;
-
-        let Ok(dir) = fs::read_dir(path) else {
-            return Self::default();
-        };
-
-        let additional_snarked_ledgers = dir
-            .filter_map(|entry| {
-                let entry = entry.ok()?;
-                let hash = entry.file_name().to_str()?.parse().ok()?;
-                let mut file = fs::File::open(entry.path()).ok()?;
-
-                let _ = Option::<LedgerHash>::binprot_read(&mut file).ok()?;
-
-                let accounts = Vec::<Account>::binprot_read(&mut file).ok()?;
-                let mut mask = Mask::new_root(Database::create(35));
-                for account in accounts {
-                    let account_id = account.id();
-                    mask.get_or_create_account(account_id, account).unwrap();
-                }
-                Some((hash, mask))
-            })
-            .collect();
-
         LedgerCtx {
-            additional_snarked_ledgers,
+            additional_snarked_ledgers: read_snarked_ledgers_dir(path),
             ..Default::default()
         }
     }
 
+    /// Enables persistence of staking and next epoch ledgers in `path`.
+    ///
+    /// Ledgers persisted by previous runs get loaded, so that they don't
+    /// need to be synced from peers again.
+    pub fn set_epoch_ledgers_dir(&mut self, path: PathBuf) {
+        let persisted = read_snarked_ledgers_dir(&path);
+        self.persisted_epoch_ledgers = persisted.keys().cloned().collect();
+        self.additional_snarked_ledgers.extend(persisted);
+        self.epoch_ledgers_writer = Some(epoch_ledgers_writer_spawn(path));
+    }
+
     // TODO(tizoc): Only used for the current workaround to make staged ledger
     // reconstruction async, can be removed when the ledger services are made async
     pub fn set_event_sender(
@@ -197,7 +193,14 @@ impl LedgerCtx {
 
         let origin = self
             .snarked_ledgers
-            .get(&origin_snarked_ledger_hash)
+            .get(&target_snarked_ledger_hash)
+            .or_else(|| {
+                // Target ledger may have been persisted by previous run
+                // (epoch ledgers), in which case there is nothing to sync.
+                self.additional_snarked_ledgers
+                    .get(&target_snarked_ledger_hash)
+            })
+            .or_else(|| self.snarked_ledgers.get(&origin_snarked_ledger_hash))
             .or_else(|| {
                 // If it doesn't exist in completed ledgers, it may be
                 // an in-progress ledger from a previous attempt that we can reuse
@@ -398,10 +401,67 @@ impl LedgerCtx {
             }
         });
 
-        let producers = accounts.into_iter().fold(
-            BTreeMap::<_, Vec<_>>::new(),
-            |mut producers, (id, delegate, balance)| {
+        let accounts = accounts
+            .into_iter()
+            .map(|(id, delegate, balance)| {
                 let index = mask.index_of_account(id.clone()).unwrap();
+                (index, id, delegate, balance)
+            })
+            .collect();
+
+        Some(Self::group_by_producer(accounts))
+    }
+
+    /// Same as [`Self::producers_with_delegates`] with a filter matching
+    /// `producers`, but answered from the ledger indexes of public keys and
+    /// delegates instead of scanning the whole ledger.
+    #[allow(clippy::type_complexity)]
+    pub fn producers_with_delegates_of(
+        &self,
+        ledger_hash: &LedgerHash,
+        producers: &BTreeSet<AccountPublicKey>,
+    ) -> Option<BTreeMap<AccountPublicKey, Vec<(ledger::AccountIndex, AccountPublicKey, u64)>>>
+    {
+        let (mask, _) = self.mask(ledger_hash)?;
+        let mut ids = HashSet::new();
+
+        for producer in producers {
+            let pub_key = CompressedPubKey::from(producer.clone());
+            ids.extend(mask.delegators(pub_key.clone()));
+            ids.extend(
+                mask.tokens(pub_key.clone())
+                    .into_iter()
+                    .map(|token_id| AccountId::create(pub_key.clone(), token_id)),
+            );
+        }
+
+        let mut accounts = ids
+            .into_iter()
+            .filter_map(|id| {
+                let addr = mask.location_of_account(&id)?;
+                let account = mask.get(addr.clone())?;
+                let delegate = account.delegate.clone();
+                Some((addr.to_index(), id, delegate, account.balance.as_u64()))
+            })
+            .collect::<Vec<_>>();
+        // Keep the same order as when iterating on the ledger
+        accounts.sort_by_key(|(index, ..)| *index);
+
+        Some(Self::group_by_producer(accounts))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn group_by_producer(
+        accounts: Vec<(
+            ledger::AccountIndex,
+            AccountId,
+            Option<CompressedPubKey>,
+            u64,
+        )>,
+    ) -> BTreeMap<AccountPublicKey, Vec<(ledger::AccountIndex, AccountPublicKey, u64)>> {
+        accounts.into_iter().fold(
+            BTreeMap::<_, Vec<_>>::new(),
+            |mut producers, (index, id, delegate, balance)| {
                 let pub_key = AccountPublicKey::from(id.public_key);
                 let producer = delegate.map(Into::into).unwrap_or(pub_key.clone());
                 producers
@@ -410,8 +470,7 @@ impl LedgerCtx {
                     .push((index, pub_key, balance));
                 producers
             },
-        );
-        Some(producers)
+        )
     }
 
     pub fn child_hashes_get(
@@ -637,6 +696,13 @@ impl LedgerCtx {
             }
         }
 
+        if let Err(error) = self.epoch_ledgers_persist(new_best_tip) {
+            openmina_core::warn!(openmina_core::log::system_time();
+                kind = "LedgerService::commit - epoch_ledgers_persist",
+                summary = "failed to persist epoch ledgers",
+                error = error.to_string());
+        }
+
         for (ledger_hash, snarked_ledger) in self.snarked_ledgers.iter_mut() {
             while let Some((parent_hash, parent)) = snarked_ledger
                 .get_parent()
@@ -688,6 +754,78 @@ impl LedgerCtx {
         }
     }
 
+    /// Persists staking and next epoch ledgers of the `best_tip`, if they
+    /// are available and weren't persisted already. Epoch ledgers that
+    /// are no longer used by the `best_tip` get removed.
+    ///
+    /// Next epoch ledger becomes available once the transition frontier
+    /// root reaches the first block of the epoch, as it is the snarked
+    /// ledger of the last block of the previous epoch.
+    ///
+    /// Only the accounts are copied here, files are written by the
+    /// epoch ledgers writer thread. A ledger counts as persisted only once
+    /// the writer reports success, failed writes are retried on the next
+    /// commit.
+    fn epoch_ledgers_persist(&mut self, best_tip: &ArcBlockWithHash) -> std::io::Result<()> {
+        let Some(writer) = self.epoch_ledgers_writer.as_ref() else {
+            return Ok(());
+        };
+        let send = |write: EpochLedgerWrite| {
+            writer.sender.send(write).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "epoch ledgers writer stopped",
+                )
+            })
+        };
+
+        for (ledger_hash, is_persisted) in writer.persisted.try_iter() {
+            if self.persisting_epoch_ledgers.remove(&ledger_hash) && is_persisted {
+                self.persisted_epoch_ledgers.insert(ledger_hash);
+            }
+        }
+
+        let epoch_ledgers = [
+            best_tip.staking_epoch_ledger_hash(),
+            best_tip.next_epoch_ledger_hash(),
+        ];
+
+        for ledger_hash in epoch_ledgers {
+            if self.persisted_epoch_ledgers.contains(ledger_hash)
+                || self.persisting_epoch_ledgers.contains(ledger_hash)
+                || ledger_hash == best_tip.genesis_ledger_hash()
+            {
+                continue;
+            }
+            let Some((mask, true)) = self.mask(ledger_hash) else {
+                continue;
+            };
+            send(EpochLedgerWrite::Persist(
+                ledger_hash.clone(),
+                mask.to_list(),
+            ))?;
+            self.persisting_epoch_ledgers.insert(ledger_hash.clone());
+        }
+
+        // Writes are done in order, so a ledger that is still being
+        // persisted gets removed right after.
+        let stale = self
+            .persisted_epoch_ledgers
+            .iter()
+            .chain(&self.persisting_epoch_ledgers)
+            .filter(|hash| !epoch_ledgers.contains(hash))
+            .cloned()
+            .collect::<Vec<_>>();
+        for ledger_hash in stale {
+            self.additional_snarked_ledgers.remove(&ledger_hash);
+            self.persisted_epoch_ledgers.remove(&ledger_hash);
+            self.persisting_epoch_ledgers.remove(&ledger_hash);
+            send(EpochLedgerWrite::Remove(ledger_hash))?;
+        }
+
+        Ok(())
+    }
+
     pub fn get_num_accounts(
         &mut self,
         ledger_hash: v2::LedgerHash,
@@ -1004,6 +1142,112 @@ impl LedgerCtx {
     }
 }
 
+/// Reads snarked ledgers stored in `path`, one file per ledger, named
+/// after the ledger hash.
+///
+/// Ledgers whose merkle root doesn't match the file name are dropped.
+fn read_snarked_ledgers_dir<P: AsRef<Path>>(path: P) -> BTreeMap<LedgerHash, Mask> {
+    use std::fs;
+
+    let Ok(dir) = fs::read_dir(path) else {
+        return Default::default();
+    };
+
+    dir.filter_map(|entry| {
+        let path = entry.ok()?.path();
+        let hash: LedgerHash = path.file_name()?.to_str()?.parse().ok()?;
+        let mut mask = read_snarked_ledger_file(&path)?;
+
+        let calculated = merkle_root(&mut mask);
+        if calculated != hash {
+            openmina_core::warn!(openmina_core::log::system_time();
+                kind = "LedgerService::read_snarked_ledgers_dir",
+                summary = format!("dropping snarked ledger {hash}, merkle root mismatch"),
+                path = path.display().to_string(),
+                calculated = calculated.to_string());
+            return None;
+        }
+        Some((hash, mask))
+    })
+    .collect()
+}
+
+fn read_snarked_ledger_file(path: &Path) -> Option<Mask> {
+    let mut file = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+
+    let _ = Option::<LedgerHash>::binprot_read(&mut file).ok()?;
+
+    let accounts = Vec::<Account>::binprot_read(&mut file).ok()?;
+    let mut mask = Mask::new_root(Database::create(35));
+    for account in accounts {
+        let account_id = account.id();
+        mask.get_or_create_account(account_id, account).ok()?;
+    }
+    Some(mask)
+}
+
+fn write_snarked_ledger_file(
+    dir: &Path,
+    ledger_hash: &LedgerHash,
+    accounts: Vec<Account>,
+) -> std::io::Result<()> {
+    use std::fs;
+
+    fs::create_dir_all(dir)?;
+    let tmp_path = dir.join(format!("{ledger_hash}.tmp"));
+    let mut file = std::io::BufWriter::new(fs::File::create(&tmp_path)?);
+    Some(ledger_hash.clone()).binprot_write(&mut file)?;
+    accounts.binprot_write(&mut file)?;
+    file.into_inner()?.sync_all()?;
+    fs::rename(tmp_path, dir.join(ledger_hash.to_string()))
+}
+
+enum EpochLedgerWrite {
+    Persist(LedgerHash, Vec<Account>),
+    Remove(LedgerHash),
+}
+
+struct EpochLedgersWriter {
+    sender: std::sync::mpsc::Sender<EpochLedgerWrite>,
+    /// Outcome of every [`EpochLedgerWrite::Persist`], `true` if the
+    /// ledger was written.
+    persisted: std::sync::mpsc::Receiver<(LedgerHash, bool)>,
+}
+
+fn epoch_ledgers_writer_spawn(dir: PathBuf) -> EpochLedgersWriter {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let (persisted_sender, persisted) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for write in receiver {
+            let (ledger_hash, result) = match write {
+                EpochLedgerWrite::Persist(ledger_hash, accounts) => {
+                    let result = write_snarked_ledger_file(&dir, &ledger_hash, accounts);
+                    let _ = persisted_sender.send((ledger_hash.clone(), result.is_ok()));
+                    (ledger_hash, result.map(|_| "persisted"))
+                }
+                EpochLedgerWrite::Remove(ledger_hash) => {
+                    let result = std::fs::remove_file(dir.join(ledger_hash.to_string()));
+                    (ledger_hash, result.map(|_| "removed"))
+                }
+            };
+            match result {
+                Ok(done) => {
+                    openmina_core::info!(openmina_core::log::system_time();
+                        kind = "LedgerService::epoch_ledgers_persist",
+                        summary = format!("{done} epoch ledger {ledger_hash}"));
+                }
+                Err(error) => {
+                    openmina_core::warn!(openmina_core::log::system_time();
+                        kind = "LedgerService::epoch_ledgers_persist",
+                        summary = format!("failed to write epoch ledger {ledger_hash}"),
+                        error = error.to_string());
+                }
+            }
+        }
+    });
+    EpochLedgersWriter { sender, persisted }
+}
+
 impl LedgerSyncState {
     fn mask(&self, hash: &LedgerHash) -> Option<(Mask, bool)> {
         self.snarked_ledgers
@@ -1170,4 +1414,36 @@ mod tests {
             assert_eq!(hash.to_string(), expected_hash);
         });
     }
+
+    #[test]
+    fn test_snarked_ledgers_dir_round_trip() {
+        let dir = std::env::temp_dir().join(format!(
+            "openmina-test-snarked-ledgers-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut mask = Mask::new_root(Database::create(35));
+        for _ in 0..10 {
+            let account = Account::rand();
+            mask.get_or_create_account(account.id(), account).unwrap();
+        }
+        let hash = merkle_root(&mut mask);
+        write_snarked_ledger_file(&dir, &hash, mask.to_list()).unwrap();
+
+        // Same accounts persisted under the name of another ledger
+        let mut other = Mask::new_root(Database::create(35));
+        let account = Account::rand();
+        other.get_or_create_account(account.id(), account).unwrap();
+        let other_hash = merkle_root(&mut other);
+        write_snarked_ledger_file(&dir, &other_hash, mask.to_list()).unwrap();
+
+        let mut ledgers = read_snarked_ledgers_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(ledgers.len(), 1);
+        let loaded = ledgers.get_mut(&hash).expect("ledger not loaded");
+        assert_eq!(merkle_root(loaded), hash);
+        assert_eq!(loaded.to_list(), mask.to_list());
+    }
 }
@@ -90,6 +90,22 @@ impl LedgerReadState {
             .iter()
             .any(|(_, pending)| pending.request() == req)
     }
+
+    /// Number of requests waiting for the response from the service.
+    pub fn pending_len(&self) -> usize {
+        self.pending
+            .iter()
+            .filter(|(_, req)| req.pending_since().is_some())
+            .count()
+    }
+
+    /// Time when the oldest of the still pending requests was sent.
+    pub fn oldest_pending_since(&self) -> Option<redux::Timestamp> {
+        self.pending
+            .iter()
+            .filter_map(|(_, req)| req.pending_since())
+            .min()
+    }
 }
 
 impl LedgerReadRequestState {
@@ -98,4 +114,12 @@ impl LedgerReadRequestState {
             Self::Pending { request, .. } | Self::Success { request, .. } => request,
         }
     }
+
+    /// Time when the request was sent to the service.
+    pub fn pending_since(&self) -> Option<redux::Timestamp> {
+        match self {
+            Self::Pending { time, .. } => Some(*time),
+            Self::Success { .. } => None,
+        }
+    }
 }
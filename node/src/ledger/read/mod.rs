@@ -6,7 +6,7 @@ pub use ledger_read_state::*;
 
 mod ledger_read_reducer;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
@@ -31,7 +31,7 @@ pub enum LedgerReadKind {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub enum LedgerReadRequest {
     /// Delegator table requested by vrf state machine.
-    DelegatorTable(v2::LedgerHash, AccountPublicKey),
+    DelegatorTable(v2::LedgerHash, BTreeSet<AccountPublicKey>),
     // p2p rpcs
     GetNumAccounts(v2::LedgerHash),
     GetChildHashesAtAddr(v2::LedgerHash, LedgerAddress),
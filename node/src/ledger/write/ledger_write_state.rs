@@ -29,6 +29,14 @@ impl LedgerWriteState {
             | Self::Success { request, .. } => Some(request),
         }
     }
+
+    /// Time when the pending request was sent to the service.
+    pub fn pending_since(&self) -> Option<redux::Timestamp> {
+        match self {
+            Self::Pending { time, .. } => Some(*time),
+            _ => None,
+        }
+    }
 }
 
 impl Default for LedgerWriteState {
@@ -1,14 +1,21 @@
+use openmina_core::block::{ArcBlockWithHash, BlockHash};
 use openmina_core::log::inner::field::{display, DisplayValue};
 use openmina_core::log::inner::Value;
-use openmina_core::log::{time_to_str, ActionEvent, EventContext};
+use openmina_core::log::{
+    block_lifecycle, time_to_str, ActionEvent, BlockLifecycleStage, EventContext,
+};
 use p2p::PeerId;
 
+use crate::consensus::ConsensusAction;
+use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
 use crate::p2p::channels::P2pChannelsAction;
 use crate::p2p::connection::P2pConnectionAction;
 use crate::p2p::network::P2pNetworkAction;
 use crate::p2p::P2pAction;
 use crate::snark::SnarkAction;
-use crate::{Action, ActionWithMetaRef, Service, Store};
+use crate::transition_frontier::sync::TransitionFrontierSyncAction;
+use crate::transition_frontier::TransitionFrontierAction;
+use crate::{Action, ActionWithMetaRef, BlockProducerAction, Service, Store};
 
 struct ActionLoggerContext {
     time: redux::Timestamp,
@@ -44,6 +51,8 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
     let (action, meta) = action.split();
     let context = ActionLoggerContext::new(meta.time(), store.state().p2p.my_id());
 
+    block_lifecycle_effects(store, action, &context);
+
     match action {
         Action::P2p(action) => match action {
             P2pAction::Initialization(action) => action.action_event(&context),
@@ -56,6 +65,7 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
             P2pAction::Identify(action) => action.action_event(&context),
             P2pAction::Channels(action) => match action {
                 P2pChannelsAction::MessageReceived(action) => action.action_event(&context),
+                P2pChannelsAction::MessageSent(action) => action.action_event(&context),
                 P2pChannelsAction::BestTip(action) => action.action_event(&context),
                 P2pChannelsAction::Transaction(action) => action.action_event(&context),
                 P2pChannelsAction::Snark(action) => action.action_event(&context),
@@ -82,3 +92,74 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
         _ => {}
     }
 }
+
+fn block_lifecycle_effects<S: Service>(
+    store: &Store<S>,
+    action: &Action,
+    context: &ActionLoggerContext,
+) {
+    let state = store.state();
+    let block_window_duration_ms = state.block_window_duration_ms();
+    let lifecycle = |block: &ArcBlockWithHash, stage| {
+        let slot_start = block.timestamp(block_window_duration_ms);
+        block_lifecycle(context, block.hash(), stage, slot_start);
+    };
+    let consensus_block = |hash: &BlockHash| {
+        let block = state.consensus.blocks.get(hash)?;
+        Some(ArcBlockWithHash {
+            hash: hash.clone(),
+            block: block.block.clone(),
+        })
+    };
+
+    match action {
+        Action::BlockProducer(BlockProducerAction::BlockProduced) => {
+            if let Some(block) = state.block_producer.produced_block() {
+                lifecycle(block, BlockLifecycleStage::Produced);
+            }
+        }
+        Action::Consensus(ConsensusAction::BlockReceived { hash, .. }) => {
+            if let Some(block) = consensus_block(hash) {
+                lifecycle(&block, BlockLifecycleStage::Received);
+            }
+        }
+        Action::Consensus(ConsensusAction::BlockSnarkVerifySuccess { hash }) => {
+            if let Some(block) = consensus_block(hash) {
+                lifecycle(&block, BlockLifecycleStage::Verified);
+            }
+        }
+        Action::TransitionFrontier(TransitionFrontierAction::Sync(
+            TransitionFrontierSyncAction::BlocksNextApplySuccess { hash },
+        )) => {
+            let sync = &state.transition_frontier.sync;
+            if let Some(block) = sync.block_state(hash).and_then(|b| b.block()) {
+                lifecycle(block, BlockLifecycleStage::Applied);
+            }
+        }
+        Action::TransitionFrontier(TransitionFrontierAction::Synced { .. }) => {
+            if let Some(best_tip) = state.transition_frontier.best_tip() {
+                lifecycle(best_tip, BlockLifecycleStage::BestTip);
+            }
+        }
+        Action::P2p(P2pAction::Channels(P2pChannelsAction::BestTip(
+            P2pChannelsBestTipAction::ResponseSend { best_tip, .. },
+        ))) => {
+            // the block is sent to each peer separately, only the first
+            // send starts the broadcast.
+            let sent_to = state
+                .p2p
+                .ready_peers_iter()
+                .filter(|(_, peer)| {
+                    peer.channels
+                        .best_tip
+                        .last_sent()
+                        .map_or(false, |block| block.hash() == best_tip.hash())
+                })
+                .count();
+            if sent_to == 1 {
+                lifecycle(best_tip, BlockLifecycleStage::Broadcast);
+            }
+        }
+        _ => {}
+    }
+}
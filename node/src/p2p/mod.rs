@@ -61,7 +61,13 @@ where
 
 impl EnablingCondition<State> for P2pInitializeAction {
     fn is_enabled(&self, state: &State, _time: redux::Timestamp) -> bool {
-        state.p2p.ready().is_none()
+        match self {
+            P2pInitializeAction::Initialize { .. } => state.p2p.ready().is_none(),
+            P2pInitializeAction::ChainIdChange { chain_id } => state
+                .p2p
+                .ready()
+                .map_or(false, |p2p| &p2p.chain_id != chain_id),
+        }
     }
 }
 
@@ -90,6 +96,7 @@ impl_into_global_action!(network::kad::P2pNetworkKademliaAction);
 impl_into_global_action!(network::pubsub::P2pNetworkPubsubAction);
 
 impl_into_global_action!(channels::P2pChannelsMessageReceivedAction);
+impl_into_global_action!(channels::P2pChannelsMessageSentAction);
 impl_into_global_action!(channels::best_tip::P2pChannelsBestTipAction);
 impl_into_global_action!(channels::transaction::P2pChannelsTransactionAction);
 impl_into_global_action!(channels::snark::P2pChannelsSnarkAction);
@@ -43,6 +43,7 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
                 store.service().start_mio();
             }
         }
+        P2pAction::Initialization(P2pInitializeAction::ChainIdChange { .. }) => {}
         P2pAction::Connection(action) => match action {
             P2pConnectionAction::Outgoing(action) => {
                 match action {
@@ -201,6 +202,7 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
             P2pChannelsAction::MessageReceived(action) => {
                 action.effects(&meta, store);
             }
+            P2pChannelsAction::MessageSent(_) => {}
             P2pChannelsAction::BestTip(action) => {
                 if let P2pChannelsBestTipAction::RequestReceived { peer_id } = action {
                     if let Some(best_tip) = store.state().transition_frontier.best_tip() {
@@ -254,15 +256,21 @@ pub fn node_p2p_effects<S: Service>(store: &mut Store<S>, action: P2pActionWithM
             P2pChannelsAction::SnarkJobCommitment(action) => {
                 // TODO: does the order matter here? if not this clone can be removed
                 action.clone().effects(&meta, store);
-                if let P2pChannelsSnarkJobCommitmentAction::Received {
-                    peer_id,
-                    commitment,
-                } = action
-                {
-                    store.dispatch(SnarkPoolAction::CommitmentAdd {
+                match action {
+                    P2pChannelsSnarkJobCommitmentAction::Received {
+                        peer_id,
+                        commitment,
+                    }
+                    | P2pChannelsSnarkJobCommitmentAction::Libp2pReceived {
+                        peer_id,
                         commitment,
-                        sender: peer_id,
-                    });
+                    } => {
+                        store.dispatch(SnarkPoolAction::CommitmentAdd {
+                            commitment,
+                            sender: peer_id,
+                        });
+                    }
+                    _ => {}
                 }
             }
             P2pChannelsAction::Rpc(action) => {
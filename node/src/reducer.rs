@@ -20,6 +20,11 @@ pub fn reducer(
                     error!(meta.time(); summary = "error initializing p2p", error = display(err));
                 }
             }
+            P2pAction::Initialization(P2pInitializeAction::ChainIdChange { chain_id }) => {
+                if let Some(p2p) = state.p2p.ready_mut() {
+                    p2p.set_chain_id(chain_id);
+                }
+            }
             action => match &mut state.p2p {
                 P2p::Pending(_) => {
                     error!(meta.time(); summary = "p2p is not initialized", action = debug(action))
@@ -1,10 +1,12 @@
 mod rpc_state;
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use mina_p2p_messages::v2::{
     MinaBaseSignedCommandPayloadBodyStableV2, MinaBaseTransactionStatusStableV2,
     MinaBaseUserCommandStableV2, MinaTransactionTransactionStableV2,
     SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponse, StateHash, TransactionHash,
+    TransactionSnarkWorkTStableV2Proofs,
 };
 use p2p::bootstrap::P2pNetworkKadBootstrapStats;
 pub use rpc_state::*;
@@ -26,20 +28,29 @@ pub use openmina_core::requests::{RpcId, RpcIdType};
 
 use ledger::scan_state::scan_state::transaction_snark::OneOrTwo;
 use ledger::scan_state::scan_state::AvailableJobMessage;
-use mina_p2p_messages::v2::{CurrencyFeeStableV1, NonZeroCurvePoint};
+use ledger::AccountIndex;
+use mina_p2p_messages::v2::{CurrencyFeeStableV1, EpochSeed, LedgerHash, NonZeroCurvePoint};
 use openmina_core::snark::SnarkJobId;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::block_producer::BlockProducerLogEntry;
+use crate::config::SnarkCoordinatorConfig;
 use crate::external_snark_worker::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkSpecError,
 };
 use crate::p2p::connection::incoming::P2pConnectionIncomingInitOpts;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
+use crate::p2p::peer::P2pPeerTraffic;
 use crate::p2p::PeerId;
+use crate::snark_pool::coordinator::RemoteSnarkWorkerId;
 use crate::snark_pool::{JobCommitment, JobSummary};
 use crate::stats::actions::{ActionStatsForBlock, ActionStatsSnapshot};
-use crate::stats::block_producer::{BlockProductionAttempt, BlockProductionAttemptWonSlot};
+use crate::stats::block_producer::{
+    BlockProducerKeyStats, BlockProducerKeyTotals, BlockProductionAttempt,
+    BlockProductionAttemptWonSlot,
+};
+use crate::stats::services::{ServiceKind, ServiceLatencyStats};
 use crate::stats::sync::SyncStatsSnapshot;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -48,7 +59,9 @@ pub enum RpcRequest {
     StatusGet,
     ActionStatsGet(ActionStatsQuery),
     SyncStatsGet(SyncStatsQuery),
-    BlockProducerStatsGet,
+    BlockProducerStatsGet(BlockProducerStatsQuery),
+    BlockProducerSlotScheduleGet,
+    MetricsGet,
     MessageProgressGet,
     PeersGet,
     P2pConnectionOutgoing(P2pConnectionOutgoingInitOpts),
@@ -56,16 +69,39 @@ pub enum RpcRequest {
     ScanStateSummaryGet(RpcScanStateSummaryGetQuery),
     SnarkPoolGet,
     SnarkPoolJobGet { job_id: SnarkJobId },
+    SnarkMarketStatsGet,
     SnarkerConfig,
     SnarkerJobCommit { job_id: SnarkJobId },
     SnarkerJobSpec { job_id: SnarkJobId },
     SnarkerWorkers,
+    SnarkCoordinatorRegister(RpcSnarkCoordinatorRegisterRequest),
+    SnarkCoordinatorLease(RpcSnarkCoordinatorWorkerRequest),
+    SnarkCoordinatorHeartbeat(RpcSnarkCoordinatorWorkerRequest),
+    SnarkCoordinatorSubmit(RpcSnarkCoordinatorSubmitRequest),
     HealthCheck,
     ReadinessCheck,
     DiscoveryRoutingTable,
     DiscoveryBoostrapStats,
 }
 
+impl RpcRequest {
+    /// Whether the snark coordinator request has a valid auth token. All
+    /// requests of remote workers are checked, not only registration,
+    /// since worker ids are sequential and easily guessed. Other requests
+    /// are always authorized.
+    pub fn is_snark_coordinator_authorized(&self, config: Option<&SnarkCoordinatorConfig>) -> bool {
+        let token = match self {
+            Self::SnarkCoordinatorRegister(req) => req.auth_token.as_deref(),
+            Self::SnarkCoordinatorLease(req) | Self::SnarkCoordinatorHeartbeat(req) => {
+                req.auth_token.as_deref()
+            }
+            Self::SnarkCoordinatorSubmit(req) => req.auth_token.as_deref(),
+            _ => return true,
+        };
+        config.map_or(true, |config| config.is_auth_token_valid(token))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ActionStatsQuery {
     SinceStart,
@@ -78,6 +114,15 @@ pub struct SyncStatsQuery {
     pub limit: Option<usize>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlockProducerStatsQuery {
+    /// Max number of production log entries returned, latest first.
+    pub log_limit: Option<usize>,
+    /// Only return production log entries for global slots (since
+    /// genesis) before this one, for requesting the next page.
+    pub log_before: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RpcScanStateSummaryGetQuery {
     ForBestTip,
@@ -109,6 +154,8 @@ pub struct RpcPeerInfo {
     pub connection_status: PeerConnectionStatus,
     pub address: Option<String>,
     pub time: u64,
+    /// Bytes and messages exchanged with the peer, by protocol.
+    pub traffic: P2pPeerTraffic,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -231,6 +278,76 @@ pub enum RpcSnarkerJobSpecResponse {
     JobNotFound,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkCoordinatorRegisterRequest {
+    pub public_key: NonZeroCurvePoint,
+    pub fee: CurrencyFeeStableV1,
+    /// Must match coordinator's `--snark-coordinator-auth-token`, if set.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+/// Request of a registered remote snark worker.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkCoordinatorWorkerRequest {
+    pub worker_id: RemoteSnarkWorkerId,
+    /// Must match coordinator's `--snark-coordinator-auth-token`, if set.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkCoordinatorSubmitRequest {
+    pub worker_id: RemoteSnarkWorkerId,
+    pub proofs: Arc<TransactionSnarkWorkTStableV2Proofs>,
+    /// Must match coordinator's `--snark-coordinator-auth-token`, if set.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum RpcSnarkCoordinatorRegisterResponse {
+    Ok { worker_id: RemoteSnarkWorkerId },
+    Disabled,
+    Unauthorized,
+    TooManyWorkers,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum RpcSnarkCoordinatorLeaseResponse {
+    Ok {
+        job_id: SnarkJobId,
+        spec: SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponse,
+    },
+    Err(SnarkWorkSpecError),
+    NoJobs,
+    UnknownWorker,
+    Unauthorized,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum RpcSnarkCoordinatorHeartbeatResponse {
+    Ok,
+    UnknownWorker,
+    /// Lease expired, or the job is no longer needed.
+    LeaseLost,
+    Unauthorized,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum RpcSnarkCoordinatorSubmitResponse {
+    /// Work is accepted for verification. It's added to the snark pool
+    /// once verified.
+    Ok,
+    UnknownWorker,
+    LeaseLost,
+    Unauthorized,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcMessageProgressResponse {
     pub messages_stats: BTreeMap<PeerId, MessagesStats>,
@@ -269,6 +386,8 @@ pub type RpcStatusGetResponse = Option<RpcNodeStatus>;
 pub type RpcActionStatsGetResponse = Option<ActionStatsResponse>;
 pub type RpcSyncStatsGetResponse = Option<Vec<SyncStatsSnapshot>>;
 pub type RpcBlockProducerStatsGetResponse = Option<RpcBlockProducerStats>;
+pub type RpcBlockProducerSlotScheduleGetResponse = Option<RpcBlockProducerSlotSchedule>;
+pub type RpcMetricsGetResponse = Option<RpcMetrics>;
 pub type RpcPeersGetResponse = Vec<RpcPeerInfo>;
 pub type RpcP2pConnectionOutgoingResponse = Result<(), String>;
 pub type RpcScanStateSummaryGetResponse = Option<RpcScanStateSummary>;
@@ -276,11 +395,110 @@ pub type RpcSnarkPoolGetResponse = Vec<RpcSnarkPoolJobSummary>;
 pub type RpcSnarkPoolJobGetResponse = Option<RpcSnarkPoolJobFull>;
 pub type RpcSnarkerConfigGetResponse = Option<RpcSnarkerConfig>;
 
+/// Node metrics in a form suitable for exporting to the monitoring systems.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcMetrics {
+    pub actions: ActionStatsSnapshot,
+    pub sync: RpcSyncMetrics,
+    pub peers: RpcPeersMetrics,
+    pub snark_pool: RpcSnarkPoolMetrics,
+    /// Stats of the recent block production attempts.
+    pub block_producers: Vec<BlockProducerKeyStats>,
+    pub block_producer_totals: Vec<BlockProducerKeyTotals>,
+    pub services: BTreeMap<ServiceKind, ServiceLatencyStats>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSyncMetrics {
+    /// Current phase of the transition frontier sync.
+    pub phase: String,
+    pub best_tip_height: Option<u32>,
+    /// Stats of the latest sync.
+    pub latest: Option<SyncStatsSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcPeersMetrics {
+    pub connecting: usize,
+    pub connected: usize,
+    pub disconnected: usize,
+    /// Traffic added up over all peers.
+    pub traffic: P2pPeerTraffic,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcSnarkPoolMetrics {
+    pub jobs: usize,
+    pub commitments: usize,
+    pub snarks: usize,
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct RpcNodeStatus {
     pub transition_frontier: RpcNodeStatusTransitionFrontier,
     pub peers: Vec<RpcPeerInfo>,
     pub snark_pool: RpcNodeStatusSnarkPool,
+    pub health: RpcNodeHealth,
+}
+
+/// Result of the node health checks.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcNodeHealth {
+    /// Worst status among the checks.
+    pub status: RpcHealthStatus,
+    pub checks: Vec<RpcHealthCheck>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcHealthCheck {
+    pub name: String,
+    pub status: RpcHealthStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RpcHealthStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl RpcNodeHealth {
+    pub fn new(checks: Vec<RpcHealthCheck>) -> Self {
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(RpcHealthStatus::Ok);
+        Self { status, checks }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status == RpcHealthStatus::Fail
+    }
+}
+
+impl RpcHealthCheck {
+    pub fn ok(name: &str) -> Self {
+        Self::new(name, RpcHealthStatus::Ok, None)
+    }
+
+    pub fn warn(name: &str, reason: String) -> Self {
+        Self::new(name, RpcHealthStatus::Warn, Some(reason))
+    }
+
+    pub fn fail(name: &str, reason: String) -> Self {
+        Self::new(name, RpcHealthStatus::Fail, Some(reason))
+    }
+
+    fn new(name: &str, status: RpcHealthStatus, reason: Option<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            status,
+            reason,
+        }
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -317,6 +535,58 @@ pub struct RpcBlockProducerStats {
     pub epoch_end: Option<u32>,
     pub attempts: Vec<BlockProductionAttempt>,
     pub future_won_slots: Vec<BlockProductionAttemptWonSlot>,
+    /// Stats for each of the producer keys.
+    pub producers: Vec<BlockProducerKeyStats>,
+    /// Page of the persisted log of the slots for which we started block
+    /// production, latest first (see [BlockProducerStatsQuery]).
+    pub production_log: Vec<BlockProducerLogEntry>,
+}
+
+/// Won slot schedule of our producers for the current and the next epoch.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerSlotSchedule {
+    pub current_time: redux::Timestamp,
+    pub current_global_slot: Option<u32>,
+    pub latest_evaluated_slot: u32,
+    pub epochs: Vec<RpcBlockProducerEpochSchedule>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerEpochSchedule {
+    pub epoch: u32,
+    pub start_slot: u32,
+    pub end_slot: u32,
+    pub seed: EpochSeed,
+    pub staking_ledger_hash: LedgerHash,
+    /// Whether vrf evaluation is finished for the whole epoch.
+    pub is_evaluated: bool,
+    pub won_slots: Vec<RpcBlockProducerScheduledSlot>,
+    /// Delegators of our producers in the staking ledger of the epoch.
+    /// Empty if the delegator table isn't constructed yet.
+    pub delegators: Vec<RpcBlockProducerDelegator>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerScheduledSlot {
+    pub slot_time: redux::Timestamp,
+    pub global_slot: u32,
+    pub producer: NonZeroCurvePoint,
+    pub delegator: NonZeroCurvePoint,
+    pub delegator_index: AccountIndex,
+    pub delegator_stake: Option<u64>,
+    /// Base58 encoded vrf output.
+    pub vrf_output: String,
+    pub value_with_threshold: Option<(f64, f64)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerDelegator {
+    pub producer: NonZeroCurvePoint,
+    pub delegator: NonZeroCurvePoint,
+    pub index: AccountIndex,
+    pub stake: u64,
+    /// Number of slots won by this delegator in the epoch.
+    pub won_slots: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -364,6 +634,81 @@ pub enum RpcSnarkWorkerStatus {
 
 pub type RpcSnarkerWorkersResponse = Vec<RpcSnarkWorker>;
 
+/// Aggregate view of the snark work market, derived from the snark pool
+/// and the completed works included in the best chain.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkMarketStats {
+    /// Number of the best chain blocks, included works are taken from.
+    pub blocks: usize,
+    /// Fees of the included works, in nanomina.
+    pub fees: Option<RpcSnarkMarketDistribution>,
+    /// Snarkers whose work got included, most bought first.
+    pub snarkers: Vec<RpcSnarkMarketSnarker>,
+    /// Time in nanoseconds, that the latest jobs waited for their first snark.
+    pub wait_until_proven: Option<RpcSnarkMarketDistribution>,
+    /// Time in nanoseconds, that the latest jobs waited for their work
+    /// to be included in the best chain.
+    pub wait_until_included: Option<RpcSnarkMarketDistribution>,
+    /// Jobs in the pool, which don't have a snark yet.
+    pub backlog: RpcSnarkMarketBacklog,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkMarketDistribution {
+    pub count: usize,
+    pub min: u64,
+    pub mean: u64,
+    pub median: u64,
+    pub p90: u64,
+    pub max: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcSnarkMarketSnarker {
+    pub snarker: NonZeroCurvePoint,
+    pub works: usize,
+    /// Sum of the fees in nanomina.
+    pub fees: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcSnarkMarketBacklog {
+    pub tx: RpcSnarkMarketBacklogKind,
+    pub merge: RpcSnarkMarketBacklogKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcSnarkMarketBacklogKind {
+    pub jobs: usize,
+    /// Jobs which some snarker committed to.
+    pub committed: usize,
+    /// Sum of the account updates of the jobs, as in [`JobSummary`].
+    pub account_updates: usize,
+}
+
+impl RpcSnarkMarketDistribution {
+    /// Returns `None` if there are no values.
+    pub fn new(mut values: Vec<u64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let count = values.len();
+        let sum = values.iter().map(|v| *v as u128).sum::<u128>();
+        let percentile = |p: usize| values[(count - 1) * p / 100];
+        Some(Self {
+            count,
+            min: values[0],
+            mean: (sum / count as u128) as u64,
+            median: percentile(50),
+            p90: percentile(90),
+            max: values[count - 1],
+        })
+    }
+}
+
+pub type RpcSnarkMarketStatsGetResponse = RpcSnarkMarketStats;
+
 impl From<&MinaTransactionTransactionStableV2> for RpcScanStateSummaryBlockTransactionKind {
     fn from(value: &MinaTransactionTransactionStableV2) -> Self {
         match value {
@@ -388,7 +733,7 @@ impl From<&MinaBaseUserCommandStableV2> for RpcScanStateSummaryBlockTransactionK
     }
 }
 
-pub type RpcHealthCheckResponse = Result<(), String>;
+pub type RpcHealthCheckResponse = RpcNodeHealth;
 pub type RpcReadinessCheckResponse = Result<(), String>;
 
 pub type RpcDiscoveryRoutingTableResponse = Option<discovery::RpcDiscoveryRoutingTable>;
@@ -474,3 +819,76 @@ pub mod discovery {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mina_p2p_messages::binprot::BinProtRead;
+    use mina_p2p_messages::gossip::GossipNetMessageV2;
+    use mina_p2p_messages::v2::NetworkPoolSnarkPoolDiffVersionedStableV2;
+    use openmina_core::snark::Snark;
+
+    use super::{
+        RpcRequest, RpcSnarkCoordinatorSubmitRequest, RpcSnarkCoordinatorWorkerRequest,
+        SnarkCoordinatorConfig,
+    };
+
+    fn config(auth_token: Option<&str>) -> SnarkCoordinatorConfig {
+        SnarkCoordinatorConfig {
+            lease_timeout: Duration::from_secs(30),
+            worker_timeout: Duration::from_secs(300),
+            max_workers: 64,
+            auth_token: auth_token.map(ToOwned::to_owned),
+        }
+    }
+
+    fn lease(auth_token: Option<&str>) -> RpcRequest {
+        RpcRequest::SnarkCoordinatorLease(RpcSnarkCoordinatorWorkerRequest {
+            worker_id: 0,
+            auth_token: auth_token.map(ToOwned::to_owned),
+        })
+    }
+
+    fn submit(auth_token: Option<&str>) -> RpcRequest {
+        const BYTES: &[u8] =
+            include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/snark_pool_diff.bin");
+        let message = GossipNetMessageV2::binprot_read(&mut &BYTES[..]).unwrap();
+        let snark: Snark =
+            match NetworkPoolSnarkPoolDiffVersionedStableV2::try_from(message).unwrap() {
+                NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work) => work.1.into(),
+                NetworkPoolSnarkPoolDiffVersionedStableV2::Empty => panic!("no work"),
+            };
+        RpcRequest::SnarkCoordinatorSubmit(RpcSnarkCoordinatorSubmitRequest {
+            worker_id: 0,
+            proofs: snark.proofs,
+            auth_token: auth_token.map(ToOwned::to_owned),
+        })
+    }
+
+    #[test]
+    fn snark_coordinator_auth_token_checked_on_every_request() {
+        let with_token = config(Some("secret"));
+        let with_token = Some(&with_token);
+
+        let unauthenticated = [
+            lease(None),
+            lease(Some("guess")),
+            submit(None),
+            submit(Some("guess")),
+        ];
+        for (i, req) in unauthenticated.iter().enumerate() {
+            assert!(
+                !req.is_snark_coordinator_authorized(with_token),
+                "request {i}"
+            );
+        }
+        assert!(lease(Some("secret")).is_snark_coordinator_authorized(with_token));
+        assert!(submit(Some("secret")).is_snark_coordinator_authorized(with_token));
+
+        // Without a configured token, or for other requests, nothing is checked.
+        assert!(lease(None).is_snark_coordinator_authorized(Some(&config(None))));
+        assert!(lease(None).is_snark_coordinator_authorized(None));
+        assert!(RpcRequest::HealthCheck.is_snark_coordinator_authorized(with_token));
+    }
+}
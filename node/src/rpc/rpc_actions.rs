@@ -1,3 +1,8 @@
+use std::sync::Arc;
+
+use mina_p2p_messages::v2::{
+    CurrencyFeeStableV1, NonZeroCurvePoint, TransactionSnarkWorkTStableV2Proofs,
+};
 use openmina_core::block::ArcBlockWithHash;
 use openmina_core::snark::SnarkJobId;
 use serde::{Deserialize, Serialize};
@@ -6,10 +11,11 @@ use crate::external_snark_worker::SnarkWorkId;
 use crate::p2p::connection::incoming::P2pConnectionIncomingInitOpts;
 use crate::p2p::connection::outgoing::{P2pConnectionOutgoingError, P2pConnectionOutgoingInitOpts};
 use crate::p2p::connection::P2pConnectionResponse;
+use crate::snark_pool::coordinator::RemoteSnarkWorkerId;
 
 use super::{
-    ActionStatsQuery, RpcId, RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob,
-    SyncStatsQuery,
+    ActionStatsQuery, BlockProducerStatsQuery, RpcId, RpcScanStateSummaryGetQuery,
+    RpcScanStateSummaryScanStateJob, SyncStatsQuery,
 };
 
 pub type RpcActionWithMeta = redux::ActionWithMeta<RpcAction>;
@@ -36,6 +42,13 @@ pub enum RpcAction {
     },
     BlockProducerStatsGet {
         rpc_id: RpcId,
+        query: BlockProducerStatsQuery,
+    },
+    BlockProducerSlotScheduleGet {
+        rpc_id: RpcId,
+    },
+    MetricsGet {
+        rpc_id: RpcId,
     },
 
     MessageProgressGet {
@@ -103,6 +116,9 @@ pub enum RpcAction {
         job_id: SnarkWorkId,
         rpc_id: RpcId,
     },
+    SnarkMarketStatsGet {
+        rpc_id: RpcId,
+    },
 
     SnarkerConfigGet {
         rpc_id: RpcId,
@@ -120,6 +136,25 @@ pub enum RpcAction {
         rpc_id: RpcId,
     },
 
+    SnarkCoordinatorRegister {
+        rpc_id: RpcId,
+        public_key: NonZeroCurvePoint,
+        fee: CurrencyFeeStableV1,
+    },
+    SnarkCoordinatorLease {
+        rpc_id: RpcId,
+        worker_id: RemoteSnarkWorkerId,
+    },
+    SnarkCoordinatorHeartbeat {
+        rpc_id: RpcId,
+        worker_id: RemoteSnarkWorkerId,
+    },
+    SnarkCoordinatorSubmit {
+        rpc_id: RpcId,
+        worker_id: RemoteSnarkWorkerId,
+        proofs: Arc<TransactionSnarkWorkTStableV2Proofs>,
+    },
+
     HealthCheck {
         rpc_id: RpcId,
     },
@@ -147,6 +182,8 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::ActionStatsGet { .. } => true,
             RpcAction::SyncStatsGet { .. } => true,
             RpcAction::BlockProducerStatsGet { .. } => true,
+            RpcAction::BlockProducerSlotScheduleGet { .. } => true,
+            RpcAction::MetricsGet { .. } => true,
             RpcAction::MessageProgressGet { .. } => true,
             RpcAction::PeersGet { .. } => true,
             RpcAction::P2pConnectionOutgoingInit { rpc_id, .. } => {
@@ -208,10 +245,15 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
                 .map_or(false, |v| v.status.is_pending()),
             RpcAction::SnarkPoolAvailableJobsGet { .. } => true,
             RpcAction::SnarkPoolJobGet { .. } => true,
+            RpcAction::SnarkMarketStatsGet { .. } => true,
             RpcAction::SnarkerConfigGet { .. } => true,
             RpcAction::SnarkerJobCommit { .. } => true,
             RpcAction::SnarkerJobSpec { .. } => true,
             RpcAction::SnarkerWorkersGet { .. } => true,
+            RpcAction::SnarkCoordinatorRegister { .. } => true,
+            RpcAction::SnarkCoordinatorLease { .. } => true,
+            RpcAction::SnarkCoordinatorHeartbeat { .. } => true,
+            RpcAction::SnarkCoordinatorSubmit { .. } => true,
             RpcAction::HealthCheck { .. } => true,
             RpcAction::ReadinessCheck { .. } => true,
             RpcAction::DiscoveryRoutingTable { .. } => true,
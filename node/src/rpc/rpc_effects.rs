@@ -1,32 +1,54 @@
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use mina_p2p_messages::rpc_kernel::QueryHeader;
-use mina_p2p_messages::v2::MinaBaseTransactionStatusStableV2;
+use mina_p2p_messages::v2::{
+    EpochSeed, LedgerHash, MinaBaseTransactionStatusStableV2, NonZeroCurvePoint,
+};
 use openmina_core::block::ArcBlockWithHash;
+use openmina_core::snark::Snark;
 
-use crate::block_producer::BlockProducerWonSlot;
+use crate::account::AccountPublicKey;
+use crate::block_producer::vrf_evaluator::BlockProducerVrfEvaluatorState;
+use crate::block_producer::{
+    to_epoch_and_slot, BlockProducerWonSlot, BlockProducerWonSlotDiscardReason,
+};
 use crate::external_snark_worker::available_job_to_snark_worker_spec;
 use crate::ledger::read::{LedgerReadAction, LedgerReadRequest};
 use crate::p2p::connection::incoming::P2pConnectionIncomingAction;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingAction;
 use crate::p2p::connection::P2pConnectionResponse;
 use crate::rpc::{PeerConnectionStatus, RpcPeerInfo};
-use crate::snark_pool::SnarkPoolAction;
+use crate::snark_pool::coordinator::{
+    is_lease_lost, RemoteSnarkWorkerId, SnarkPoolCoordinatorAction,
+};
+use crate::snark_pool::{JobSummary, SnarkPoolAction};
+use crate::stats::block_producer::{BlockProductionAttempt, BlockProductionStatus};
 use crate::transition_frontier::sync::ledger::TransitionFrontierSyncLedgerState;
 use crate::transition_frontier::sync::TransitionFrontierSyncState;
 use crate::{p2p_ready, Service, Store};
 
 use super::{
     ActionStatsQuery, ActionStatsResponse, CurrentMessageProgress, MessagesStats, RpcAction,
-    RpcActionWithMeta, RpcBlockProducerStats, RpcMessageProgressResponse, RpcNodeStatus,
+    RpcActionWithMeta, RpcBlockProducerDelegator, RpcBlockProducerEpochSchedule,
+    RpcBlockProducerScheduledSlot, RpcBlockProducerSlotSchedule, RpcBlockProducerStats,
+    RpcHealthCheck, RpcMessageProgressResponse, RpcMetrics, RpcNodeHealth, RpcNodeStatus,
     RpcNodeStatusTransitionFrontier, RpcNodeStatusTransitionFrontierBlockSummary,
-    RpcNodeStatusTransitionFrontierSync, RpcRequest, RpcRequestExtraData, RpcScanStateSummary,
-    RpcScanStateSummaryBlock, RpcScanStateSummaryBlockTransaction,
+    RpcNodeStatusTransitionFrontierSync, RpcPeersMetrics, RpcRequest, RpcRequestExtraData,
+    RpcScanStateSummary, RpcScanStateSummaryBlock, RpcScanStateSummaryBlockTransaction,
     RpcScanStateSummaryBlockTransactionKind, RpcScanStateSummaryGetQuery,
-    RpcScanStateSummaryScanStateJob, RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork,
-    RpcSnarkPoolJobSummary, RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse,
+    RpcScanStateSummaryScanStateJob, RpcSnarkCoordinatorHeartbeatResponse,
+    RpcSnarkCoordinatorLeaseResponse, RpcSnarkCoordinatorRegisterResponse,
+    RpcSnarkCoordinatorSubmitResponse, RpcSnarkMarketBacklog, RpcSnarkMarketDistribution,
+    RpcSnarkMarketSnarker, RpcSnarkMarketStats, RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork,
+    RpcSnarkPoolJobSummary, RpcSnarkPoolMetrics, RpcSnarkerJobCommitResponse,
+    RpcSnarkerJobSpecResponse, RpcSyncMetrics,
 };
 
+/// Production log entries returned by the block producer stats RPC if
+/// the query doesn't set the limit.
+const PRODUCTION_LOG_PAGE_LIMIT: usize = 100;
+
 macro_rules! respond_or_log {
     ($e:expr, $t:expr) => {
         if let Err(err) = $e {
@@ -46,6 +68,10 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
         }
         RpcAction::StatusGet { rpc_id } => {
             let state = store.state.get();
+            let attempts = store
+                .service
+                .stats()
+                .map(|stats| stats.block_producer().collect_attempts());
 
             let block_summary =
                 |b: &ArcBlockWithHash| RpcNodeStatusTransitionFrontierBlockSummary {
@@ -71,6 +97,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                         acc
                     },
                 ),
+                health: collect_node_health(state, attempts.as_deref()),
             };
             let _ = store.service.respond_status_get(rpc_id, Some(status));
         }
@@ -107,14 +134,16 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                 .map(|s| s.collect_sync_stats(query.limit));
             let _ = store.service.respond_sync_stats_get(rpc_id, resp);
         }
-        RpcAction::BlockProducerStatsGet { rpc_id } => {
+        RpcAction::BlockProducerStatsGet { rpc_id, query } => {
             let resp = None.or_else(|| {
                 let state = store.state.get();
                 let best_tip = state.transition_frontier.best_tip()?;
                 let won_slots = &state.block_producer.vrf_evaluator()?.won_slots;
+                let production_log = state.block_producer.production_log()?;
 
                 let stats = store.service.stats()?;
                 let attempts = stats.block_producer().collect_attempts();
+                let producers = stats.block_producer().collect_producer_stats();
                 let future_slot = attempts.last().map_or(0, |v| v.won_slot.global_slot + 1);
 
                 let cur_global_slot = state.cur_global_slot();
@@ -134,14 +163,74 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
                             let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
                                 won_slot,
                                 best_tip.genesis_timestamp(),
+                                slots_per_epoch,
+                                state.block_window_duration_ms(),
                             );
                             (&won_slot).into()
                         })
                         .collect(),
+                    producers,
+                    production_log: production_log
+                        .page(
+                            query.log_before,
+                            query.log_limit.unwrap_or(PRODUCTION_LOG_PAGE_LIMIT),
+                        )
+                        .cloned()
+                        .collect(),
                 })
             });
             let _ = store.service.respond_block_producer_stats_get(rpc_id, resp);
         }
+        RpcAction::BlockProducerSlotScheduleGet { rpc_id } => {
+            let resp = collect_block_producer_slot_schedule(store.state(), meta.time());
+            let _ = store
+                .service
+                .respond_block_producer_slot_schedule_get(rpc_id, resp);
+        }
+        RpcAction::MetricsGet { rpc_id } => {
+            let resp = None.or_else(|| {
+                let state = store.state.get();
+                let sync_phase = state.transition_frontier.sync.to_string();
+                let best_tip_height = state.transition_frontier.best_tip().map(|b| b.height());
+                let peers = collect_rpc_peers_info(state).into_iter().fold(
+                    RpcPeersMetrics::default(),
+                    |mut acc, peer| {
+                        match peer.connection_status {
+                            PeerConnectionStatus::Connecting => acc.connecting += 1,
+                            PeerConnectionStatus::Connected => acc.connected += 1,
+                            PeerConnectionStatus::Disconnected => acc.disconnected += 1,
+                        }
+                        acc.traffic.add(&peer.traffic);
+                        acc
+                    },
+                );
+                let snark_pool = state.snark_pool.jobs_iter().fold(
+                    RpcSnarkPoolMetrics::default(),
+                    |mut acc, job| {
+                        acc.jobs += 1;
+                        acc.commitments += job.commitment.is_some() as usize;
+                        acc.snarks += job.snark.is_some() as usize;
+                        acc
+                    },
+                );
+
+                let stats = store.service.stats()?;
+                Some(RpcMetrics {
+                    actions: stats.collect_action_stats_since_start(),
+                    sync: RpcSyncMetrics {
+                        phase: sync_phase,
+                        best_tip_height,
+                        latest: stats.collect_sync_stats(Some(1)).pop(),
+                    },
+                    peers,
+                    snark_pool,
+                    block_producers: stats.block_producer().collect_producer_stats(),
+                    block_producer_totals: stats.block_producer().collect_producer_totals(),
+                    services: stats.collect_service_stats(),
+                })
+            });
+            let _ = store.service.respond_metrics_get(rpc_id, resp);
+        }
         RpcAction::MessageProgressGet { rpc_id } => {
             // TODO: move to stats
             let p2p = p2p_ready!(store.state().p2p, meta.time());
@@ -460,6 +549,10 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
             });
             let _ = store.service().respond_snark_pool_job_get(rpc_id, resp);
         }
+        RpcAction::SnarkMarketStatsGet { rpc_id } => {
+            let resp = collect_snark_market_stats(store.state());
+            let _ = store.service().respond_snark_market_stats_get(rpc_id, resp);
+        }
         RpcAction::SnarkerConfigGet { rpc_id } => {
             let config =
                 store
@@ -535,27 +628,122 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
             let _ = store.service().respond_snarker_job_spec(rpc_id, input);
         }
         RpcAction::SnarkerWorkersGet { rpc_id } => {
-            let the_only = store.state().external_snark_worker.0.clone();
+            let workers = store
+                .state()
+                .external_snark_worker
+                .iter()
+                .map(|(id, worker)| (id, worker.clone()).into())
+                .collect();
 
             // TODO: handle potential errors
-            let _ = store
-                .service()
-                .respond_snarker_workers(rpc_id, vec![the_only.into()]);
+            let _ = store.service().respond_snarker_workers(rpc_id, workers);
         }
-        RpcAction::HealthCheck { rpc_id } => {
-            let some_peers = store
+        RpcAction::SnarkCoordinatorRegister {
+            rpc_id,
+            public_key,
+            fee,
+        } => {
+            let worker_id = store.state().snark_pool.coordinator.next_worker_id();
+            let response = if store.dispatch(SnarkPoolCoordinatorAction::WorkerRegister {
+                worker_id,
+                public_key,
+                fee,
+            }) {
+                RpcSnarkCoordinatorRegisterResponse::Ok { worker_id }
+            } else if store.state().config.snark_coordinator.is_none() {
+                RpcSnarkCoordinatorRegisterResponse::Disabled
+            } else {
+                RpcSnarkCoordinatorRegisterResponse::TooManyWorkers
+            };
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_snark_coordinator_register(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::SnarkCoordinatorLease { rpc_id, worker_id } => {
+            // Polling for jobs keeps an idle worker registered.
+            store.dispatch(SnarkPoolCoordinatorAction::WorkerHeartbeat { worker_id });
+            let state = store.state();
+            let next_job_id = state
+                .snark_pool
+                .coordinator
+                .get(worker_id)
+                .filter(|worker| worker.lease.is_none())
+                .and_then(|_| {
+                    let jobs = state.snark_pool.available_jobs_with_highest_priority(1);
+                    jobs.into_iter().next().map(|job| job.id.clone())
+                });
+            if let Some(job_id) = next_job_id {
+                store.dispatch(SnarkPoolCoordinatorAction::JobLease { worker_id, job_id });
+            }
+            let response = snark_coordinator_lease_response(store.state(), worker_id);
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_snark_coordinator_lease(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::SnarkCoordinatorHeartbeat { rpc_id, worker_id } => {
+            let state = store.state();
+            let response = match state.snark_pool.coordinator.get(worker_id) {
+                None => RpcSnarkCoordinatorHeartbeatResponse::UnknownWorker,
+                Some(worker) if !worker.is_proving() || is_lease_lost(state, worker) => {
+                    RpcSnarkCoordinatorHeartbeatResponse::LeaseLost
+                }
+                Some(_) => RpcSnarkCoordinatorHeartbeatResponse::Ok,
+            };
+            store.dispatch(SnarkPoolCoordinatorAction::WorkerHeartbeat { worker_id });
+            respond_or_log!(
+                store
+                    .service()
+                    .respond_snark_coordinator_heartbeat(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::SnarkCoordinatorSubmit {
+            rpc_id,
+            worker_id,
+            proofs,
+        } => {
+            let snark = store
                 .state()
-                .p2p
-                .ready_peers_iter()
-                .map(|(peer_id, _peer)| {
-                    openmina_core::log::debug!(openmina_core::log::system_time(); "found ready peer: {peer_id}")
-                })
-                .next()
-                .ok_or_else(|| {
-                    openmina_core::log::warn!(openmina_core::log::system_time(); "no ready peers");
-                    String::from("no ready peers") });
+                .snark_pool
+                .coordinator
+                .get(worker_id)
+                .map(|worker| Snark {
+                    snarker: worker.public_key.clone(),
+                    fee: worker.fee.clone(),
+                    proofs,
+                });
+            let response = match snark {
+                None => RpcSnarkCoordinatorSubmitResponse::UnknownWorker,
+                Some(snark) => {
+                    if store.dispatch(SnarkPoolCoordinatorAction::WorkSubmit { worker_id, snark }) {
+                        RpcSnarkCoordinatorSubmitResponse::Ok
+                    } else {
+                        RpcSnarkCoordinatorSubmitResponse::LeaseLost
+                    }
+                }
+            };
             respond_or_log!(
-                store.service().respond_health_check(rpc_id, some_peers),
+                store
+                    .service()
+                    .respond_snark_coordinator_submit(rpc_id, response),
+                meta.time()
+            );
+        }
+        RpcAction::HealthCheck { rpc_id } => {
+            let state = store.state.get();
+            let attempts = store
+                .service
+                .stats()
+                .map(|stats| stats.block_producer().collect_attempts());
+            let health = collect_node_health(state, attempts.as_deref());
+            respond_or_log!(
+                store.service().respond_health_check(rpc_id, health),
                 meta.time()
             );
         }
@@ -634,6 +822,7 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: RpcActionWithMeta)
 }
 
 fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
+    let block_window_duration_ms = state.block_window_duration_ms();
     state.p2p.ready().map_or_else(Vec::new, |p2p| {
         p2p.peers
             .iter()
@@ -662,10 +851,624 @@ fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
                     best_tip: best_tip.map(|bt| bt.hash.clone()),
                     best_tip_height: best_tip.map(|bt| bt.height()),
                     best_tip_global_slot: best_tip.map(|bt| bt.global_slot_since_genesis()),
-                    best_tip_timestamp: best_tip.map(|bt| bt.timestamp().into()),
+                    best_tip_timestamp: best_tip
+                        .map(|bt| bt.timestamp(block_window_duration_ms).into()),
                     time,
+                    traffic: state.traffic.clone(),
                 }
             })
             .collect()
     })
 }
+
+fn collect_snark_market_stats(state: &crate::State) -> RpcSnarkMarketStats {
+    let best_chain = &state.transition_frontier.best_chain;
+    let mut fees = Vec::new();
+    let mut snarkers = BTreeMap::<_, RpcSnarkMarketSnarker>::new();
+    for work in best_chain
+        .iter()
+        .flat_map(|block| block.completed_works_iter())
+    {
+        let fee = work.fee.0.as_u64();
+        fees.push(fee);
+        let snarker =
+            snarkers
+                .entry(work.prover.clone())
+                .or_insert_with(|| RpcSnarkMarketSnarker {
+                    snarker: work.prover.clone(),
+                    works: 0,
+                    fees: 0,
+                });
+        snarker.works += 1;
+        snarker.fees = snarker.fees.saturating_add(fee);
+    }
+    let mut snarkers = snarkers.into_values().collect::<Vec<_>>();
+    snarkers.sort_by(|a, b| b.works.cmp(&a.works).then(b.fees.cmp(&a.fees)));
+
+    let backlog = state
+        .snark_pool
+        .jobs_iter()
+        .filter(|job| job.snark_fee_and_prover().is_none())
+        .fold(RpcSnarkMarketBacklog::default(), |mut acc, job| {
+            let (kind, account_updates) = match job.summary() {
+                JobSummary::Tx(n) => (&mut acc.tx, n),
+                JobSummary::Merge(n) => (&mut acc.merge, n),
+            };
+            kind.jobs += 1;
+            kind.committed += job.commitment.is_some() as usize;
+            kind.account_updates += account_updates;
+            acc
+        });
+
+    let wait_times = state.snark_pool.wait_times();
+    RpcSnarkMarketStats {
+        blocks: best_chain.len(),
+        fees: RpcSnarkMarketDistribution::new(fees),
+        snarkers,
+        wait_until_proven: RpcSnarkMarketDistribution::new(
+            wait_times.until_proven.iter().copied().collect(),
+        ),
+        wait_until_included: RpcSnarkMarketDistribution::new(
+            wait_times.until_included.iter().copied().collect(),
+        ),
+        backlog,
+    }
+}
+
+fn collect_node_health(
+    state: &crate::State,
+    attempts: Option<&[BlockProductionAttempt]>,
+) -> RpcNodeHealth {
+    RpcNodeHealth::new(vec![
+        best_tip_age_health(state),
+        peers_health(state),
+        sync_health(state),
+        block_production_health(state, attempts),
+        snark_worker_health(state),
+        ledger_service_health(state),
+    ])
+}
+
+fn best_tip_age_health(state: &crate::State) -> RpcHealthCheck {
+    const NAME: &str = "best_tip_age";
+    let config = &state.config.health;
+    if !state.transition_frontier.sync.is_synced() {
+        return RpcHealthCheck::warn(NAME, "not synced".to_owned());
+    }
+    let (Some(best_tip), Some(cur_slot)) = (
+        state.transition_frontier.best_tip(),
+        state.cur_global_slot(),
+    ) else {
+        return RpcHealthCheck::warn(NAME, "no best tip".to_owned());
+    };
+    let age = cur_slot.saturating_sub(best_tip.global_slot());
+    let reason = || format!("best tip is {age} slots behind the current slot");
+    if age >= config.best_tip_age_fail_slots {
+        RpcHealthCheck::fail(NAME, reason())
+    } else if age >= config.best_tip_age_warn_slots {
+        RpcHealthCheck::warn(NAME, reason())
+    } else {
+        RpcHealthCheck::ok(NAME)
+    }
+}
+
+fn peers_health(state: &crate::State) -> RpcHealthCheck {
+    const NAME: &str = "peers";
+    let min_peers = state.config.health.min_peers;
+    match state.p2p.ready_peers_iter().count() {
+        0 => RpcHealthCheck::fail(NAME, "no ready peers".to_owned()),
+        n if n < min_peers => RpcHealthCheck::warn(
+            NAME,
+            format!("{n} ready peers, less than the threshold {min_peers}"),
+        ),
+        _ => RpcHealthCheck::ok(NAME),
+    }
+}
+
+fn sync_health(state: &crate::State) -> RpcHealthCheck {
+    const NAME: &str = "sync";
+    let sync = &state.transition_frontier.sync;
+    let timeout = state.config.health.sync_stuck_timeout;
+    match sync.time().and_then(|time| state.time().checked_sub(time)) {
+        Some(duration) if !sync.is_synced() && duration > timeout => RpcHealthCheck::fail(
+            NAME,
+            format!("sync is stuck in {sync} phase for {duration:?}"),
+        ),
+        _ => RpcHealthCheck::ok(NAME),
+    }
+}
+
+fn block_production_health(
+    state: &crate::State,
+    attempts: Option<&[BlockProductionAttempt]>,
+) -> RpcHealthCheck {
+    let (Some(_), Some(attempts)) = (state.block_producer.config(), attempts) else {
+        return RpcHealthCheck::ok(BLOCK_PRODUCTION_HEALTH);
+    };
+    let slot_duration = Duration::from_millis(state.block_window_duration_ms());
+    block_production_attempts_health(
+        attempts,
+        state.time(),
+        state.config.health.missed_slot_window,
+        slot_duration,
+    )
+}
+
+const BLOCK_PRODUCTION_HEALTH: &str = "block_production";
+
+/// Won slots which weren't produced, or whose block proof failed, within
+/// `window` are a failure. Won slots discarded because of a better best tip
+/// are expected to happen, so they are only a warning, and dry-run discards
+/// are ignored.
+fn block_production_attempts_health(
+    attempts: &[BlockProductionAttempt],
+    now: redux::Timestamp,
+    window: Duration,
+    slot_duration: Duration,
+) -> RpcHealthCheck {
+    const NAME: &str = BLOCK_PRODUCTION_HEALTH;
+    let mut missed = Vec::new();
+    let mut discarded = Vec::new();
+    for attempt in attempts {
+        let Some(since_slot) = now.checked_sub(attempt.won_slot.slot_time) else {
+            continue;
+        };
+        if since_slot > window {
+            continue;
+        }
+        let global_slot = attempt.won_slot.global_slot;
+        match &attempt.status {
+            BlockProductionStatus::Discarded { discard_reason } => match discard_reason {
+                BlockProducerWonSlotDiscardReason::DryRun => {}
+                BlockProducerWonSlotDiscardReason::BestTipStakingLedgerDifferent
+                | BlockProducerWonSlotDiscardReason::BestTipGlobalSlotHigher
+                | BlockProducerWonSlotDiscardReason::BestTipSuperior => discarded.push(global_slot),
+                BlockProducerWonSlotDiscardReason::BlockProofFailed { .. } => {
+                    missed.push(global_slot)
+                }
+            },
+            BlockProductionStatus::Scheduled
+            | BlockProductionStatus::StagedLedgerDiffCreatePending
+            | BlockProductionStatus::StagedLedgerDiffCreateSuccess
+                if since_slot > slot_duration =>
+            {
+                missed.push(global_slot)
+            }
+            _ => {}
+        }
+    }
+    if let Some(last) = missed.last() {
+        RpcHealthCheck::fail(
+            NAME,
+            format!(
+                "missed {} won slots in the last {window:?}, latest at global slot {last}",
+                missed.len()
+            ),
+        )
+    } else if let Some(last) = discarded.last() {
+        RpcHealthCheck::warn(
+            NAME,
+            format!(
+                "discarded {} won slots for a better best tip in the last {window:?}, \
+                 latest at global slot {last}",
+                discarded.len()
+            ),
+        )
+    } else {
+        RpcHealthCheck::ok(NAME)
+    }
+}
+
+fn snark_worker_health(state: &crate::State) -> RpcHealthCheck {
+    const NAME: &str = "snark_worker";
+    if state.config.snarker.is_none() {
+        return RpcHealthCheck::ok(NAME);
+    }
+    let config = &state.config.health;
+    let workers = &state.external_snark_worker;
+    let window = config.snark_worker_crash_window;
+    let errors = workers.errors_within(state.time(), window);
+    if errors >= config.snark_worker_crash_threshold {
+        RpcHealthCheck::fail(
+            NAME,
+            format!("snark worker failed {errors} times in the last {window:?}"),
+        )
+    } else if let Some((worker_id, error)) = workers.errors().next() {
+        RpcHealthCheck::warn(NAME, format!("snark worker {worker_id} error: {error}"))
+    } else if errors > 0 {
+        RpcHealthCheck::warn(
+            NAME,
+            format!("snark worker failed {errors} times in the last {window:?}"),
+        )
+    } else {
+        RpcHealthCheck::ok(NAME)
+    }
+}
+
+fn ledger_service_health(state: &crate::State) -> RpcHealthCheck {
+    const NAME: &str = "ledger_service";
+    let config = &state.config.health;
+    let ledger = &state.ledger;
+    let oldest = [
+        ledger.read.oldest_pending_since(),
+        ledger.write.pending_since(),
+    ]
+    .into_iter()
+    .flatten()
+    .min();
+    let pending_for = oldest.and_then(|time| state.time().checked_sub(time));
+    let pending = ledger.read.pending_len();
+    match pending_for {
+        Some(duration) if duration > config.ledger_request_timeout => {
+            RpcHealthCheck::fail(NAME, format!("ledger request is pending for {duration:?}"))
+        }
+        _ if pending > config.ledger_backlog => RpcHealthCheck::warn(
+            NAME,
+            format!(
+                "{pending} ledger read requests pending, more than the threshold {}",
+                config.ledger_backlog
+            ),
+        ),
+        _ => RpcHealthCheck::ok(NAME),
+    }
+}
+
+fn collect_block_producer_slot_schedule(
+    state: &crate::State,
+    time: redux::Timestamp,
+) -> Option<RpcBlockProducerSlotSchedule> {
+    let best_tip = state.transition_frontier.best_tip()?;
+    let vrf_evaluator = state.block_producer.vrf_evaluator()?;
+    let consensus_state = best_tip.consensus_state();
+    let slots_per_epoch = best_tip.constants().slots_per_epoch.as_u32();
+    let (current_epoch, _) = to_epoch_and_slot(&consensus_state.curr_global_slot_since_hard_fork);
+
+    let epochs = [
+        (
+            current_epoch,
+            &consensus_state.staking_epoch_data.seed,
+            &consensus_state.staking_epoch_data.ledger.hash,
+        ),
+        (
+            current_epoch + 1,
+            &consensus_state.next_epoch_data.seed,
+            &consensus_state.next_epoch_data.ledger.hash,
+        ),
+    ];
+    let epochs = epochs
+        .into_iter()
+        .map(|(epoch, seed, staking_ledger_hash)| {
+            block_producer_epoch_schedule(
+                vrf_evaluator,
+                epoch,
+                seed,
+                staking_ledger_hash,
+                best_tip.genesis_timestamp(),
+                slots_per_epoch,
+                state.block_window_duration_ms(),
+            )
+        })
+        .collect();
+
+    Some(RpcBlockProducerSlotSchedule {
+        current_time: time,
+        current_global_slot: state.cur_global_slot(),
+        latest_evaluated_slot: vrf_evaluator.latest_evaluated_global_slot(),
+        epochs,
+    })
+}
+
+/// Schedule of the `epoch`. Only the won slots evaluated with the epoch's
+/// staking ledger are included, so slots of a stale evaluation aren't
+/// shown.
+fn block_producer_epoch_schedule(
+    vrf_evaluator: &BlockProducerVrfEvaluatorState,
+    epoch: u32,
+    seed: &EpochSeed,
+    staking_ledger_hash: &LedgerHash,
+    genesis_timestamp: redux::Timestamp,
+    slots_per_epoch: u32,
+    block_window_duration_ms: u64,
+) -> RpcBlockProducerEpochSchedule {
+    let start_slot = epoch * slots_per_epoch;
+    let end_slot = start_slot + slots_per_epoch - 1;
+    let delegator_table = vrf_evaluator.delegator_tables.get(staking_ledger_hash);
+
+    let won_slots = vrf_evaluator
+        .won_slots
+        .range(start_slot..=end_slot)
+        .filter(|(_, won_slot)| &won_slot.staking_ledger_hash == staking_ledger_hash)
+        .map(|(_, won_slot)| {
+            let vrf_output = won_slot.won_slot.vrf_output.to_base_58();
+            let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
+                won_slot,
+                genesis_timestamp,
+                slots_per_epoch,
+                block_window_duration_ms,
+            );
+            let (delegator, delegator_index) = won_slot.delegator.clone();
+            let delegator_stake = delegator_table
+                .and_then(|table| table.get(&AccountPublicKey::from(won_slot.producer.clone())))
+                .and_then(|delegators| delegators.get(&delegator_index))
+                .map(|(_, stake)| *stake);
+            RpcBlockProducerScheduledSlot {
+                slot_time: won_slot.slot_time,
+                global_slot: won_slot.global_slot(),
+                producer: won_slot.producer,
+                delegator,
+                delegator_index,
+                delegator_stake,
+                vrf_output,
+                value_with_threshold: won_slot.value_with_threshold,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let delegators = delegator_table
+        .into_iter()
+        .flat_map(|table| table.iter())
+        .flat_map(|(producer, delegators)| {
+            let producer = NonZeroCurvePoint::from(producer.clone());
+            delegators.iter().map(move |(index, (delegator, stake))| {
+                (producer.clone(), *index, delegator, *stake)
+            })
+        })
+        .map(|(producer, index, delegator, stake)| {
+            let won = won_slots
+                .iter()
+                .filter(|slot| slot.producer == producer && slot.delegator_index == index)
+                .count();
+            RpcBlockProducerDelegator {
+                producer,
+                delegator: delegator.clone().into(),
+                index,
+                stake,
+                won_slots: won as u32,
+            }
+        })
+        .collect();
+
+    RpcBlockProducerEpochSchedule {
+        epoch,
+        start_slot,
+        end_slot,
+        seed: seed.clone(),
+        staking_ledger_hash: staking_ledger_hash.clone(),
+        is_evaluated: vrf_evaluator.is_epoch_evaluated(epoch),
+        won_slots,
+        delegators,
+    }
+}
+
+fn snark_coordinator_lease_response(
+    state: &crate::State,
+    worker_id: RemoteSnarkWorkerId,
+) -> RpcSnarkCoordinatorLeaseResponse {
+    let Some(worker) = state.snark_pool.coordinator.get(worker_id) else {
+        return RpcSnarkCoordinatorLeaseResponse::UnknownWorker;
+    };
+    let Some(job) = worker
+        .lease
+        .as_ref()
+        .filter(|_| worker.is_proving())
+        .and_then(|lease| state.snark_pool.get(&lease.job_id))
+    else {
+        return RpcSnarkCoordinatorLeaseResponse::NoJobs;
+    };
+    match available_job_to_snark_worker_spec(job.job.clone(), &state.transition_frontier) {
+        Ok(instances) => RpcSnarkCoordinatorLeaseResponse::Ok {
+            job_id: job.id.clone(),
+            spec: mina_p2p_messages::v2::SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponse(Some((
+                mina_p2p_messages::v2::SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0 {
+                    instances,
+                    fee: worker.fee.clone(),
+                },
+                worker.public_key.clone(),
+            ))),
+        },
+        Err(err) => RpcSnarkCoordinatorLeaseResponse::Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ledger::AccountIndex;
+    use mina_p2p_messages::v2::{EpochSeed, LedgerHash, StateHash};
+    use vrf::VrfWonSlot;
+
+    use crate::account::AccountSecretKey;
+    use crate::block_producer::vrf_evaluator::{
+        BlockProducerVrfEvaluatorState, VrfWonSlotWithHash,
+    };
+    use crate::block_producer::BlockProducerWonSlotDiscardReason as DiscardReason;
+    use crate::rpc::RpcHealthStatus;
+    use crate::stats::block_producer::{
+        BlockProductionAttempt, BlockProductionAttemptWonSlot, BlockProductionStatus,
+        BlockProductionTimes,
+    };
+
+    use super::{block_producer_epoch_schedule, block_production_attempts_health};
+
+    const SLOT: Duration = Duration::from_secs(180);
+    const WINDOW: Duration = Duration::from_secs(3600);
+
+    fn slot_time(global_slot: u32) -> redux::Timestamp {
+        redux::Timestamp::new((SLOT * global_slot).as_nanos() as u64)
+    }
+
+    fn attempt(global_slot: u32, status: BlockProductionStatus) -> BlockProductionAttempt {
+        let producer = AccountSecretKey::genesis_producer().public_key();
+        let slot_time = slot_time(global_slot);
+        BlockProductionAttempt {
+            won_slot: BlockProductionAttemptWonSlot {
+                slot_time,
+                global_slot,
+                epoch: 0,
+                producer: producer.clone().into(),
+                delegator: (producer.into(), ledger::AccountIndex(0)),
+                value_with_threshold: None,
+            },
+            block: None,
+            times: BlockProductionTimes {
+                scheduled: slot_time,
+                staged_ledger_diff_create_start: None,
+                staged_ledger_diff_create_end: None,
+                produced: None,
+                proof_create_start: None,
+                proof_create_end: None,
+                block_apply_start: None,
+                block_apply_end: None,
+                committed: None,
+                discarded: None,
+            },
+            status,
+        }
+    }
+
+    fn discarded(global_slot: u32, discard_reason: DiscardReason) -> BlockProductionAttempt {
+        attempt(
+            global_slot,
+            BlockProductionStatus::Discarded { discard_reason },
+        )
+    }
+
+    fn status(attempts: &[BlockProductionAttempt]) -> RpcHealthStatus {
+        let now = slot_time(10);
+        block_production_attempts_health(attempts, now, WINDOW, SLOT).status
+    }
+
+    #[test]
+    fn block_production_health_dry_run_is_ok() {
+        let attempts = [discarded(5, DiscardReason::DryRun)];
+        assert_eq!(status(&attempts), RpcHealthStatus::Ok);
+    }
+
+    #[test]
+    fn block_production_health_better_tip_is_warn() {
+        let attempts = [
+            discarded(4, DiscardReason::DryRun),
+            discarded(5, DiscardReason::BestTipSuperior),
+        ];
+        assert_eq!(status(&attempts), RpcHealthStatus::Warn);
+    }
+
+    #[test]
+    fn block_production_health_missed_slot_is_fail() {
+        let proof_failed = discarded(
+            5,
+            DiscardReason::BlockProofFailed {
+                block_hash: StateHash::from_fp(5.into()),
+                error: "error".to_owned(),
+            },
+        );
+        assert_eq!(status(&[proof_failed]), RpcHealthStatus::Fail);
+
+        // Still scheduled more than a slot after the won slot
+        let attempts = [
+            discarded(4, DiscardReason::BestTipSuperior),
+            attempt(5, BlockProductionStatus::Scheduled),
+        ];
+        assert_eq!(status(&attempts), RpcHealthStatus::Fail);
+        // Still within the won slot
+        let attempts = [attempt(10, BlockProductionStatus::Scheduled)];
+        assert_eq!(status(&attempts), RpcHealthStatus::Ok);
+    }
+
+    #[test]
+    fn block_production_health_ignores_old_attempts() {
+        let now_slot = 10 + (WINDOW.as_secs() / SLOT.as_secs()) as u32;
+        let now = slot_time(now_slot);
+        let attempts = [attempt(5, BlockProductionStatus::Scheduled)];
+        let check = block_production_attempts_health(&attempts, now, WINDOW, SLOT);
+        assert_eq!(check.status, RpcHealthStatus::Ok);
+    }
+
+    #[test]
+    fn block_producer_epoch_schedule_splits_won_slots_by_epoch() {
+        const SLOTS_PER_EPOCH: u32 = 10;
+        let producer = AccountSecretKey::genesis_producer().public_key();
+        let (current_ledger, next_ledger) =
+            (LedgerHash::from_fp(1.into()), LedgerHash::from_fp(2.into()));
+        let schedule = |vrf_evaluator: &BlockProducerVrfEvaluatorState, epoch, ledger_hash| {
+            block_producer_epoch_schedule(
+                vrf_evaluator,
+                epoch,
+                &EpochSeed::zero(),
+                ledger_hash,
+                redux::Timestamp::ZERO,
+                SLOTS_PER_EPOCH,
+                SLOT.as_millis() as u64,
+            )
+        };
+
+        // Nothing evaluated yet.
+        let mut vrf_evaluator = BlockProducerVrfEvaluatorState::new(redux::Timestamp::ZERO);
+        for (epoch, ledger_hash) in [(0, &current_ledger), (1, &next_ledger)] {
+            let epoch_schedule = schedule(&vrf_evaluator, epoch, ledger_hash);
+            assert_eq!(epoch_schedule.start_slot, epoch * SLOTS_PER_EPOCH);
+            assert_eq!(epoch_schedule.end_slot, epoch * SLOTS_PER_EPOCH + 9);
+            assert!(!epoch_schedule.is_evaluated);
+            assert!(epoch_schedule.won_slots.is_empty());
+            assert!(epoch_schedule.delegators.is_empty());
+        }
+
+        let vrf_output = vrf::genesis_vrf(EpochSeed::zero()).unwrap();
+        let won_slots = [
+            (3, &current_ledger),
+            (9, &current_ledger),
+            (10, &next_ledger),
+            (15, &next_ledger),
+            // Evaluated with a stale ledger.
+            (17, &current_ledger),
+        ];
+        for (global_slot, ledger_hash) in won_slots {
+            let won_slot = VrfWonSlot {
+                producer: producer.to_string(),
+                winner_account: producer.to_string(),
+                global_slot,
+                account_index: AccountIndex(0),
+                vrf_output: vrf_output.clone(),
+                value_with_threshold: None,
+            };
+            let won_slot = VrfWonSlotWithHash::new(won_slot, ledger_hash.clone());
+            vrf_evaluator.won_slots.insert(global_slot, won_slot);
+        }
+        let delegators = BTreeMap::from([(AccountIndex(0), (producer.clone(), 1000))]);
+        let delegator_table = BTreeMap::from([(producer.clone(), delegators)]);
+        vrf_evaluator
+            .delegator_tables
+            .insert(current_ledger.clone(), Arc::new(delegator_table));
+
+        let current = schedule(&vrf_evaluator, 0, &current_ledger);
+        let slots = current
+            .won_slots
+            .iter()
+            .map(|slot| slot.global_slot)
+            .collect::<Vec<_>>();
+        assert_eq!(slots, vec![3, 9]);
+        assert!(current
+            .won_slots
+            .iter()
+            .all(|slot| slot.delegator_stake == Some(1000)));
+        assert_eq!(current.delegators.len(), 1);
+        assert_eq!(current.delegators[0].won_slots, 2);
+
+        // Delegator table of the next epoch isn't constructed yet.
+        let next = schedule(&vrf_evaluator, 1, &next_ledger);
+        let slots = next
+            .won_slots
+            .iter()
+            .map(|slot| slot.global_slot)
+            .collect::<Vec<_>>();
+        assert_eq!(slots, vec![10, 15]);
+        assert!(next
+            .won_slots
+            .iter()
+            .all(|slot| slot.delegator_stake.is_none()));
+        assert!(next.delegators.is_empty());
+    }
+}
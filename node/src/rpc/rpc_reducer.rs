@@ -12,6 +12,8 @@ impl RpcState {
             RpcAction::ActionStatsGet { .. } => {}
             RpcAction::SyncStatsGet { .. } => {}
             RpcAction::BlockProducerStatsGet { .. } => {}
+            RpcAction::BlockProducerSlotScheduleGet { .. } => {}
+            RpcAction::MetricsGet { .. } => {}
             RpcAction::MessageProgressGet { .. } => {}
             RpcAction::PeersGet { .. } => {}
             RpcAction::P2pConnectionOutgoingInit { rpc_id, opts } => {
@@ -97,10 +99,15 @@ impl RpcState {
             }
             RpcAction::SnarkPoolAvailableJobsGet { .. } => {}
             RpcAction::SnarkPoolJobGet { .. } => {}
+            RpcAction::SnarkMarketStatsGet { .. } => {}
             RpcAction::SnarkerConfigGet { .. } => {}
             RpcAction::SnarkerJobCommit { .. } => {}
             RpcAction::SnarkerJobSpec { .. } => {}
             RpcAction::SnarkerWorkersGet { .. } => {}
+            RpcAction::SnarkCoordinatorRegister { .. } => {}
+            RpcAction::SnarkCoordinatorLease { .. } => {}
+            RpcAction::SnarkCoordinatorHeartbeat { .. } => {}
+            RpcAction::SnarkCoordinatorSubmit { .. } => {}
             RpcAction::HealthCheck { .. } => {}
             RpcAction::ReadinessCheck { .. } => {}
             RpcAction::DiscoveryRoutingTable { .. } => {}
@@ -5,10 +5,14 @@ use crate::p2p::connection::P2pConnectionResponse;
 use crate::State;
 
 use super::{
-    RpcActionStatsGetResponse, RpcBlockProducerStatsGetResponse, RpcDiscoveryBoostrapStatsResponse,
+    RpcActionStatsGetResponse, RpcBlockProducerSlotScheduleGetResponse,
+    RpcBlockProducerStatsGetResponse, RpcDiscoveryBoostrapStatsResponse,
     RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse, RpcId, RpcMessageProgressResponse,
-    RpcP2pConnectionOutgoingResponse, RpcPeersGetResponse, RpcReadinessCheckResponse,
-    RpcScanStateSummaryGetResponse, RpcSnarkPoolGetResponse, RpcSnarkPoolJobGetResponse,
+    RpcMetricsGetResponse, RpcP2pConnectionOutgoingResponse, RpcPeersGetResponse,
+    RpcReadinessCheckResponse, RpcScanStateSummaryGetResponse,
+    RpcSnarkCoordinatorHeartbeatResponse, RpcSnarkCoordinatorLeaseResponse,
+    RpcSnarkCoordinatorRegisterResponse, RpcSnarkCoordinatorSubmitResponse,
+    RpcSnarkMarketStatsGetResponse, RpcSnarkPoolGetResponse, RpcSnarkPoolJobGetResponse,
     RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse, RpcSnarkerWorkersResponse,
     RpcStatusGetResponse, RpcSyncStatsGetResponse,
 };
@@ -63,6 +67,16 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcBlockProducerStatsGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_block_producer_slot_schedule_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcBlockProducerSlotScheduleGetResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_metrics_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcMetricsGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_message_progress_stats_get(
         &mut self,
         rpc_id: RpcId,
@@ -103,6 +117,11 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcSnarkPoolJobGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_snark_market_stats_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcSnarkMarketStatsGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_snarker_config_get(
         &mut self,
         rpc_id: RpcId,
@@ -123,6 +142,26 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcSnarkerWorkersResponse,
     ) -> Result<(), RespondError>;
+    fn respond_snark_coordinator_register(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcSnarkCoordinatorRegisterResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_snark_coordinator_lease(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcSnarkCoordinatorLeaseResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_snark_coordinator_heartbeat(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcSnarkCoordinatorHeartbeatResponse,
+    ) -> Result<(), RespondError>;
+    fn respond_snark_coordinator_submit(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcSnarkCoordinatorSubmitResponse,
+    ) -> Result<(), RespondError>;
     fn respond_health_check(
         &mut self,
         rpc_id: RpcId,
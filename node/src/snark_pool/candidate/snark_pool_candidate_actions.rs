@@ -7,6 +7,7 @@ use crate::p2p::channels::rpc::P2pRpcId;
 use crate::p2p::PeerId;
 use crate::snark::work_verify::SnarkWorkVerifyId;
 
+use super::snark_pool_candidate_state::candidate_cmp;
 use super::SnarkPoolCandidateState;
 
 pub type SnarkPoolCandidateActionWithMeta = redux::ActionWithMeta<SnarkPoolCandidateAction>;
@@ -35,18 +36,14 @@ pub enum SnarkPoolCandidateAction {
     },
     WorkVerifyNext,
     WorkVerifyPending {
-        peer_id: PeerId,
-        job_ids: Vec<SnarkJobId>,
+        batch: Vec<(PeerId, SnarkJobId)>,
         verify_id: SnarkWorkVerifyId,
     },
     WorkVerifyError {
-        peer_id: PeerId,
         verify_id: SnarkWorkVerifyId,
     },
     WorkVerifySuccess {
-        peer_id: PeerId,
         verify_id: SnarkWorkVerifyId,
-        batch: Vec<Snark>,
     },
     PeerPrune {
         peer_id: PeerId,
@@ -57,7 +54,11 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolCandidateAction {
     fn is_enabled(&self, state: &crate::State, _time: redux::Timestamp) -> bool {
         match self {
             SnarkPoolCandidateAction::InfoReceived { peer_id, info } => {
-                state.snark_pool.contains(&info.job_id)
+                // Snark which wouldn't replace the one we already have
+                // (e.g. restored after restart) isn't fetched and verified.
+                state
+                    .snark_pool
+                    .is_snark_replacement(&info.job_id, info.fee.0.as_u64())
                     && state
                         .snark_pool
                         .candidates
@@ -95,7 +96,7 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolCandidateAction {
                         .snark_pool
                         .candidates
                         .get(*peer_id, &job_id)
-                        .map_or(true, |v| match work.partial_cmp(v).unwrap() {
+                        .map_or(true, |v| match candidate_cmp(work, v) {
                             Ordering::Less => false,
                             Ordering::Greater => true,
                             Ordering::Equal => {
@@ -104,26 +105,25 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolCandidateAction {
                         })
             }
             SnarkPoolCandidateAction::WorkVerifyNext => state.snark.work_verify.jobs.is_empty(),
-            SnarkPoolCandidateAction::WorkVerifyPending {
-                peer_id, job_ids, ..
-            } => {
-                !job_ids.is_empty()
-                    && state
-                        .snark_pool
-                        .candidates
-                        .jobs_from_peer_with_job_ids(*peer_id, job_ids)
-                        .all(|(_, state)| {
-                            matches!(state, Some(SnarkPoolCandidateState::WorkReceived { .. }))
-                        })
-            }
-            SnarkPoolCandidateAction::WorkVerifyError { .. } => {
-                // TODO(binier)
-                true
-            }
-            SnarkPoolCandidateAction::WorkVerifySuccess { .. } => {
-                // TODO(binier)
-                true
+            SnarkPoolCandidateAction::WorkVerifyPending { batch, .. } => {
+                !batch.is_empty()
+                    && batch.iter().all(|(peer_id, job_id)| {
+                        state
+                            .snark_pool
+                            .candidates
+                            .get(*peer_id, job_id)
+                            .map_or(false, |s| {
+                                matches!(s, SnarkPoolCandidateState::WorkReceived { .. })
+                            })
+                    })
             }
+            SnarkPoolCandidateAction::WorkVerifyError { verify_id }
+            | SnarkPoolCandidateAction::WorkVerifySuccess { verify_id } => state
+                .snark_pool
+                .candidates
+                .verify_pending_batch(*verify_id)
+                .next()
+                .is_some(),
             SnarkPoolCandidateAction::PeerPrune { peer_id } => {
                 state.snark_pool.candidates.peer_work_count(peer_id) > 0
             }
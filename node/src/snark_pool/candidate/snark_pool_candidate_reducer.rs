@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::{p2p_ready, SnarkPoolAction};
 use openmina_core::snark::Snark;
@@ -90,72 +90,81 @@ impl SnarkPoolCandidatesState {
                     .snark_pool
                     .candidates
                     .get_batch_to_verify(job_ids_ordered_iter);
-                let Some((peer_id, batch)) = batch else {
+                if batch.is_empty() {
                     return;
-                };
+                }
 
                 let req_id = global_state.snark.work_verify.next_req_id();
-                let job_ids = batch.iter().map(|v| v.job_id()).collect::<Vec<_>>();
-                let sender = peer_id.to_string();
+                let job_ids = batch
+                    .iter()
+                    .map(|(peer_id, work)| (*peer_id, work.job_id()))
+                    .collect::<Vec<_>>();
+                let sender = batch
+                    .iter()
+                    .map(|(peer_id, _)| *peer_id)
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .map(|peer_id| peer_id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let batch = batch.into_iter().map(|(_, work)| work).collect();
                 dispatcher.push(SnarkWorkVerifyAction::Init {
                     req_id,
                     batch,
                     sender,
                     on_success: redux::callback!(
-                        on_snark_pool_candidate_work_verify_success((req_id: SnarkWorkVerifyId, sender: String, batch: Vec<Snark>)) -> crate::Action {
+                        on_snark_pool_candidate_work_verify_success((req_id: SnarkWorkVerifyId, _sender: String, _batch: Vec<Snark>)) -> crate::Action {
                             SnarkPoolCandidateAction::WorkVerifySuccess {
-                                peer_id: sender.parse().unwrap(),
                                 verify_id: req_id,
-                                batch
                             }
                         }),
                     on_error: redux::callback!(
-                        on_snark_pool_candidate_work_verify_error((req_id: SnarkWorkVerifyId, sender: String)) -> crate::Action {
+                        on_snark_pool_candidate_work_verify_error((req_id: SnarkWorkVerifyId, _sender: String)) -> crate::Action {
                             SnarkPoolCandidateAction::WorkVerifyError {
-                                peer_id: sender.parse().unwrap(),
                                 verify_id: req_id,
                             }
                         }),
                 });
                 dispatcher.push(SnarkPoolCandidateAction::WorkVerifyPending {
-                    peer_id,
-                    job_ids,
+                    batch: job_ids,
                     verify_id: req_id,
                 });
             }
-            SnarkPoolCandidateAction::WorkVerifyPending {
-                peer_id,
-                job_ids,
-                verify_id,
-            } => {
-                state.verify_pending(meta.time(), peer_id, *verify_id, job_ids);
+            SnarkPoolCandidateAction::WorkVerifyPending { batch, verify_id } => {
+                state.verify_pending(meta.time(), *verify_id, batch);
             }
-            SnarkPoolCandidateAction::WorkVerifyError { peer_id, verify_id } => {
-                state.verify_result(meta.time(), peer_id, *verify_id, Err(()));
+            SnarkPoolCandidateAction::WorkVerifyError { verify_id } => {
+                let batch = state
+                    .verify_pending_batch(*verify_id)
+                    .map(|(peer_id, _)| peer_id)
+                    .collect::<Vec<_>>();
+                let [peer_id] = batch[..] else {
+                    // Batch check doesn't tell us which snark is invalid,
+                    // so split the batch and verify halves separately.
+                    state.verify_batch_bisect(meta.time(), *verify_id);
+                    return;
+                };
+                state.verify_result(meta.time(), *verify_id, Err(()));
 
                 // TODO(binier): blacklist peer
                 let dispatcher = state_context.into_dispatcher();
-                let peer_id = *peer_id;
                 dispatcher.push(P2pDisconnectionAction::Init {
                     peer_id,
                     reason: P2pDisconnectionReason::SnarkPoolVerifyError,
                 });
             }
-            SnarkPoolCandidateAction::WorkVerifySuccess {
-                peer_id,
-                verify_id,
-                batch,
-            } => {
-                state.verify_result(meta.time(), peer_id, *verify_id, Ok(()));
+            SnarkPoolCandidateAction::WorkVerifySuccess { verify_id } => {
+                let batch = state
+                    .verify_pending_batch(*verify_id)
+                    .map(|(peer_id, snark)| (peer_id, snark.clone()))
+                    .collect::<Vec<_>>();
+                state.verify_result(meta.time(), *verify_id, Ok(()));
 
                 // Dispatch
                 let dispatcher = state_context.into_dispatcher();
 
-                for snark in batch {
-                    dispatcher.push(SnarkPoolAction::WorkAdd {
-                        snark: snark.clone(),
-                        sender: *peer_id,
-                    });
+                for (sender, snark) in batch {
+                    dispatcher.push(SnarkPoolAction::WorkAdd { snark, sender });
                 }
             }
             SnarkPoolCandidateAction::PeerPrune { peer_id } => {
@@ -1,6 +1,7 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
-use openmina_core::snark::{Snark, SnarkInfo, SnarkJobId};
+use openmina_core::snark::{Snark, SnarkCmp, SnarkInfo, SnarkJobId};
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
@@ -10,10 +11,16 @@ use crate::snark::work_verify::SnarkWorkVerifyId;
 
 static EMPTY_PEER_WORK_CANDIDATES: BTreeMap<SnarkJobId, SnarkPoolCandidateState> = BTreeMap::new();
 
+/// Max number of snarks verified together in a single batch.
+pub const SNARK_POOL_CANDIDATE_VERIFY_BATCH_MAX_SIZE: usize = 16;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct SnarkPoolCandidatesState {
     by_peer: BTreeMap<PeerId, BTreeMap<SnarkJobId, SnarkPoolCandidateState>>,
     by_job_id: BTreeMap<SnarkJobId, BTreeSet<PeerId>>,
+    /// Halves of batches which failed verification. They are verified
+    /// before any new batch, until the invalid snark is isolated.
+    bisected_batches: VecDeque<Vec<(PeerId, SnarkJobId)>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -173,47 +180,68 @@ impl SnarkPoolCandidatesState {
             .insert(job_id, state);
     }
 
-    pub fn get_batch_to_verify<'a, I>(&'a self, job_ids_ordered: I) -> Option<(PeerId, Vec<Snark>)>
+    /// Collects next batch of snarks to verify.
+    ///
+    /// Halves of previously failed batches take priority. Otherwise, batch
+    /// is aggregated across peers in the given job order, taking the best
+    /// received work for each job, which doesn't already have a work
+    /// pending verification.
+    pub fn get_batch_to_verify<'a, I>(&'a self, job_ids_ordered: I) -> Vec<(PeerId, Snark)>
     where
         I: IntoIterator<Item = &'a SnarkJobId>,
     {
-        for job_id in job_ids_ordered {
-            if let Some(res) = None.or_else(|| {
-                for peer_id in self.by_job_id.get(job_id)? {
-                    let peer_jobs = self.by_peer.get(peer_id)?;
-                    if peer_jobs.get(job_id)?.work().is_some() {
-                        let jobs = peer_jobs
-                            .iter()
-                            .filter_map(|(_, v)| match v {
-                                SnarkPoolCandidateState::WorkReceived { work, .. } => Some(work),
-                                _ => None,
-                            })
-                            .cloned()
-                            .collect();
-                        return Some((*peer_id, jobs));
-                    }
-                }
-                None
-            }) {
-                return Some(res);
-            }
+        let received_work =
+            |peer_id: &PeerId, job_id: &SnarkJobId| match self.by_peer.get(peer_id)?.get(job_id)? {
+                SnarkPoolCandidateState::WorkReceived { work, .. } => Some(work),
+                _ => None,
+            };
+
+        let bisected = self.bisected_batches.iter().find_map(|batch| {
+            let batch = batch
+                .iter()
+                .filter_map(|(peer_id, job_id)| {
+                    Some((*peer_id, received_work(peer_id, job_id)?.clone()))
+                })
+                .collect::<Vec<_>>();
+            Some(batch).filter(|batch| !batch.is_empty())
+        });
+        if let Some(batch) = bisected {
+            return batch;
         }
-        None
+
+        job_ids_ordered
+            .into_iter()
+            .filter_map(|job_id| {
+                let peers = self.by_job_id.get(job_id)?;
+                let is_verify_pending = peers
+                    .iter()
+                    .filter_map(|peer_id| self.by_peer.get(peer_id)?.get(job_id))
+                    .any(|state| state.pending_verify_id().is_some());
+                if is_verify_pending {
+                    return None;
+                }
+                peers
+                    .iter()
+                    .filter_map(|peer_id| Some((*peer_id, received_work(peer_id, job_id)?)))
+                    .max_by(|(_, w1), (_, w2)| candidate_cmp(*w1, *w2))
+                    .map(|(peer_id, work)| (peer_id, work.clone()))
+            })
+            .take(SNARK_POOL_CANDIDATE_VERIFY_BATCH_MAX_SIZE)
+            .collect()
     }
 
     pub fn verify_pending(
         &mut self,
         time: Timestamp,
-        peer_id: &PeerId,
         verify_id: SnarkWorkVerifyId,
-        job_ids: &[SnarkJobId],
+        batch: &[(PeerId, SnarkJobId)],
     ) {
-        let Some(peer_jobs) = self.by_peer.get_mut(peer_id) else {
-            return;
-        };
-
-        for job_id in job_ids {
-            if let Some(job_state) = peer_jobs.get_mut(job_id) {
+        for (peer_id, job_id) in batch {
+            if let Some(job_state) = self
+                .by_peer
+                .get_mut(peer_id)
+                .and_then(|jobs| jobs.get_mut(job_id))
+            {
                 if let SnarkPoolCandidateState::WorkReceived { work, .. } = job_state {
                     *job_state = SnarkPoolCandidateState::WorkVerifyPending {
                         time,
@@ -223,39 +251,98 @@ impl SnarkPoolCandidatesState {
                 }
             }
         }
+        self.bisected_batches_prune();
+    }
+
+    /// Candidates, which are part of the pending verification batch.
+    pub fn verify_pending_batch(
+        &self,
+        verify_id: SnarkWorkVerifyId,
+    ) -> impl Iterator<Item = (PeerId, &Snark)> {
+        self.by_peer.iter().flat_map(move |(peer_id, jobs)| {
+            jobs.values()
+                .filter(move |state| state.pending_verify_id() == Some(verify_id))
+                .filter_map(move |state| Some((*peer_id, state.work()?)))
+        })
     }
 
     pub fn verify_result(
         &mut self,
         time: Timestamp,
-        peer_id: &PeerId,
         verify_id: SnarkWorkVerifyId,
         result: Result<(), ()>,
     ) {
-        if let Some(peer_jobs) = self.by_peer.get_mut(peer_id) {
-            for (_, job_state) in peer_jobs
-                .iter_mut()
-                .filter(|(_, job_state)| job_state.pending_verify_id() == Some(verify_id))
-            {
-                let SnarkPoolCandidateState::WorkVerifyPending { work, .. } = job_state else {
+        let job_states = self
+            .by_peer
+            .values_mut()
+            .flat_map(|jobs| jobs.values_mut())
+            .filter(|job_state| job_state.pending_verify_id() == Some(verify_id));
+        for job_state in job_states {
+            let SnarkPoolCandidateState::WorkVerifyPending { work, .. } = job_state else {
+                continue;
+            };
+            match result {
+                Ok(_) => {
+                    *job_state = SnarkPoolCandidateState::WorkVerifySuccess {
+                        time,
+                        work: work.clone(),
+                    };
+                }
+                Err(_) => {
+                    *job_state = SnarkPoolCandidateState::WorkVerifyError {
+                        time,
+                        work: work.clone(),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Puts candidates of the failed batch back to `WorkReceived` state
+    /// and schedules both halves of the batch for verification.
+    pub fn verify_batch_bisect(&mut self, time: Timestamp, verify_id: SnarkWorkVerifyId) {
+        let mut batch = vec![];
+        for (peer_id, jobs) in self.by_peer.iter_mut() {
+            for (job_id, job_state) in jobs.iter_mut() {
+                let SnarkPoolCandidateState::WorkVerifyPending {
+                    work,
+                    verify_id: id,
+                    ..
+                } = job_state
+                else {
                     continue;
                 };
-                match result {
-                    Ok(_) => {
-                        *job_state = SnarkPoolCandidateState::WorkVerifySuccess {
-                            time,
-                            work: work.clone(),
-                        };
-                    }
-                    Err(_) => {
-                        *job_state = SnarkPoolCandidateState::WorkVerifyError {
-                            time,
-                            work: work.clone(),
-                        };
-                    }
+                if *id != verify_id {
+                    continue;
                 }
+                *job_state = SnarkPoolCandidateState::WorkReceived {
+                    time,
+                    work: work.clone(),
+                };
+                batch.push((*peer_id, job_id.clone()));
             }
         }
+
+        if batch.len() > 1 {
+            let second_half = batch.split_off(batch.len() / 2);
+            self.bisected_batches.push_front(second_half);
+        }
+        self.bisected_batches.push_front(batch);
+    }
+
+    fn bisected_batches_prune(&mut self) {
+        let by_peer = &self.by_peer;
+        self.bisected_batches.retain_mut(|batch| {
+            batch.retain(|(peer_id, job_id)| {
+                by_peer
+                    .get(peer_id)
+                    .and_then(|jobs| jobs.get(job_id))
+                    .map_or(false, |state| {
+                        matches!(state, SnarkPoolCandidateState::WorkReceived { .. })
+                    })
+            });
+            !batch.is_empty()
+        });
     }
 
     pub fn peer_remove(&mut self, peer_id: PeerId) {
@@ -268,6 +355,7 @@ impl SnarkPoolCandidatesState {
                     }
                 }
             }
+            self.bisected_batches_prune();
         }
     }
 
@@ -291,6 +379,7 @@ impl SnarkPoolCandidatesState {
             if peers.is_empty() {
                 self.by_job_id.remove(&job_id);
             }
+            self.bisected_batches_prune();
         }
     }
 
@@ -317,7 +406,8 @@ impl SnarkPoolCandidatesState {
                 }
             });
             !peers.is_empty()
-        })
+        });
+        self.bisected_batches_prune();
     }
 }
 
@@ -365,3 +455,151 @@ impl<'a> From<&'a SnarkPoolCandidateState> for openmina_core::snark::SnarkCmp<'a
         }
     }
 }
+
+/// Compares candidates for the same job, the better one is greater:
+/// lower fee is better, tie is broken by the hash of the job and the
+/// prover.
+///
+/// [`SnarkCmp`]'s `PartialOrd` is used directly, as `snark_cmp` and
+/// `Snark`'s `PartialOrd` go through its derived `Ord`, which prefers
+/// higher fee.
+pub(super) fn candidate_cmp<'a>(
+    a: impl Into<SnarkCmp<'a>>,
+    b: impl Into<SnarkCmp<'a>>,
+) -> Ordering {
+    let (a, b) = (a.into(), b.into());
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_p2p_messages::binprot::BinProtRead;
+    use mina_p2p_messages::gossip::GossipNetMessageV2;
+    use mina_p2p_messages::v2::{
+        CurrencyFeeStableV1, NetworkPoolSnarkPoolDiffVersionedStableV2,
+        UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+    };
+    use openmina_core::requests::RequestId;
+    use openmina_core::snark::Snark;
+    use redux::Timestamp;
+
+    use std::cmp::Ordering;
+
+    use super::{candidate_cmp, SnarkPoolCandidateState, SnarkPoolCandidatesState};
+    use crate::account::AccountSecretKey;
+    use crate::p2p::PeerId;
+    use crate::snark::work_verify::SnarkWorkVerifyId;
+
+    fn sample_snark(fee: u64) -> Snark {
+        const BYTES: &[u8] = include_bytes!(
+            "../../../../mina-p2p-messages/tests/files/v2/gossip/snark_pool_diff.bin"
+        );
+        let message = GossipNetMessageV2::binprot_read(&mut &BYTES[..]).unwrap();
+        let mut snark: Snark =
+            match NetworkPoolSnarkPoolDiffVersionedStableV2::try_from(message).unwrap() {
+                NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work) => work.1.into(),
+                NetworkPoolSnarkPoolDiffVersionedStableV2::Empty => panic!("no work"),
+            };
+        snark.fee = CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+            fee.into(),
+        ));
+        snark
+    }
+
+    fn peer(i: u8) -> PeerId {
+        PeerId::from_bytes([i; 32])
+    }
+
+    fn verify_id(counter: usize) -> SnarkWorkVerifyId {
+        RequestId::new_unchecked(counter, counter)
+    }
+
+    fn verify_next(
+        candidates: &mut SnarkPoolCandidatesState,
+        id: SnarkWorkVerifyId,
+    ) -> Vec<PeerId> {
+        let job_ids = candidates.by_job_id.keys().cloned().collect::<Vec<_>>();
+        let batch = candidates
+            .get_batch_to_verify(&job_ids)
+            .into_iter()
+            .map(|(peer_id, work)| (peer_id, work.job_id()))
+            .collect::<Vec<_>>();
+        candidates.verify_pending(Timestamp::ZERO, id, &batch);
+        batch.into_iter().map(|(peer_id, _)| peer_id).collect()
+    }
+
+    #[test]
+    fn cheaper_candidate_is_better() {
+        let (cheap, expensive) = (sample_snark(5), sample_snark(10));
+        assert_eq!(candidate_cmp(&cheap, &expensive), Ordering::Greater);
+        assert_eq!(candidate_cmp(&expensive, &cheap), Ordering::Less);
+        assert_eq!(candidate_cmp(&cheap, &cheap), Ordering::Equal);
+
+        // Same fee, tie is broken by the prover.
+        let mut other_prover = sample_snark(5);
+        other_prover.snarker = AccountSecretKey::genesis_producer().public_key().into();
+        assert_ne!(candidate_cmp(&cheap, &other_prover), Ordering::Equal);
+        assert_eq!(
+            candidate_cmp(&cheap, &other_prover),
+            candidate_cmp(&other_prover, &cheap).reverse()
+        );
+    }
+
+    #[test]
+    fn batch_takes_cheapest_work_for_job() {
+        let mut candidates = SnarkPoolCandidatesState::new();
+        candidates.work_received(Timestamp::ZERO, peer(1), sample_snark(10));
+        candidates.work_received(Timestamp::ZERO, peer(2), sample_snark(5));
+        candidates.work_received(Timestamp::ZERO, peer(3), sample_snark(20));
+
+        assert_eq!(verify_next(&mut candidates, verify_id(1)), vec![peer(2)]);
+        // Job already has a work pending verification.
+        assert!(verify_next(&mut candidates, verify_id(2)).is_empty());
+    }
+
+    #[test]
+    fn failed_batch_is_bisected_until_invalid_snark_is_isolated() {
+        let mut candidates = SnarkPoolCandidatesState::new();
+        let snark = sample_snark(10);
+        let job_id = snark.job_id();
+        let peers = (1..=4).map(peer).collect::<Vec<_>>();
+        for peer_id in &peers {
+            candidates.work_received(Timestamp::ZERO, *peer_id, snark.clone());
+        }
+        let batch = peers
+            .iter()
+            .map(|peer_id| (*peer_id, job_id.clone()))
+            .collect::<Vec<_>>();
+        candidates.verify_pending(Timestamp::ZERO, verify_id(1), &batch);
+        assert_eq!(candidates.verify_pending_batch(verify_id(1)).count(), 4);
+
+        // Proof of `peer(3)` is invalid.
+        candidates.verify_batch_bisect(Timestamp::ZERO, verify_id(1));
+        assert_eq!(
+            verify_next(&mut candidates, verify_id(2)),
+            vec![peer(1), peer(2)]
+        );
+        candidates.verify_result(Timestamp::ZERO, verify_id(2), Ok(()));
+
+        assert_eq!(
+            verify_next(&mut candidates, verify_id(3)),
+            vec![peer(3), peer(4)]
+        );
+        candidates.verify_batch_bisect(Timestamp::ZERO, verify_id(3));
+
+        assert_eq!(verify_next(&mut candidates, verify_id(4)), vec![peer(3)]);
+        candidates.verify_result(Timestamp::ZERO, verify_id(4), Err(()));
+        assert_eq!(verify_next(&mut candidates, verify_id(5)), vec![peer(4)]);
+        candidates.verify_result(Timestamp::ZERO, verify_id(5), Ok(()));
+
+        assert!(candidates.bisected_batches.is_empty());
+        for (peer_id, is_valid) in peers.iter().zip([true, true, false, true]) {
+            let state = candidates.get(*peer_id, &job_id).unwrap();
+            match state {
+                SnarkPoolCandidateState::WorkVerifySuccess { .. } => assert!(is_valid),
+                SnarkPoolCandidateState::WorkVerifyError { .. } => assert!(!is_valid),
+                _ => panic!("unexpected state: {state:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod snark_pool_coordinator_state;
+pub use snark_pool_coordinator_state::*;
+
+mod snark_pool_coordinator_actions;
+pub use snark_pool_coordinator_actions::*;
+
+mod snark_pool_coordinator_reducer;
@@ -0,0 +1,131 @@
+use mina_p2p_messages::v2::{CurrencyFeeStableV1, NonZeroCurvePoint};
+use openmina_core::snark::{Snark, SnarkJobId};
+use serde::{Deserialize, Serialize};
+
+use crate::snark::work_verify::SnarkWorkVerifyId;
+
+use super::{RemoteSnarkWorker, RemoteSnarkWorkerId, RemoteSnarkWorkerLeaseStatus};
+
+pub type SnarkPoolCoordinatorActionWithMeta = redux::ActionWithMeta<SnarkPoolCoordinatorAction>;
+pub type SnarkPoolCoordinatorActionWithMetaRef<'a> =
+    redux::ActionWithMeta<&'a SnarkPoolCoordinatorAction>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum SnarkPoolCoordinatorAction {
+    WorkerRegister {
+        worker_id: RemoteSnarkWorkerId,
+        public_key: NonZeroCurvePoint,
+        fee: CurrencyFeeStableV1,
+    },
+    WorkerHeartbeat {
+        worker_id: RemoteSnarkWorkerId,
+    },
+    /// Idle worker didn't contact the coordinator for too long.
+    WorkerRemove {
+        worker_id: RemoteSnarkWorkerId,
+    },
+    JobLease {
+        worker_id: RemoteSnarkWorkerId,
+        job_id: SnarkJobId,
+    },
+    /// Lease timed out or the job is no longer needed, release it and
+    /// make the job available again.
+    LeaseRelease {
+        worker_id: RemoteSnarkWorkerId,
+    },
+    WorkSubmit {
+        worker_id: RemoteSnarkWorkerId,
+        snark: Snark,
+    },
+    WorkVerifyPending {
+        worker_id: RemoteSnarkWorkerId,
+        verify_id: SnarkWorkVerifyId,
+    },
+    WorkVerifyError {
+        verify_id: SnarkWorkVerifyId,
+    },
+    WorkVerifySuccess {
+        verify_id: SnarkWorkVerifyId,
+    },
+}
+
+impl redux::EnablingCondition<crate::State> for SnarkPoolCoordinatorAction {
+    fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
+        let coordinator = &state.snark_pool.coordinator;
+        match self {
+            SnarkPoolCoordinatorAction::WorkerRegister { worker_id, .. } => state
+                .config
+                .snark_coordinator
+                .as_ref()
+                .map_or(false, |config| {
+                    *worker_id == coordinator.next_worker_id()
+                        && coordinator.len() < config.max_workers
+                }),
+            SnarkPoolCoordinatorAction::WorkerHeartbeat { worker_id } => {
+                coordinator.get(*worker_id).is_some()
+            }
+            SnarkPoolCoordinatorAction::WorkerRemove { worker_id } => {
+                let Some(config) = state.config.snark_coordinator.as_ref() else {
+                    return false;
+                };
+                coordinator.get(*worker_id).map_or(false, |worker| {
+                    worker.is_inactive(time, config.worker_timeout)
+                })
+            }
+            SnarkPoolCoordinatorAction::JobLease { worker_id, job_id } => {
+                coordinator
+                    .get(*worker_id)
+                    .map_or(false, |worker| worker.lease.is_none())
+                    && state.snark_pool.should_create_commitment(job_id)
+            }
+            SnarkPoolCoordinatorAction::LeaseRelease { worker_id } => {
+                let Some(config) = state.config.snark_coordinator.as_ref() else {
+                    return false;
+                };
+                coordinator.get(*worker_id).map_or(false, |worker| {
+                    worker.is_lease_timed_out(time, config.lease_timeout)
+                        || (worker.is_proving() && is_lease_lost(state, worker))
+                })
+            }
+            SnarkPoolCoordinatorAction::WorkSubmit { worker_id, snark } => coordinator
+                .get(*worker_id)
+                .filter(|worker| worker.is_proving())
+                .map_or(false, |worker| {
+                    worker.leased_job_id() == Some(&snark.job_id())
+                        && worker.public_key == snark.snarker
+                        && worker.fee == snark.fee
+                }),
+            SnarkPoolCoordinatorAction::WorkVerifyPending { worker_id, .. } => coordinator
+                .get(*worker_id)
+                .and_then(|worker| worker.lease.as_ref())
+                .map_or(false, |lease| {
+                    matches!(lease.status, RemoteSnarkWorkerLeaseStatus::Submitted { .. })
+                }),
+            SnarkPoolCoordinatorAction::WorkVerifyError { verify_id }
+            | SnarkPoolCoordinatorAction::WorkVerifySuccess { verify_id } => {
+                coordinator.verify_pending(*verify_id).is_some()
+            }
+        }
+    }
+}
+
+/// Leased job is either no longer in the pool, or someone else has
+/// committed to it since (e.g. our commitment timed out).
+pub fn is_lease_lost(state: &crate::State, worker: &RemoteSnarkWorker) -> bool {
+    let Some(job_id) = worker.leased_job_id() else {
+        return true;
+    };
+    state.snark_pool.get(job_id).map_or(true, |job| {
+        job.commitment
+            .as_ref()
+            .map_or(true, |c| c.commitment.snarker != worker.public_key)
+    })
+}
+
+use crate::snark_pool::SnarkPoolAction;
+
+impl From<SnarkPoolCoordinatorAction> for crate::Action {
+    fn from(value: SnarkPoolCoordinatorAction) -> Self {
+        Self::SnarkPool(SnarkPoolAction::Coordinator(value))
+    }
+}
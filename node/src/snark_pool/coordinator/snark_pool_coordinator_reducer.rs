@@ -0,0 +1,168 @@
+use openmina_core::snark::{Snark, SnarkJobCommitment};
+use snark::{work_verify::SnarkWorkVerifyAction, work_verify_effectful::SnarkWorkVerifyId};
+
+use crate::SnarkPoolAction;
+
+use super::{
+    RemoteSnarkWorkerLease, RemoteSnarkWorkerLeaseStatus, SnarkPoolCoordinatorAction,
+    SnarkPoolCoordinatorActionWithMetaRef, SnarkPoolCoordinatorState,
+};
+
+impl SnarkPoolCoordinatorState {
+    pub fn reducer(
+        mut state_context: crate::Substate<Self>,
+        action: SnarkPoolCoordinatorActionWithMetaRef<'_>,
+    ) {
+        let Ok(state) = state_context.get_substate_mut() else {
+            // TODO: log or propagate
+            return;
+        };
+        let (action, meta) = action.split();
+
+        match action {
+            SnarkPoolCoordinatorAction::WorkerRegister {
+                public_key, fee, ..
+            } => {
+                state.register(meta.time(), public_key.clone(), fee.clone());
+            }
+            SnarkPoolCoordinatorAction::WorkerHeartbeat { worker_id } => {
+                if let Some(worker) = state.get_mut(*worker_id) {
+                    worker.last_heartbeat_t = meta.time();
+                }
+            }
+            SnarkPoolCoordinatorAction::WorkerRemove { worker_id } => {
+                state.remove(*worker_id);
+            }
+            SnarkPoolCoordinatorAction::JobLease { worker_id, job_id } => {
+                let Some(worker) = state.get_mut(*worker_id) else {
+                    return;
+                };
+                worker.last_heartbeat_t = meta.time();
+                worker.lease = Some(RemoteSnarkWorkerLease {
+                    job_id: job_id.clone(),
+                    time: meta.time(),
+                    status: RemoteSnarkWorkerLeaseStatus::Leased,
+                });
+                let commitment = SnarkJobCommitment::new(
+                    meta.time_as_nanos() / 1_000_000,
+                    job_id.clone(),
+                    worker.fee.clone(),
+                    worker.public_key.clone(),
+                );
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                dispatcher.push(SnarkPoolAction::CommitmentAdd {
+                    commitment,
+                    sender: global_state.p2p.my_id(),
+                });
+            }
+            SnarkPoolCoordinatorAction::LeaseRelease { worker_id } => {
+                let Some(worker) = state.get_mut(*worker_id) else {
+                    return;
+                };
+                let Some(lease) = worker.lease.take() else {
+                    return;
+                };
+                worker.expired += 1;
+                let snarker = worker.public_key.clone();
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(SnarkPoolAction::CommitmentRemove {
+                    job_id: lease.job_id,
+                    snarker,
+                });
+            }
+            SnarkPoolCoordinatorAction::WorkSubmit { worker_id, snark } => {
+                let worker_id = *worker_id;
+                let Some(lease) = state.get_mut(worker_id).and_then(|w| w.lease.as_mut()) else {
+                    return;
+                };
+                lease.status = RemoteSnarkWorkerLeaseStatus::Submitted {
+                    snark: snark.clone(),
+                };
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                let req_id = global_state.snark.work_verify.next_req_id();
+                dispatcher.push(SnarkWorkVerifyAction::Init {
+                    req_id,
+                    batch: vec![snark.clone()],
+                    sender: format!("remote-{worker_id}"),
+                    on_success: redux::callback!(
+                        on_snark_pool_coordinator_work_verify_success((req_id: SnarkWorkVerifyId, _sender: String, _batch: Vec<Snark>)) -> crate::Action {
+                            SnarkPoolCoordinatorAction::WorkVerifySuccess {
+                                verify_id: req_id,
+                            }
+                        }),
+                    on_error: redux::callback!(
+                        on_snark_pool_coordinator_work_verify_error((req_id: SnarkWorkVerifyId, _sender: String)) -> crate::Action {
+                            SnarkPoolCoordinatorAction::WorkVerifyError {
+                                verify_id: req_id,
+                            }
+                        }),
+                });
+                dispatcher.push(SnarkPoolCoordinatorAction::WorkVerifyPending {
+                    worker_id,
+                    verify_id: req_id,
+                });
+            }
+            SnarkPoolCoordinatorAction::WorkVerifyPending {
+                worker_id,
+                verify_id,
+            } => {
+                let Some(lease) = state.get_mut(*worker_id).and_then(|w| w.lease.as_mut()) else {
+                    return;
+                };
+                if let RemoteSnarkWorkerLeaseStatus::Submitted { snark } = &lease.status {
+                    lease.status = RemoteSnarkWorkerLeaseStatus::VerifyPending {
+                        snark: snark.clone(),
+                        verify_id: *verify_id,
+                    };
+                }
+            }
+            SnarkPoolCoordinatorAction::WorkVerifyError { verify_id } => {
+                let Some(worker) = state
+                    .verify_pending(*verify_id)
+                    .and_then(|worker_id| state.get_mut(worker_id))
+                else {
+                    return;
+                };
+                let Some(lease) = worker.lease.take() else {
+                    return;
+                };
+                worker.rejected += 1;
+                let snarker = worker.public_key.clone();
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(SnarkPoolAction::CommitmentRemove {
+                    job_id: lease.job_id,
+                    snarker,
+                });
+            }
+            SnarkPoolCoordinatorAction::WorkVerifySuccess { verify_id } => {
+                let Some(worker) = state
+                    .verify_pending(*verify_id)
+                    .and_then(|worker_id| state.get_mut(worker_id))
+                else {
+                    return;
+                };
+                let Some(RemoteSnarkWorkerLeaseStatus::VerifyPending { snark, .. }) =
+                    worker.lease.take().map(|lease| lease.status)
+                else {
+                    return;
+                };
+                worker.accepted += 1;
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                dispatcher.push(SnarkPoolAction::WorkAdd {
+                    snark,
+                    sender: global_state.p2p.my_id(),
+                });
+            }
+        }
+    }
+}
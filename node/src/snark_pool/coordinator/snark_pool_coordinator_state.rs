@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use mina_p2p_messages::v2::{CurrencyFeeStableV1, NonZeroCurvePoint};
+use openmina_core::snark::{Snark, SnarkJobId};
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::snark::work_verify::SnarkWorkVerifyId;
+
+pub type RemoteSnarkWorkerId = u64;
+
+/// Remote snark workers, registered with the node running in
+/// coordinator mode.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SnarkPoolCoordinatorState {
+    next_worker_id: RemoteSnarkWorkerId,
+    workers: BTreeMap<RemoteSnarkWorkerId, RemoteSnarkWorker>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteSnarkWorker {
+    pub public_key: NonZeroCurvePoint,
+    pub fee: CurrencyFeeStableV1,
+    pub registered_t: Timestamp,
+    pub last_heartbeat_t: Timestamp,
+    pub lease: Option<RemoteSnarkWorkerLease>,
+    /// Number of submitted snarks which passed verification.
+    pub accepted: usize,
+    /// Number of submitted snarks which failed verification.
+    pub rejected: usize,
+    /// Number of leases which expired or were lost.
+    pub expired: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemoteSnarkWorkerLease {
+    pub job_id: SnarkJobId,
+    pub time: Timestamp,
+    pub status: RemoteSnarkWorkerLeaseStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RemoteSnarkWorkerLeaseStatus {
+    Leased,
+    Submitted {
+        snark: Snark,
+    },
+    VerifyPending {
+        snark: Snark,
+        verify_id: SnarkWorkVerifyId,
+    },
+}
+
+impl SnarkPoolCoordinatorState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next_worker_id(&self) -> RemoteSnarkWorkerId {
+        self.next_worker_id
+    }
+
+    pub fn get(&self, worker_id: RemoteSnarkWorkerId) -> Option<&RemoteSnarkWorker> {
+        self.workers.get(&worker_id)
+    }
+
+    pub(super) fn get_mut(
+        &mut self,
+        worker_id: RemoteSnarkWorkerId,
+    ) -> Option<&mut RemoteSnarkWorker> {
+        self.workers.get_mut(&worker_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (RemoteSnarkWorkerId, &RemoteSnarkWorker)> {
+        self.workers.iter().map(|(id, worker)| (*id, worker))
+    }
+
+    pub fn is_job_leased(&self, job_id: &SnarkJobId) -> bool {
+        self.workers
+            .values()
+            .any(|worker| worker.leased_job_id() == Some(job_id))
+    }
+
+    pub fn verify_pending(&self, verify_id: SnarkWorkVerifyId) -> Option<RemoteSnarkWorkerId> {
+        self.iter()
+            .find(|(_, worker)| {
+                matches!(
+                    worker.lease.as_ref().map(|lease| &lease.status),
+                    Some(RemoteSnarkWorkerLeaseStatus::VerifyPending { verify_id: id, .. })
+                        if *id == verify_id
+                )
+            })
+            .map(|(id, _)| id)
+    }
+
+    pub(super) fn register(
+        &mut self,
+        time: Timestamp,
+        public_key: NonZeroCurvePoint,
+        fee: CurrencyFeeStableV1,
+    ) {
+        let worker = RemoteSnarkWorker {
+            public_key,
+            fee,
+            registered_t: time,
+            last_heartbeat_t: time,
+            lease: None,
+            accepted: 0,
+            rejected: 0,
+            expired: 0,
+        };
+        self.workers.insert(self.next_worker_id, worker);
+        self.next_worker_id += 1;
+    }
+
+    pub(super) fn remove(&mut self, worker_id: RemoteSnarkWorkerId) -> Option<RemoteSnarkWorker> {
+        self.workers.remove(&worker_id)
+    }
+}
+
+impl RemoteSnarkWorker {
+    pub fn leased_job_id(&self) -> Option<&SnarkJobId> {
+        self.lease.as_ref().map(|lease| &lease.job_id)
+    }
+
+    /// Whether the worker is still proving the leased job, so it hasn't
+    /// submitted it yet.
+    pub fn is_proving(&self) -> bool {
+        matches!(
+            self.lease.as_ref().map(|lease| &lease.status),
+            Some(RemoteSnarkWorkerLeaseStatus::Leased)
+        )
+    }
+
+    pub fn is_lease_timed_out(&self, time_now: Timestamp, timeout: Duration) -> bool {
+        self.is_proving()
+            && time_now
+                .checked_sub(self.last_heartbeat_t)
+                .map_or(false, |dur| dur >= timeout)
+    }
+
+    /// Worker has no lease and didn't lease a job or send a heartbeat
+    /// for `timeout`. Workers with a lease are first handled by the lease
+    /// timeout, so that the job gets released.
+    pub fn is_inactive(&self, time_now: Timestamp, timeout: Duration) -> bool {
+        self.lease.is_none()
+            && time_now
+                .checked_sub(self.last_heartbeat_t)
+                .map_or(false, |dur| dur >= timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mina_p2p_messages::v2::{
+        CurrencyFeeStableV1, NonZeroCurvePoint, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+    };
+    use redux::Timestamp;
+
+    use crate::account::AccountSecretKey;
+
+    use super::{RemoteSnarkWorkerLease, RemoteSnarkWorkerLeaseStatus, SnarkPoolCoordinatorState};
+
+    const SEC: u64 = 1_000_000_000;
+
+    fn register(state: &mut SnarkPoolCoordinatorState, time: u64) {
+        let public_key: NonZeroCurvePoint = AccountSecretKey::rand().public_key().into();
+        let fee = CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+            1_000_000u64.into(),
+        ));
+        state.register(Timestamp::new(time), public_key, fee);
+    }
+
+    #[test]
+    fn register_and_remove() {
+        let mut state = SnarkPoolCoordinatorState::new();
+        register(&mut state, 0);
+        register(&mut state, 0);
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.next_worker_id(), 2);
+
+        assert!(state.remove(0).is_some());
+        assert!(state.remove(0).is_none());
+        assert_eq!(state.len(), 1);
+
+        // Ids of removed workers aren't reused.
+        register(&mut state, 0);
+        assert_eq!(
+            state.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn worker_inactivity() {
+        let timeout = Duration::from_secs(60);
+        let mut state = SnarkPoolCoordinatorState::new();
+        register(&mut state, 10 * SEC);
+
+        let worker = state.get(0).unwrap();
+        assert!(!worker.is_inactive(Timestamp::new(10 * SEC), timeout));
+        assert!(!worker.is_inactive(Timestamp::new(69 * SEC), timeout));
+        assert!(worker.is_inactive(Timestamp::new(70 * SEC), timeout));
+        // Time before the last heartbeat.
+        assert!(!worker.is_inactive(Timestamp::new(0), timeout));
+
+        let worker = state.get_mut(0).unwrap();
+        worker.last_heartbeat_t = Timestamp::new(50 * SEC);
+        assert!(!worker.is_inactive(Timestamp::new(70 * SEC), timeout));
+
+        // Leased job must be released by the lease timeout first.
+        worker.lease = Some(RemoteSnarkWorkerLease {
+            job_id: "jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc->jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc"
+                .parse()
+                .unwrap(),
+            time: Timestamp::new(50 * SEC),
+            status: RemoteSnarkWorkerLeaseStatus::Leased,
+        });
+        let worker = state.get(0).unwrap();
+        assert!(!worker.is_inactive(Timestamp::new(200 * SEC), timeout));
+        assert!(worker.is_lease_timed_out(Timestamp::new(200 * SEC), timeout));
+    }
+}
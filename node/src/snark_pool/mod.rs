@@ -1,4 +1,5 @@
 pub mod candidate;
+pub mod coordinator;
 
 mod snark_pool_config;
 pub use snark_pool_config::*;
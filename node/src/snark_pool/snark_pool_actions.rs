@@ -1,11 +1,15 @@
+use std::collections::BTreeSet;
+
 use ledger::scan_state::scan_state::transaction_snark::OneOrTwo;
 use ledger::scan_state::scan_state::AvailableJobMessage;
+use mina_p2p_messages::v2::NonZeroCurvePoint;
 use openmina_core::snark::{Snark, SnarkJobCommitment, SnarkJobId};
 use serde::{Deserialize, Serialize};
 
 use crate::p2p::PeerId;
 
 use super::candidate::SnarkPoolCandidateAction;
+use super::coordinator::SnarkPoolCoordinatorAction;
 use super::SnarkWork;
 
 pub type SnarkPoolActionWithMeta = redux::ActionWithMeta<SnarkPoolAction>;
@@ -14,10 +18,13 @@ pub type SnarkPoolActionWithMetaRef<'a> = redux::ActionWithMeta<&'a SnarkPoolAct
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SnarkPoolAction {
     Candidate(SnarkPoolCandidateAction),
+    Coordinator(SnarkPoolCoordinatorAction),
 
     JobsUpdate {
         jobs: Vec<OneOrTwo<AvailableJobMessage>>,
         orphaned_snarks: Vec<SnarkWork>,
+        /// Jobs whose work is included in the new best tip.
+        included_jobs: BTreeSet<SnarkJobId>,
     },
     AutoCreateCommitment,
     CommitmentCreateMany {
@@ -42,12 +49,26 @@ pub enum SnarkPoolAction {
     JobCommitmentTimeout {
         job_id: SnarkJobId,
     },
+    /// Remove commitment made by `snarker`, making the job available again.
+    CommitmentRemove {
+        job_id: SnarkJobId,
+        snarker: NonZeroCurvePoint,
+    },
+    /// Persist completed work, so that it survives restarts.
+    Persist,
+    /// Send our own snarks to the peers again.
+    Rebroadcast {
+        job_ids: Vec<SnarkJobId>,
+    },
+    /// Drop jobs and snarks of the chain which ended with the hard fork.
+    Reset,
 }
 
 impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
     fn is_enabled(&self, state: &crate::State, time: redux::Timestamp) -> bool {
         match self {
             SnarkPoolAction::Candidate(action) => action.is_enabled(state, time),
+            SnarkPoolAction::Coordinator(action) => action.is_enabled(state, time),
             SnarkPoolAction::AutoCreateCommitment => state
                 .config
                 .snarker
@@ -55,24 +76,21 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
                 .map_or(false, |v| v.auto_commit),
             SnarkPoolAction::CommitmentCreateMany { .. } => state.config.snarker.is_some(),
             SnarkPoolAction::CommitmentCreate { job_id } => {
-                state.config.snarker.is_some() && state.snark_pool.should_create_commitment(job_id)
+                state.config.snarker.as_ref().map_or(false, |config| {
+                    state.snark_pool.commitment_fee(job_id, config).is_some()
+                })
             }
             SnarkPoolAction::CommitmentAdd { commitment, .. } => state
                 .snark_pool
                 .get(&commitment.job_id)
+                .filter(|s| !s.is_commitment_expired(commitment, time))
                 .map_or(false, |s| match s.commitment.as_ref() {
                     Some(cur) => commitment > &cur.commitment,
                     None => true,
                 }),
-            SnarkPoolAction::WorkAdd { snark, .. } => {
-                state
-                    .snark_pool
-                    .get(&snark.job_id())
-                    .map_or(false, |s| match s.snark.as_ref() {
-                        Some(cur) => snark > &cur.work,
-                        None => true,
-                    })
-            }
+            SnarkPoolAction::WorkAdd { snark, .. } => state
+                .snark_pool
+                .is_snark_replacement(&snark.job_id(), snark.fee.0.as_u64()),
             SnarkPoolAction::P2pSend { peer_id } => state
                 .p2p
                 .get_ready_peer(peer_id)
@@ -108,7 +126,15 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
             SnarkPoolAction::JobCommitmentTimeout { job_id } => {
                 state.snark_pool.is_commitment_timed_out(job_id, time)
             }
+            SnarkPoolAction::CommitmentRemove { job_id, snarker } => state
+                .snark_pool
+                .get(job_id)
+                .and_then(|job| job.commitment.as_ref())
+                .map_or(false, |c| &c.commitment.snarker == snarker),
+            SnarkPoolAction::Persist => state.snark_pool.should_persist(time),
+            SnarkPoolAction::Rebroadcast { .. } => state.snark_pool.should_rebroadcast(time),
             SnarkPoolAction::JobsUpdate { .. } => true,
+            SnarkPoolAction::Reset => true,
             SnarkPoolAction::P2pSendAll => true,
         }
     }
@@ -123,6 +149,9 @@ pub enum SnarkPoolEffectfulAction {
         count: usize,
         on_result: redux::Callback<Vec<SnarkJobId>>,
     },
+    /// Completed work is read from the state by the effect, so that the
+    /// proofs aren't cloned into the action.
+    SnarkPoolPersist,
 }
 
 pub type SnarkPoolEffectfulActionWithMeta = redux::ActionWithMeta<SnarkPoolEffectfulAction>;
@@ -1,4 +1,42 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct SnarkPoolConfig {}
+#[serde(default)]
+pub struct SnarkPoolConfig {
+    /// Snark replaces the existing one for the same job only if its fee
+    /// is lower by at least this percentage of the existing fee (and by
+    /// at least 1 nanomina).
+    pub replace_min_fee_decrease_percent: u64,
+    /// Max number of snarks kept in the pool. Once exceeded, snarks of
+    /// the lowest value are evicted, see [`super::SnarkPoolState::evict_snarks`].
+    pub max_snarks: usize,
+    /// How often snarks produced by us are rebroadcast to peers.
+    pub rebroadcast_interval: Duration,
+}
+
+impl Default for SnarkPoolConfig {
+    fn default() -> Self {
+        Self {
+            replace_min_fee_decrease_percent: 5,
+            max_snarks: 10_000,
+            rebroadcast_interval: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl SnarkPoolConfig {
+    /// Highest fee with which a snark replaces the existing one with
+    /// `fee`, `None` if it can't be replaced.
+    pub fn max_replacement_fee(&self, fee: u64) -> Option<u64> {
+        let decrease = fee.saturating_mul(self.replace_min_fee_decrease_percent) / 100;
+        fee.checked_sub(decrease.max(1))
+    }
+
+    /// Whether a snark with `new_fee` replaces the one with `old_fee`.
+    pub fn is_replacement_fee(&self, old_fee: u64, new_fee: u64) -> bool {
+        self.max_replacement_fee(old_fee)
+            .map_or(false, |max_fee| new_fee <= max_fee)
+    }
+}
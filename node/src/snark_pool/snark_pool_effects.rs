@@ -17,5 +17,14 @@ pub fn snark_pool_effects<S: Service>(
             let job_ids = store.service.random_choose(choices.iter(), count);
             store.dispatch_callback(on_result, job_ids);
         }
+        SnarkPoolEffectfulAction::SnarkPoolPersist => {
+            let snarks = store
+                .state()
+                .snark_pool
+                .completed_works_iter()
+                .cloned()
+                .collect();
+            store.service.persist_snarks(snarks);
+        }
     }
 }
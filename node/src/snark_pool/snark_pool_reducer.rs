@@ -7,6 +7,7 @@ use p2p::channels::{
 
 use crate::{snark_pool::JobCommitment, ExternalSnarkWorkerAction, SnarkerStrategy};
 
+use super::coordinator::{is_lease_lost, SnarkPoolCoordinatorAction};
 use super::{
     JobState, SnarkPoolAction, SnarkPoolActionWithMetaRef, SnarkPoolEffectfulAction,
     SnarkPoolState, SnarkWork,
@@ -30,9 +31,16 @@ impl SnarkPoolState {
                     meta.with_action(action),
                 );
             }
+            SnarkPoolAction::Coordinator(action) => {
+                super::coordinator::SnarkPoolCoordinatorState::reducer(
+                    crate::Substate::from_compatible_substate(state_context),
+                    meta.with_action(action),
+                );
+            }
             SnarkPoolAction::JobsUpdate {
                 jobs,
                 orphaned_snarks,
+                included_jobs,
             } => {
                 let mut jobs_map = jobs
                     .iter()
@@ -40,7 +48,9 @@ impl SnarkPoolState {
                     .map(|(index, job)| (SnarkJobId::from(job), (index, job.clone())))
                     .collect::<BTreeMap<_, _>>();
 
-                state.retain(|id| jobs_map.remove(id).map(|(order, _)| order));
+                let removed_jobs = state.retain(|id| jobs_map.remove(id).map(|(order, _)| order));
+                let removed_jobs = removed_jobs.iter().map(|job| (&job.id, job.time));
+                state.jobs_included(removed_jobs, included_jobs, meta.time());
                 for (id, (order, job)) in jobs_map {
                     state.insert(JobState {
                         time: meta.time(),
@@ -48,37 +58,57 @@ impl SnarkPoolState {
                         job,
                         commitment: None,
                         snark: None,
+                        evicted_snark: None,
                         order,
                     });
                 }
 
                 let orphaned_snarks = orphaned_snarks
                     .iter()
-                    .map(|snark| (snark.work.job_id(), snark.clone()));
-
+                    .cloned()
+                    .map(|snark| (snark.work.job_id(), snark));
                 for (id, snark) in orphaned_snarks {
-                    let take = state
-                        .get(&id)
-                        .and_then(|job| job.snark.as_ref())
-                        .map_or(true, |old_snark| snark.work > old_snark.work);
-                    if take {
+                    if state.is_snark_replacement(&id, snark.work.fee.0.as_u64()) {
                         if let Some(mut job) = state.remove(&id) {
-                            job.snark = Some(snark.clone());
+                            job.snark = Some(snark);
+                            job.evicted_snark = None;
                             state.insert(job);
                         }
                     }
                 }
 
+                state.evict_snarks();
                 state.candidates_prune();
 
+                // Snarks restored from the previous run were verified
+                // before they were persisted, so the ones still needed by
+                // the scan state are added without verifying them again.
+                // Those no longer needed are dropped.
+                let restored_snarks = state
+                    .take_restored_snarks()
+                    .into_iter()
+                    .filter(|snark| {
+                        let job_id = snark.work.job_id();
+                        state.is_snark_replacement(&job_id, snark.work.fee.0.as_u64())
+                    })
+                    .collect::<Vec<_>>();
+
                 // Dispatch
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if let Some(job_id) = global_state.external_snark_worker.working_job_id() {
-                    if !global_state.snark_pool.contains(job_id) {
-                        // job is no longer needed.
-                        dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
-                    }
-                } else {
+                for snark in restored_snarks {
+                    dispatcher.push(SnarkPoolAction::WorkAdd {
+                        snark: snark.work,
+                        sender: snark.sender,
+                    });
+                }
+                // Cancel jobs that are no longer needed.
+                for worker_id in global_state
+                    .external_snark_worker
+                    .working_on_unneeded_job(|job_id| global_state.snark_pool.contains(job_id))
+                {
+                    dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
+                }
+                if global_state.external_snark_worker.has_idle() {
                     dispatcher.push(SnarkPoolAction::AutoCreateCommitment);
                 }
             }
@@ -116,6 +146,24 @@ impl SnarkPoolState {
                                 ),
                             });
                         }
+                        SnarkerStrategy::Profit => {
+                            let demand = global_state
+                                .transition_frontier
+                                .best_tip()
+                                .map_or(0, |tip| tip.completed_works_iter().count());
+                            let job_ids = global_state
+                                .snark_pool
+                                .profitable_jobs(
+                                    snarker_config,
+                                    demand,
+                                    meta.time(),
+                                    available_workers,
+                                )
+                                .into_iter()
+                                .map(|job| job.id.clone())
+                                .collect();
+                            dispatcher.push(SnarkPoolAction::CommitmentCreateMany { job_ids });
+                        }
                     }
                 };
             }
@@ -131,22 +179,27 @@ impl SnarkPoolState {
                 let Some(summary) = global_state.snark_pool.job_summary(&job_id) else {
                     return;
                 };
+                let Some(config) = global_state.config.snarker.as_ref() else {
+                    return;
+                };
+                let Some(fee) = global_state.snark_pool.commitment_fee(&job_id, config) else {
+                    return;
+                };
 
-                if global_state.external_snark_worker.is_idle() {
+                if let Some(worker_id) = global_state.external_snark_worker.idle_worker() {
                     dispatcher.push(ExternalSnarkWorkerAction::SubmitWork {
+                        worker_id,
                         job_id: job_id.clone(),
                         summary,
+                        fee: fee.clone(),
                     });
 
                     let timestamp_ms = meta.time_as_nanos() / 1_000_000;
-                    let Some(config) = global_state.config.snarker.as_ref() else {
-                        return;
-                    };
                     dispatcher.push(SnarkPoolAction::CommitmentAdd {
                         commitment: SnarkJobCommitment::new(
                             timestamp_ms,
                             job_id,
-                            config.fee.clone(),
+                            fee,
                             config.public_key.clone().into(),
                         ),
                         sender: global_state.p2p.my_id(),
@@ -167,14 +220,21 @@ impl SnarkPoolState {
                 // Dispatch
                 let commitment = commitment.clone();
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if let Some(job_id) = global_state.external_snark_worker.working_job_id() {
-                    let Some(config) = global_state.config.snarker.as_ref() else {
-                        return;
-                    };
-                    if &commitment.job_id == job_id
-                        && &commitment.snarker != config.public_key.as_ref()
+                // Commitments received over libp2p are already relayed by pubsub.
+                if !global_state.p2p.is_libp2p_peer(sender) {
+                    dispatcher.push(P2pChannelsSnarkJobCommitmentAction::Libp2pBroadcast {
+                        commitment: commitment.clone(),
+                    });
+                }
+                let Some(config) = global_state.config.snarker.as_ref() else {
+                    return;
+                };
+                if &commitment.snarker != config.public_key.as_ref() {
+                    for (worker_id, job_id) in global_state.external_snark_worker.working_job_ids()
                     {
-                        dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
+                        if &commitment.job_id == job_id {
+                            dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
+                        }
                     }
                 }
             }
@@ -183,21 +243,27 @@ impl SnarkPoolState {
                 let Some(mut job) = state.remove(&job_id) else {
                     return;
                 };
+                if job.snark_fee_and_prover().is_none() {
+                    state.job_proven(&job, meta.time());
+                }
                 job.snark = Some(SnarkWork {
                     work: snark.clone(),
                     received_t: meta.time(),
                     sender: *sender,
                 });
+                job.evicted_snark = None;
                 state.insert(job);
+                state.evict_snarks();
                 state.candidates.remove_inferior_snarks(snark);
 
                 // Dispatch
                 let snark = snark.clone();
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if let Some(job_id) = global_state
+                let snark_job_id = snark.job_id();
+                for (worker_id, job_id) in global_state
                     .external_snark_worker
-                    .working_job_id()
-                    .filter(|job_id| *job_id == &snark.job_id())
+                    .working_job_ids()
+                    .filter(|(_, job_id)| *job_id == &snark_job_id)
                 {
                     if let Some(commitment) = global_state
                         .snark_pool
@@ -205,7 +271,7 @@ impl SnarkPoolState {
                         .and_then(|job| job.commitment.as_ref())
                     {
                         if snark > commitment.commitment {
-                            dispatcher.push(ExternalSnarkWorkerAction::CancelWork);
+                            dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
                         }
                     }
                 }
@@ -271,10 +337,81 @@ impl SnarkPoolState {
                 for job_id in timed_out_ids {
                     dispatcher.push(SnarkPoolAction::JobCommitmentTimeout { job_id });
                 }
+                dispatcher.push(SnarkPoolAction::Persist);
+
+                if global_state.snark_pool.should_rebroadcast(meta.time()) {
+                    let my_id = global_state.p2p.my_id();
+                    let job_ids = global_state
+                        .snark_pool
+                        .jobs_iter()
+                        .filter(|job| job.snark.as_ref().map_or(false, |s| s.sender == my_id))
+                        .map(|job| job.id.clone())
+                        .collect();
+                    dispatcher.push(SnarkPoolAction::Rebroadcast { job_ids });
+                }
+
+                let coordinator = &global_state.snark_pool.coordinator;
+                if let Some(config) = global_state.config.snark_coordinator.as_ref() {
+                    let released = coordinator
+                        .iter()
+                        .filter(|(_, worker)| {
+                            worker.is_lease_timed_out(meta.time(), config.lease_timeout)
+                                || (worker.is_proving() && is_lease_lost(global_state, worker))
+                        })
+                        .map(|(worker_id, _)| worker_id)
+                        .collect::<Vec<_>>();
+                    for worker_id in released {
+                        dispatcher.push(SnarkPoolCoordinatorAction::LeaseRelease { worker_id });
+                    }
+
+                    let inactive = coordinator
+                        .iter()
+                        .filter(|(_, worker)| {
+                            worker.is_inactive(meta.time(), config.worker_timeout)
+                        })
+                        .map(|(worker_id, _)| worker_id)
+                        .collect::<Vec<_>>();
+                    for worker_id in inactive {
+                        dispatcher.push(SnarkPoolCoordinatorAction::WorkerRemove { worker_id });
+                    }
+                }
+            }
+            SnarkPoolAction::Reset => {
+                state.reset();
+
+                // Dispatch
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                for (worker_id, _) in global_state.external_snark_worker.working_job_ids() {
+                    dispatcher.push(ExternalSnarkWorkerAction::CancelWork { worker_id });
+                }
+            }
+            SnarkPoolAction::Rebroadcast { job_ids } => {
+                state.last_rebroadcast = Some(meta.time());
+                let snarks = state.rebroadcast(job_ids);
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                for snark in snarks {
+                    dispatcher.push(P2pChannelsSnarkAction::Libp2pBroadcast { snark, nonce: 0 });
+                }
+            }
+            SnarkPoolAction::Persist => {
+                state.persisted(meta.time());
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(SnarkPoolEffectfulAction::SnarkPoolPersist);
             }
             SnarkPoolAction::JobCommitmentTimeout { job_id } => {
                 state.remove_commitment(job_id);
 
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(SnarkPoolAction::AutoCreateCommitment);
+            }
+            SnarkPoolAction::CommitmentRemove { job_id, .. } => {
+                state.remove_commitment(job_id);
+
                 // Dispatch
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(SnarkPoolAction::AutoCreateCommitment);
@@ -1,9 +1,15 @@
 use crate::core::snark::SnarkJobId;
 
+use super::SnarkWork;
+
 pub trait SnarkPoolService: redux::Service {
     fn random_choose<'a>(
         &mut self,
         iter: impl Iterator<Item = &'a SnarkJobId>,
         n: usize,
     ) -> Vec<SnarkJobId>;
+
+    /// Persist completed work in the pool, replacing previously
+    /// persisted one.
+    fn persist_snarks(&mut self, snarks: Vec<SnarkWork>);
 }
@@ -1,14 +1,23 @@
 use std::time::Duration;
-use std::{collections::BTreeMap, fmt, ops::RangeBounds};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt,
+    ops::RangeBounds,
+};
 
 use ledger::scan_state::scan_state::{transaction_snark::OneOrTwo, AvailableJobMessage};
+use mina_p2p_messages::v2::{
+    CurrencyFeeStableV1, NonZeroCurvePoint, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+};
 use openmina_core::snark::{Snark, SnarkInfo, SnarkJobCommitment, SnarkJobId};
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
 use crate::p2p::PeerId;
+use crate::{SnarkerConfig, SnarkerStrategy};
 
 use super::candidate::SnarkPoolCandidatesState;
+use super::coordinator::SnarkPoolCoordinatorState;
 use super::SnarkPoolConfig;
 
 #[derive(Clone)]
@@ -18,9 +27,27 @@ pub struct SnarkPoolState {
     list: BTreeMap<u64, JobState>,
     by_ledger_hash_index: BTreeMap<SnarkJobId, u64>,
     pub candidates: SnarkPoolCandidatesState,
+    pub coordinator: SnarkPoolCoordinatorState,
     pub(super) last_check_timeouts: Timestamp,
+    /// Snarks persisted by the previous run of the node, waiting to be
+    /// reconciled with the scan state on the next jobs update.
+    restored_snarks: Vec<SnarkWork>,
+    /// Time and `counter` of the last persist of the completed work.
+    last_persist: Option<(Timestamp, u64)>,
+    pub(super) last_rebroadcast: Option<Timestamp>,
+    wait_times: SnarkPoolWaitTimes,
 }
 
+/// How often completed work in the pool is persisted, if it changed.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many of the latest wait times are kept.
+const WAIT_TIMES_LIMIT: usize = 1024;
+
+/// How far in the future commitment's timestamp can be, to allow for
+/// clock differences between nodes.
+const COMMITMENT_TIMESTAMP_MAX_SKEW: Duration = Duration::from_secs(30);
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JobState {
     pub time: Timestamp,
@@ -28,6 +55,11 @@ pub struct JobState {
     pub job: OneOrTwo<AvailableJobMessage>,
     pub commitment: Option<JobCommitment>,
     pub snark: Option<SnarkWork>,
+    /// Snark which was dropped because the pool was full. Job stays done,
+    /// so that it isn't proven again, and the snark can only be replaced
+    /// as if it was still in the pool.
+    #[serde(default)]
+    pub evicted_snark: Option<SnarkInfo>,
     /// Lower order has higher priority to be done as it represents older job.
     pub order: usize,
 }
@@ -46,6 +78,26 @@ pub struct SnarkWork {
     pub sender: PeerId,
 }
 
+/// Wait times of the latest jobs, in nanoseconds since the job was added
+/// to the pool.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SnarkPoolWaitTimes {
+    /// Until the first snark for the job was received.
+    pub until_proven: VecDeque<u64>,
+    /// Until the job's work got included in the best tip.
+    pub until_included: VecDeque<u64>,
+}
+
+impl SnarkPoolWaitTimes {
+    fn push(list: &mut VecDeque<u64>, since: Timestamp, time: Timestamp) {
+        let wait = time.checked_sub(since).unwrap_or_default();
+        list.push_back(wait.as_nanos() as u64);
+        if list.len() > WAIT_TIMES_LIMIT {
+            list.pop_front();
+        }
+    }
+}
+
 /// Whether the job is a merge proof job, or a transaction proof job, with particular number of account updates.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobSummary {
@@ -62,15 +114,95 @@ impl Default for SnarkPoolState {
 impl SnarkPoolState {
     pub fn new() -> Self {
         Self {
-            config: SnarkPoolConfig {},
+            config: SnarkPoolConfig::default(),
             counter: 0,
             list: Default::default(),
             by_ledger_hash_index: Default::default(),
             candidates: SnarkPoolCandidatesState::new(),
+            coordinator: SnarkPoolCoordinatorState::new(),
             last_check_timeouts: Timestamp::ZERO,
+            restored_snarks: Vec::new(),
+            last_persist: None,
+            last_rebroadcast: None,
+            wait_times: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> &SnarkPoolConfig {
+        &self.config
+    }
+
+    pub fn wait_times(&self) -> &SnarkPoolWaitTimes {
+        &self.wait_times
+    }
+
+    pub(super) fn job_proven(&mut self, job: &JobState, time: Timestamp) {
+        SnarkPoolWaitTimes::push(&mut self.wait_times.until_proven, job.time, time);
+    }
+
+    /// Records wait times of the jobs removed from the pool, given as job
+    /// id and the time the job was added, whose work got included in the
+    /// new best tip. Others are removed because the best chain switched
+    /// to a fork or the scan state no longer needs them.
+    pub(super) fn jobs_included<'a>(
+        &mut self,
+        removed_jobs: impl IntoIterator<Item = (&'a SnarkJobId, Timestamp)>,
+        included_jobs: &BTreeSet<SnarkJobId>,
+        time: Timestamp,
+    ) {
+        let list = &mut self.wait_times.until_included;
+        for (id, job_time) in removed_jobs {
+            if included_jobs.contains(id) {
+                SnarkPoolWaitTimes::push(list, job_time, time);
+            }
+        }
+    }
+
+    /// Restore completed work persisted by the previous run of the node.
+    ///
+    /// Only verified snarks are persisted. They are kept aside until the
+    /// next jobs update, which adds the ones still needed by the scan
+    /// state to the pool.
+    pub fn restore_snarks(&mut self, snarks: Vec<SnarkWork>) {
+        self.restored_snarks = snarks;
+    }
+
+    pub(super) fn take_restored_snarks(&mut self) -> Vec<SnarkWork> {
+        std::mem::take(&mut self.restored_snarks)
+    }
+
+    /// Whether completed work should be persisted now. It isn't until
+    /// restored snarks are reconciled, so that they aren't overwritten
+    /// by the empty pool.
+    pub fn should_persist(&self, time_now: Timestamp) -> bool {
+        if !self.restored_snarks.is_empty() {
+            return false;
+        }
+        match self.last_persist {
+            None => self.counter > 0,
+            Some((time, counter)) => {
+                counter != self.counter
+                    && time_now
+                        .checked_sub(time)
+                        .map_or(false, |dur| dur >= PERSIST_INTERVAL)
+            }
         }
     }
 
+    pub(super) fn persisted(&mut self, time: Timestamp) {
+        self.last_persist = Some((time, self.counter));
+    }
+
+    /// Removes all jobs with their commitments and snarks, and candidate
+    /// snarks. `counter` keeps growing, as peers track what was sent to
+    /// them by it.
+    pub(super) fn reset(&mut self) {
+        self.list.clear();
+        self.by_ledger_hash_index.clear();
+        self.candidates = SnarkPoolCandidatesState::new();
+        self.restored_snarks.clear();
+    }
+
     pub fn last_index(&self) -> u64 {
         self.list.last_key_value().map_or(0, |(k, _)| *k)
     }
@@ -111,15 +243,18 @@ impl SnarkPoolState {
         self.list.get_mut(index)?.commitment.take()
     }
 
-    pub fn retain<F>(&mut self, mut get_new_job_order: F)
+    /// Keeps only the jobs for which `get_new_job_order` returns the new
+    /// order. Returns the removed jobs.
+    pub fn retain<F>(&mut self, mut get_new_job_order: F) -> Vec<JobState>
     where
         F: FnMut(&SnarkJobId) -> Option<usize>,
     {
         let list = &mut self.list;
+        let mut removed = Vec::new();
         self.by_ledger_hash_index
             .retain(|id, index| match get_new_job_order(id) {
                 None => {
-                    list.remove(index);
+                    removed.extend(list.remove(index));
                     false
                 }
                 Some(order) => {
@@ -131,6 +266,7 @@ impl SnarkPoolState {
                     }
                 }
             });
+        removed
     }
 
     pub fn range<R>(&self, range: R) -> impl '_ + DoubleEndedIterator<Item = (u64, &'_ JobState)>
@@ -144,6 +280,65 @@ impl SnarkPoolState {
         self.get(job_id).map_or(false, |s| s.is_available())
     }
 
+    /// Whether snark with the `fee` should be added to the pool, replacing
+    /// the existing one for the job if there is one.
+    pub fn is_snark_replacement(&self, job_id: &SnarkJobId, fee: u64) -> bool {
+        self.get(job_id)
+            .map_or(false, |job| match job.snark_fee_and_prover() {
+                None => true,
+                Some((cur_fee, _)) => self.config.is_replacement_fee(cur_fee, fee),
+            })
+    }
+
+    /// Once there are more than `config.max_snarks` snarks, evicts the
+    /// ones of the lowest value: for jobs which will be needed last, and
+    /// of them the most expensive ones. Evicts down to 90% of the limit,
+    /// so that it doesn't run again on every added snark.
+    ///
+    /// Jobs of the evicted snarks aren't made available again, see
+    /// [`JobState::evicted_snark`].
+    pub(super) fn evict_snarks(&mut self) {
+        let count = self.completed_works_iter().count();
+        if count <= self.config.max_snarks {
+            return;
+        }
+        let snarks = self
+            .list
+            .iter()
+            .filter_map(|(index, job)| {
+                let fee = job.snark.as_ref()?.work.fee.0.as_u64();
+                Some((job.order, fee, *index))
+            })
+            .collect();
+        for index in snarks_to_evict(snarks, self.config.max_snarks) {
+            if let Some(job) = self.list.get_mut(&index) {
+                job.evicted_snark = job.snark.take().map(|snark| snark.work.info());
+            }
+        }
+    }
+
+    pub fn should_rebroadcast(&self, time_now: Timestamp) -> bool {
+        self.last_rebroadcast.map_or(true, |time| {
+            time_now
+                .checked_sub(time)
+                .map_or(false, |dur| dur >= self.config.rebroadcast_interval)
+        })
+    }
+
+    /// Moves the jobs to the end of the list, so that they get sent to
+    /// the webrtc peers again. Returns their snarks, to be broadcast over
+    /// libp2p.
+    pub(super) fn rebroadcast(&mut self, job_ids: &[SnarkJobId]) -> Vec<Snark> {
+        let mut snarks = Vec::new();
+        for job_id in job_ids {
+            if let Some(job) = self.remove(job_id) {
+                snarks.extend(job.snark.as_ref().map(|snark| snark.work.clone()));
+                self.insert(job);
+            }
+        }
+        snarks
+    }
+
     pub fn is_commitment_timed_out(&self, id: &SnarkJobId, time_now: Timestamp) -> bool {
         self.by_ledger_hash_index.get(id).map_or(false, |i| {
             self.is_commitment_timed_out_by_index(i, time_now)
@@ -157,6 +352,10 @@ impl SnarkPoolState {
         let Some(commitment) = job.commitment.as_ref() else {
             return false;
         };
+        if self.coordinator.is_job_leased(&job.id) {
+            // Remote worker's lease is kept alive by its heartbeats instead.
+            return false;
+        }
 
         let timeout = job.estimated_duration();
         let passed_time = time_now.checked_sub(commitment.commitment.timestamp());
@@ -200,6 +399,53 @@ impl SnarkPoolState {
             })
     }
 
+    /// Up to `n` jobs, most profitable first, that [`SnarkerStrategy::Profit`]
+    /// would commit to. `demand` is the number of jobs included in the best
+    /// tip, see [`JobState::profit_score`].
+    ///
+    /// [`SnarkerStrategy::Profit`]: crate::SnarkerStrategy::Profit
+    pub fn profitable_jobs(
+        &self,
+        config: &SnarkerConfig,
+        demand: usize,
+        time_now: Timestamp,
+        n: usize,
+    ) -> Vec<&JobState> {
+        let mut jobs = self
+            .jobs_iter()
+            .filter_map(|job| {
+                let score = job.profit_score(config, &self.config, demand, time_now)?;
+                Some((score, job))
+            })
+            .collect::<Vec<_>>();
+        jobs.sort_by(|(score1, job1), (score2, job2)| {
+            score2.cmp(score1).then(job1.order.cmp(&job2.order))
+        });
+        jobs.into_iter().take(n).map(|(_, job)| job).collect()
+    }
+
+    /// Fee with which we would commit to the job, or `None` if we
+    /// shouldn't commit to it.
+    pub fn commitment_fee(
+        &self,
+        job_id: &SnarkJobId,
+        config: &SnarkerConfig,
+    ) -> Option<CurrencyFeeStableV1> {
+        let job = self.get(job_id)?;
+        match config.strategy {
+            SnarkerStrategy::Profit => job.profit_fee(config, &self.config).map(|fee| {
+                CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+                    fee.into(),
+                ))
+            }),
+            _ => job.is_available().then(|| config.fee.clone()),
+        }
+    }
+
+    pub fn completed_works_iter(&self) -> impl '_ + Iterator<Item = &'_ SnarkWork> {
+        self.list.values().filter_map(|job| job.snark.as_ref())
+    }
+
     pub fn completed_snarks_iter(&self) -> impl '_ + Iterator<Item = &'_ Snark> {
         self.list
             .iter()
@@ -214,17 +460,31 @@ impl SnarkPoolState {
     pub fn candidates_prune(&mut self) {
         self.candidates.retain(|id| {
             let job = Self::get_by_job_id(&self.by_ledger_hash_index, &self.list, id);
+            let config = &self.config;
             move |candidate| match job {
                 None => false,
-                Some(job) => match job.snark.as_ref() {
+                Some(job) => match job.snark_fee_and_prover() {
                     None => true,
-                    Some(snark) => &snark.work < candidate,
+                    Some((fee, _)) => config.is_replacement_fee(fee, candidate.fee()),
                 },
             }
         });
     }
 }
 
+/// Indexes of the snarks, given as `(job order, fee, index)`, to evict
+/// so that 90% of `max_snarks` are left. Not sorted.
+fn snarks_to_evict(mut snarks: Vec<(usize, u64, u64)>, max_snarks: usize) -> Vec<u64> {
+    let keep = max_snarks - max_snarks / 10;
+    let Some(excess) = snarks.len().checked_sub(keep).filter(|n| *n > 0) else {
+        return Vec::new();
+    };
+    // Partitions the lowest value snarks to the front, without sorting.
+    snarks.select_nth_unstable_by(excess - 1, |a, b| b.cmp(a));
+    snarks.truncate(excess);
+    snarks.into_iter().map(|(_, _, index)| index).collect()
+}
+
 impl fmt::Debug for SnarkPoolState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("JobCommitments")
@@ -236,7 +496,16 @@ impl fmt::Debug for SnarkPoolState {
 
 impl JobState {
     pub fn is_available(&self) -> bool {
-        self.commitment.is_none() && self.snark.is_none()
+        self.commitment.is_none() && self.snark.is_none() && self.evicted_snark.is_none()
+    }
+
+    /// Fee and prover of the snark for this job, also if it was evicted.
+    pub fn snark_fee_and_prover(&self) -> Option<(u64, &NonZeroCurvePoint)> {
+        match (&self.snark, &self.evicted_snark) {
+            (Some(snark), _) => Some((snark.work.fee.0.as_u64(), &snark.work.snarker)),
+            (None, Some(info)) => Some((info.fee.0.as_u64(), &info.prover)),
+            (None, None) => None,
+        }
     }
 
     pub fn commitment_msg(&self) -> Option<&SnarkJobCommitment> {
@@ -280,6 +549,149 @@ impl JobState {
     pub fn estimated_duration(&self) -> Duration {
         self.summary().estimated_duration()
     }
+
+    /// Whether the commitment is older than the time needed to do this job,
+    /// in which case it would time out right away, so it isn't worth
+    /// accepting or relaying further.
+    ///
+    /// Commitment from too far in the future is treated as expired too,
+    /// otherwise it would never time out.
+    pub fn is_commitment_expired(
+        &self,
+        commitment: &SnarkJobCommitment,
+        time_now: Timestamp,
+    ) -> bool {
+        is_commitment_expired(commitment.timestamp(), self.estimated_duration(), time_now)
+    }
+
+    /// Fee for which we can still win this job: just below the cheapest
+    /// competing commitment or snark, but no more than `config.fee` and no
+    /// less than `config.min_fee`. `None` if we already have a commitment
+    /// or a snark for it, or if it can't be won above the minimum.
+    pub fn profit_fee(&self, config: &SnarkerConfig, pool_config: &SnarkPoolConfig) -> Option<u64> {
+        let our_key = config.public_key.as_ref();
+        let commitment = self.commitment_msg();
+        let snark = self.snark_fee_and_prover();
+        if commitment.map_or(false, |c| &c.snarker == our_key)
+            || snark.map_or(false, |(_, prover)| prover == our_key)
+        {
+            return None;
+        }
+
+        let max_fee = config.fee.0.as_u64();
+        let min_fee = config
+            .min_fee
+            .as_ref()
+            .map_or(max_fee, |fee| fee.0.as_u64());
+        job_profit_fee(
+            max_fee,
+            min_fee,
+            commitment.map(|c| c.fee.0.as_u64()),
+            snark.map(|(fee, _)| fee),
+            pool_config,
+        )
+    }
+
+    /// Expected profit per second of proving, relative to other jobs.
+    /// `None` if the job can't be won.
+    ///
+    /// `demand` is the number of jobs included in the best tip. Jobs are
+    /// included into a block in their order, so the next block most likely
+    /// needs about as many jobs with the lowest order, which makes them
+    /// worth more than the rest.
+    pub fn profit_score(
+        &self,
+        config: &SnarkerConfig,
+        pool_config: &SnarkPoolConfig,
+        demand: usize,
+        time_now: Timestamp,
+    ) -> Option<u64> {
+        let fee = self.profit_fee(config, pool_config)?;
+        let competition = match (self.snark_fee_and_prover(), &self.commitment) {
+            (Some(_), _) => JobCompetition::Snark,
+            (None, Some(_)) => JobCompetition::Commitment,
+            (None, None) => JobCompetition::None,
+        };
+        Some(job_profit_score(
+            fee,
+            self.order < demand,
+            time_now.checked_sub(self.time).unwrap_or_default(),
+            competition,
+            self.estimated_duration(),
+        ))
+    }
+}
+
+/// Best existing offer for a job, which we would have to outbid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobCompetition {
+    None,
+    Commitment,
+    Snark,
+}
+
+/// See [`JobState::is_commitment_expired`].
+fn is_commitment_expired(
+    commitment_time: Timestamp,
+    duration: Duration,
+    time_now: Timestamp,
+) -> bool {
+    match time_now.checked_sub(commitment_time) {
+        Some(age) => age >= duration,
+        None => {
+            commitment_time.checked_sub(time_now).unwrap_or_default()
+                > COMMITMENT_TIMESTAMP_MAX_SKEW
+        }
+    }
+}
+
+/// See [`JobState::profit_fee`]. Snark in the pool is only replaced if
+/// the fee improvement is big enough, commitment by any lower fee.
+fn job_profit_fee(
+    max_fee: u64,
+    min_fee: u64,
+    commitment_fee: Option<u64>,
+    snark_fee: Option<u64>,
+    pool_config: &SnarkPoolConfig,
+) -> Option<u64> {
+    let commitment_max_fee = match commitment_fee {
+        None => Some(max_fee),
+        Some(fee) => fee.checked_sub(1),
+    };
+    let snark_max_fee = match snark_fee {
+        None => Some(max_fee),
+        Some(fee) => pool_config.max_replacement_fee(fee),
+    };
+    let fee = commitment_max_fee?.min(snark_max_fee?).min(max_fee);
+    (fee >= min_fee).then_some(fee)
+}
+
+/// See [`JobState::profit_score`]. Fee per second of proving, weighted:
+/// - x4 if the job is likely needed for the next block;
+/// - x1 to x4 by how many minutes, up to 3, the job has been waiting;
+/// - /2 if it's already committed to, /4 if there's a snark for it,
+///   since the competitor may still win it.
+fn job_profit_score(
+    fee: u64,
+    is_demanded: bool,
+    age: Duration,
+    competition: JobCompetition,
+    duration: Duration,
+) -> u64 {
+    let demand_weight = if is_demanded { 4 } else { 1 };
+    let age_weight = 1 + (age.as_secs() / 60).min(3);
+    let competition = match competition {
+        JobCompetition::None => 1,
+        JobCompetition::Commitment => 2,
+        JobCompetition::Snark => 4,
+    };
+    let duration = duration.as_secs().max(1);
+
+    fee.max(1)
+        .saturating_mul(demand_weight)
+        .saturating_mul(age_weight)
+        / competition
+        / duration
 }
 
 impl JobSummary {
@@ -302,7 +714,17 @@ mod ser {
         counter: u64,
         list: BTreeMap<u64, JobState>,
         candidates: SnarkPoolCandidatesState,
+        #[serde(default)]
+        coordinator: SnarkPoolCoordinatorState,
         last_check_timeouts: Timestamp,
+        #[serde(default)]
+        restored_snarks: Vec<SnarkWork>,
+        #[serde(default)]
+        last_persist: Option<(Timestamp, u64)>,
+        #[serde(default)]
+        last_rebroadcast: Option<Timestamp>,
+        #[serde(default)]
+        wait_times: SnarkPoolWaitTimes,
     }
 
     impl Serialize for super::SnarkPoolState {
@@ -310,12 +732,17 @@ mod ser {
         where
             S: serde::Serializer,
         {
-            let mut s = serializer.serialize_struct("SnarkPool", 5)?;
+            let mut s = serializer.serialize_struct("SnarkPool", 10)?;
             s.serialize_field("config", &self.config)?;
             s.serialize_field("counter", &self.counter)?;
             s.serialize_field("list", &self.list)?;
             s.serialize_field("candidates", &self.candidates)?;
+            s.serialize_field("coordinator", &self.coordinator)?;
             s.serialize_field("last_check_timeouts", &self.last_check_timeouts)?;
+            s.serialize_field("restored_snarks", &self.restored_snarks)?;
+            s.serialize_field("last_persist", &self.last_persist)?;
+            s.serialize_field("last_rebroadcast", &self.last_rebroadcast)?;
+            s.serialize_field("wait_times", &self.wait_times)?;
             s.end()
         }
     }
@@ -332,8 +759,202 @@ mod ser {
                 list: v.list,
                 by_ledger_hash_index,
                 candidates: v.candidates,
+                coordinator: v.coordinator,
                 last_check_timeouts: v.last_check_timeouts,
+                restored_snarks: v.restored_snarks,
+                last_persist: v.last_persist,
+                last_rebroadcast: v.last_rebroadcast,
+                wait_times: v.wait_times,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mina_p2p_messages::v2::LedgerHash;
+    use openmina_core::snark::SnarkJobId;
+    use redux::Timestamp;
+
+    use super::{
+        is_commitment_expired, job_profit_fee, job_profit_score, snarks_to_evict, JobCompetition,
+        SnarkPoolConfig, SnarkPoolState,
+    };
+
+    #[test]
+    fn replacement_fee() {
+        let config = SnarkPoolConfig::default();
+        assert_eq!(config.replace_min_fee_decrease_percent, 5);
+
+        assert_eq!(config.max_replacement_fee(1_000), Some(950));
+        assert!(config.is_replacement_fee(1_000, 950));
+        assert!(config.is_replacement_fee(1_000, 0));
+        assert!(!config.is_replacement_fee(1_000, 951));
+        assert!(!config.is_replacement_fee(1_000, 1_000));
+        assert!(!config.is_replacement_fee(1_000, 2_000));
+
+        // Decrease is at least 1 nanomina.
+        assert_eq!(config.max_replacement_fee(10), Some(9));
+        assert_eq!(config.max_replacement_fee(1), Some(0));
+        // Free snark can't be replaced.
+        assert_eq!(config.max_replacement_fee(0), None);
+        assert!(!config.is_replacement_fee(0, 0));
+    }
+
+    #[test]
+    fn commitment_expiry() {
+        let duration = Duration::from_secs(20);
+        let now = Timestamp::new(Duration::from_secs(1_000).as_nanos() as u64);
+        let at = |secs: u64| Timestamp::new(Duration::from_secs(secs).as_nanos() as u64);
+
+        assert!(!is_commitment_expired(at(1_000), duration, now));
+        assert!(!is_commitment_expired(at(981), duration, now));
+        assert!(is_commitment_expired(at(980), duration, now));
+        assert!(is_commitment_expired(at(0), duration, now));
+
+        // Small clock skew is tolerated, but a commitment from the future
+        // beyond that would never expire, so it's rejected right away.
+        assert!(!is_commitment_expired(at(1_030), duration, now));
+        assert!(is_commitment_expired(at(1_031), duration, now));
+        assert!(is_commitment_expired(at(u32::MAX as u64), duration, now));
+    }
+
+    #[test]
+    fn evict_lowest_value_snarks() {
+        // (order, fee, index)
+        let snarks = vec![
+            (0, 100, 10),
+            (5, 100, 11),
+            (5, 300, 12),
+            (1, 900, 13),
+            (9, 1, 14),
+            (3, 100, 15),
+        ];
+        assert!(snarks_to_evict(snarks.clone(), 6).is_empty());
+        assert!(snarks_to_evict(snarks.clone(), 10).is_empty());
+
+        // Jobs needed last go first, then the most expensive ones.
+        assert_eq!(snarks_to_evict(snarks.clone(), 5), vec![14]);
+
+        let mut evicted = snarks_to_evict(snarks.clone(), 4);
+        evicted.sort();
+        assert_eq!(evicted, vec![12, 14]);
+
+        let mut evicted = snarks_to_evict(snarks.clone(), 3);
+        evicted.sort();
+        assert_eq!(evicted, vec![11, 12, 14]);
+
+        // Evicts down to 90% of the limit, so not on every added snark.
+        let snarks = (0..101).map(|i| (i, 1, i as u64)).collect::<Vec<_>>();
+        let mut evicted = snarks_to_evict(snarks, 100);
+        evicted.sort();
+        assert_eq!(evicted, (90..101).collect::<Vec<_>>());
+
+        let mut evicted = snarks_to_evict(vec![(0, 1, 0), (1, 1, 1)], 0);
+        evicted.sort();
+        assert_eq!(evicted, vec![0, 1]);
+    }
+
+    #[test]
+    fn rebroadcast_interval() {
+        let mut state = SnarkPoolState::new();
+        let interval = state.config().rebroadcast_interval;
+        let at = |d: Duration| Timestamp::new(d.as_nanos() as u64);
+
+        assert!(state.should_rebroadcast(Timestamp::ZERO));
+
+        let start = Duration::from_secs(1_000);
+        state.last_rebroadcast = Some(at(start));
+        assert!(!state.should_rebroadcast(at(start)));
+        assert!(!state.should_rebroadcast(at(start + interval - Duration::from_secs(1))));
+        assert!(state.should_rebroadcast(at(start + interval)));
+        // Time before the last rebroadcast.
+        assert!(!state.should_rebroadcast(at(Duration::ZERO)));
+    }
+
+    #[test]
+    fn included_jobs_wait_times() {
+        let job_id = |i: u64| {
+            let hash = LedgerHash::from_fp(i.into());
+            let id = format!("{hash}_{hash}-{hash}_{hash}");
+            id.parse::<SnarkJobId>().unwrap()
+        };
+        let secs = |s: u64| Timestamp::new(s * 1_000_000_000);
+
+        let mut state = SnarkPoolState::new();
+        let removed_jobs = [
+            (job_id(1), secs(10)),
+            (job_id(2), secs(20)),
+            (job_id(3), secs(5)),
+        ];
+        let included_jobs = [job_id(1), job_id(3), job_id(4)].into_iter().collect();
+        let removed_jobs = removed_jobs.iter().map(|(id, time)| (id, *time));
+        state.jobs_included(removed_jobs, &included_jobs, secs(30));
+
+        // Job 2 was removed without its work being included, e.g. on fork switch.
+        let wait_times = &state.wait_times().until_included;
+        assert_eq!(wait_times, &[20, 25].map(|s| s * 1_000_000_000));
+    }
+
+    #[test]
+    fn profit_fee_outbids_competition() {
+        let config = SnarkPoolConfig::default();
+        let fee = |min_fee, commitment_fee, snark_fee| {
+            job_profit_fee(1_000, min_fee, commitment_fee, snark_fee, &config)
+        };
+
+        assert_eq!(fee(0, None, None), Some(1_000));
+        assert_eq!(fee(0, Some(500), None), Some(499));
+        assert_eq!(fee(0, Some(5_000), None), Some(1_000));
+        assert_eq!(fee(0, None, Some(500)), Some(475));
+        assert_eq!(fee(0, Some(400), Some(500)), Some(399));
+        assert_eq!(fee(475, None, Some(500)), Some(475));
+        // Can't be won above the minimum fee.
+        assert_eq!(fee(480, None, Some(500)), None);
+        assert_eq!(fee(0, Some(0), None), None);
+        assert_eq!(fee(0, None, Some(0)), None);
+    }
+
+    #[test]
+    fn profit_score_ordering() {
+        const MIN: Duration = Duration::from_secs(60);
+        let duration = Duration::from_secs(20);
+        let score = |fee, is_demanded, age, competition| {
+            job_profit_score(fee, is_demanded, age, competition, duration)
+        };
+        let base = score(1_000, false, Duration::ZERO, JobCompetition::None);
+        assert_eq!(base, 50);
+
+        assert!(score(2_000, false, Duration::ZERO, JobCompetition::None) > base);
+        assert_eq!(
+            score(1_000, true, Duration::ZERO, JobCompetition::None),
+            base * 4
+        );
+        assert_eq!(score(1_000, false, 2 * MIN, JobCompetition::None), base * 3);
+        // Waiting longer than 3 minutes doesn't matter.
+        assert_eq!(score(1_000, false, 3 * MIN, JobCompetition::None), base * 4);
+        assert_eq!(
+            score(1_000, false, 60 * MIN, JobCompetition::None),
+            base * 4
+        );
+        assert_eq!(
+            score(1_000, false, Duration::ZERO, JobCompetition::Commitment),
+            base / 2
+        );
+        assert_eq!(
+            score(1_000, false, Duration::ZERO, JobCompetition::Snark),
+            base / 4
+        );
+        // Longer job earns less per second.
+        let longer = job_profit_score(
+            1_000,
+            false,
+            Duration::ZERO,
+            JobCompetition::None,
+            duration * 2,
+        );
+        assert_eq!(longer, base / 2);
+    }
+}
@@ -1,4 +1,4 @@
-use openmina_core::{constants::CONSTRAINT_CONSTANTS, error, ChainId};
+use openmina_core::{error, ChainId};
 use p2p::{P2pConfig, P2pPeerState, P2pPeerStatusReady, PeerId};
 use redux::{ActionMeta, EnablingCondition, Timestamp};
 use serde::{Deserialize, Serialize};
@@ -14,6 +14,7 @@ pub use crate::p2p::P2pState;
 pub use crate::rpc::RpcState;
 pub use crate::snark::SnarkState;
 pub use crate::snark_pool::candidate::SnarkPoolCandidatesState;
+pub use crate::snark_pool::coordinator::SnarkPoolCoordinatorState;
 pub use crate::snark_pool::SnarkPoolState;
 use crate::transition_frontier::genesis::TransitionFrontierGenesisState;
 use crate::transition_frontier::sync::ledger::snarked::TransitionFrontierSyncLedgerSnarkedState;
@@ -62,6 +63,7 @@ impl_substate_access!(
 impl_substate_access!(State, TransitionFrontierSyncState, transition_frontier.sync);
 impl_substate_access!(State, SnarkPoolState, snark_pool);
 impl_substate_access!(State, SnarkPoolCandidatesState, snark_pool.candidates);
+impl_substate_access!(State, SnarkPoolCoordinatorState, snark_pool.coordinator);
 impl_substate_access!(State, ExternalSnarkWorkers, external_snark_worker);
 impl_substate_access!(State, BlockProducerState, block_producer);
 impl_substate_access!(State, RpcState, rpc);
@@ -178,7 +180,10 @@ impl State {
             snark: SnarkState::new(config.snark),
             consensus: ConsensusState::new(),
             transition_frontier: TransitionFrontierState::new(config.transition_frontier),
-            external_snark_worker: ExternalSnarkWorkers::new(now),
+            external_snark_worker: ExternalSnarkWorkers::new(
+                now,
+                config.global.snarker.as_ref().map_or(1, |c| c.workers),
+            ),
             block_producer: BlockProducerState::new(now, config.block_producer),
             rpc: RpcState::new(),
 
@@ -209,24 +214,46 @@ impl State {
         self.applied_actions_count += 1;
     }
 
+    /// Slot duration of the chain that the node currently follows.
+    ///
+    /// Taken from the genesis config, so it changes with the hard fork.
+    pub fn block_window_duration_ms(&self) -> u64 {
+        self.transition_frontier
+            .config
+            .genesis
+            .block_window_duration_ms()
+    }
+
     /// Current global slot based on constants and current time.
     ///
     /// It's not equal to global slot of the best tip.
     pub fn cur_global_slot(&self) -> Option<u32> {
         let best_tip = self.transition_frontier.best_tip()?;
-        let best_tip_ms = u64::from(best_tip.timestamp()) / 1_000_000;
+        let block_window_duration_ms = self.block_window_duration_ms();
+        let best_tip_ms = u64::from(best_tip.timestamp(block_window_duration_ms)) / 1_000_000;
         let now_ms = u64::from(self.time()) / 1_000_000;
         let ms = now_ms.saturating_sub(best_tip_ms);
-        let slots = ms / CONSTRAINT_CONSTANTS.block_window_duration_ms;
+        let slots = ms / block_window_duration_ms;
 
         Some(best_tip.global_slot() + (slots as u32))
     }
 
+    /// Current global slot since genesis based on constants and current time.
+    pub fn cur_global_slot_since_genesis(&self) -> Option<u32> {
+        let best_tip = self.transition_frontier.best_tip()?;
+        let cur_global_slot = self.cur_global_slot()?;
+        Some(cur_global_slot + best_tip.global_slot_diff())
+    }
+
     pub fn current_epoch(&self) -> Option<u32> {
-        // TODO: Should not be hardcoded
-        const SLOTS_PER_EPOCH: u32 = 7140;
+        let slots_per_epoch = self
+            .transition_frontier
+            .best_tip()?
+            .constants()
+            .slots_per_epoch
+            .as_u32();
         let current_global_slot = self.cur_global_slot()?;
-        Some(current_global_slot / SLOTS_PER_EPOCH)
+        Some(current_global_slot / slots_per_epoch)
     }
 }
 
@@ -16,6 +16,15 @@ pub mod block_producer {
 }
 use block_producer::BlockProducerStats;
 
+mod stats_services;
+pub mod services {
+    pub use super::stats_services::*;
+}
+use services::{ServiceKind, ServiceLatencyStats, ServiceStats};
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
 use openmina_core::block::{ArcBlockWithHash, Block, BlockWithHash};
 use redux::{ActionMeta, ActionWithMeta, Timestamp};
 
@@ -31,6 +40,7 @@ pub struct Stats {
     action_stats: ActionStats,
     sync_stats: SyncStats,
     block_producer_stats: BlockProducerStats,
+    service_stats: ServiceStats,
 }
 
 impl Stats {
@@ -40,6 +50,7 @@ impl Stats {
             action_stats: Default::default(),
             sync_stats: Default::default(),
             block_producer_stats: Default::default(),
+            service_stats: Default::default(),
         }
     }
 
@@ -47,6 +58,11 @@ impl Stats {
         &mut self.block_producer_stats
     }
 
+    pub fn service_call_finished(&mut self, kind: ServiceKind, latency: Duration) -> &mut Self {
+        self.service_stats.add(kind, latency);
+        self
+    }
+
     pub fn new_sync_target(
         &mut self,
         time: Timestamp,
@@ -121,6 +137,10 @@ impl Stats {
             .and_then(|stats| stats.synced)
     }
 
+    pub fn collect_service_stats(&self) -> BTreeMap<ServiceKind, ServiceLatencyStats> {
+        self.service_stats.collect_stats()
+    }
+
     pub fn staging_ledger_fetch_failure(
         &mut self,
         error: &PeerStagedLedgerPartsFetchError,
@@ -14,6 +14,9 @@ const MAX_HISTORY: usize = 2048;
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct BlockProducerStats {
     pub(super) attempts: VecDeque<BlockProductionAttempt>,
+    /// Totals since the node started, not limited by the history.
+    #[serde(default)]
+    totals: Vec<BlockProducerKeyTotals>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -30,6 +33,7 @@ pub struct BlockProductionAttemptWonSlot {
     pub slot_time: redux::Timestamp,
     pub global_slot: u32,
     pub epoch: u32,
+    pub producer: v2::NonZeroCurvePoint,
     pub delegator: (v2::NonZeroCurvePoint, AccountIndex),
     pub value_with_threshold: Option<(f64, f64)>,
 }
@@ -71,6 +75,28 @@ pub enum BlockProductionStatus {
     },
 }
 
+/// Block production stats of a single producer key, based on the
+/// attempts in the history.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerKeyStats {
+    pub producer: v2::NonZeroCurvePoint,
+    pub won_slots: u32,
+    pub produced: u32,
+    pub canonical: u32,
+    pub orphaned: u32,
+    pub discarded: u32,
+}
+
+/// Block production outcomes of a single producer key since the node
+/// started. Unlike [`BlockProducerKeyStats`], these only ever increase.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerKeyTotals {
+    pub producer: v2::NonZeroCurvePoint,
+    pub won_slots: u64,
+    pub produced: u64,
+    pub discarded: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ProducedBlock {
     pub hash: BlockHash,
@@ -100,6 +126,62 @@ impl BlockProducerStats {
         self.attempts.iter().cloned().collect()
     }
 
+    pub fn collect_producer_totals(&self) -> Vec<BlockProducerKeyTotals> {
+        self.totals.clone()
+    }
+
+    /// Totals of the producer of the latest attempt.
+    fn latest_producer_totals_mut(&mut self) -> Option<&mut BlockProducerKeyTotals> {
+        let producer = &self.attempts.back()?.won_slot.producer;
+        let i = match self.totals.iter().position(|v| &v.producer == producer) {
+            Some(i) => i,
+            None => {
+                self.totals.push(BlockProducerKeyTotals {
+                    producer: producer.clone(),
+                    won_slots: 0,
+                    produced: 0,
+                    discarded: 0,
+                });
+                self.totals.len() - 1
+            }
+        };
+        self.totals.get_mut(i)
+    }
+
+    pub fn collect_producer_stats(&self) -> Vec<BlockProducerKeyStats> {
+        self.attempts
+            .iter()
+            .fold(Vec::<BlockProducerKeyStats>::new(), |mut res, attempt| {
+                let producer = &attempt.won_slot.producer;
+                let i = match res.iter().position(|v| &v.producer == producer) {
+                    Some(i) => i,
+                    None => {
+                        res.push(BlockProducerKeyStats {
+                            producer: producer.clone(),
+                            won_slots: 0,
+                            produced: 0,
+                            canonical: 0,
+                            orphaned: 0,
+                            discarded: 0,
+                        });
+                        res.len() - 1
+                    }
+                };
+                let stats = &mut res[i];
+                stats.won_slots += 1;
+                if attempt.block.is_some() {
+                    stats.produced += 1;
+                }
+                match attempt.status {
+                    BlockProductionStatus::Canonical { .. } => stats.canonical += 1,
+                    BlockProductionStatus::Orphaned { .. } => stats.orphaned += 1,
+                    BlockProductionStatus::Discarded { .. } => stats.discarded += 1,
+                    _ => {}
+                }
+                res
+            })
+    }
+
     pub fn new_best_chain<T: AsRef<Block>>(
         &mut self,
         time: redux::Timestamp,
@@ -140,7 +222,9 @@ impl BlockProducerStats {
             });
     }
 
-    fn update<F>(&mut self, kind: &'static str, with: F)
+    /// Updates the latest attempt, returns whether it was in the state
+    /// expected by the update.
+    fn update<F>(&mut self, kind: &'static str, with: F) -> bool
     where
         F: FnOnce(&mut BlockProductionAttempt) -> bool,
     {
@@ -150,6 +234,7 @@ impl BlockProducerStats {
                     kind = "BlockProducerStatsAttemptsEmpty",
                     summary = "attempts are empty when they aren't expected to be",
                     update_kind = kind);
+                false
             }
             Some(mut attempt) => {
                 let was_correct_state = with(&mut attempt);
@@ -160,6 +245,7 @@ impl BlockProducerStats {
                         summary = format!("update kind `{kind}` is not applicable to state: {attempt:?}"));
                 }
                 self.attempts.push_back(attempt);
+                was_correct_state
             }
         }
     }
@@ -185,6 +271,9 @@ impl BlockProducerStats {
             },
             status: BlockProductionStatus::Scheduled,
         });
+        if let Some(totals) = self.latest_producer_totals_mut() {
+            totals.won_slots += 1;
+        }
     }
 
     pub fn staged_ledger_diff_create_start(&mut self, time: redux::Timestamp) {
@@ -221,7 +310,7 @@ impl BlockProducerStats {
         block_hash: &BlockHash,
         block: &BlockWithoutProof,
     ) {
-        self.update("produced", move |attempt| match attempt.status {
+        let updated = self.update("produced", move |attempt| match attempt.status {
             BlockProductionStatus::StagedLedgerDiffCreateSuccess => {
                 attempt.status = BlockProductionStatus::Produced;
                 attempt.times.produced = Some(time);
@@ -230,6 +319,9 @@ impl BlockProducerStats {
             }
             _ => false,
         });
+        if let Some(totals) = self.latest_producer_totals_mut().filter(|_| updated) {
+            totals.produced += 1;
+        }
     }
 
     pub fn proof_create_start(&mut self, time: redux::Timestamp) {
@@ -305,13 +397,16 @@ impl BlockProducerStats {
     }
 
     pub fn discarded(&mut self, time: redux::Timestamp, reason: BlockProducerWonSlotDiscardReason) {
-        self.update("discarded", move |attempt| {
+        let updated = self.update("discarded", move |attempt| {
             attempt.status = BlockProductionStatus::Discarded {
                 discard_reason: reason,
             };
             attempt.times.discarded = Some(time);
             true
         });
+        if let Some(totals) = self.latest_producer_totals_mut().filter(|_| updated) {
+            totals.discarded += 1;
+        }
     }
 }
 
@@ -321,6 +416,7 @@ impl From<&BlockProducerWonSlot> for BlockProductionAttemptWonSlot {
             slot_time: won_slot.slot_time,
             global_slot: won_slot.global_slot(),
             epoch: won_slot.epoch(),
+            producer: won_slot.producer.clone(),
             delegator: won_slot.delegator.clone(),
             value_with_threshold: won_slot.value_with_threshold,
         }
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Upper bounds (inclusive) of the latency buckets in nanoseconds.
+pub const SERVICE_LATENCY_BUCKETS: [u64; 10] = [
+    1_000_000,
+    5_000_000,
+    10_000_000,
+    50_000_000,
+    100_000_000,
+    500_000_000,
+    1_000_000_000,
+    5_000_000_000,
+    10_000_000_000,
+    60_000_000_000,
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ServiceKind {
+    LedgerRead,
+    LedgerWrite,
+    SnarkBlockVerify,
+    SnarkWorkVerify,
+}
+
+impl ServiceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LedgerRead => "ledger_read",
+            Self::LedgerWrite => "ledger_write",
+            Self::SnarkBlockVerify => "snark_block_verify",
+            Self::SnarkWorkVerify => "snark_work_verify",
+        }
+    }
+}
+
+/// Latencies of the requests to the asynchronous services, measured
+/// from the moment the request was sent until the response event got
+/// processed by the state machine.
+#[derive(Default)]
+pub struct ServiceStats {
+    calls: BTreeMap<ServiceKind, ServiceLatencyStats>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ServiceLatencyStats {
+    /// Total number of finished calls.
+    pub count: u64,
+    /// Sum of latencies in nanoseconds.
+    pub total_duration: u64,
+    /// Max latency in nanoseconds.
+    pub max_duration: u64,
+    /// Number of calls per bucket in `SERVICE_LATENCY_BUCKETS`. Calls
+    /// slower than the last bucket aren't counted in any of them.
+    pub buckets: [u64; SERVICE_LATENCY_BUCKETS.len()],
+}
+
+impl ServiceStats {
+    pub fn add(&mut self, kind: ServiceKind, latency: Duration) {
+        let duration = latency.as_nanos().min(u64::MAX as u128) as u64;
+        let stats = self.calls.entry(kind).or_default();
+        stats.count += 1;
+        stats.total_duration = stats.total_duration.saturating_add(duration);
+        stats.max_duration = stats.max_duration.max(duration);
+        if let Some(i) = SERVICE_LATENCY_BUCKETS.iter().position(|b| duration <= *b) {
+            stats.buckets[i] += 1;
+        }
+    }
+
+    pub fn collect_stats(&self) -> BTreeMap<ServiceKind, ServiceLatencyStats> {
+        self.calls.clone()
+    }
+}
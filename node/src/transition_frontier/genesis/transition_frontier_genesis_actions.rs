@@ -27,7 +27,11 @@ pub enum TransitionFrontierGenesisAction {
     ProveSuccess {
         proof: Box<v2::MinaBaseProofStableV2>,
     },
-    // TODO(refactor): add prove error
+    /// Genesis block proof generation failed. Genesis block goes back to
+    /// unproven, so that proving can be initiated again.
+    ProveError {
+        error: String,
+    },
 }
 
 impl redux::EnablingCondition<crate::State> for TransitionFrontierGenesisAction {
@@ -61,7 +65,8 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierGenesisAction
                 genesis_state,
                 TransitionFrontierGenesisState::Produced { .. }
             ),
-            TransitionFrontierGenesisAction::ProveSuccess { .. } => matches!(
+            TransitionFrontierGenesisAction::ProveSuccess { .. }
+            | TransitionFrontierGenesisAction::ProveError { .. } => matches!(
                 genesis_state,
                 TransitionFrontierGenesisState::ProvePending { .. }
             ),
@@ -59,6 +59,21 @@ pub struct GenesisConfigLoaded {
     pub next_epoch_seed: v2::EpochSeed,
 }
 
+/// Checks that the fork point declared in the config is the one that
+/// is compiled into the circuits.
+fn check_fork(config: &DaemonJson) -> Result<(), GenesisConfigError> {
+    let Some(configured) = config.fork() else {
+        return Ok(());
+    };
+    match &CONSTRAINT_CONSTANTS.fork {
+        Some(compiled) if configured.matches(compiled) => Ok(()),
+        compiled => Err(GenesisConfigError::ForkMismatch {
+            configured: configured.clone(),
+            compiled: compiled.as_ref().map(Into::into),
+        }),
+    }
+}
+
 fn bp_num_delegators(i: usize) -> usize {
     (i + 1) * 2
 }
@@ -78,9 +93,24 @@ pub enum GenesisConfigError {
     Prebuilt(#[from] binprot::Error),
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("fork in configuration doesn't match the one node was built for: {configured:?} != {compiled:?}")]
+    ForkMismatch {
+        configured: daemon_json::Fork,
+        compiled: Option<daemon_json::Fork>,
+    },
 }
 
 impl GenesisConfig {
+    /// Slot duration of the chain, `proof.block_window_duration_ms` from
+    /// the daemon config, or the compiled in value if it isn't set.
+    pub fn block_window_duration_ms(&self) -> u64 {
+        match self {
+            Self::DaemonJson(config) => config.block_window_duration_ms(),
+            _ => None,
+        }
+        .unwrap_or(CONSTRAINT_CONSTANTS.block_window_duration_ms)
+    }
+
     pub fn load(&self) -> Result<(ledger::Mask, GenesisConfigLoaded), GenesisConfigError> {
         Ok(match self {
             Self::Counts {
@@ -155,6 +185,7 @@ impl GenesisConfig {
                 prebuilt.load()
             }
             Self::DaemonJson(config) => {
+                check_fork(config)?;
                 let constants = config
                     .genesis
                     .as_ref()
@@ -500,6 +531,7 @@ impl TryFrom<DaemonJson> for PrebuiltGenesisConfig {
     type Error = GenesisConfigError;
 
     fn try_from(config: DaemonJson) -> Result<Self, Self::Error> {
+        check_fork(&config)?;
         let constants = config
             .genesis
             .as_ref()
@@ -11,7 +11,10 @@ use openmina_core::{
     constants::PROTOCOL_VERSION,
     error,
 };
-use p2p::P2pInitializeAction;
+use p2p::{
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    P2pInitializeAction,
+};
 
 use super::{
     empty_block_body, empty_block_body_hash, empty_pending_coinbase, empty_pending_coinbase_hash,
@@ -88,24 +91,36 @@ impl TransitionFrontierGenesisState {
 
                 // Dispatch
                 let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
-                if global_state.p2p.ready().is_none() {
-                    let TransitionFrontierGenesisState::Produced { genesis, .. } =
-                        &global_state.transition_frontier.genesis
-                    else {
-                        error!(meta.time(); "incorrect state: {:?}", global_state.transition_frontier.genesis);
-                        return;
-                    };
-                    use openmina_core::{constants, ChainId};
-                    let genesis_state_hash = genesis.hash();
-                    let chain_id = ChainId::compute(
-                        constants::CONSTRAINT_SYSTEM_DIGESTS.as_slice(),
-                        &genesis_state_hash,
-                        &genesis.body.constants,
-                        constants::PROTOCOL_TRANSACTION_VERSION,
-                        constants::PROTOCOL_NETWORK_VERSION,
-                        &v2::UnsignedExtendedUInt32StableV1::from(constants::TX_POOL_MAX_SIZE),
-                    );
-                    dispatcher.push(P2pInitializeAction::Initialize { chain_id });
+                let TransitionFrontierGenesisState::Produced { genesis, .. } =
+                    &global_state.transition_frontier.genesis
+                else {
+                    error!(meta.time(); "incorrect state: {:?}", global_state.transition_frontier.genesis);
+                    return;
+                };
+                use openmina_core::{constants, ChainId};
+                let genesis_state_hash = genesis.hash();
+                let chain_id = ChainId::compute(
+                    constants::CONSTRAINT_SYSTEM_DIGESTS.as_slice(),
+                    &genesis_state_hash,
+                    &genesis.body.constants,
+                    constants::PROTOCOL_TRANSACTION_VERSION,
+                    constants::PROTOCOL_NETWORK_VERSION,
+                    &v2::UnsignedExtendedUInt32StableV1::from(constants::TX_POOL_MAX_SIZE),
+                );
+                match global_state.p2p.ready() {
+                    None => dispatcher.push(P2pInitializeAction::Initialize { chain_id }),
+                    // Genesis has changed after the hard fork, peers from
+                    // the old chain are of no use anymore.
+                    Some(p2p) if p2p.chain_id != chain_id => {
+                        for peer_id in p2p.ready_peers() {
+                            dispatcher.push(P2pDisconnectionAction::Init {
+                                peer_id,
+                                reason: P2pDisconnectionReason::ChainIdChanged,
+                            });
+                        }
+                        dispatcher.push(P2pInitializeAction::ChainIdChange { chain_id });
+                    }
+                    Some(_) => {}
                 }
                 dispatcher.push(TransitionFrontierGenesisAction::ProveInit);
             }
@@ -213,6 +228,24 @@ impl TransitionFrontierGenesisState {
                     ),
                 };
             }
+            TransitionFrontierGenesisAction::ProveError { .. } => {
+                let Self::ProvePending {
+                    negative_one,
+                    genesis,
+                    genesis_producer_stake_proof,
+                    ..
+                } = state
+                else {
+                    return;
+                };
+
+                *state = Self::Produced {
+                    time: meta.time(),
+                    negative_one: negative_one.clone(),
+                    genesis: genesis.clone(),
+                    genesis_producer_stake_proof: genesis_producer_stake_proof.clone(),
+                };
+            }
         }
     }
 }
@@ -62,6 +62,17 @@ impl TransitionFrontierGenesisState {
         }
     }
 
+    /// Hash of the genesis block, once it's produced.
+    pub fn genesis_state_hash(&self) -> Option<v2::StateHash> {
+        match self {
+            Self::Produced { genesis, .. } | Self::ProvePending { genesis, .. } => {
+                Some(genesis.hash())
+            }
+            Self::ProveSuccess { genesis, .. } => Some(genesis.hash.clone()),
+            _ => None,
+        }
+    }
+
     pub fn proven_block(&self) -> Option<&ArcBlockWithHash> {
         match self {
             Self::ProveSuccess { genesis, .. } => Some(genesis),
@@ -1,6 +1,6 @@
 use mina_p2p_messages::v2::StateHash;
 use openmina_core::block::ArcBlockWithHash;
-use openmina_core::consensus::consensus_take;
+use openmina_core::consensus::{consensus_take, is_short_range_fork};
 use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
@@ -146,7 +146,7 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
                             // tip.hash() == &best_tip.header().protocol_state.body.genesis_state_hash
                             true
                         } else {
-                            consensus_take(tip.consensus_state(), best_tip.consensus_state(), tip.hash(), best_tip.hash())
+                            consensus_take(tip.consensus_state(), best_tip.consensus_state(), tip.hash(), best_tip.hash(), tip.constants())
                         }
                     })
                 // Don't sync to best tip if we are in the middle of producing
@@ -159,10 +159,15 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
                 // let that get in the way of us producing a block.
                 && state.block_producer.producing_won_slot()
                     .filter(|_| !state.block_producer.is_me(best_tip.producer()))
-                    // TODO(binier): check if candidate best tip is short or
-                    // long range fork and based on that compare slot that
-                    // we are producing.
-                    .map_or(true, |won_slot| won_slot < best_tip)
+                    .map_or(true, |won_slot| match state.transition_frontier.best_tip() {
+                        // Block that we are producing extends our best tip, so
+                        // if candidate is a long range fork, it has to win
+                        // the chain density comparison against our chain.
+                        Some(tip) if !is_short_range_fork(tip.consensus_state(), best_tip.consensus_state()) => {
+                            consensus_take(tip.consensus_state(), best_tip.consensus_state(), tip.hash(), best_tip.hash(), tip.constants())
+                        }
+                        _ => won_slot < best_tip,
+                    })
             }
             TransitionFrontierSyncAction::LedgerStakingPending => {
                 matches!(
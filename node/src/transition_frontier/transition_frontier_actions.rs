@@ -32,6 +32,13 @@ pub enum TransitionFrontierAction {
         /// Required protocol states for root block.
         needed_protocol_states: BTreeSet<StateHash>,
     },
+    /// Switch to the new chain once the current one has ended because
+    /// of the scheduled hard fork.
+    ///
+    /// Transition frontier is reset and genesis block of the new chain
+    /// gets loaded from the hard fork config.
+    #[action_event(level = info)]
+    HardForkTransition,
 }
 
 impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
@@ -55,6 +62,12 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
                 state.transition_frontier.sync,
                 TransitionFrontierSyncState::CommitSuccess { .. }
             ),
+            TransitionFrontierAction::HardForkTransition => {
+                state.cur_global_slot_since_genesis().map_or(false, |slot| {
+                    state.transition_frontier.is_past_chain_end(slot)
+                        && !state.transition_frontier.sync.is_commit_pending()
+                })
+            }
         }
     }
 }
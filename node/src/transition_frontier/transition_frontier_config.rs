@@ -7,10 +7,29 @@ use super::genesis::TransitionFrontierGenesisConfig;
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TransitionFrontierConfig {
     pub genesis: Arc<TransitionFrontierGenesisConfig>,
+    /// Scheduled hard fork, after which node will switch to the new chain.
+    pub hard_fork: Option<TransitionFrontierHardForkConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransitionFrontierHardForkConfig {
+    /// Last global slot since genesis of the current chain. Blocks after
+    /// this slot are neither produced nor accepted.
+    pub slot_chain_end: u32,
+    /// Genesis config of the chain that we will follow after the fork.
+    pub genesis: Arc<TransitionFrontierGenesisConfig>,
 }
 
 impl TransitionFrontierConfig {
     pub fn new(genesis: Arc<TransitionFrontierGenesisConfig>) -> Self {
-        TransitionFrontierConfig { genesis }
+        TransitionFrontierConfig {
+            genesis,
+            hard_fork: None,
+        }
+    }
+
+    pub fn with_hard_fork(mut self, hard_fork: TransitionFrontierHardForkConfig) -> Self {
+        self.hard_fork = Some(hard_fork);
+        self
     }
 }
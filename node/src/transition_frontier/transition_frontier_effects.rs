@@ -1,3 +1,6 @@
+use std::collections::BTreeSet;
+
+use openmina_core::snark::SnarkJobId;
 use redux::Timestamp;
 
 use crate::block_producer::BlockProducerAction;
@@ -240,6 +243,21 @@ pub fn transition_frontier_effects<S: crate::Service>(
                             sender: own_peer_id,
                         })
                         .collect();
+                    // Works included by every block that is new in the best
+                    // chain, not only by the best tip, as several blocks may
+                    // be added at once.
+                    let prev_best_chain = transition_frontier
+                        .best_chain
+                        .iter()
+                        .map(|block| block.hash())
+                        .collect::<BTreeSet<_>>();
+                    let included_jobs = chain
+                        .iter()
+                        .rev()
+                        .take_while(|block| !prev_best_chain.contains(block.hash()))
+                        .flat_map(|block| block.completed_works_iter())
+                        .map(|work| SnarkJobId::from(&work.proofs))
+                        .collect();
 
                     store.dispatch(TransitionFrontierAction::Synced {
                         needed_protocol_states: result.needed_protocol_states,
@@ -247,6 +265,7 @@ pub fn transition_frontier_effects<S: crate::Service>(
                     store.dispatch(SnarkPoolAction::JobsUpdate {
                         jobs: result.available_jobs,
                         orphaned_snarks,
+                        included_jobs,
                     });
                     return;
                 }
@@ -259,6 +278,7 @@ pub fn transition_frontier_effects<S: crate::Service>(
         TransitionFrontierAction::Synced { .. } => {
             synced_effects(&meta, store);
         }
+        TransitionFrontierAction::HardForkTransition => {}
     }
 }
 
@@ -1,3 +1,8 @@
+use crate::block_producer::BlockProducerAction;
+use crate::consensus::ConsensusAction;
+use crate::snark_pool::SnarkPoolAction;
+
+use super::genesis::TransitionFrontierGenesisState;
 use super::sync::TransitionFrontierSyncState;
 use super::{
     TransitionFrontierAction, TransitionFrontierActionWithMetaRef, TransitionFrontierState,
@@ -74,6 +79,22 @@ impl TransitionFrontierState {
                 state.best_chain = new_chain;
                 state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
             }
+            TransitionFrontierAction::HardForkTransition => {
+                let Some(hard_fork) = state.config.hard_fork.take() else {
+                    return;
+                };
+                state.config.genesis = hard_fork.genesis;
+                state.genesis = TransitionFrontierGenesisState::Idle;
+                state.best_chain.clear();
+                state.needed_protocol_states.clear();
+                state.sync = TransitionFrontierSyncState::Idle;
+
+                // Dispatch
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(ConsensusAction::Reset);
+                dispatcher.push(BlockProducerAction::Reset);
+                dispatcher.push(SnarkPoolAction::Reset);
+            }
         }
     }
 }
@@ -43,6 +43,31 @@ impl TransitionFrontierState {
         self.best_chain.first()
     }
 
+    /// Whether the current chain ends before the given global slot since
+    /// genesis, because of the scheduled hard fork.
+    pub fn is_past_chain_end(&self, global_slot_since_genesis: u32) -> bool {
+        self.config.hard_fork.as_ref().map_or(false, |hard_fork| {
+            global_slot_since_genesis > hard_fork.slot_chain_end
+        })
+    }
+
+    /// Whether the block belongs to the chain that this node follows.
+    ///
+    /// Blocks built on top of a different genesis and blocks after the
+    /// end of the current chain (see [`Self::is_past_chain_end`]) don't.
+    /// Until the genesis block is ready, e.g. right after the hard fork,
+    /// no block does.
+    pub fn is_block_on_followed_chain(&self, block: &ArcBlockWithHash) -> bool {
+        if self.is_past_chain_end(block.global_slot_since_genesis()) {
+            return false;
+        }
+        self.genesis
+            .genesis_state_hash()
+            .map_or(false, |genesis_hash| {
+                block.header().protocol_state.body.genesis_state_hash == genesis_hash
+            })
+    }
+
     /// Looks up state body by state hash.
     pub fn get_state_body(
         &self,
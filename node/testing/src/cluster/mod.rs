@@ -270,6 +270,8 @@ impl Cluster {
             global: GlobalConfig {
                 build: BuildEnv::get().into(),
                 snarker: testing_config.snark_worker,
+                health: Default::default(),
+                snark_coordinator: None,
             },
             p2p: P2pConfig {
                 libp2p_port: Some(libp2p_port),
@@ -342,7 +344,10 @@ impl Cluster {
             network: Default::default(),
             block_producer: None,
             keypair: p2p_sec_key.clone().into(),
-            snark_worker_sender: None,
+            snark_worker_kind: Default::default(),
+            snark_worker_threads: None,
+            snark_workers: Default::default(),
+            snark_pool_writer: None,
             rpc: rpc_service,
             stats: node::stats::Stats::new(),
             recorder: match testing_config.recorder {
@@ -355,7 +360,7 @@ impl Cluster {
             invariants_state: Default::default(),
         };
         if let Some(producer_key) = block_producer_sec_key {
-            real_service.block_producer_start(producer_key);
+            real_service.block_producer_start(vec![producer_key]);
         }
         let mut service = NodeTestingService::new(real_service, node_id, shutdown_rx);
         service.set_proof_kind(self.config.proof_kind());
@@ -363,7 +363,15 @@ impl<'a> ClusterRunner<'a> {
             let next_won_slot = state
                 .block_producer
                 .vrf_evaluator()
-                .and_then(|vrf_state| vrf_state.next_won_slot(current_global_slot, best_tip));
+                .zip(state.block_producer.production_log())
+                .and_then(|(vrf_state, production_log)| {
+                    vrf_state.next_won_slot(
+                        current_global_slot,
+                        best_tip,
+                        production_log,
+                        state.block_window_duration_ms(),
+                    )
+                });
 
             let best_tip_slot = &best_tip
                 .consensus_state()
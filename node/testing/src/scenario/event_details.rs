@@ -10,7 +10,7 @@ use node::{
 pub fn event_details(state: &State, event: &Event) -> Option<String> {
     if let Event::P2p(P2pEvent::Channel(P2pChannelEvent::Received(
         peer_id,
-        Ok(ChannelMsg::Rpc(RpcChannelMsg::Response(req_id, _))),
+        Ok((ChannelMsg::Rpc(RpcChannelMsg::Response(req_id, _)), _)),
     ))) = event
     {
         let rpc_state = &state.p2p.get_ready_peer(peer_id)?.channels.rpc;
@@ -43,8 +43,11 @@ impl MultiNodeVrfGetCorrectLedgers {
             block_producer: Some(RustNodeBlockProducerTestingConfig {
                 config: BlockProducerConfig {
                     pub_key: sec_key.public_key().into(),
+                    additional_pub_keys: vec![],
                     custom_coinbase_receiver: None,
+                    coinbase_receiver_policy: Default::default(),
                     proposed_protocol_version: None,
+                    dry_run: false,
                 },
                 sec_key,
             }),
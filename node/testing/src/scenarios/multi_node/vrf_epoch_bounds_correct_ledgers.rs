@@ -65,8 +65,11 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
             block_producer: Some(RustNodeBlockProducerTestingConfig {
                 config: BlockProducerConfig {
                     pub_key: sec_key.public_key().into(),
+                    additional_pub_keys: vec![],
                     custom_coinbase_receiver: None,
+                    coinbase_receiver_policy: Default::default(),
                     proposed_protocol_version: None,
+                    dry_run: false,
                 },
                 sec_key: sec_key.clone(),
             }),
@@ -81,6 +84,8 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
                 )),
                 strategy: SnarkerStrategy::Sequential,
                 auto_commit: true,
+                workers: 1,
+                min_fee: None,
             }),
             ..rust_config
         });
@@ -51,8 +51,11 @@ impl MultiNodeVrfEpochBoundsEvaluation {
             block_producer: Some(RustNodeBlockProducerTestingConfig {
                 config: BlockProducerConfig {
                     pub_key: sec_key.public_key().into(),
+                    additional_pub_keys: vec![],
                     custom_coinbase_receiver: None,
+                    coinbase_receiver_policy: Default::default(),
                     proposed_protocol_version: None,
+                    dry_run: false,
                 },
                 sec_key: sec_key.clone(),
             }),
@@ -67,6 +70,8 @@ impl MultiNodeVrfEpochBoundsEvaluation {
                 )),
                 strategy: SnarkerStrategy::Sequential,
                 auto_commit: true,
+                workers: 1,
+                min_fee: None,
             }),
             ..rust_config
         });
@@ -223,7 +223,7 @@ impl SoloNodeSyncRootSnarkedLedger {
     fn event_ledger_query_addr(self, state: &State, event: &Event) -> Option<LedgerAddress> {
         let Event::P2p(P2pEvent::Channel(P2pChannelEvent::Received(
             peer_id,
-            Ok(ChannelMsg::Rpc(RpcChannelMsg::Response(_, _))),
+            Ok((ChannelMsg::Rpc(RpcChannelMsg::Response(_, _)), _)),
         ))) = event
         else {
             return None;
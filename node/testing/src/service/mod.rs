@@ -18,7 +18,7 @@ use mina_p2p_messages::v2::{
 };
 use node::account::AccountPublicKey;
 use node::block_producer::vrf_evaluator::VrfEvaluatorInput;
-use node::block_producer::BlockProducerEvent;
+use node::block_producer::{BlockProducerEvent, BlockProducerLogEntry, BlockProducerPayoutReport};
 use node::core::channels::mpsc;
 use node::core::snark::{Snark, SnarkJobId};
 use node::external_snark_worker::ExternalSnarkWorkerEvent;
@@ -33,12 +33,12 @@ use node::snark::block_verify::{
 };
 use node::snark::work_verify::{SnarkWorkVerifyId, SnarkWorkVerifyService};
 use node::snark::{SnarkEvent, VerifierIndex, VerifierSRS};
-use node::snark_pool::SnarkPoolService;
+use node::snark_pool::{SnarkPoolService, SnarkWork};
 use node::stats::Stats;
 use node::transition_frontier::genesis::GenesisConfig;
 use node::{
     event_source::Event,
-    external_snark_worker::{ExternalSnarkWorkerService, SnarkWorkSpec},
+    external_snark_worker::{ExternalSnarkWorkerId, ExternalSnarkWorkerService, SnarkWorkSpec},
     p2p::{
         connection::outgoing::P2pConnectionOutgoingInitOpts,
         service_impl::webrtc::{Cmd, P2pServiceWebrtc, PeerState},
@@ -130,7 +130,7 @@ pub struct NodeTestingService {
     //pending_events: PendingRequests<PendingEventIdType, Event>,
     dyn_effects: Option<DynEffects>,
 
-    snarker_sok_digest: Option<ByteString>,
+    snarker_public_key: Option<NonZeroCurvePoint>,
     /// Once dropped, it will cause all threads associated to shutdown.
     _shutdown: mpsc::Receiver<()>,
 }
@@ -146,7 +146,7 @@ impl NodeTestingService {
             monotonic_time: Instant::now(),
             pending_events: PendingEvents::new(),
             dyn_effects: None,
-            snarker_sok_digest: None,
+            snarker_public_key: None,
             _shutdown,
         }
     }
@@ -198,10 +198,6 @@ impl NodeTestingService {
         self.dyn_effects.take()
     }
 
-    pub fn set_snarker_sok_digest(&mut self, digest: ByteString) {
-        self.snarker_sok_digest = Some(digest);
-    }
-
     pub fn pending_events(&mut self, poll: bool) -> impl Iterator<Item = (PendingEventId, &Event)> {
         while let Ok(req) = self.real.rpc.req_receiver().try_recv() {
             self.real.process_rpc_request(req);
@@ -429,6 +425,10 @@ impl SnarkPoolService for NodeTestingService {
     ) -> Vec<SnarkJobId> {
         self.real.random_choose(iter, n)
     }
+
+    fn persist_snarks(&mut self, snarks: Vec<SnarkWork>) {
+        self.real.persist_snarks(snarks)
+    }
 }
 
 impl BlockProducerVrfEvaluatorService for NodeTestingService {
@@ -443,12 +443,33 @@ thread_local! {
 }
 
 impl BlockProducerService for NodeTestingService {
+    fn production_log_record(&mut self, entry: BlockProducerLogEntry) {
+        BlockProducerService::production_log_record(&mut self.real, entry)
+    }
+
+    fn payout_report_write(&mut self, report: BlockProducerPayoutReport) {
+        BlockProducerService::payout_report_write(&mut self.real, report)
+    }
+
     fn prove(&mut self, block_hash: StateHash, input: Box<ProverExtendBlockchainInputStableV2>) {
         fn dummy_proof_event(block_hash: StateHash) -> Event {
             let dummy_proof = (*ledger::dummy::dummy_blockchain_proof()).clone();
             BlockProducerEvent::BlockProve(block_hash, Ok(dummy_proof.into())).into()
         }
-        let keypair = self.real.block_producer.as_ref().unwrap().keypair();
+        let keypair = match self
+            .real
+            .block_producer
+            .as_ref()
+            .unwrap()
+            .producer_keypair(&input)
+        {
+            Ok(keypair) => keypair,
+            Err(err) => {
+                let event = BlockProducerEvent::BlockProve(block_hash, Err(err));
+                let _ = self.real.event_sender.send(event.into());
+                return;
+            }
+        };
 
         match self.proof_kind() {
             ProofKind::Dummy => {
@@ -499,25 +520,27 @@ impl BlockProducerService for NodeTestingService {
 impl ExternalSnarkWorkerService for NodeTestingService {
     fn start(
         &mut self,
+        worker_id: ExternalSnarkWorkerId,
         public_key: NonZeroCurvePoint,
-        fee: CurrencyFeeStableV1,
     ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        let pub_key = AccountPublicKey::from(public_key);
-        let sok_message = SokMessage::create((&fee).into(), pub_key.into());
-        self.set_snarker_sok_digest((&sok_message.digest()).into());
-        let _ = self
-            .real
-            .event_sender
-            .send(ExternalSnarkWorkerEvent::Started.into());
+        self.snarker_public_key = Some(public_key);
+        let _ = self.real.event_sender.send(Event::ExternalSnarkWorker(
+            worker_id,
+            ExternalSnarkWorkerEvent::Started,
+        ));
         Ok(())
-        // self.real.start(path, public_key, fee)
+        // self.real.start(worker_id, public_key)
     }
 
     fn submit(
         &mut self,
+        worker_id: ExternalSnarkWorkerId,
         spec: SnarkWorkSpec,
+        fee: CurrencyFeeStableV1,
     ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        let sok_digest = self.snarker_sok_digest.clone().unwrap();
+        let pub_key = AccountPublicKey::from(self.snarker_public_key.clone().unwrap());
+        let sok_message = SokMessage::create((&fee).into(), pub_key.into());
+        let sok_digest: ByteString = (&sok_message.digest()).into();
         let make_dummy_proof = |spec| {
             let statement = match spec {
                 SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single::Transition(v, _) => v.0,
@@ -544,30 +567,36 @@ impl ExternalSnarkWorkerService for NodeTestingService {
                 make_dummy_proof(v2),
             )),
         };
-        let _ = self
-            .real
-            .event_sender
-            .send(ExternalSnarkWorkerEvent::WorkResult(Arc::new(res)).into());
+        let _ = self.real.event_sender.send(Event::ExternalSnarkWorker(
+            worker_id,
+            ExternalSnarkWorkerEvent::WorkResult(Arc::new(res)),
+        ));
         Ok(())
-        // self.real.submit(spec)
+        // self.real.submit(worker_id, spec)
     }
 
-    fn cancel(&mut self) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        let _ = self
-            .real
-            .event_sender
-            .send(ExternalSnarkWorkerEvent::WorkCancelled.into());
+    fn cancel(
+        &mut self,
+        worker_id: ExternalSnarkWorkerId,
+    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
+        let _ = self.real.event_sender.send(Event::ExternalSnarkWorker(
+            worker_id,
+            ExternalSnarkWorkerEvent::WorkCancelled,
+        ));
         Ok(())
-        // self.real.cancel()
+        // self.real.cancel(worker_id)
     }
 
-    fn kill(&mut self) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
-        let _ = self
-            .real
-            .event_sender
-            .send(ExternalSnarkWorkerEvent::Killed.into());
+    fn kill(
+        &mut self,
+        worker_id: ExternalSnarkWorkerId,
+    ) -> Result<(), node::external_snark_worker::ExternalSnarkWorkerError> {
+        let _ = self.real.event_sender.send(Event::ExternalSnarkWorker(
+            worker_id,
+            ExternalSnarkWorkerEvent::Killed,
+        ));
         Ok(())
-        // self.real.kill()
+        // self.real.kill(worker_id)
     }
 }
 
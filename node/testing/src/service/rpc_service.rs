@@ -19,6 +19,11 @@ impl RpcService for super::NodeTestingService {
         respond_block_producer_stats_get,
         node::rpc::RpcBlockProducerStatsGetResponse
     );
+    to_real!(
+        respond_block_producer_slot_schedule_get,
+        node::rpc::RpcBlockProducerSlotScheduleGetResponse
+    );
+    to_real!(respond_metrics_get, node::rpc::RpcMetricsGetResponse);
 
     to_real!(
         respond_action_stats_get,
@@ -48,6 +53,10 @@ impl RpcService for super::NodeTestingService {
         respond_snark_pool_job_get,
         node::rpc::RpcSnarkPoolJobGetResponse,
     );
+    to_real!(
+        respond_snark_market_stats_get,
+        node::rpc::RpcSnarkMarketStatsGetResponse,
+    );
     to_real!(
         respond_snarker_job_commit,
         node::rpc::RpcSnarkerJobCommitResponse,
@@ -64,6 +73,22 @@ impl RpcService for super::NodeTestingService {
         respond_snarker_config_get,
         node::rpc::RpcSnarkerConfigGetResponse,
     );
+    to_real!(
+        respond_snark_coordinator_register,
+        node::rpc::RpcSnarkCoordinatorRegisterResponse,
+    );
+    to_real!(
+        respond_snark_coordinator_lease,
+        node::rpc::RpcSnarkCoordinatorLeaseResponse,
+    );
+    to_real!(
+        respond_snark_coordinator_heartbeat,
+        node::rpc::RpcSnarkCoordinatorHeartbeatResponse,
+    );
+    to_real!(
+        respond_snark_coordinator_submit,
+        node::rpc::RpcSnarkCoordinatorSubmitResponse,
+    );
     to_real!(respond_health_check, node::rpc::RpcHealthCheckResponse,);
     to_real!(
         respond_readiness_check,
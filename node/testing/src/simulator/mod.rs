@@ -149,6 +149,8 @@ impl Simulator {
                     )),
                     strategy: SnarkerStrategy::Sequential,
                     auto_commit: true,
+                    workers: 1,
+                    min_fee: None,
                 }),
                 ..node_config.clone()
             };
@@ -186,8 +188,11 @@ impl Simulator {
                 block_producer: Some(RustNodeBlockProducerTestingConfig {
                     config: BlockProducerConfig {
                         pub_key: sec_key.public_key().into(),
+                        additional_pub_keys: vec![],
                         custom_coinbase_receiver: None,
+                        coinbase_receiver_policy: Default::default(),
                         proposed_protocol_version: None,
+                        dry_run: false,
                     },
                     sec_key,
                 }),
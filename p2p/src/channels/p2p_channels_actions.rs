@@ -6,7 +6,7 @@ use crate::{P2pState, PeerId};
 use super::{
     best_tip::P2pChannelsBestTipAction, rpc::P2pChannelsRpcAction, snark::P2pChannelsSnarkAction,
     snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
-    transaction::P2pChannelsTransactionAction, ChannelMsg,
+    transaction::P2pChannelsTransactionAction, ChannelId, ChannelMsg,
 };
 
 pub type P2pChannelsActionWithMetaRef<'a> = redux::ActionWithMeta<&'a P2pChannelsAction>;
@@ -14,6 +14,7 @@ pub type P2pChannelsActionWithMetaRef<'a> = redux::ActionWithMeta<&'a P2pChannel
 #[derive(Serialize, Deserialize, Debug, Clone, openmina_core::ActionEvent)]
 pub enum P2pChannelsAction {
     MessageReceived(P2pChannelsMessageReceivedAction),
+    MessageSent(P2pChannelsMessageSentAction),
 
     BestTip(P2pChannelsBestTipAction),
     Transaction(P2pChannelsTransactionAction),
@@ -26,10 +27,11 @@ impl P2pChannelsAction {
     pub fn peer_id(&self) -> Option<&PeerId> {
         match self {
             Self::MessageReceived(v) => Some(&v.peer_id),
+            Self::MessageSent(v) => Some(&v.peer_id),
             Self::BestTip(v) => Some(v.peer_id()),
             Self::Transaction(v) => v.peer_id(),
             Self::Snark(v) => v.peer_id(),
-            Self::SnarkJobCommitment(v) => Some(v.peer_id()),
+            Self::SnarkJobCommitment(v) => v.peer_id(),
             Self::Rpc(v) => Some(v.peer_id()),
         }
     }
@@ -39,6 +41,7 @@ impl redux::EnablingCondition<crate::P2pState> for P2pChannelsAction {
     fn is_enabled(&self, state: &crate::P2pState, time: redux::Timestamp) -> bool {
         match self {
             P2pChannelsAction::MessageReceived(a) => a.is_enabled(state, time),
+            P2pChannelsAction::MessageSent(a) => a.is_enabled(state, time),
             P2pChannelsAction::Transaction(a) => a.is_enabled(state, time),
             P2pChannelsAction::BestTip(a) => a.is_enabled(state, time),
             P2pChannelsAction::Snark(a) => a.is_enabled(state, time),
@@ -52,6 +55,8 @@ impl redux::EnablingCondition<crate::P2pState> for P2pChannelsAction {
 pub struct P2pChannelsMessageReceivedAction {
     pub peer_id: PeerId,
     pub message: ChannelMsg,
+    /// Number of bytes read, including the length prefix.
+    pub bytes: usize,
 }
 
 impl redux::EnablingCondition<P2pState> for P2pChannelsMessageReceivedAction {
@@ -75,3 +80,32 @@ impl ActionEvent for P2pChannelsMessageReceivedAction {
     {
     }
 }
+
+/// Message was written to the WebRTC channel.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pChannelsMessageSentAction {
+    pub peer_id: PeerId,
+    pub channel_id: ChannelId,
+    /// Number of bytes written, including the length prefix.
+    pub bytes: usize,
+}
+
+impl redux::EnablingCondition<P2pState> for P2pChannelsMessageSentAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        state.get_ready_peer(&self.peer_id).is_some()
+    }
+}
+
+impl From<P2pChannelsMessageSentAction> for crate::P2pAction {
+    fn from(a: P2pChannelsMessageSentAction) -> Self {
+        Self::Channels(P2pChannelsAction::MessageSent(a))
+    }
+}
+
+impl ActionEvent for P2pChannelsMessageSentAction {
+    fn action_event<T>(&self, _context: &T)
+    where
+        T: openmina_core::log::EventContext,
+    {
+    }
+}
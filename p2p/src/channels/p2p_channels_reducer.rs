@@ -5,6 +5,7 @@ impl P2pChannelsState {
         let (action, meta) = action.split();
         match action {
             P2pChannelsAction::MessageReceived(_) => {}
+            P2pChannelsAction::MessageSent(_) => {}
             P2pChannelsAction::BestTip(action) => {
                 self.best_tip.reducer(meta.with_action(action), is_libp2p);
             }
@@ -152,6 +152,12 @@ impl P2pChannelsRpcState {
         }
     }
 
+    pub fn remote_request(&self, id: P2pRpcId) -> Option<&P2pRpcRequest> {
+        self.remote_requests()
+            .find(|req| req.id == id)
+            .map(|req| &req.request)
+    }
+
     pub fn remote_todo_requests_iter(
         &self,
     ) -> impl Iterator<Item = &P2pRpcRemotePendingRequestState> {
@@ -44,10 +44,19 @@ pub enum P2pChannelsSnarkJobCommitmentAction {
         first_index: u64,
         last_index: u64,
     },
+    /// Commitment received over libp2p pubsub.
+    Libp2pReceived {
+        peer_id: PeerId,
+        commitment: SnarkJobCommitment,
+    },
+    /// Broadcast commitment to libp2p peers over pubsub.
+    Libp2pBroadcast {
+        commitment: SnarkJobCommitment,
+    },
 }
 
 impl P2pChannelsSnarkJobCommitmentAction {
-    pub fn peer_id(&self) -> &PeerId {
+    pub fn peer_id(&self) -> Option<&PeerId> {
         match self {
             Self::Init { peer_id }
             | Self::Pending { peer_id }
@@ -56,7 +65,9 @@ impl P2pChannelsSnarkJobCommitmentAction {
             | Self::PromiseReceived { peer_id, .. }
             | Self::Received { peer_id, .. }
             | Self::RequestReceived { peer_id, .. }
-            | Self::ResponseSend { peer_id, .. } => peer_id,
+            | Self::ResponseSend { peer_id, .. }
+            | Self::Libp2pReceived { peer_id, .. } => Some(peer_id),
+            Self::Libp2pBroadcast { .. } => None,
         }
     }
 }
@@ -168,6 +179,21 @@ impl redux::EnablingCondition<P2pState> for P2pChannelsSnarkJobCommitmentAction
                         }
                     })
             }
+            P2pChannelsSnarkJobCommitmentAction::Libp2pReceived { peer_id, .. } => {
+                cfg!(feature = "p2p-libp2p")
+                    && state
+                        .peers
+                        .get(peer_id)
+                        .filter(|p| p.is_libp2p())
+                        .map_or(false, |p| p.status.as_ready().is_some())
+            }
+            P2pChannelsSnarkJobCommitmentAction::Libp2pBroadcast { .. } => {
+                cfg!(feature = "p2p-libp2p")
+                    && state
+                        .peers
+                        .iter()
+                        .any(|(_, p)| p.is_libp2p() && p.status.as_ready().is_some())
+            }
         }
     }
 }
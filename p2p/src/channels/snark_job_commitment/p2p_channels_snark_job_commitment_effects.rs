@@ -1,6 +1,8 @@
 use redux::ActionMeta;
 
 use crate::channels::{ChannelId, MsgId, P2pChannelsService};
+#[cfg(feature = "p2p-libp2p")]
+use crate::P2pNetworkPubsubAction;
 
 use super::{P2pChannelsSnarkJobCommitmentAction, SnarkJobCommitmentPropagationChannelMsg};
 
@@ -57,6 +59,13 @@ impl P2pChannelsSnarkJobCommitmentAction {
             P2pChannelsSnarkJobCommitmentAction::Pending { .. } => {}
             P2pChannelsSnarkJobCommitmentAction::PromiseReceived { .. } => {}
             P2pChannelsSnarkJobCommitmentAction::RequestReceived { .. } => {}
+            P2pChannelsSnarkJobCommitmentAction::Libp2pReceived { .. } => {}
+            #[cfg(not(feature = "p2p-libp2p"))]
+            P2pChannelsSnarkJobCommitmentAction::Libp2pBroadcast { .. } => {}
+            #[cfg(feature = "p2p-libp2p")]
+            P2pChannelsSnarkJobCommitmentAction::Libp2pBroadcast { commitment } => {
+                store.dispatch(P2pNetworkPubsubAction::BroadcastSnarkJobCommitment { commitment });
+            }
         }
     }
 }
@@ -108,6 +108,8 @@ impl P2pChannelsSnarkJobCommitmentState {
                     count,
                 };
             }
+            P2pChannelsSnarkJobCommitmentAction::Libp2pReceived { .. }
+            | P2pChannelsSnarkJobCommitmentAction::Libp2pBroadcast { .. } => {}
         }
     }
 }
@@ -37,4 +37,7 @@ pub enum P2pDisconnectionReason {
 
     #[error("timeout")]
     Timeout,
+
+    #[error("chain id changed")]
+    ChainIdChanged,
 }
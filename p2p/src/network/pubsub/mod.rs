@@ -16,3 +16,66 @@ mod p2p_network_pubsub_effects;
 
 #[cfg(feature = "p2p-libp2p")]
 const TOPIC: &str = "coda/consensus-messages/0.0.1";
+
+/// Openmina specific topic, OCaml nodes don't subscribe to it.
+#[cfg(feature = "p2p-libp2p")]
+const SNARK_JOB_COMMITMENT_TOPIC: &str = "openmina/snark-job-commitments/0.0.1";
+
+/// Encodes the message with binprot, prefixed by its length as 8 bytes little endian.
+#[cfg(feature = "p2p-libp2p")]
+fn encode_message<T: binprot::BinProtWrite>(message: &T) -> Vec<u8> {
+    let mut buffer = vec![0; 8];
+    message.binprot_write(&mut buffer).expect("msg");
+    let len = buffer.len() - 8;
+    buffer[..8].clone_from_slice(&(len as u64).to_le_bytes());
+    buffer
+}
+
+/// Decodes the message encoded by [`encode_message`]. `None` if there is
+/// nothing after the length prefix.
+#[cfg(feature = "p2p-libp2p")]
+fn decode_message<T: binprot::BinProtRead>(data: &[u8]) -> Option<Result<T, binprot::Error>> {
+    let mut slice = data.get(8..).filter(|slice| !slice.is_empty())?;
+    Some(T::binprot_read(&mut slice))
+}
+
+#[cfg(all(test, feature = "p2p-libp2p"))]
+mod tests {
+    use mina_p2p_messages::v2::{
+        CurrencyFeeStableV1, NonZeroCurvePoint, UnsignedExtendedUInt64Int64ForVersionTagsStableV1,
+    };
+    use openmina_core::snark::SnarkJobCommitment;
+
+    use super::{decode_message, encode_message};
+
+    #[test]
+    fn snark_job_commitment_encoding_round_trip() {
+        let job_id = "jw9nPCs68UNaKaLZwV6QzdswKWomwQxvTgrpmKWmnFJyswnrn4N:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc->jwiLuRrEqNgASgXEqibGs4VqKwSwiuFEtuPD53v8hiTtVuLfmTr:jwhHYWzvJG8esmqtYXbUZy3UGbLSjhKvn1FSxBGL1JDFHqbHMJc";
+        let fee = CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+            1_000_000.into(),
+        ));
+        let commitment = SnarkJobCommitment::new(
+            1_700_000_000_000,
+            job_id.parse().unwrap(),
+            fee,
+            NonZeroCurvePoint::default(),
+        );
+
+        let data = encode_message(&commitment);
+        let len = u64::from_le_bytes(data[..8].try_into().unwrap());
+        assert_eq!(len as usize, data.len() - 8);
+
+        let decoded: SnarkJobCommitment = decode_message(&data).unwrap().unwrap();
+        assert_eq!(decoded.timestamp(), commitment.timestamp());
+        assert_eq!(decoded.job_id, commitment.job_id);
+        assert_eq!(decoded.fee, commitment.fee);
+        assert_eq!(decoded.snarker, commitment.snarker);
+
+        assert!(decode_message::<SnarkJobCommitment>(&data[..8]).is_none());
+        assert!(
+            decode_message::<SnarkJobCommitment>(&data[..data.len() - 1])
+                .unwrap()
+                .is_err()
+        );
+    }
+}
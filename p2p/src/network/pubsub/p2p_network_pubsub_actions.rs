@@ -1,7 +1,7 @@
 use std::net::SocketAddr;
 
 use mina_p2p_messages::gossip::GossipNetMessageV2;
-use openmina_core::ActionEvent;
+use openmina_core::{snark::SnarkJobCommitment, ActionEvent};
 
 use serde::{Deserialize, Serialize};
 
@@ -27,6 +27,9 @@ pub enum P2pNetworkPubsubAction {
     Broadcast {
         message: Box<GossipNetMessageV2>,
     },
+    BroadcastSnarkJobCommitment {
+        commitment: SnarkJobCommitment,
+    },
     Sign {
         seqno: u64,
         author: PeerId,
@@ -3,12 +3,15 @@ use std::sync::Arc;
 use openmina_core::{block::BlockWithHash, fuzz_maybe};
 
 use crate::{
-    channels::{snark::P2pChannelsSnarkAction, transaction::P2pChannelsTransactionAction},
+    channels::{
+        snark::P2pChannelsSnarkAction, snark_job_commitment::P2pChannelsSnarkJobCommitmentAction,
+        transaction::P2pChannelsTransactionAction,
+    },
     peer::P2pPeerAction,
     P2pCryptoService, P2pNetworkYamuxAction,
 };
 
-use super::{pb, P2pNetworkPubsubAction, TOPIC};
+use super::{encode_message, pb, P2pNetworkPubsubAction, SNARK_JOB_COMMITMENT_TOPIC, TOPIC};
 
 fn message_is_empty(msg: &pb::Rpc) -> bool {
     msg.subscriptions.is_empty() && msg.publish.is_empty() && msg.control.is_none()
@@ -26,11 +29,15 @@ impl P2pNetworkPubsubAction {
                 peer_id, incoming, ..
             } => {
                 if !incoming {
+                    let topics = [TOPIC, SNARK_JOB_COMMITMENT_TOPIC];
                     let msg = pb::Rpc {
-                        subscriptions: vec![pb::rpc::SubOpts {
-                            subscribe: Some(true),
-                            topic_id: Some(TOPIC.to_owned()),
-                        }],
+                        subscriptions: topics
+                            .iter()
+                            .map(|topic| pb::rpc::SubOpts {
+                                subscribe: Some(true),
+                                topic_id: Some((*topic).to_owned()),
+                            })
+                            .collect(),
                         publish: vec![],
                         control: None,
                     };
@@ -41,9 +48,12 @@ impl P2pNetworkPubsubAction {
                         control: Some(pb::ControlMessage {
                             ihave: vec![],
                             iwant: vec![],
-                            graft: vec![pb::ControlGraft {
-                                topic_id: Some(TOPIC.to_owned()),
-                            }],
+                            graft: topics
+                                .iter()
+                                .map(|topic| pb::ControlGraft {
+                                    topic_id: Some((*topic).to_owned()),
+                                })
+                                .collect(),
                             prune: vec![],
                         }),
                     };
@@ -51,10 +61,7 @@ impl P2pNetworkPubsubAction {
                 }
             }
             Self::Broadcast { message } => {
-                let mut buffer = vec![0; 8];
-                binprot::BinProtWrite::binprot_write(&message, &mut buffer).expect("msg");
-                let len = buffer.len() - 8;
-                buffer[..8].clone_from_slice(&(len as u64).to_le_bytes());
+                let buffer = encode_message(&message);
 
                 store.dispatch(Self::Sign {
                     seqno: state.seq + store.state().config.initial_time.as_nanos() as u64,
@@ -63,6 +70,16 @@ impl P2pNetworkPubsubAction {
                     topic: TOPIC.to_owned(),
                 });
             }
+            Self::BroadcastSnarkJobCommitment { commitment } => {
+                let buffer = encode_message(&commitment);
+
+                store.dispatch(Self::Sign {
+                    seqno: state.seq + store.state().config.initial_time.as_nanos() as u64,
+                    author: store.state().config.identity_pub_key.peer_id(),
+                    data: buffer.into(),
+                    topic: SNARK_JOB_COMMITMENT_TOPIC.to_owned(),
+                });
+            }
             Self::Sign { .. } => {
                 if let Some(to_sign) = state.to_sign.front() {
                     let mut publication = vec![];
@@ -76,6 +93,7 @@ impl P2pNetworkPubsubAction {
                 let incoming_block = state.incoming_block.as_ref().cloned();
                 let incoming_transactions = state.incoming_transactions.clone();
                 let incoming_snarks = state.incoming_snarks.clone();
+                let incoming_snark_job_commitments = state.incoming_snark_job_commitments.clone();
 
                 broadcast(store);
                 if let Some((_, block)) = incoming_block {
@@ -96,6 +114,12 @@ impl P2pNetworkPubsubAction {
                         nonce,
                     });
                 }
+                for commitment in incoming_snark_job_commitments {
+                    store.dispatch(P2pChannelsSnarkJobCommitmentAction::Libp2pReceived {
+                        peer_id,
+                        commitment,
+                    });
+                }
             }
             Self::OutgoingMessage { msg, peer_id } => {
                 if !message_is_empty(&msg) {
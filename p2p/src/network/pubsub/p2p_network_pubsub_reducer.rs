@@ -1,9 +1,14 @@
 use std::collections::BTreeSet;
 
-use binprot::BinProtRead;
 use mina_p2p_messages::{gossip, v2};
+use openmina_core::snark::SnarkJobCommitment;
 
-use super::{pb, P2pNetworkPubsubAction, P2pNetworkPubsubClientState, P2pNetworkPubsubState};
+use crate::PeerId;
+
+use super::{
+    decode_message, pb, P2pNetworkPubsubAction, P2pNetworkPubsubClientState, P2pNetworkPubsubState,
+    SNARK_JOB_COMMITMENT_TOPIC,
+};
 
 impl P2pNetworkPubsubState {
     pub fn reducer(&mut self, action: redux::ActionWithMeta<&P2pNetworkPubsubAction>) {
@@ -64,6 +69,7 @@ impl P2pNetworkPubsubState {
             P2pNetworkPubsubAction::IncomingData { peer_id, data, .. } => {
                 self.incoming_transactions.clear();
                 self.incoming_snarks.clear();
+                self.incoming_snark_job_commitments.clear();
                 let Some(state) = self.clients.get_mut(peer_id) else {
                     return;
                 };
@@ -106,11 +112,24 @@ impl P2pNetworkPubsubState {
                                 .for_each(|(_, state)| state.message.publish.push(message.clone()));
 
                             if let Some(data) = message.data {
-                                if data.len() <= 8 {
+                                if message.topic == SNARK_JOB_COMMITMENT_TOPIC {
+                                    match decode_message::<SnarkJobCommitment>(&data) {
+                                        None => {}
+                                        Some(Ok(commitment)) => {
+                                            self.incoming_snark_job_commitments.push(commitment);
+                                        }
+                                        Some(Err(err)) => {
+                                            self.decode_error(action.time(), peer_id, err);
+                                        }
+                                    }
                                     continue;
                                 }
-                                let mut slice = &data[8..];
-                                match gossip::GossipNetMessageV2::binprot_read(&mut slice) {
+                                let Some(gossip_message) =
+                                    decode_message::<gossip::GossipNetMessageV2>(&data)
+                                else {
+                                    continue;
+                                };
+                                match gossip_message {
                                     Ok(gossip::GossipNetMessageV2::NewState(block)) => {
                                         self.incoming_block = Some((*peer_id, block));
                                     }
@@ -130,7 +149,7 @@ impl P2pNetworkPubsubState {
                                         }
                                     }
                                     Err(err) => {
-                                        dbg!(err);
+                                        self.decode_error(action.time(), peer_id, err);
                                     }
                                 }
                             }
@@ -139,10 +158,13 @@ impl P2pNetworkPubsubState {
                     }
                     Err(err) => {
                         // bad way to check the error, but `prost` doesn't provide better
-                        if err.to_string().contains("buffer underflow") && state.buffer.is_empty() {
-                            state.buffer = data.to_vec();
+                        if err.to_string().contains("buffer underflow") {
+                            if state.buffer.is_empty() {
+                                state.buffer = data.to_vec();
+                            }
+                        } else {
+                            self.decode_error(action.time(), peer_id, err);
                         }
-                        dbg!(err);
                     }
                 }
             }
@@ -153,7 +175,8 @@ impl P2pNetworkPubsubState {
                     v.message.control = None;
                 }
             }
-            P2pNetworkPubsubAction::Broadcast { .. } => {}
+            P2pNetworkPubsubAction::Broadcast { .. }
+            | P2pNetworkPubsubAction::BroadcastSnarkJobCommitment { .. } => {}
             P2pNetworkPubsubAction::Sign {
                 seqno,
                 author,
@@ -184,4 +207,23 @@ impl P2pNetworkPubsubState {
             P2pNetworkPubsubAction::OutgoingData { .. } => {}
         }
     }
+
+    /// Counts the incoming message which failed to decode. Only the first
+    /// failure and then each time their number doubles is logged, so that
+    /// a misbehaving peer can't flood the log.
+    fn decode_error(
+        &mut self,
+        time: redux::Timestamp,
+        peer_id: &PeerId,
+        error: impl std::fmt::Display,
+    ) {
+        self.decode_errors += 1;
+        if self.decode_errors.is_power_of_two() {
+            openmina_core::warn!(time;
+                summary = "failed to decode pubsub message",
+                peer_id = peer_id.to_string(),
+                error = error.to_string(),
+                decode_errors = self.decode_errors);
+        }
+    }
 }
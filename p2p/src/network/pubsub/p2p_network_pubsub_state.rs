@@ -4,7 +4,10 @@ use std::{
 };
 
 use mina_p2p_messages::v2;
-use openmina_core::{snark::Snark, transaction::Transaction};
+use openmina_core::{
+    snark::{Snark, SnarkJobCommitment},
+    transaction::Transaction,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{token::BroadcastAlgorithm, PeerId, StreamId};
@@ -21,6 +24,11 @@ pub struct P2pNetworkPubsubState {
     pub incoming_block: Option<(PeerId, v2::MinaBlockBlockStableV2)>,
     pub incoming_transactions: Vec<(Transaction, u32)>,
     pub incoming_snarks: Vec<(Snark, u32)>,
+    #[serde(default)]
+    pub incoming_snark_job_commitments: Vec<SnarkJobCommitment>,
+    /// Number of incoming messages which failed to decode.
+    #[serde(default)]
+    pub decode_errors: u64,
 }
 
 impl P2pNetworkPubsubState {
@@ -32,6 +32,13 @@ pub enum P2pInitializeAction {
     /// Initializes p2p layer.
     #[action_event(level = info)]
     Initialize { chain_id: openmina_core::ChainId },
+    /// Switches already initialized p2p layer to the new chain id.
+    ///
+    /// Happens after the hard fork, when the node starts following the
+    /// new chain. Peers connected under previous chain id must be
+    /// disconnected beforehand.
+    #[action_event(level = info)]
+    ChainIdChange { chain_id: openmina_core::ChainId },
 }
 
 impl EnablingCondition<P2pState> for P2pInitializeAction {
@@ -273,6 +273,7 @@ where
         P2pAction::Discovery(action) => action.effects(&meta, store),
         P2pAction::Channels(action) => match action {
             P2pChannelsAction::MessageReceived(action) => action.effects(&meta, store),
+            P2pChannelsAction::MessageSent(_) => {}
             P2pChannelsAction::BestTip(action) => action.effects(&meta, store),
             P2pChannelsAction::Transaction(action) => action.effects(&meta, store),
             P2pChannelsAction::Snark(action) => action.effects(&meta, store),
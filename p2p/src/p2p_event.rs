@@ -60,8 +60,11 @@ pub enum P2pConnectionEvent {
 #[derive(Serialize, Deserialize, From, Debug, Clone)]
 pub enum P2pChannelEvent {
     Opened(PeerId, ChannelId, Result<(), String>),
-    Sent(PeerId, ChannelId, MsgId, Result<(), String>),
-    Received(PeerId, Result<ChannelMsg, String>),
+    /// Message was sent. On success carries the number of bytes written.
+    Sent(PeerId, ChannelId, MsgId, Result<usize, String>),
+    /// Message was received. On success carries the number of bytes read,
+    /// including the length prefix.
+    Received(PeerId, Result<(ChannelMsg, usize), String>),
     Closed(PeerId, ChannelId),
 }
 
@@ -136,7 +139,7 @@ impl fmt::Display for P2pChannelEvent {
                 write!(f, "Received, {peer_id}, ")?;
                 let msg = match res {
                     Err(_) => return write!(f, "Err"),
-                    Ok(msg) => {
+                    Ok((msg, _)) => {
                         write!(f, "{:?}, ", msg.channel_id())?;
                         msg
                     }
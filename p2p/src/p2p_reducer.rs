@@ -1,5 +1,7 @@
 use openmina_core::SubstateAccess;
 
+use crate::channels::rpc::{P2pChannelsRpcAction, P2pRpcKind, RpcChannelMsg};
+use crate::channels::{ChannelId, ChannelMsg, P2pChannelsAction};
 use crate::connection::incoming::{IncomingSignalingMethod, P2pConnectionIncomingAction};
 use crate::connection::outgoing::{P2pConnectionOutgoingAction, P2pConnectionOutgoingInitOpts};
 use crate::connection::{p2p_connection_reducer, P2pConnectionAction, P2pConnectionState};
@@ -41,6 +43,7 @@ impl P2pState {
                         dial_opts: Some(opts.clone()),
                         status: P2pPeerStatus::Connecting(P2pConnectionState::outgoing_init(opts)),
                         identify: None,
+                        traffic: Default::default(),
                     }),
                     P2pConnectionAction::Incoming(P2pConnectionIncomingAction::Init {
                         opts,
@@ -63,6 +66,7 @@ impl P2pState {
                         },
                         status: P2pPeerStatus::Connecting(P2pConnectionState::incoming_init(opts)),
                         identify: None,
+                        traffic: Default::default(),
                     }),
                     P2pConnectionAction::Incoming(
                         P2pConnectionIncomingAction::FinalizePendingLibp2p { .. },
@@ -73,6 +77,7 @@ impl P2pState {
                             // correct status later set in the child reducer.
                             status: P2pPeerStatus::Disconnected { time: meta.time() },
                             identify: None,
+                            traffic: Default::default(),
                         })
                     }
                     _ => match state.peers.get_mut(peer_id) {
@@ -110,6 +115,9 @@ impl P2pState {
                     return;
                 };
                 let is_libp2p = state.is_libp2p_peer(peer_id);
+                if !is_libp2p {
+                    state.channels_traffic_reducer(action);
+                }
                 let Some(peer) = state.get_ready_peer_mut(peer_id) else {
                     return;
                 };
@@ -135,10 +143,193 @@ impl P2pState {
             P2pAction::Network(_action) => {
                 #[cfg(feature = "p2p-libp2p")]
                 {
+                    match _action {
+                        crate::P2pNetworkAction::Yamux(action) => {
+                            state.yamux_traffic_reducer(action)
+                        }
+                        crate::P2pNetworkAction::Rpc(action) => state.rpc_traffic_reducer(action),
+                        _ => {}
+                    }
                     let limits = &state.config.limits;
                     state.network.reducer(meta.with_action(_action), limits);
                 }
             }
         }
     }
+
+    /// Accounts WebRTC channel messages in the peer's traffic stats.
+    fn channels_traffic_reducer(&mut self, action: &P2pChannelsAction) {
+        let Some(peer) = action.peer_id().and_then(|id| self.peers.get_mut(id)) else {
+            return;
+        };
+        let rpc_state = peer.status.as_ready().map(|peer| &peer.channels.rpc);
+        match action {
+            P2pChannelsAction::MessageReceived(action) => {
+                let rpc_kind = match &action.message {
+                    ChannelMsg::Rpc(RpcChannelMsg::Request(_, request)) => Some(request.kind()),
+                    ChannelMsg::Rpc(RpcChannelMsg::Response(id, _)) => rpc_state
+                        .filter(|rpc| rpc.pending_local_rpc_id() == Some(*id))
+                        .and_then(|rpc| rpc.pending_local_rpc_kind()),
+                    _ => None,
+                };
+                let protocol = webrtc_traffic_protocol(action.message.channel_id());
+                peer.traffic.incoming(&protocol, action.bytes);
+                if let Some(kind) = rpc_kind {
+                    let method = webrtc_rpc_method(kind);
+                    peer.traffic.rpc_incoming(&method, action.bytes);
+                }
+            }
+            P2pChannelsAction::MessageSent(action) => {
+                let protocol = webrtc_traffic_protocol(action.channel_id);
+                peer.traffic.outgoing(&protocol, action.bytes);
+            }
+            // bytes of sent RPCs are only known by channel.
+            P2pChannelsAction::Rpc(P2pChannelsRpcAction::RequestSend { request, .. }) => {
+                let method = webrtc_rpc_method(request.kind());
+                peer.traffic.rpc_outgoing(&method, 0);
+            }
+            P2pChannelsAction::Rpc(P2pChannelsRpcAction::ResponseSend { id, .. }) => {
+                let Some(kind) = rpc_state
+                    .and_then(|rpc| rpc.remote_request(*id))
+                    .map(|request| request.kind())
+                else {
+                    return;
+                };
+                peer.traffic.rpc_outgoing(&webrtc_rpc_method(kind), 0);
+            }
+            _ => {}
+        }
+    }
+
+    /// Accounts bytes sent and received over yamux streams in the peer's
+    /// traffic stats, by the protocol negotiated for the stream.
+    #[cfg(feature = "p2p-libp2p")]
+    fn yamux_traffic_reducer(&mut self, action: &crate::P2pNetworkYamuxAction) {
+        use crate::{P2pNetworkYamuxAction, YamuxFrameInner};
+
+        let (addr, stream_id, bytes, incoming) = match action {
+            P2pNetworkYamuxAction::IncomingFrame { addr, frame } => match &frame.inner {
+                YamuxFrameInner::Data(data) => (addr, frame.stream_id, data.len(), true),
+                _ => return,
+            },
+            P2pNetworkYamuxAction::OutgoingData {
+                addr,
+                stream_id,
+                data,
+                ..
+            } => (addr, *stream_id, data.len(), false),
+            _ => return,
+        };
+        let Some(cn) = self.network.scheduler.connections.get(addr) else {
+            return;
+        };
+        let Some(peer) = cn.peer_id().and_then(|id| self.peers.get_mut(id)) else {
+            return;
+        };
+        // data sent before the stream protocol is negotiated belongs to
+        // the multistream-select handshake.
+        let protocol = cn
+            .streams
+            .get(&stream_id)
+            .and_then(|stream| stream.select.negotiated)
+            .flatten()
+            .map_or(MULTISTREAM_PROTOCOL, |protocol| protocol.name_str());
+        if incoming {
+            peer.traffic.bytes_in(protocol, bytes);
+        } else {
+            peer.traffic.bytes_out(protocol, bytes);
+        }
+    }
+
+    /// Accounts libp2p RPC queries and responses in the peer's traffic
+    /// stats, by method.
+    #[cfg(feature = "p2p-libp2p")]
+    fn rpc_traffic_reducer(&mut self, action: &crate::P2pNetworkRpcAction) {
+        use crate::{P2pNetworkRpcAction, RpcMessage};
+
+        // Responses are matched with the query pending on the stream.
+        let pending_tag = || {
+            self.network
+                .find_rpc_state(action)
+                .and_then(|rpc| rpc.pending.as_ref())
+                .map(|query| libp2p_rpc_method(query.tag.as_ref()))
+        };
+        let (peer_id, method, bytes, incoming) = match action {
+            P2pNetworkRpcAction::IncomingMessage {
+                peer_id, message, ..
+            } => match message {
+                RpcMessage::Query { header, bytes } => (
+                    peer_id,
+                    Some(libp2p_rpc_method(header.tag.as_ref())),
+                    bytes.len(),
+                    true,
+                ),
+                RpcMessage::Response { bytes, .. } => (peer_id, pending_tag(), bytes.len(), true),
+                _ => return,
+            },
+            P2pNetworkRpcAction::OutgoingQuery {
+                peer_id,
+                query,
+                data,
+            } => (
+                peer_id,
+                Some(libp2p_rpc_method(query.tag.as_ref())),
+                data.len(),
+                false,
+            ),
+            P2pNetworkRpcAction::OutgoingResponse { peer_id, data, .. } => {
+                (peer_id, pending_tag(), data.len(), false)
+            }
+            _ => return,
+        };
+        let Some(method) = method else {
+            return;
+        };
+        let Some(peer) = self.peers.get_mut(peer_id) else {
+            return;
+        };
+        if incoming {
+            peer.traffic.rpc_incoming(method, bytes);
+        } else {
+            peer.traffic.rpc_outgoing(method, bytes);
+        }
+    }
+}
+
+#[cfg(feature = "p2p-libp2p")]
+const MULTISTREAM_PROTOCOL: &str = "/multistream/1.0.0";
+
+/// Methods we know of. Tags of other queries are sent by the peer, so
+/// they are all accounted as `unknown`, to keep the number of methods
+/// bounded.
+#[cfg(feature = "p2p-libp2p")]
+fn libp2p_rpc_method(tag: &[u8]) -> &'static str {
+    use mina_p2p_messages::rpc::*;
+    use mina_p2p_messages::rpc_kernel::RpcMethod;
+
+    [
+        VersionedRpcMenuV1::NAME_STR,
+        GetSomeInitialPeersV1ForV2::NAME_STR,
+        GetStagedLedgerAuxAndPendingCoinbasesAtHashV2::NAME_STR,
+        AnswerSyncLedgerQueryV2::NAME_STR,
+        GetTransitionChainV2::NAME_STR,
+        GetTransitionChainProofV1ForV2::NAME_STR,
+        GetTransitionKnowledgeV1ForV2::NAME_STR,
+        GetAncestryV2::NAME_STR,
+        BanNotifyV1::NAME_STR,
+        GetBestTipV2::NAME_STR,
+        GetNodeStatusV2::NAME_STR,
+        GetEpochLedgerV2::NAME_STR,
+    ]
+    .into_iter()
+    .find(|name| name.as_bytes() == tag)
+    .unwrap_or("unknown")
+}
+
+fn webrtc_traffic_protocol(channel_id: ChannelId) -> String {
+    format!("webrtc/{}", channel_id.name())
+}
+
+fn webrtc_rpc_method(kind: P2pRpcKind) -> String {
+    format!("webrtc/{kind:?}")
 }
@@ -11,6 +11,7 @@ use crate::connection::incoming::P2pConnectionIncomingState;
 use crate::connection::outgoing::{P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingState};
 use crate::network::identify::P2pNetworkIdentify;
 use crate::network::P2pNetworkState;
+use crate::peer::P2pPeerTraffic;
 use crate::{is_time_passed, Limit, P2pTimeouts, PeerId};
 
 use super::connection::P2pConnectionState;
@@ -68,6 +69,7 @@ impl P2pState {
                             time: Timestamp::ZERO,
                         },
                         identify: None,
+                        traffic: Default::default(),
                     },
                 )
             })
@@ -92,6 +94,13 @@ impl P2pState {
         self.config.identity_pub_key.peer_id()
     }
 
+    /// Switches to the new chain id, so that new connections will be
+    /// using the corresponding preshared key.
+    pub fn set_chain_id(&mut self, chain_id: &ChainId) {
+        self.network.scheduler.pnet_key = chain_id.preshared_key();
+        self.chain_id = chain_id.clone();
+    }
+
     pub fn peer_connection_rpc_id(&self, peer_id: &PeerId) -> Option<RpcId> {
         self.peers.get(peer_id)?.connection_rpc_id()
     }
@@ -256,6 +265,8 @@ pub struct P2pPeerState {
     pub dial_opts: Option<P2pConnectionOutgoingInitOpts>,
     pub status: P2pPeerStatus,
     pub identify: Option<P2pNetworkIdentify>,
+    #[serde(default)]
+    pub traffic: P2pPeerTraffic,
 }
 
 impl P2pPeerState {
@@ -5,3 +5,6 @@ mod p2p_peer_reducer;
 pub use p2p_peer_reducer::*;
 
 mod p2p_peer_effects;
+
+mod p2p_peer_traffic;
+pub use p2p_peer_traffic::*;
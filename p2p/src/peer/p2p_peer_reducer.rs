@@ -13,6 +13,7 @@ pub fn p2p_peer_reducer(state: &mut P2pState, action: P2pPeerActionWithMetaRef<'
                 is_libp2p: true,
                 dial_opts: dial_opts.clone(),
                 identify: None,
+                traffic: Default::default(),
                 status: P2pPeerStatus::Disconnected {
                     time: Timestamp::ZERO,
                 },
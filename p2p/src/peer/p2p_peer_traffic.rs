@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Bytes and messages exchanged with the peer, by protocol and by RPC
+/// method.
+///
+/// For libp2p peers the protocol is the one negotiated for the yamux
+/// stream (e.g. `/meshsub/1.1.0`). Yamux doesn't know where messages
+/// start or end, so only bytes are counted by protocol, messages are
+/// counted by RPC method once the RPC layer decodes them.
+/// For WebRTC peers the protocol is `webrtc/<channel>` and a message
+/// is a channel message.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct P2pPeerTraffic {
+    pub protocols: BTreeMap<String, P2pPeerTrafficCounters>,
+    /// RPC queries and responses by method.
+    ///
+    /// For libp2p peers bytes are the query or response payload. For
+    /// WebRTC peers bytes are only known for received messages, sent
+    /// ones are accounted in the `webrtc/rpc` protocol.
+    pub rpc_methods: BTreeMap<String, P2pPeerTrafficCounters>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct P2pPeerTrafficCounters {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub messages_in: u64,
+    pub messages_out: u64,
+}
+
+impl P2pPeerTrafficCounters {
+    fn message_in(&mut self, bytes: usize) {
+        self.bytes_in += bytes as u64;
+        self.messages_in += 1;
+    }
+
+    fn message_out(&mut self, bytes: usize) {
+        self.bytes_out += bytes as u64;
+        self.messages_out += 1;
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.bytes_in += other.bytes_in;
+        self.bytes_out += other.bytes_out;
+        self.messages_in += other.messages_in;
+        self.messages_out += other.messages_out;
+    }
+}
+
+impl P2pPeerTraffic {
+    /// Received bytes which aren't a whole message.
+    pub fn bytes_in(&mut self, protocol: &str, bytes: usize) {
+        counters_mut(&mut self.protocols, protocol).bytes_in += bytes as u64;
+    }
+
+    /// Sent bytes which aren't a whole message.
+    pub fn bytes_out(&mut self, protocol: &str, bytes: usize) {
+        counters_mut(&mut self.protocols, protocol).bytes_out += bytes as u64;
+    }
+
+    pub fn incoming(&mut self, protocol: &str, bytes: usize) {
+        counters_mut(&mut self.protocols, protocol).message_in(bytes);
+    }
+
+    pub fn outgoing(&mut self, protocol: &str, bytes: usize) {
+        counters_mut(&mut self.protocols, protocol).message_out(bytes);
+    }
+
+    pub fn rpc_incoming(&mut self, method: &str, bytes: usize) {
+        counters_mut(&mut self.rpc_methods, method).message_in(bytes);
+    }
+
+    pub fn rpc_outgoing(&mut self, method: &str, bytes: usize) {
+        counters_mut(&mut self.rpc_methods, method).message_out(bytes);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.protocols.is_empty() && self.rpc_methods.is_empty()
+    }
+
+    pub fn total(&self) -> P2pPeerTrafficCounters {
+        self.protocols
+            .values()
+            .fold(P2pPeerTrafficCounters::default(), |mut total, v| {
+                total.add(v);
+                total
+            })
+    }
+
+    /// Adds up traffic of another peer, e.g. to get the traffic of all peers.
+    pub fn add(&mut self, other: &Self) {
+        for (protocol, counters) in &other.protocols {
+            counters_mut(&mut self.protocols, protocol).add(counters);
+        }
+        for (method, counters) in &other.rpc_methods {
+            counters_mut(&mut self.rpc_methods, method).add(counters);
+        }
+    }
+}
+
+fn counters_mut<'a>(
+    counters: &'a mut BTreeMap<String, P2pPeerTrafficCounters>,
+    key: &str,
+) -> &'a mut P2pPeerTrafficCounters {
+    counters.entry(key.to_owned()).or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::P2pPeerTraffic;
+
+    #[test]
+    fn traffic_by_protocol_and_rpc_method() {
+        let mut traffic = P2pPeerTraffic::default();
+        traffic.bytes_in("/coda/rpcs/0.0.1", 100);
+        traffic.bytes_in("/coda/rpcs/0.0.1", 50);
+        traffic.bytes_out("/coda/rpcs/0.0.1", 10);
+        traffic.rpc_incoming("get_best_tip", 120);
+        traffic.rpc_outgoing("get_best_tip", 8);
+        traffic.incoming("webrtc/rpc", 30);
+        traffic.outgoing("webrtc/rpc", 20);
+
+        let rpcs = traffic.protocols["/coda/rpcs/0.0.1"];
+        assert_eq!((rpcs.bytes_in, rpcs.bytes_out), (150, 10));
+        // yamux frames aren't messages.
+        assert_eq!((rpcs.messages_in, rpcs.messages_out), (0, 0));
+
+        let method = traffic.rpc_methods["get_best_tip"];
+        assert_eq!((method.bytes_in, method.messages_in), (120, 1));
+        assert_eq!((method.bytes_out, method.messages_out), (8, 1));
+
+        // RPC methods are a breakdown of protocol traffic, not added to it.
+        let total = traffic.total();
+        assert_eq!((total.bytes_in, total.bytes_out), (180, 30));
+        assert_eq!((total.messages_in, total.messages_out), (1, 1));
+
+        let mut all = P2pPeerTraffic::default();
+        all.add(&traffic);
+        all.add(&traffic);
+        assert_eq!(all.protocols["webrtc/rpc"].bytes_in, 60);
+        assert_eq!(all.rpc_methods["get_best_tip"].messages_out, 2);
+    }
+}
@@ -518,7 +518,7 @@ async fn peer_loop(
                                 encoded.chunks(CHUNK_SIZE).map(|b| encoded.slice_ref(b));
                             let result = loop {
                                 let Some(chunk) = chunks.next() else {
-                                    break Ok(());
+                                    break Ok(encoded.len());
                                 };
                                 if let Err(err) = chan_clone
                                     .send(&chunk)
@@ -544,7 +544,7 @@ async fn peer_loop(
                         buf: &mut Vec<u8>,
                         len: &mut u32,
                         msg: &mut &[u8],
-                    ) -> Result<Option<ChannelMsg>, String> {
+                    ) -> Result<Option<(ChannelMsg, usize)>, String> {
                         let len = if buf.is_empty() {
                             if msg.len() < 4 {
                                 return Err("WebRTCMessageTooSmall".to_owned());
@@ -576,8 +576,10 @@ async fn peer_loop(
                         *msg = &msg[bytes_left..];
                         let msg = ChannelMsg::decode(&mut &buf[..], chan_id)
                             .map_err(|err| err.to_string())?;
+                        // 4 bytes for the length prefix.
+                        let bytes = 4 + buf.len();
                         buf.clear();
-                        Ok(Some(msg))
+                        Ok(Some((msg, bytes)))
                     }
 
                     let mut len = 0;
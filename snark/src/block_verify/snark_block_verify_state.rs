@@ -78,6 +78,14 @@ impl SnarkBlockVerifyStatus {
         matches!(self, Self::Pending { .. })
     }
 
+    /// Time when the verification was requested from the service.
+    pub fn pending_since(&self) -> Option<redux::Timestamp> {
+        match self {
+            Self::Pending { time, .. } => Some(*time),
+            _ => None,
+        }
+    }
+
     pub fn is_finished(&self) -> bool {
         matches!(self, Self::Error { .. } | Self::Success { .. })
     }